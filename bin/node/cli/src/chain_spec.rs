@@ -23,7 +23,7 @@ use node_runtime::{
 	AuthorityDiscoveryConfig, BabeConfig, BalancesConfig, CouncilConfig, DemocracyConfig, ElectionsConfig,
 	GrandpaConfig, ImOnlineConfig, SessionConfig, SessionKeys, StakerStatus, StakingConfig,
 	IndicesConfig, SocietyConfig, SudoConfig, SystemConfig, TechnicalCommitteeConfig, WASM_BINARY,
-	AssetsConfig, BridgeEosConfig, VoucherConfig,
+	AssetsConfig, BridgeConfig, BridgeEosConfig, VoucherConfig,
 };
 use node_runtime::Block;
 use node_runtime::constants::currency::*;
@@ -37,6 +37,7 @@ use sp_authority_discovery::AuthorityId as AuthorityDiscoveryId;
 use sp_runtime::{Perbill, traits::{Verify, IdentifyAccount}};
 
 pub use node_primitives::{AccountId, Balance, Signature};
+use node_primitives::BlockchainType;
 pub use node_runtime::GenesisConfig;
 
 type AccountPublic = <Signature as Verify>::Signer;
@@ -318,12 +319,24 @@ pub fn testnet_genesis(
 			token_details: vec![],
 			prices: vec![],
 		}),
+		brml_bridge: Some(BridgeConfig {
+			// asset 0, 1 and 2 are DOT on Polkadot, KSM on Kusama and EOS on EOS
+			bridge_assets: vec![
+				(0, BlockchainType::POLKADOT, b"DOT".to_vec(), 10),
+				(1, BlockchainType::KUSAMA, b"KSM".to_vec(), 12),
+				(2, BlockchainType::EOS, b"EOS".to_vec(), 4),
+			],
+		}),
 		brml_bridge_eos: Some(BridgeEosConfig {
 			bridge_contract_account: (b"bifrostcross".to_vec(), 2),
 			notary_keys: initial_authorities.iter().map(|x| x.0.clone()).collect::<Vec<_>>(),
 			// alice and bob have the privilege to sign cross transaction
 			cross_chain_privilege: [(root_key, true)].iter().cloned().collect::<Vec<_>>(),
 			all_crosschain_privilege: Vec::new(),
+			// EOS on eosio.token is mapped to asset 2
+			bridge_tokens: vec![(2, b"eosio.token".to_vec(), b"EOS".to_vec(), 4)],
+			// EOS keys of notaries are set by root
+			notary_eos_keys: Vec::new(),
 		}),
 		brml_voucher: {
 			if let Some(vouchers) = initialize_all_vouchers() {
@@ -475,12 +488,24 @@ pub fn bifrost_genesis(
 			token_details: vec![],
 			prices: vec![],
 		}),
+		brml_bridge: Some(BridgeConfig {
+			// asset 0, 1 and 2 are DOT on Polkadot, KSM on Kusama and EOS on EOS
+			bridge_assets: vec![
+				(0, BlockchainType::POLKADOT, b"DOT".to_vec(), 10),
+				(1, BlockchainType::KUSAMA, b"KSM".to_vec(), 12),
+				(2, BlockchainType::EOS, b"EOS".to_vec(), 4),
+			],
+		}),
 		brml_bridge_eos: Some(BridgeEosConfig {
 			bridge_contract_account: (b"bifrostcross".to_vec(), 2),
 			notary_keys: initial_authorities[0..3].iter().map(|x| x.0.clone()).collect::<Vec<_>>(),
 			// root_key has the privilege to sign cross transaction
 			cross_chain_privilege: [(root_key, true)].iter().cloned().collect::<Vec<_>>(),
 			all_crosschain_privilege: Vec::new(),
+			// EOS on eosio.token is mapped to asset 2
+			bridge_tokens: vec![(2, b"eosio.token".to_vec(), b"EOS".to_vec(), 4)],
+			// EOS keys of notaries are set by root
+			notary_eos_keys: Vec::new(),
 		}),
 		brml_voucher: {
 			if let Some(vouchers) = initialize_all_vouchers() {
@@ -14,7 +14,8 @@
 // You should have received a copy of the GNU General Public License
 // along with Bifrost.  If not, see <http://www.gnu.org/licenses/>.
 
-use sc_cli::RunCmd;
+use sc_cli::{ImportParams, RunCmd, SharedParams};
+use std::path::PathBuf;
 use structopt::StructOpt;
 
 /// An overarching CLI command definition.
@@ -41,4 +42,35 @@ pub enum Subcommand {
 		about = "Decode given block or extrinsic using current native runtime."
 	)]
 	Inspect(node_inspect::cli::InspectCmd),
+
+	/// Export all vouchers at a given block.
+	#[structopt(
+		name = "export-vouchers",
+		about = "Export a snapshot of all vouchers at a given block to a JSON or CSV file."
+	)]
+	ExportVouchers(ExportVouchersCmd),
+}
+
+/// The `export-vouchers` command used to dump all voucher balances.
+#[derive(Clone, Debug, StructOpt)]
+pub struct ExportVouchersCmd {
+	/// Block hash or number the snapshot is taken at, the best block is used by default.
+	#[structopt(long = "at", value_name = "HASH or NUMBER")]
+	pub at: Option<String>,
+
+	/// Format of the exported file.
+	#[structopt(long = "format", default_value = "json", possible_values = &["json", "csv"])]
+	pub format: String,
+
+	/// File the snapshot is written to.
+	#[structopt(long = "output", parse(from_os_str), default_value = "all_vouchers.json")]
+	pub output: PathBuf,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub import_params: ImportParams,
 }
@@ -14,9 +14,20 @@
 // You should have received a copy of the GNU General Public License
 // along with Bifrost.  If not, see <http://www.gnu.org/licenses/>.
 
-use crate::{chain_spec, service, Cli, Subcommand};
+use crate::{chain_spec, service, Cli, ExportVouchersCmd, Subcommand};
+use brml_voucher_rpc::VoucherRuntimeApi;
+use node_primitives::{Balance, BlockNumber, Hash};
 use node_runtime::{Block, RuntimeApi};
-use sc_cli::{Result, SubstrateCli};
+use sc_cli::{CliConfiguration, ImportParams, Result, SharedParams, SubstrateCli};
+use sc_service::Configuration;
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::crypto::Ss58Codec;
+use sp_runtime::generic::BlockId;
+use std::{collections::BTreeMap, io::Write};
+
+/// How many vouchers are fetched from runtime in one call
+const VOUCHERS_PER_PAGE: u32 = 1000;
 
 impl SubstrateCli for Cli {
 	fn impl_name() -> &'static str {
@@ -81,6 +92,11 @@ pub fn run() -> Result<()> {
 
 			runner.sync_run(|config| cmd.run::<Block, RuntimeApi, crate::executor::Executor>(config))
 		}
+		Some(Subcommand::ExportVouchers(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+
+			runner.sync_run(|config| cmd.run(config))
+		}
 		Some(Subcommand::Base(subcommand)) => {
 			let runner = cli.create_runner(subcommand)?;
 
@@ -88,3 +104,155 @@ pub fn run() -> Result<()> {
 		}
 	}
 }
+
+impl ExportVouchersCmd {
+	/// Export all vouchers at the given block to the output file.
+	pub fn run(&self, config: Configuration) -> Result<()> {
+		let client = sc_service::new_full_client::<Block, RuntimeApi, crate::executor::Executor>(&config)?;
+
+		let at = match self.at {
+			Some(ref at) => parse_block_id(at)?,
+			None => BlockId::<Block>::hash(client.info().best_hash),
+		};
+
+		let mut vouchers = BTreeMap::new();
+		let mut start = 0u32;
+		loop {
+			let page = client.runtime_api().vouchers(&at, start, VOUCHERS_PER_PAGE)
+				.map_err(|e| format!("Failed to get vouchers at {}: {:?}", at, e))?;
+			let fetched = page.len() as u32;
+			for (who, balance) in page {
+				vouchers.insert(who.to_ss58check(), balance);
+			}
+
+			if fetched < VOUCHERS_PER_PAGE {
+				break;
+			}
+			start += fetched;
+		}
+
+		let mut file = std::fs::File::create(&self.output)?;
+		write_vouchers(&mut file, &self.format, &vouchers)?;
+
+		println!("Exported {} vouchers at {} to {}", vouchers.len(), at, self.output.display());
+
+		Ok(())
+	}
+}
+
+/// Block number, or block hash with or without `0x`
+fn parse_block_id(at: &str) -> Result<BlockId<Block>> {
+	match at.parse::<BlockNumber>() {
+		Ok(number) => Ok(BlockId::<Block>::number(number)),
+		Err(_) => {
+			let hash = at.trim_start_matches("0x").parse::<Hash>()
+				.map_err(|e| format!("Invalid block hash {}: {:?}", at, e))?;
+			Ok(BlockId::<Block>::hash(hash))
+		}
+	}
+}
+
+/// Write vouchers sorted by account in json or csv
+fn write_vouchers<W: Write>(writer: &mut W, format: &str, vouchers: &BTreeMap<String, Balance>) -> Result<()> {
+	match format {
+		"csv" => {
+			writeln!(writer, "account,balance")?;
+			for (who, balance) in vouchers {
+				writeln!(writer, "{},{}", who, balance)?;
+			}
+		}
+		_ => {
+			// same layout as bnc_vouchers.json, so a snapshot can be used for genesis
+			let vouchers: Vec<(&String, String)> = vouchers.iter()
+				.map(|(who, balance)| (who, balance.to_string()))
+				.collect();
+			serde_json::to_writer_pretty(writer, &vouchers)
+				.map_err(|e| format!("Failed to write vouchers: {}", e))?;
+		}
+	}
+
+	Ok(())
+}
+
+impl CliConfiguration for ExportVouchersCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn import_params(&self) -> Option<&ImportParams> {
+		Some(&self.import_params)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use structopt::StructOpt;
+
+	const ALICE: &str = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+	const BOB: &str = "5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty";
+
+	fn vouchers() -> BTreeMap<String, Balance> {
+		vec![(BOB.to_string(), 2), (ALICE.to_string(), 1_000_000_000_000)].into_iter().collect()
+	}
+
+	#[test]
+	fn export_vouchers_command_should_be_parsed() {
+		let cli = Cli::from_iter(&["bifrost", "export-vouchers", "--at", "10", "--format", "csv", "--output", "vouchers.csv"]);
+		match cli.subcommand {
+			Some(Subcommand::ExportVouchers(cmd)) => {
+				assert_eq!(cmd.at, Some("10".to_string()));
+				assert_eq!(cmd.format, "csv");
+				assert_eq!(cmd.output, std::path::PathBuf::from("vouchers.csv"));
+			}
+			_ => panic!("export-vouchers is expected"),
+		}
+
+		let cli = Cli::from_iter(&["bifrost", "export-vouchers"]);
+		match cli.subcommand {
+			Some(Subcommand::ExportVouchers(cmd)) => {
+				assert_eq!(cmd.at, None);
+				assert_eq!(cmd.format, "json");
+				assert_eq!(cmd.output, std::path::PathBuf::from("all_vouchers.json"));
+			}
+			_ => panic!("export-vouchers is expected"),
+		}
+
+		assert!(Cli::from_iter_safe(&["bifrost", "export-vouchers", "--format", "xml"]).is_err());
+	}
+
+	#[test]
+	fn block_id_should_be_parsed() {
+		assert_eq!(parse_block_id("10").unwrap(), BlockId::<Block>::number(10));
+
+		let hash = Hash::repeat_byte(0xab);
+		assert_eq!(parse_block_id(&format!("{:?}", hash)).unwrap(), BlockId::<Block>::hash(hash));
+		assert_eq!(parse_block_id(&format!("{:?}", hash)[2..]).unwrap(), BlockId::<Block>::hash(hash));
+
+		assert!(parse_block_id("best").is_err());
+	}
+
+	#[test]
+	fn vouchers_should_be_exported_to_csv() {
+		let mut output = Vec::new();
+		write_vouchers(&mut output, "csv", &vouchers()).unwrap();
+
+		assert_eq!(
+			String::from_utf8(output).unwrap(),
+			format!("account,balance\n{},2\n{},1000000000000\n", BOB, ALICE)
+		);
+	}
+
+	#[test]
+	fn vouchers_should_be_exported_to_json_as_genesis_vouchers() {
+		let mut output = Vec::new();
+		write_vouchers(&mut output, "json", &vouchers()).unwrap();
+
+		// the layout read by chain spec from bnc_vouchers.json
+		let exported: Vec<(String, String)> = serde_json::from_slice(&output).unwrap();
+		assert_eq!(exported, vec![
+			(BOB.to_string(), "2".to_string()),
+			(ALICE.to_string(), "1000000000000".to_string()),
+		]);
+	}
+}
@@ -31,7 +31,7 @@
 
 use std::{sync::Arc, fmt};
 
-use node_primitives::{Block, BlockNumber, AccountId, AssetId, ConvertPrice, Index, Balance, Hash};
+use node_primitives::{Block, BlockNumber, AccountId, AssetId, AssetSymbol, ConvertPrice, Index, Balance, Hash};
 use node_runtime::UncheckedExtrinsic;
 use sp_api::ProvideRuntimeApi;
 use sp_transaction_pool::TransactionPool;
@@ -100,6 +100,17 @@ pub fn create_full<C, P, M, SC>(
 	C::Api: pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance, UncheckedExtrinsic>,
 	C::Api: brml_assets_rpc::AssetsRuntimeApi<Block, AssetId, AccountId, Balance>,
 	C::Api: brml_convert_rpc::ConvertRateRuntimeApi<Block, AssetId, ConvertPrice>,
+	C::Api: brml_voucher_rpc::VoucherRuntimeApi<Block, AccountId, Balance>,
+	C::Api: brml_validator_rpc::ValidatorRuntimeApi<Block, AssetSymbol, AccountId, Balance>,
+	C::Api: brml_bridge_eos_rpc::BridgeEosRuntimeApi<
+		Block,
+		AccountId,
+		brml_bridge_eos::TxOutId,
+		brml_bridge_eos::TxOutStatus,
+		brml_bridge_eos::ActionReceipt,
+		brml_bridge_eos::ProducerScheduleInfo,
+		brml_bridge_eos::NotaryStatus,
+	>,
 	C::Api: BabeApi<Block>,
 	<C::Api as sp_api::ApiErrorExt>::Error: fmt::Debug,
 	P: TransactionPool + 'static,
@@ -153,7 +164,19 @@ pub fn create_full<C, P, M, SC>(
 	);
 	// register brml-convert rpc handler
 	io.extend_with(
-		brml_convert_rpc::ConvertPriceApi::to_delegate(brml_convert_rpc::Convert::new(client))
+		brml_convert_rpc::ConvertPriceApi::to_delegate(brml_convert_rpc::Convert::new(client.clone()))
+	);
+	// register brml-voucher rpc handler
+	io.extend_with(
+		brml_voucher_rpc::VoucherApi::to_delegate(brml_voucher_rpc::Voucher::new(client.clone()))
+	);
+	// register brml-validator rpc handler
+	io.extend_with(
+		brml_validator_rpc::ValidatorApi::to_delegate(brml_validator_rpc::Validator::new(client.clone()))
+	);
+	// register brml-bridge-eos rpc handler
+	io.extend_with(
+		brml_bridge_eos_rpc::BridgeEosApi::to_delegate(brml_bridge_eos_rpc::BridgeEos::new(client))
 	);
 
 	io
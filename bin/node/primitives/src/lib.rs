@@ -20,7 +20,8 @@
 
 use codec::{Encode, Decode};
 use sp_runtime::{
-	generic, traits::{Verify, BlakeTwo256, IdentifyAccount}, OpaqueExtrinsic, MultiSignature
+	generic, traits::{Verify, BlakeTwo256, IdentifyAccount}, DispatchError, DispatchResult,
+	OpaqueExtrinsic, MultiSignature
 };
 use sp_std::prelude::*;
 
@@ -59,6 +60,12 @@ pub type Price = u64;
 /// Precision of symbol.
 pub type Precision = u32;
 
+/// An index to a settlement period.
+pub type SettlementId = u32;
+
+/// Duration of balance, balance multiplied by blocks it is held.
+pub type Duration = u128;
+
 /// Type used for expressing timestamp.
 pub type Moment = u64;
 
@@ -202,6 +209,7 @@ pub trait ClearingHandler<AssetId, AccountId, BlockNumber, Balance> {
 	/// Clearing for assets change
 	fn asset_clearing(
 		asset_id: AssetId,
+		token_type: TokenType,
 		target: AccountId,
 		last_block: BlockNumber,
 		prev_amount: Balance,
@@ -211,6 +219,7 @@ pub trait ClearingHandler<AssetId, AccountId, BlockNumber, Balance> {
 	/// Clearing for token change
 	fn token_clearing(
 		asset_id: AssetId,
+		token_type: TokenType,
 		last_block: BlockNumber,
 		prev_amount: Balance,
 		curr_amount: Balance,
@@ -218,8 +227,8 @@ pub trait ClearingHandler<AssetId, AccountId, BlockNumber, Balance> {
 }
 
 impl<A, AC, BN, B> ClearingHandler<A, AC, BN, B> for () {
-	fn asset_clearing(_: A, _: AC, _: BN, _: B, _: B) {}
-	fn token_clearing(_: A, _: BN, _: B, _: B) {}
+	fn asset_clearing(_: A, _: TokenType, _: AC, _: BN, _: B, _: B) {}
+	fn token_clearing(_: A, _: TokenType, _: BN, _: B, _: B) {}
 }
 
 pub trait AssetTrait<AssetId, AccountId, Balance, Cost, Income> {
@@ -270,12 +279,14 @@ impl<Price> TokenPriceHandler<Price> for () {
 
 /// Asset redeem handler
 pub trait AssetRedeem<AssetId, AccountId, Balance> {
-	/// Asset redeem
-	fn asset_redeem(asset_id: AssetId, token_type: TokenType, target: AccountId, amount: Balance, to_name: Option<Vec<u8>>);
+	/// Redeem asset of the account to another blockchain, the handler takes the asset from the account
+	fn asset_redeem(asset_id: AssetId, token_type: TokenType, target: AccountId, amount: Balance, to_name: Option<Vec<u8>>) -> DispatchResult;
 }
 
 impl<A, AC, B> AssetRedeem<A, AC, B> for () {
-	fn asset_redeem(_: A, _: TokenType, _: AC, _: B, _: Option<Vec<u8>>) {}
+	fn asset_redeem(_: A, _: TokenType, _: AC, _: B, _: Option<Vec<u8>>) -> DispatchResult {
+		Err(DispatchError::Other("asset redeem is not supported"))
+	}
 }
 
 /// Fetch convert rate handler
@@ -289,10 +300,13 @@ impl<A, ER: Default> FetchConvertPrice<A, ER> for () {
 }
 
 /// Blockchain types
-#[derive(PartialEq, Debug, Clone, Encode, Decode)]
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(serde::Deserialize, serde::Serialize))]
 pub enum BlockchainType {
 	BIFROST,
 	EOS,
+	POLKADOT,
+	KUSAMA,
 }
 
 impl Default for BlockchainType {
@@ -302,7 +316,7 @@ impl Default for BlockchainType {
 }
 
 /// Symbol type of bridge asset
-#[derive(Clone, Default, Encode, Decode)]
+#[derive(Clone, Default, Eq, PartialEq, Debug, Encode, Decode)]
 pub struct BridgeAssetSymbol<Precision> {
 	pub blockchain: BlockchainType,
 	pub symbol: Vec<u8>,
@@ -351,6 +365,134 @@ impl<A, P, B> BridgeAssetTo<A, P, B> for () {
 	fn bridge_asset_to(_: Vec<u8>, _: BridgeAssetBalance<A, P, B>) -> Result<(), Self::Error> { Ok(()) }
 }
 
+/// Bridge of a blockchain, which is plugged into the bridge hub. Bridges are combined in a tuple,
+/// like `(BridgeEos, BridgeDot)`, the first one connecting the blockchain handles it.
+pub trait ChainBridge<AccountId, Precision, Balance> {
+	/// Whether this bridge connects the blockchain
+	fn connects(blockchain: BlockchainType) -> bool;
+
+	/// Whether the account is a valid address on the blockchain
+	fn is_valid_account(blockchain: BlockchainType, account: &[u8]) -> bool;
+
+	/// Whether the proof shows the account signed the message. Bridges of blockchains where
+	/// ownership cannot be proved on Bifrost accept binding the account without proof.
+	fn verify_account_proof(blockchain: BlockchainType, account: &[u8], message: &[u8], proof: Option<&[u8]>) -> bool;
+
+	/// Send the asset to the account on the blockchain of asset symbol
+	fn send_asset(target: Vec<u8>, bridge_asset: BridgeAssetBalance<AccountId, Precision, Balance>) -> DispatchResult;
+
+	/// Stake the asset locked by the bridge to the validator on the blockchain of asset symbol
+	fn stake(validator: Vec<u8>, bridge_asset: BridgeAssetBalance<AccountId, Precision, Balance>) -> DispatchResult;
+
+	/// Un-stake the asset staked by the bridge from the validator on the blockchain of asset symbol
+	fn unstake(validator: Vec<u8>, bridge_asset: BridgeAssetBalance<AccountId, Precision, Balance>) -> DispatchResult;
+}
+
+impl<A, P, B> ChainBridge<A, P, B> for () {
+	fn connects(_: BlockchainType) -> bool { false }
+
+	fn is_valid_account(_: BlockchainType, _: &[u8]) -> bool { false }
+
+	fn verify_account_proof(_: BlockchainType, _: &[u8], _: &[u8], _: Option<&[u8]>) -> bool { false }
+
+	fn send_asset(_: Vec<u8>, _: BridgeAssetBalance<A, P, B>) -> DispatchResult {
+		Err(DispatchError::Other("no bridge connects the blockchain"))
+	}
+
+	fn stake(_: Vec<u8>, _: BridgeAssetBalance<A, P, B>) -> DispatchResult {
+		Err(DispatchError::Other("no bridge connects the blockchain"))
+	}
+
+	fn unstake(_: Vec<u8>, _: BridgeAssetBalance<A, P, B>) -> DispatchResult {
+		Err(DispatchError::Other("no bridge connects the blockchain"))
+	}
+}
+
+macro_rules! impl_chain_bridge_for_tuple {
+	($($bridge:ident),+) => {
+		impl<A, P, B, $($bridge: ChainBridge<A, P, B>),+> ChainBridge<A, P, B> for ($($bridge,)+) {
+			fn connects(blockchain: BlockchainType) -> bool {
+				$($bridge::connects(blockchain))||+
+			}
+
+			fn is_valid_account(blockchain: BlockchainType, account: &[u8]) -> bool {
+				$(
+					if $bridge::connects(blockchain) {
+						return $bridge::is_valid_account(blockchain, account);
+					}
+				)+
+				false
+			}
+
+			fn verify_account_proof(blockchain: BlockchainType, account: &[u8], message: &[u8], proof: Option<&[u8]>) -> bool {
+				$(
+					if $bridge::connects(blockchain) {
+						return $bridge::verify_account_proof(blockchain, account, message, proof);
+					}
+				)+
+				false
+			}
+
+			fn send_asset(target: Vec<u8>, bridge_asset: BridgeAssetBalance<A, P, B>) -> DispatchResult {
+				$(
+					if $bridge::connects(bridge_asset.symbol.blockchain) {
+						return $bridge::send_asset(target, bridge_asset);
+					}
+				)+
+				<() as ChainBridge<A, P, B>>::send_asset(target, bridge_asset)
+			}
+
+			fn stake(validator: Vec<u8>, bridge_asset: BridgeAssetBalance<A, P, B>) -> DispatchResult {
+				$(
+					if $bridge::connects(bridge_asset.symbol.blockchain) {
+						return $bridge::stake(validator, bridge_asset);
+					}
+				)+
+				<() as ChainBridge<A, P, B>>::stake(validator, bridge_asset)
+			}
+
+			fn unstake(validator: Vec<u8>, bridge_asset: BridgeAssetBalance<A, P, B>) -> DispatchResult {
+				$(
+					if $bridge::connects(bridge_asset.symbol.blockchain) {
+						return $bridge::unstake(validator, bridge_asset);
+					}
+				)+
+				<() as ChainBridge<A, P, B>>::unstake(validator, bridge_asset)
+			}
+		}
+	}
+}
+
+impl_chain_bridge_for_tuple!(B1);
+impl_chain_bridge_for_tuple!(B1, B2);
+impl_chain_bridge_for_tuple!(B1, B2, B3);
+impl_chain_bridge_for_tuple!(B1, B2, B3, B4);
+
+/// Staking assets to validators on the blockchains they are bridged from
+pub trait BridgeStake<AssetId, AccountId, Balance> {
+	/// Stake the amount of asset to the validator address on the blockchain, on behalf of the validator account
+	fn bridge_stake(asset_id: AssetId, validator: AccountId, validator_address: Vec<u8>, amount: Balance) -> DispatchResult;
+
+	/// Un-stake the amount of asset from the validator address on the blockchain
+	fn bridge_unstake(asset_id: AssetId, validator: AccountId, validator_address: Vec<u8>, amount: Balance) -> DispatchResult;
+}
+
+impl<A, AC, B> BridgeStake<A, AC, B> for () {
+	fn bridge_stake(_: A, _: AC, _: Vec<u8>, _: B) -> DispatchResult { Ok(()) }
+
+	fn bridge_unstake(_: A, _: AC, _: Vec<u8>, _: B) -> DispatchResult { Ok(()) }
+}
+
+/// Assets locked in a pool, which are staked to validators
+pub trait StakingPool<AssetId, Balance> {
+	/// How much of the asset can be staked
+	fn staking_pool(asset_id: AssetId) -> Balance;
+}
+
+impl<A, B: Default> StakingPool<A, B> for () {
+	fn staking_pool(_: A) -> B { Default::default() }
+}
+
 pub trait AssetReward<AssetId, Balance> {
 	fn set_asset_reward(token_id: AssetId, reward: Balance) -> Result<(), ()>;
 }
@@ -37,7 +37,7 @@ use sp_core::{
 pub use node_primitives::{AccountId, Signature};
 use node_primitives::{
 	AccountIndex, Balance, BlockNumber, Cost, Hash, Income, Index, Moment, Price,
-	BridgeAssetTo, AssetId, Precision, TokenType, ConvertPrice, RatePerBlock
+	BridgeAssetTo, AssetId, Precision, TokenType, ConvertPrice, RatePerBlock, SettlementId, Duration, AssetSymbol,
 };
 use sp_api::impl_runtime_apis;
 use sp_runtime::{
@@ -56,7 +56,7 @@ use sp_version::NativeVersion;
 use pallet_grandpa::{AuthorityId as GrandpaId, AuthorityList as GrandpaAuthorityList};
 use pallet_grandpa::fg_primitives;
 use pallet_im_online::sr25519::AuthorityId as ImOnlineId;
-use brml_bridge_eos::sr25519::{AuthorityId as BridgeEosId};
+use brml_bridge_eos::sr25519::BridgeEosAuthId;
 use sp_authority_discovery::AuthorityId as AuthorityDiscoveryId;
 use pallet_transaction_payment_rpc_runtime_api::RuntimeDispatchInfo;
 use pallet_session::{historical as pallet_session_historical};
@@ -680,13 +680,16 @@ impl brml_assets::Trait for Runtime {
 	type Cost = Cost;
 	type Income = Income;
 	type Convert = ConvertPrice;
-	type AssetRedeem = ();
+	type AssetRedeem = Bridge;
 	type FetchConvertPrice = Convert;
+	type ClearingHandler = Settlement;
 }
 
 impl brml_voucher::Trait for Runtime {
 	type Event = Event;
 	type Balance = Balance;
+	type Currency = Balances;
+	type VestingSchedule = Vesting;
 }
 
 parameter_types! {
@@ -707,18 +710,85 @@ impl brml_convert::Trait for Runtime {
 
 //type BridgeSubmitTransaction = TransactionSubmitter<BridgeEosId, Runtime, UncheckedExtrinsic>;
 
+parameter_types! {
+	pub const BridgeTxOutRetention: BlockNumber = 7 * DAYS;
+	pub const BridgeTxOutExpiry: BlockNumber = 1 * HOURS;
+	pub const BridgeEosRelayPotId: ModuleId = ModuleId(*b"bf/eosrl");
+}
+
+impl brml_bridge::Trait for Runtime {
+	type Event = Event;
+	type Balance = Balance;
+	type AssetId = AssetId;
+	type Precision = Precision;
+	type Cost = Cost;
+	type Income = Income;
+	type AssetTrait = Assets;
+	type ChainBridges = (BridgeEos, BridgeDot);
+	type BridgeAssetFrom = ();
+}
+
+impl brml_bridge_dot::Trait for Runtime {
+	type Event = Event;
+	type Balance = Balance;
+	type AssetId = AssetId;
+	type Precision = Precision;
+	type Cost = Cost;
+	type Income = Income;
+	type AssetTrait = Assets;
+	type BridgeAssetFrom = Bridge;
+}
+
 impl brml_bridge_eos::Trait for Runtime {
-	type AuthorityId = BridgeEosId;
+	type AuthorityId = BridgeEosAuthId;
 	type Event = Event;
 	type Balance = Balance;
 	type AssetId = AssetId;
 	type Cost = Cost;
 	type Income = Income;
 	type Precision = Precision;
-	type BridgeAssetFrom = ();
+	type BridgeAssetFrom = Bridge;
 	type Call = Call;
 //	type SubmitTransaction = BridgeSubmitTransaction;
 	type AssetTrait = Assets;
+	type TxOutRetention = BridgeTxOutRetention;
+	type TxOutExpiry = BridgeTxOutExpiry;
+	type Currency = Balances;
+	type RelayPotId = BridgeEosRelayPotId;
+}
+
+parameter_types! {
+	pub const SettlementPeriod: BlockNumber = 24 * 60 * MINUTES;
+	pub const MaxClearingsPerBlock: u32 = 500;
+	pub const SettlementRewardPotId: ModuleId = ModuleId(*b"bf/stlrw");
+}
+
+impl brml_settlement::Trait for Runtime {
+	type Event = Event;
+	type SettlementId = SettlementId;
+	type SettlementPeriod = SettlementPeriod;
+	type Duration = Duration;
+	type MaxClearingsPerBlock = MaxClearingsPerBlock;
+	type RewardPotId = SettlementRewardPotId;
+}
+
+parameter_types! {
+	pub const ValidatorEraLength: BlockNumber = 1 * DAYS;
+	pub const MaxValidatorsPerAsset: u32 = 100;
+}
+
+impl brml_validator::Trait for Runtime {
+	type Event = Event;
+	type Balance = Balance;
+	type AssetId = AssetId;
+	type Cost = Cost;
+	type Income = Income;
+	type AssetTrait = Assets;
+	type BridgeStake = Bridge;
+	type StakingPool = Convert;
+	type AssetReward = Settlement;
+	type EraLength = ValidatorEraLength;
+	type MaxValidators = MaxValidatorsPerAsset;
 }
 
 impl brml_swap::Trait for Runtime {
@@ -734,14 +804,30 @@ impl brml_swap::Trait for Runtime {
 	type Income = Income;
 }
 
+parameter_types! {
+	pub const OracleFetchPeriod: BlockNumber = 10 * MINUTES;
+}
+
 impl brml_oracle::Trait for Runtime {
 	type Event = Event;
 	type Price = Price;
 	type TokenPriceHandler = Assets;
+	type ChainlinkCallback = brml_oracle::Call<Runtime>;
+	type AuthorityId = brml_oracle::crypto::OracleAuthId;
+	type FetchPeriod = OracleFetchPeriod;
+}
+
+parameter_types! {
+	pub const ChainlinkValidityPeriod: BlockNumber = 50;
+	pub const ChainlinkMaxCallbackWeight: Weight = WEIGHT_PER_SECOND / 1_000;
 }
 
 impl chainlink::Trait for Runtime {
 	type Event = Event;
+	type Currency = Balances;
+	type Callback = brml_oracle::Call<Runtime>;
+	type ValidityPeriod = ChainlinkValidityPeriod;
+	type MaxCallbackWeight = ChainlinkMaxCallbackWeight;
 }
 
 // bifrost rumtine time end
@@ -784,11 +870,15 @@ construct_runtime!(
 		// Modules from brml
 		Assets: brml_assets::{Module, Call, Storage, Event<T>, Config<T>},
 		Convert: brml_convert::{Module, Call, Storage, Event},
-		BridgeEos: brml_bridge_eos::{Module, Call, Storage, Event<T>, ValidateUnsigned, Config<T>},
+		Bridge: brml_bridge::{Module, Call, Storage, Event<T>, Config<T>},
+		BridgeEos: brml_bridge_eos::{Module, Call, Storage, Event<T>, Config<T>},
+		BridgeDot: brml_bridge_dot::{Module, Call, Storage, Event<T>},
 		Swap: brml_swap::{Module, Call, Storage, Event},
+		Settlement: brml_settlement::{Module, Call, Storage, Event<T>},
+		Validator: brml_validator::{Module, Call, Storage, Event<T>},
 		Voucher: brml_voucher::{Module, Call, Storage, Event<T>, Config<T>},
 		// chainlink
-		Oracle: brml_oracle::{Module, Call, Storage},
+		Oracle: brml_oracle::{Module, Call, Storage, Event<T>},
 		Chainlink: chainlink::{Module, Call, Storage, Event<T>},
 	}
 );
@@ -984,9 +1074,11 @@ impl_runtime_apis! {
 			// we need these two lines below.
 			use pallet_session_benchmarking::Module as SessionBench;
 			use pallet_offences_benchmarking::Module as OffencesBench;
+			use frame_system_benchmarking::Module as SystemBench;
 
 			impl pallet_session_benchmarking::Trait for Runtime {}
 			impl pallet_offences_benchmarking::Trait for Runtime {}
+			impl frame_system_benchmarking::Trait for Runtime {}
 
 			let mut batches = Vec::<BenchmarkBatch>::new();
 			let params = (&pallet, &benchmark, &lowest_range_values, &highest_range_values, &steps, repeat);
@@ -1005,6 +1097,9 @@ impl_runtime_apis! {
 			add_benchmark!(params, batches, b"treasury", Treasury);
 			add_benchmark!(params, batches, b"utility", Utility);
 			add_benchmark!(params, batches, b"vesting", Vesting);
+			// EOS fixtures are decoded by serde, so bridge-eos is benchmarked by native runtime only
+			#[cfg(feature = "std")]
+			add_benchmark!(params, batches, b"bridge-eos", BridgeEos);
 
 			if batches.is_empty() { return Err("Benchmark not found for this pallet.".into()) }
 			Ok(batches)
@@ -1027,6 +1122,48 @@ impl_runtime_apis! {
 			Convert::get_convert(vtoken_id)
 		}
 	}
+
+	impl brml_bridge_eos_rpc_runtime_api::BridgeEosApi<
+		node_primitives::Block,
+		AccountId,
+		brml_bridge_eos::TxOutId,
+		brml_bridge_eos::TxOutStatus,
+		brml_bridge_eos::ActionReceipt,
+		brml_bridge_eos::ProducerScheduleInfo,
+		brml_bridge_eos::NotaryStatus,
+	> for Runtime {
+		fn tx_out_status(id: brml_bridge_eos::TxOutId) -> Option<brml_bridge_eos::TxOutStatus> {
+			BridgeEos::tx_out_status(id)
+		}
+
+		fn producer_schedules() -> (Option<brml_bridge_eos::ProducerScheduleInfo>, Option<brml_bridge_eos::ProducerScheduleInfo>) {
+			BridgeEos::producer_schedules()
+		}
+
+		fn is_action_proven(action_receipt: brml_bridge_eos::ActionReceipt) -> bool {
+			BridgeEos::is_action_proven(&action_receipt)
+		}
+
+		fn account_tx_outs(who: AccountId) -> Vec<(brml_bridge_eos::TxOutId, brml_bridge_eos::TxOutStatus)> {
+			BridgeEos::account_tx_outs(&who)
+		}
+
+		fn notary_status(who: AccountId) -> brml_bridge_eos::NotaryStatus {
+			BridgeEos::notary_status(&who)
+		}
+	}
+
+	impl brml_voucher_rpc_runtime_api::VoucherApi<node_primitives::Block, AccountId, Balance> for Runtime {
+		fn vouchers(start: u32, count: u32) -> Vec<(AccountId, Balance)> {
+			Voucher::vouchers(start, count)
+		}
+	}
+
+	impl brml_validator_rpc_runtime_api::ValidatorApi<node_primitives::Block, AssetSymbol, AccountId, Balance> for Runtime {
+		fn allocation_plan(asset_symbol: AssetSymbol) -> Vec<(AccountId, Balance, Balance)> {
+			Validator::allocation_plan(asset_symbol)
+		}
+	}
 }
 
 #[cfg(test)]
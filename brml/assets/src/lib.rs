@@ -24,7 +24,8 @@ use sp_runtime::traits::{Member, AtLeast32Bit, Saturating, One, Zero, StaticLook
 use sp_std::prelude::*;
 use system::{ensure_signed, ensure_root};
 use node_primitives::{
-	AccountAsset, AssetRedeem, AssetTrait, AssetSymbol, FetchConvertPrice, Token, TokenPair, TokenPriceHandler, TokenType,
+	AccountAsset, AssetRedeem, AssetTrait, AssetSymbol, ClearingHandler, FetchConvertPrice, Token, TokenPair,
+	TokenPriceHandler, TokenType,
 };
 
 mod mock;
@@ -73,6 +74,9 @@ pub trait Trait: system::Trait {
 
 	/// Handler for fetch convert rate from convert runtime
 	type FetchConvertPrice: FetchConvertPrice<Self::AssetId, Self::Convert>;
+
+	/// Handler for clearing balances and total supply when they change
+	type ClearingHandler: ClearingHandler<Self::AssetId, Self::AccountId, Self::BlockNumber, Self::Balance>;
 }
 
 decl_event! {
@@ -258,9 +262,7 @@ decl_module! {
 			let balance = <AccountAssets<T>>::get(&origin_account).balance;
 			ensure!(amount <= balance , Error::<T>::InvalidBalanceForTransaction);
 
-			T::AssetRedeem::asset_redeem(id, token_type, origin.clone(), amount, to_name);
-
-			Self::asset_destroy(id, token_type, origin, amount);
+			T::AssetRedeem::asset_redeem(id, token_type, origin, amount, to_name)?;
 		}
 	}
 }
@@ -300,10 +302,12 @@ impl<T: Trait> AssetTrait<T::AssetId, T::AccountId, T::Balance, T::Cost, T::Inco
 	) {
 		let convert_rate = T::FetchConvertPrice::fetch_convert_price(asset_id);
 		let target_asset = (asset_id, token_type, target.clone());
+		let prev_balance = <AccountAssets<T>>::get(&target_asset).balance;
 		<AccountAssets<T>>::mutate(&target_asset, |asset| {
 			asset.balance = asset.balance.saturating_add(amount);
 			asset.cost = asset.cost.saturating_add(amount.saturating_mul(convert_rate.into()).into());
 		});
+		Self::asset_clearing(asset_id, token_type, target.clone(), prev_balance);
 
 		// save asset id for this account
 		if <AccountAssetIds<T>>::contains_key(&target) {
@@ -316,6 +320,7 @@ impl<T: Trait> AssetTrait<T::AssetId, T::AccountId, T::Balance, T::Cost, T::Inco
 			<AccountAssetIds<T>>::insert(&target, vec![asset_id]);
 		}
 
+		let prev_supply = Self::total_supply(asset_id, token_type);
 		<Tokens<T>>::mutate(asset_id, |token| {
 			match token_type {
 				TokenType::Token => {
@@ -326,6 +331,7 @@ impl<T: Trait> AssetTrait<T::AssetId, T::AccountId, T::Balance, T::Cost, T::Inco
 				}
 			}
 		});
+		Self::token_clearing(asset_id, token_type, prev_supply);
 	}
 
 	fn asset_redeem(
@@ -344,12 +350,15 @@ impl<T: Trait> AssetTrait<T::AssetId, T::AccountId, T::Balance, T::Cost, T::Inco
 		amount: T::Balance,
 	) {
 		let convert_rate = T::FetchConvertPrice::fetch_convert_price(asset_id);
-		let target_asset = (asset_id, token_type, target);
+		let target_asset = (asset_id, token_type, target.clone());
+		let prev_balance = <AccountAssets<T>>::get(&target_asset).balance;
 		<AccountAssets<T>>::mutate(&target_asset, |asset| {
 			asset.balance = asset.balance.saturating_sub(amount);
 			asset.income = asset.income.saturating_add(amount.saturating_mul(convert_rate.into()).into());
 		});
+		Self::asset_clearing(asset_id, token_type, target, prev_balance);
 
+		let prev_supply = Self::total_supply(asset_id, token_type);
 		<Tokens<T>>::mutate(asset_id, |token| {
 			match token_type {
 				TokenType::Token => {
@@ -360,6 +369,7 @@ impl<T: Trait> AssetTrait<T::AssetId, T::AccountId, T::Balance, T::Cost, T::Inco
 				}
 			}
 		});
+		Self::token_clearing(asset_id, token_type, prev_supply);
 	}
 
 	fn asset_id_exists(who: &T::AccountId, symbol: &[u8], precision: u16) -> Option<T::AssetId> {
@@ -401,22 +411,27 @@ impl<T: Trait> TokenPriceHandler<T::Price> for Module<T> {
 
 // The main implementation block for the module.
 impl<T: Trait> Module<T> {
-	fn asset_transfer(
+	/// Move assets between accounts, the balance of `from` is checked by the caller.
+	pub fn asset_transfer(
 		asset_id: T::AssetId,
 		token_type: TokenType,
 		from: T::AccountId,
 		to: T::AccountId,
 		amount: T::Balance,
 	) {
-		let from_asset = (asset_id, token_type, from);
+		let from_asset = (asset_id, token_type, from.clone());
+		let prev_from_balance = <AccountAssets<T>>::get(&from_asset).balance;
 		<AccountAssets<T>>::mutate(&from_asset, |asset| {
 			asset.balance = asset.balance.saturating_sub(amount);
 		});
+		Self::asset_clearing(asset_id, token_type, from, prev_from_balance);
 
-		let to_asset = (asset_id, token_type, &to);
-		<AccountAssets<T>>::mutate(to_asset, |asset| {
+		let to_asset = (asset_id, token_type, to.clone());
+		let prev_to_balance = <AccountAssets<T>>::get(&to_asset).balance;
+		<AccountAssets<T>>::mutate(&to_asset, |asset| {
 			asset.balance = asset.balance.saturating_add(amount);
 		});
+		Self::asset_clearing(asset_id, token_type, to.clone(), prev_to_balance);
 
 		// save asset id for this account
 		if <AccountAssetIds<T>>::contains_key(&to) {
@@ -429,6 +444,28 @@ impl<T: Trait> Module<T> {
 		}
 	}
 
+	fn total_supply(asset_id: T::AssetId, token_type: TokenType) -> T::Balance {
+		let token_pair = <Tokens<T>>::get(asset_id);
+		match token_type {
+			TokenType::Token => token_pair.token.total_supply,
+			TokenType::VToken => token_pair.vtoken.total_supply,
+		}
+	}
+
+	/// Report the balance change of the account to clearing handler
+	fn asset_clearing(asset_id: T::AssetId, token_type: TokenType, target: T::AccountId, prev_balance: T::Balance) {
+		let now_block = <system::Module<T>>::block_number();
+		let curr_balance = <AccountAssets<T>>::get((asset_id, token_type, &target)).balance;
+		T::ClearingHandler::asset_clearing(asset_id, token_type, target, now_block, prev_balance, curr_balance);
+	}
+
+	/// Report the total supply change of the token to clearing handler
+	fn token_clearing(asset_id: T::AssetId, token_type: TokenType, prev_supply: T::Balance) {
+		let now_block = <system::Module<T>>::block_number();
+		let curr_supply = Self::total_supply(asset_id, token_type);
+		T::ClearingHandler::token_clearing(asset_id, token_type, now_block, prev_supply, curr_supply);
+	}
+
 	pub fn asset_balances(asset_id: T::AssetId, token_type: TokenType, target: T::AccountId) -> u64 {
 		let origin_account = (asset_id, token_type, target);
 		let balance_u128 = <AccountAssets<T>>::get(origin_account).balance;
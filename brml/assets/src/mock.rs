@@ -76,6 +76,7 @@ impl Trait for Test {
 	type Convert = u128;
 	type AssetRedeem = ();
 	type FetchConvertPrice = ();
+	type ClearingHandler = ();
 }
 
 mod assets {
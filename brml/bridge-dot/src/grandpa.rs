@@ -0,0 +1,185 @@
+// Copyright 2019-2020 Liebi Technologies.
+// This file is part of Bifrost.
+
+// Bifrost is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Bifrost is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Bifrost.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Verification of GRANDPA justifications of relay chain headers.
+//!
+//! The types mirror `finality_grandpa` and `sc_finality_grandpa` so that justifications fetched
+//! from a relay chain node decode as they are.
+
+use codec::{Decode, Encode};
+use sp_application_crypto::RuntimeAppPublic;
+use sp_core::H256;
+use sp_finality_grandpa::{
+	AuthorityId, AuthorityList, AuthoritySignature, ConsensusLog, ScheduledChange, SetId, GRANDPA_ENGINE_ID,
+};
+use sp_runtime::{generic::OpaqueDigestItemId, traits::Header as HeaderT};
+use sp_std::{collections::btree_map::BTreeMap, prelude::*};
+
+use crate::{RelayBlockNumber, RelayHeader};
+
+/// Index of precommit variant in `finality_grandpa::Message`
+const PRECOMMIT_MESSAGE_INDEX: u8 = 1;
+
+/// A precommit for a block and its ancestors
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+pub struct Precommit {
+	pub target_hash: H256,
+	pub target_number: RelayBlockNumber,
+}
+
+/// A precommit signed by a voter
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+pub struct SignedPrecommit {
+	pub precommit: Precommit,
+	pub signature: AuthoritySignature,
+	pub id: AuthorityId,
+}
+
+/// Precommits of a round, which finalize the target block
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+pub struct Commit {
+	pub target_hash: H256,
+	pub target_number: RelayBlockNumber,
+	pub precommits: Vec<SignedPrecommit>,
+}
+
+/// Justification of a finalized block, with headers between the block and the precommit targets
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+pub struct GrandpaJustification {
+	pub round: u64,
+	pub commit: Commit,
+	pub votes_ancestries: Vec<RelayHeader>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum JustificationError {
+	/// The justification cannot be decoded
+	InvalidEncoding,
+	/// The justification finalizes another block
+	TargetMismatch,
+	/// The authority set is empty
+	EmptyAuthoritySet,
+	/// A precommit is signed by a voter outside the authority set
+	UnknownAuthority,
+	/// A voter signed more than one precommit
+	DuplicateVote,
+	/// A precommit signature is invalid for the round and set id
+	InvalidSignature,
+	/// A precommit target isn't a descendant of the finalized block
+	NotDescendant,
+	/// The precommits don't reach the supermajority of the authority set
+	InsufficientWeight,
+}
+
+/// Message signed by a voter for the precommit in the round of the authority set
+pub fn precommit_payload(precommit: &Precommit, round: u64, set_id: SetId) -> Vec<u8> {
+	(PRECOMMIT_MESSAGE_INDEX, precommit, round, set_id).encode()
+}
+
+/// Verify that the encoded justification finalizes the block by the authority set
+pub fn verify_justification(
+	hash: H256,
+	number: RelayBlockNumber,
+	set_id: SetId,
+	authorities: &AuthorityList,
+	encoded: &[u8],
+) -> Result<(), JustificationError> {
+	let justification = GrandpaJustification::decode(&mut &encoded[..])
+		.map_err(|_| JustificationError::InvalidEncoding)?;
+	let commit = &justification.commit;
+	if commit.target_hash != hash || commit.target_number != number {
+		return Err(JustificationError::TargetMismatch);
+	}
+
+	let total_weight = authorities.iter().fold(0u64, |sum, (_, weight)| sum.saturating_add(*weight));
+	if total_weight == 0 {
+		return Err(JustificationError::EmptyAuthoritySet);
+	}
+	// more than 2/3 of the authority set
+	let threshold = total_weight - (total_weight - 1) / 3;
+
+	let ancestry = justification.votes_ancestries.iter()
+		.map(|header| (header.hash(), (*header.parent_hash(), *header.number())))
+		.collect::<BTreeMap<_, _>>();
+
+	let mut voters: Vec<&AuthorityId> = Vec::new();
+	let mut weight = 0u64;
+	for signed in commit.precommits.iter() {
+		let voter_weight = authorities.iter()
+			.find(|(id, _)| *id == signed.id)
+			.map(|(_, weight)| *weight)
+			.ok_or(JustificationError::UnknownAuthority)?;
+		if voters.contains(&&signed.id) {
+			return Err(JustificationError::DuplicateVote);
+		}
+		voters.push(&signed.id);
+
+		let payload = precommit_payload(&signed.precommit, justification.round, set_id);
+		if !signed.id.verify(&payload, &signed.signature) {
+			return Err(JustificationError::InvalidSignature);
+		}
+
+		let precommit = &signed.precommit;
+		if !descends_from(&ancestry, precommit.target_hash, precommit.target_number, hash, number) {
+			return Err(JustificationError::NotDescendant);
+		}
+
+		weight = weight.saturating_add(voter_weight);
+	}
+
+	if weight < threshold {
+		return Err(JustificationError::InsufficientWeight);
+	}
+
+	Ok(())
+}
+
+/// Whether the block is the base block or its descendant, walking through the ancestry
+fn descends_from(
+	ancestry: &BTreeMap<H256, (H256, RelayBlockNumber)>,
+	mut hash: H256,
+	mut number: RelayBlockNumber,
+	base_hash: H256,
+	base_number: RelayBlockNumber,
+) -> bool {
+	loop {
+		if hash == base_hash && number == base_number {
+			return true;
+		}
+		if number <= base_number {
+			return false;
+		}
+		match ancestry.get(&hash) {
+			Some((parent_hash, header_number)) if *header_number == number => {
+				hash = *parent_hash;
+				number -= 1;
+			}
+			_ => return false,
+		}
+	}
+}
+
+/// Authority set change signalled by the header, and whether it's forced
+pub fn find_authority_set_change(header: &RelayHeader) -> Option<(ScheduledChange<RelayBlockNumber>, bool)> {
+	let id = OpaqueDigestItemId::Consensus(&GRANDPA_ENGINE_ID);
+	header.digest().convert_first(|log| {
+		log.try_to(id).and_then(|log: ConsensusLog<RelayBlockNumber>| match log {
+			ConsensusLog::ScheduledChange(change) => Some((change, false)),
+			ConsensusLog::ForcedChange(_, change) => Some((change, true)),
+			_ => None,
+		})
+	})
+}
@@ -0,0 +1,712 @@
+// Copyright 2019-2020 Liebi Technologies.
+// This file is part of Bifrost.
+
+// Bifrost is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Bifrost is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Bifrost.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Bridge of Polkadot and Kusama.
+//!
+//! Relay chain headers finalized by GRANDPA are imported with their justifications and the headers
+//! skipped since the best finalized one, following authority set changes signalled by any of them.
+//! Every account is assigned a deposit account on the relay chain, which is a derivative account of
+//! the bridge multisig account. Deposits are proved by storage proofs of the deposit account balance
+//! against imported headers, and DOT or KSM is issued on Bifrost.
+//!
+//! Withdrawals and sweeps of deposit accounts are executed by the multisig on the relay chain,
+//! notaries approve them on Bifrost before they're executed, and report the results afterwards.
+//!
+//! Relayers take justifications from `chain_getBlock` and storage proofs from `state_getReadProof`
+//! of a relay chain node, which works with a local `--dev` relay chain as well.
+
+// Ensure we're `no_std` when compiling for Wasm.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{Decode, Encode};
+use frame_support::{
+	decl_event, decl_error, decl_module, decl_storage, ensure, Parameter,
+	dispatch::DispatchResult,
+	weights::{DispatchClass, FunctionOf, Pays, Weight},
+};
+use frame_system::{self as system, ensure_root, ensure_signed};
+use sp_core::{sr25519, H256};
+use sp_finality_grandpa::{AuthorityList, SetId};
+use sp_runtime::{
+	generic,
+	traits::{AtLeast32Bit, BlakeTwo256, Header as HeaderT, Member, SaturatedConversion, Zero},
+};
+use sp_std::prelude::*;
+
+use node_primitives::{
+	AssetTrait, BlockchainType, BridgeAssetBalance, BridgeAssetFrom, BridgeAssetSymbol, ChainBridge, TokenType,
+};
+
+pub mod grandpa;
+pub mod relay;
+mod mock;
+mod tests;
+
+pub use relay::RelayAccountId;
+
+/// Block number of relay chains
+pub type RelayBlockNumber = u32;
+
+/// Header of relay chains
+pub type RelayHeader = generic::Header<RelayBlockNumber, BlakeTwo256>;
+
+/// How many finalized headers are kept for proving deposits
+pub const FINALIZED_HEADERS_HISTORY: usize = 256;
+
+/// Base weight of importing a header, besides verifying its justification
+const IMPORT_HEADER_WEIGHT: Weight = 50_000_000;
+
+/// Weight of verifying a byte of justification, which is mostly signatures
+const JUSTIFICATION_BYTE_WEIGHT: Weight = 10_000;
+
+/// Weight of hashing a skipped header and reading its digest
+const ANCESTRY_HEADER_WEIGHT: Weight = 1_000_000;
+
+/// Base weight of proving a deposit, besides reading its storage proof
+const PROVE_DEPOSIT_WEIGHT: Weight = 50_000_000;
+
+/// Weight of reading a byte of storage proof
+const PROOF_BYTE_WEIGHT: Weight = 1_000;
+
+/// Light client and custody state of a relay chain
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+pub struct RelayChain<AssetId> {
+	/// Asset on Bifrost for the native token
+	pub asset_id: AssetId,
+	/// Multisig account holding assets on the relay chain
+	pub bridge_account: RelayAccountId,
+	/// Number and hash of the best finalized header
+	pub best_finalized: (RelayBlockNumber, H256),
+	/// Id of the current authority set
+	pub set_id: SetId,
+	/// Current authority set
+	pub authorities: AuthorityList,
+	/// Authority set change signalled by a finalized header
+	pub pending_change: Option<PendingChange>,
+}
+
+/// Authority set change which isn't enacted yet
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+pub struct PendingChange {
+	/// Headers after the block number are finalized by the next authority set
+	pub enacted_at: RelayBlockNumber,
+	/// Next authority set
+	pub next_authorities: AuthorityList,
+	/// Forced changes are enacted without the block being finalized by the current authority set
+	pub forced: bool,
+}
+
+/// Deposit account of an account on Bifrost
+#[derive(Encode, Decode, Clone, Default, PartialEq, Eq, Debug)]
+pub struct DepositAccount<AccountId> {
+	/// Account on Bifrost to issue deposits to
+	pub owner: AccountId,
+	/// Derivative index of the bridge account
+	pub index: u16,
+	/// Block number of the latest proved balance
+	pub proved_at: RelayBlockNumber,
+	/// Total amount credited on Bifrost
+	pub credited: u128,
+	/// Total amount swept to the bridge account
+	pub swept: u128,
+	/// Block number of the latest sweep executed on the relay chain
+	pub swept_at: RelayBlockNumber,
+	/// Amount of sweeps which are not finished yet
+	pub sweeping: u128,
+}
+
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+pub enum WithdrawalKind<AccountId, Balance> {
+	/// Redeem asset of the account, the escrowed balance is refunded if it fails
+	Redeem(AccountId, Balance),
+	/// Sweep the deposit account into the bridge account
+	Sweep(RelayAccountId),
+}
+
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WithdrawalStatus {
+	/// Waiting for approvals of notaries
+	Pending,
+	/// Approved by notaries, it's being executed by the multisig
+	Approved,
+	/// Executed on the relay chain
+	Completed,
+	/// Failed to be executed on the relay chain
+	Failed,
+}
+
+/// Transfer from a multisig controlled account on the relay chain
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+pub struct Withdrawal<AccountId, Balance> {
+	pub blockchain: BlockchainType,
+	pub kind: WithdrawalKind<AccountId, Balance>,
+	pub to: RelayAccountId,
+	pub amount: u128,
+	pub approvals: Vec<AccountId>,
+	pub status: WithdrawalStatus,
+}
+
+/// The module configuration trait.
+pub trait Trait: frame_system::Trait {
+	/// The overarching event type.
+	type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
+
+	/// The units in which we record balances.
+	type Balance: Member + Parameter + AtLeast32Bit + Default + Copy;
+
+	/// The arithmetic type of asset identifier.
+	type AssetId: Member + Parameter + AtLeast32Bit + Default + Copy;
+
+	/// The units in which we record asset precision.
+	type Precision: Member + Parameter + AtLeast32Bit + Default + Copy;
+
+	/// The units in which we record costs.
+	type Cost: Member + Parameter + AtLeast32Bit + Default + Copy;
+
+	/// The units in which we record incomes.
+	type Income: Member + Parameter + AtLeast32Bit + Default + Copy;
+
+	/// Assets on Bifrost
+	type AssetTrait: AssetTrait<Self::AssetId, Self::AccountId, Self::Balance, Self::Cost, Self::Income>;
+
+	/// Handler of assets bridged from relay chains
+	type BridgeAssetFrom: BridgeAssetFrom<Self::AccountId, Self::Precision, Self::Balance>;
+}
+
+decl_event! {
+	pub enum Event<T> where
+		<T as frame_system::Trait>::AccountId,
+		<T as Trait>::Balance,
+	{
+		/// Light client of the relay chain was initialized at the block.
+		RelayInitialized(BlockchainType, RelayBlockNumber, H256),
+		/// Header of the relay chain was finalized.
+		HeaderFinalized(BlockchainType, RelayBlockNumber, H256),
+		/// Authority set of the relay chain was changed to the set id.
+		AuthoritySetChanged(BlockchainType, SetId),
+		/// Notaries and threshold of the relay chain were set.
+		NotariesSet(BlockchainType, Vec<AccountId>, u32),
+		/// Deposit account on the relay chain was assigned to the account.
+		DepositAccountAssigned(BlockchainType, AccountId, RelayAccountId),
+		/// Deposit was proved and issued to the account.
+		DepositReceived(BlockchainType, AccountId, Balance),
+		/// Withdrawal was created.
+		WithdrawalCreated(BlockchainType, u64),
+		/// Withdrawal was approved by notaries, it can be executed by the multisig.
+		WithdrawalApproved(BlockchainType, u64),
+		/// Withdrawal was executed on the relay chain.
+		WithdrawalCompleted(BlockchainType, u64),
+		/// Withdrawal failed on the relay chain, asset was refunded if it's a redemption.
+		WithdrawalFailed(BlockchainType, u64),
+	}
+}
+
+decl_error! {
+	pub enum Error for Module<T: Trait> {
+		/// The blockchain isn't a relay chain
+		NotRelayChain,
+		/// Light client of the relay chain is initialized already
+		RelayAlreadyInitialized,
+		/// Light client of the relay chain isn't initialized
+		RelayNotInitialized,
+		/// The header isn't newer than the best finalized header
+		OldHeader,
+		/// The justification doesn't finalize the header
+		InvalidJustification,
+		/// Another authority set change is pending
+		ChangeAlreadyPending,
+		/// The headers don't link the header to the best finalized header
+		InvalidAncestry,
+		/// The header is after an authority set change, whose block isn't imported yet
+		ChangeHeaderSkipped,
+		/// The header isn't finalized or it's pruned
+		UnknownHeader,
+		/// The header is before the latest sweep of the deposit account
+		HeaderBeforeSweep,
+		/// The storage proof is invalid for the header
+		InvalidStorageProof,
+		/// The deposit account isn't assigned
+		UnknownDepositAccount,
+		/// The account has a deposit account already
+		DepositAccountAssigned,
+		/// All derivative indices were assigned
+		DepositAccountsExhausted,
+		/// Only tokens can be withdrawn
+		VTokenNotWithdrawable,
+		/// The account isn't a valid address on the relay chain
+		InvalidRelayAccount,
+		/// The amount is zero
+		ZeroAmount,
+		/// The balance isn't enough for the withdrawal
+		InsufficientBalance,
+		/// The amount exceeds what's able to be swept from the deposit account
+		InsufficientDeposit,
+		/// The sender isn't a notary of the relay chain
+		NotNotary,
+		/// Staking on relay chains isn't supported by the bridge
+		StakingNotSupported,
+		/// Threshold is zero or more than the notaries
+		InvalidThreshold,
+		/// The withdrawal doesn't exist
+		WithdrawalNotExist,
+		/// The withdrawal isn't in the right status
+		InvalidWithdrawalStatus,
+		/// The notary approved the withdrawal already
+		AlreadyApproved,
+	}
+}
+
+decl_storage! {
+	trait Store for Module<T: Trait> as BridgeDot {
+		/// Light client and custody state of relay chains
+		RelayChains get(fn relay_chain): map hasher(blake2_128_concat) BlockchainType => Option<RelayChain<T::AssetId>>;
+
+		/// Number and state root of finalized headers
+		FinalizedHeaders get(fn finalized_header): double_map hasher(blake2_128_concat) BlockchainType, hasher(identity) H256 => Option<(RelayBlockNumber, H256)>;
+
+		/// Hashes of finalized headers in the order they're imported, for pruning
+		FinalizedHashes get(fn finalized_hashes): map hasher(blake2_128_concat) BlockchainType => Vec<H256>;
+
+		/// Notaries of relay chains and how many of them approve a withdrawal
+		Notaries get(fn notaries): map hasher(blake2_128_concat) BlockchainType => (Vec<T::AccountId>, u32);
+
+		/// Derivative index assigned to the account
+		DepositIndices get(fn deposit_index): double_map hasher(blake2_128_concat) BlockchainType, hasher(blake2_128_concat) T::AccountId => Option<u16>;
+
+		/// Next derivative index to assign
+		NextDepositIndex get(fn next_deposit_index): map hasher(blake2_128_concat) BlockchainType => Option<u16>;
+
+		/// Deposit accounts on relay chains
+		DepositAccounts get(fn deposit_account): double_map hasher(blake2_128_concat) BlockchainType, hasher(blake2_128_concat) RelayAccountId => Option<DepositAccount<T::AccountId>>;
+
+		/// Withdrawals from the multisig controlled accounts
+		Withdrawals get(fn withdrawal): map hasher(twox_64_concat) u64 => Option<Withdrawal<T::AccountId, T::Balance>>;
+
+		/// Id of the next withdrawal
+		NextWithdrawalId get(fn next_withdrawal_id): u64;
+	}
+}
+
+decl_module! {
+	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		type Error = Error<T>;
+
+		fn deposit_event() = default;
+
+		/// Initialize light client of the relay chain from a trusted finalized header
+		#[weight = T::DbWeight::get().reads_writes(1, 4)]
+		fn init_relay(
+			origin,
+			blockchain: BlockchainType,
+			asset_id: T::AssetId,
+			bridge_account: RelayAccountId,
+			header: RelayHeader,
+			authorities: AuthorityList,
+			set_id: SetId,
+		) {
+			ensure_root(origin)?;
+
+			ensure!(Self::ss58_network(blockchain).is_some(), Error::<T>::NotRelayChain);
+			ensure!(!RelayChains::<T>::contains_key(blockchain), Error::<T>::RelayAlreadyInitialized);
+
+			let (number, hash) = (*header.number(), header.hash());
+			RelayChains::<T>::insert(blockchain, RelayChain {
+				asset_id,
+				bridge_account,
+				best_finalized: (number, hash),
+				set_id,
+				authorities,
+				pending_change: None,
+			});
+			Self::insert_finalized_header(blockchain, hash, number, *header.state_root());
+
+			Self::deposit_event(RawEvent::RelayInitialized(blockchain, number, hash));
+		}
+
+		/// Set notaries of the relay chain, who are signatories of the bridge multisig account
+		#[weight = T::DbWeight::get().writes(1)]
+		fn set_notaries(origin, blockchain: BlockchainType, notaries: Vec<T::AccountId>, threshold: u32) {
+			ensure_root(origin)?;
+
+			ensure!(Self::ss58_network(blockchain).is_some(), Error::<T>::NotRelayChain);
+			ensure!(threshold > 0 && threshold as usize <= notaries.len(), Error::<T>::InvalidThreshold);
+
+			Notaries::<T>::insert(blockchain, (notaries.clone(), threshold));
+
+			Self::deposit_event(RawEvent::NotariesSet(blockchain, notaries, threshold));
+		}
+
+		/// Import a header of the relay chain with its GRANDPA justification, and the headers
+		/// skipped since the best finalized header in ascending order.
+		#[weight = FunctionOf(
+			|args: (_, _, &Vec<RelayHeader>, &Vec<u8>)| {
+				IMPORT_HEADER_WEIGHT
+					.saturating_add((args.2.len() as Weight).saturating_mul(ANCESTRY_HEADER_WEIGHT))
+					.saturating_add((args.3.len() as Weight).saturating_mul(JUSTIFICATION_BYTE_WEIGHT))
+			},
+			DispatchClass::Normal,
+			Pays::Yes
+		)]
+		fn submit_finalized_header(
+			origin,
+			blockchain: BlockchainType,
+			header: RelayHeader,
+			ancestry: Vec<RelayHeader>,
+			justification: Vec<u8>,
+		) {
+			let _ = ensure_signed(origin)?;
+
+			let mut relay = RelayChains::<T>::get(blockchain).ok_or(Error::<T>::RelayNotInitialized)?;
+			let (number, hash) = (*header.number(), header.hash());
+			ensure!(number > relay.best_finalized.0, Error::<T>::OldHeader);
+			ensure!(Self::links_to_best(&relay, &ancestry, &header), Error::<T>::InvalidAncestry);
+
+			// headers after the change block are finalized by the next authority set, and the change
+			// block of a standard change is always justified by the current one, so it can't be skipped
+			let mut set_changed = false;
+			if let Some(change) = relay.pending_change.clone() {
+				if number > change.enacted_at {
+					ensure!(change.forced || relay.best_finalized.0 == change.enacted_at, Error::<T>::ChangeHeaderSkipped);
+					relay.authorities = change.next_authorities;
+					relay.set_id += 1;
+					relay.pending_change = None;
+					set_changed = true;
+				}
+			}
+
+			grandpa::verify_justification(hash, number, relay.set_id, &relay.authorities, &justification)
+				.map_err(|_| Error::<T>::InvalidJustification)?;
+
+			// skipped headers are finalized along with the header, so changes signalled by them are
+			// followed as well, unless the header is after the change block
+			for signal in ancestry.iter().chain(sp_std::iter::once(&header)) {
+				if let Some((change, forced)) = grandpa::find_authority_set_change(signal) {
+					ensure!(relay.pending_change.is_none(), Error::<T>::ChangeAlreadyPending);
+					let enacted_at = signal.number().saturating_add(change.delay);
+					ensure!(number <= enacted_at, Error::<T>::ChangeHeaderSkipped);
+					relay.pending_change = Some(PendingChange {
+						enacted_at,
+						next_authorities: change.next_authorities,
+						forced,
+					});
+				}
+			}
+
+			relay.best_finalized = (number, hash);
+			let set_id = relay.set_id;
+			RelayChains::<T>::insert(blockchain, relay);
+			Self::insert_finalized_header(blockchain, hash, number, *header.state_root());
+
+			if set_changed {
+				Self::deposit_event(RawEvent::AuthoritySetChanged(blockchain, set_id));
+			}
+			Self::deposit_event(RawEvent::HeaderFinalized(blockchain, number, hash));
+		}
+
+		/// Assign a deposit account on the relay chain to the sender
+		#[weight = T::DbWeight::get().reads_writes(3, 3)]
+		fn assign_deposit_account(origin, blockchain: BlockchainType) {
+			let who = ensure_signed(origin)?;
+
+			let relay = RelayChains::<T>::get(blockchain).ok_or(Error::<T>::RelayNotInitialized)?;
+			ensure!(!DepositIndices::<T>::contains_key(blockchain, &who), Error::<T>::DepositAccountAssigned);
+
+			let index = NextDepositIndex::get(blockchain).unwrap_or_default();
+			let next_index = index.checked_add(1).ok_or(Error::<T>::DepositAccountsExhausted)?;
+			let account = relay::derivative_account(&relay.bridge_account, index);
+
+			NextDepositIndex::insert(blockchain, next_index);
+			DepositIndices::<T>::insert(blockchain, &who, index);
+			DepositAccounts::<T>::insert(blockchain, account, DepositAccount {
+				owner: who.clone(),
+				index,
+				..Default::default()
+			});
+
+			Self::deposit_event(RawEvent::DepositAccountAssigned(blockchain, who, account));
+		}
+
+		/// Prove balance of the deposit account at a finalized header, what's received since the
+		/// last proof is issued to the owner.
+		#[weight = FunctionOf(
+			|args: (_, _, _, &Vec<Vec<u8>>)| {
+				let proof_len = args.3.iter().fold(0, |len, node| len + node.len()) as Weight;
+				PROVE_DEPOSIT_WEIGHT.saturating_add(proof_len.saturating_mul(PROOF_BYTE_WEIGHT))
+			},
+			DispatchClass::Normal,
+			Pays::Yes
+		)]
+		fn prove_deposit(
+			origin,
+			blockchain: BlockchainType,
+			block_hash: H256,
+			account: RelayAccountId,
+			proof: Vec<Vec<u8>>,
+		) {
+			let _ = ensure_signed(origin)?;
+
+			let relay = RelayChains::<T>::get(blockchain).ok_or(Error::<T>::RelayNotInitialized)?;
+			let (number, state_root) = FinalizedHeaders::get(blockchain, block_hash).ok_or(Error::<T>::UnknownHeader)?;
+			let mut deposit = DepositAccounts::<T>::get(blockchain, account).ok_or(Error::<T>::UnknownDepositAccount)?;
+			ensure!(number > deposit.proved_at, Error::<T>::OldHeader);
+			// the balance before a sweep doesn't tell what's received since the sweep
+			ensure!(number >= deposit.swept_at, Error::<T>::HeaderBeforeSweep);
+
+			let free = relay::read_free_balance(state_root, &account, proof).ok_or(Error::<T>::InvalidStorageProof)?;
+			// balance only goes down by sweeps, which are counted once they are reported
+			let received = free.saturating_add(deposit.swept).saturating_sub(deposit.credited);
+			deposit.proved_at = number;
+			deposit.credited = deposit.credited.saturating_add(received);
+			let owner = deposit.owner.clone();
+			DepositAccounts::<T>::insert(blockchain, account, deposit);
+
+			if received > 0 {
+				let amount: T::Balance = received.saturated_into();
+				T::AssetTrait::asset_issue(relay.asset_id, TokenType::Token, owner.clone(), amount);
+
+				let bridge_asset = BridgeAssetBalance {
+					symbol: Self::native_symbol(blockchain),
+					amount,
+					memo: Vec::new(),
+					from: owner.clone(),
+					token_type: TokenType::Token,
+				};
+				T::BridgeAssetFrom::bridge_asset_from(owner.clone(), bridge_asset);
+
+				Self::deposit_event(RawEvent::DepositReceived(blockchain, owner, amount));
+			}
+		}
+
+		/// Propose sweeping the deposit account into the bridge account
+		#[weight = T::DbWeight::get().reads_writes(4, 3)]
+		fn propose_sweep(origin, blockchain: BlockchainType, account: RelayAccountId, amount: u128) {
+			let who = ensure_signed(origin)?;
+			Self::ensure_notary(blockchain, &who)?;
+
+			let relay = RelayChains::<T>::get(blockchain).ok_or(Error::<T>::RelayNotInitialized)?;
+			ensure!(amount > 0, Error::<T>::ZeroAmount);
+			let mut deposit = DepositAccounts::<T>::get(blockchain, account).ok_or(Error::<T>::UnknownDepositAccount)?;
+			let unswept = deposit.credited.saturating_sub(deposit.swept).saturating_sub(deposit.sweeping);
+			ensure!(amount <= unswept, Error::<T>::InsufficientDeposit);
+
+			deposit.sweeping = deposit.sweeping.saturating_add(amount);
+			DepositAccounts::<T>::insert(blockchain, account, deposit);
+
+			let id = Self::create_withdrawal(blockchain, WithdrawalKind::Sweep(account), relay.bridge_account, amount);
+			Self::approve(id, who)?;
+		}
+
+		/// Approve the withdrawal by a notary
+		#[weight = T::DbWeight::get().reads_writes(2, 1)]
+		fn approve_withdrawal(origin, id: u64) {
+			let who = ensure_signed(origin)?;
+
+			Self::approve(id, who)?;
+		}
+
+		/// Report result of the approved withdrawal by a notary, with the relay chain block number
+		/// it's executed at.
+		#[weight = T::DbWeight::get().reads_writes(3, 2)]
+		fn report_withdrawal(origin, id: u64, success: bool, executed_at: RelayBlockNumber) {
+			let who = ensure_signed(origin)?;
+
+			let mut withdrawal = Withdrawals::<T>::get(id).ok_or(Error::<T>::WithdrawalNotExist)?;
+			let blockchain = withdrawal.blockchain;
+			Self::ensure_notary(blockchain, &who)?;
+			ensure!(withdrawal.status == WithdrawalStatus::Approved, Error::<T>::InvalidWithdrawalStatus);
+
+			match withdrawal.kind {
+				WithdrawalKind::Sweep(account) => {
+					DepositAccounts::<T>::mutate(blockchain, account, |deposit| {
+						if let Some(deposit) = deposit {
+							deposit.sweeping = deposit.sweeping.saturating_sub(withdrawal.amount);
+							if success {
+								deposit.swept = deposit.swept.saturating_add(withdrawal.amount);
+								deposit.swept_at = deposit.swept_at.max(executed_at);
+							}
+						}
+					});
+				}
+				WithdrawalKind::Redeem(ref owner, escrow) if !success => {
+					let relay = RelayChains::<T>::get(blockchain).ok_or(Error::<T>::RelayNotInitialized)?;
+					T::AssetTrait::asset_issue(relay.asset_id, TokenType::Token, owner.clone(), escrow);
+				}
+				_ => {}
+			}
+
+			if success {
+				withdrawal.status = WithdrawalStatus::Completed;
+				Self::deposit_event(RawEvent::WithdrawalCompleted(blockchain, id));
+			} else {
+				withdrawal.status = WithdrawalStatus::Failed;
+				Self::deposit_event(RawEvent::WithdrawalFailed(blockchain, id));
+			}
+			Withdrawals::<T>::insert(id, withdrawal);
+		}
+	}
+}
+
+impl<T: Trait> Module<T> {
+	/// SS58 network prefix of the relay chain
+	pub fn ss58_network(blockchain: BlockchainType) -> Option<u8> {
+		match blockchain {
+			BlockchainType::POLKADOT => Some(0),
+			BlockchainType::KUSAMA => Some(2),
+			_ => None,
+		}
+	}
+
+	/// Symbol of the native token of the relay chain
+	pub fn native_symbol(blockchain: BlockchainType) -> BridgeAssetSymbol<T::Precision> {
+		match blockchain {
+			BlockchainType::KUSAMA => BridgeAssetSymbol::new(blockchain, b"KSM".to_vec(), T::Precision::from(12u32)),
+			_ => BridgeAssetSymbol::new(blockchain, b"DOT".to_vec(), T::Precision::from(10u32)),
+		}
+	}
+
+	/// Deposit account on the relay chain assigned to the account
+	pub fn deposit_account_of(blockchain: BlockchainType, who: &T::AccountId) -> Option<RelayAccountId> {
+		let relay = RelayChains::<T>::get(blockchain)?;
+		let index = DepositIndices::<T>::get(blockchain, who)?;
+		Some(relay::derivative_account(&relay.bridge_account, index))
+	}
+
+	/// Whether the skipped headers link the header to the best finalized header
+	fn links_to_best(relay: &RelayChain<T::AssetId>, ancestry: &[RelayHeader], header: &RelayHeader) -> bool {
+		let mut parent = relay.best_finalized;
+		for next in ancestry.iter().chain(sp_std::iter::once(header)) {
+			if *next.parent_hash() != parent.1 || *next.number() != parent.0.saturating_add(1) {
+				return false;
+			}
+			parent = (*next.number(), next.hash());
+		}
+		true
+	}
+
+	fn insert_finalized_header(blockchain: BlockchainType, hash: H256, number: RelayBlockNumber, state_root: H256) {
+		FinalizedHeaders::insert(blockchain, hash, (number, state_root));
+		FinalizedHashes::mutate(blockchain, |hashes| {
+			hashes.push(hash);
+			if hashes.len() > FINALIZED_HEADERS_HISTORY {
+				let pruned = hashes.remove(0);
+				FinalizedHeaders::remove(blockchain, pruned);
+			}
+		});
+	}
+
+	fn ensure_notary(blockchain: BlockchainType, who: &T::AccountId) -> DispatchResult {
+		let (notaries, _) = Notaries::<T>::get(blockchain);
+		ensure!(notaries.contains(who), Error::<T>::NotNotary);
+		Ok(())
+	}
+
+	fn create_withdrawal(
+		blockchain: BlockchainType,
+		kind: WithdrawalKind<T::AccountId, T::Balance>,
+		to: RelayAccountId,
+		amount: u128,
+	) -> u64 {
+		let id = NextWithdrawalId::mutate(|id| {
+			let current = *id;
+			*id += 1;
+			current
+		});
+		Withdrawals::<T>::insert(id, Withdrawal {
+			blockchain,
+			kind,
+			to,
+			amount,
+			approvals: Vec::new(),
+			status: WithdrawalStatus::Pending,
+		});
+
+		Self::deposit_event(RawEvent::WithdrawalCreated(blockchain, id));
+		id
+	}
+
+	fn approve(id: u64, who: T::AccountId) -> DispatchResult {
+		let mut withdrawal = Withdrawals::<T>::get(id).ok_or(Error::<T>::WithdrawalNotExist)?;
+		let blockchain = withdrawal.blockchain;
+		Self::ensure_notary(blockchain, &who)?;
+		ensure!(withdrawal.status == WithdrawalStatus::Pending, Error::<T>::InvalidWithdrawalStatus);
+		ensure!(!withdrawal.approvals.contains(&who), Error::<T>::AlreadyApproved);
+
+		withdrawal.approvals.push(who);
+		let (_, threshold) = Notaries::<T>::get(blockchain);
+		if withdrawal.approvals.len() >= threshold as usize {
+			withdrawal.status = WithdrawalStatus::Approved;
+			Self::deposit_event(RawEvent::WithdrawalApproved(blockchain, id));
+		}
+		Withdrawals::<T>::insert(id, withdrawal);
+
+		Ok(())
+	}
+}
+
+impl<T: Trait> ChainBridge<T::AccountId, T::Precision, T::Balance> for Module<T> {
+	fn connects(blockchain: BlockchainType) -> bool {
+		Self::ss58_network(blockchain).is_some()
+	}
+
+	fn is_valid_account(blockchain: BlockchainType, account: &[u8]) -> bool {
+		Self::ss58_network(blockchain).map_or(false, |network| relay::decode_ss58(account, network).is_some())
+	}
+
+	fn verify_account_proof(blockchain: BlockchainType, account: &[u8], message: &[u8], proof: Option<&[u8]>) -> bool {
+		// the proof is the sr25519 signature of the message by the account
+		let public = match Self::ss58_network(blockchain).and_then(|network| relay::decode_ss58(account, network)) {
+			Some(public) => sr25519::Public::from_raw(public),
+			None => return false,
+		};
+		match proof {
+			Some(proof) if proof.len() == 64 => {
+				let mut signature = [0u8; 64];
+				signature.copy_from_slice(proof);
+				sp_io::crypto::sr25519_verify(&sr25519::Signature::from_raw(signature), message, &public)
+			}
+			_ => false,
+		}
+	}
+
+	fn send_asset(target: Vec<u8>, bridge_asset: BridgeAssetBalance<T::AccountId, T::Precision, T::Balance>) -> DispatchResult {
+		let blockchain = bridge_asset.symbol.blockchain;
+		let network = Self::ss58_network(blockchain).ok_or(Error::<T>::NotRelayChain)?;
+		let relay = RelayChains::<T>::get(blockchain).ok_or(Error::<T>::RelayNotInitialized)?;
+		ensure!(bridge_asset.token_type == TokenType::Token, Error::<T>::VTokenNotWithdrawable);
+		ensure!(!bridge_asset.amount.is_zero(), Error::<T>::ZeroAmount);
+		let to = relay::decode_ss58(&target, network).ok_or(Error::<T>::InvalidRelayAccount)?;
+
+		let from = bridge_asset.from;
+		let balance = T::AssetTrait::get_account_asset(&relay.asset_id, TokenType::Token, &from).balance;
+		ensure!(balance >= bridge_asset.amount, Error::<T>::InsufficientBalance);
+
+		// tokens are escrowed, and will be refunded if this withdrawal fails
+		T::AssetTrait::asset_destroy(relay.asset_id, TokenType::Token, from.clone(), bridge_asset.amount);
+		let amount = bridge_asset.amount.saturated_into::<u128>();
+		Self::create_withdrawal(blockchain, WithdrawalKind::Redeem(from, bridge_asset.amount), to, amount);
+
+		Ok(())
+	}
+
+	fn stake(_: Vec<u8>, _: BridgeAssetBalance<T::AccountId, T::Precision, T::Balance>) -> DispatchResult {
+		// nominating on relay chains needs the bridge account to bond, which notaries don't do yet
+		Err(Error::<T>::StakingNotSupported.into())
+	}
+
+	fn unstake(_: Vec<u8>, _: BridgeAssetBalance<T::AccountId, T::Precision, T::Balance>) -> DispatchResult {
+		Err(Error::<T>::StakingNotSupported.into())
+	}
+}
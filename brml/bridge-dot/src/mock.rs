@@ -0,0 +1,230 @@
+// Copyright 2019-2020 Liebi Technologies.
+// This file is part of Bifrost.
+
+// Bifrost is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Bifrost is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Bifrost.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Test utilities
+
+#![cfg(test)]
+
+use frame_support::{assert_ok, impl_outer_origin, impl_outer_dispatch, impl_outer_event, parameter_types};
+use sp_core::{ed25519, Pair};
+use sp_finality_grandpa::{AuthorityId, AuthoritySignature};
+use sp_runtime::{Perbill, traits::IdentityLookup, testing::Header, Digest, DigestItem};
+use sp_trie::{Layout, MemoryDB, TrieDBMut, TrieMut};
+use super::*;
+use crate::grandpa::{Commit, GrandpaJustification, Precommit, SignedPrecommit};
+
+impl_outer_origin! {
+	pub enum Origin for Test {}
+}
+
+impl_outer_dispatch! {
+	pub enum Call for Test where origin: Origin {
+		bridge_dot::BridgeDot,
+	}
+}
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct Test;
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+	pub const MaximumBlockWeight: u32 = 1024;
+	pub const MaximumBlockLength: u32 = 2 * 1024;
+	pub const AvailableBlockRatio: Perbill = Perbill::one();
+}
+
+impl frame_system::Trait for Test {
+	type Origin = Origin;
+	type Call = Call;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = TestEvent;
+	type ModuleToIndex = ();
+	type BlockHashCount = BlockHashCount;
+	type MaximumBlockWeight = MaximumBlockWeight;
+	type MaximumBlockLength = MaximumBlockLength;
+	type AvailableBlockRatio = AvailableBlockRatio;
+	type Version = ();
+	type AccountData = ();
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type DbWeight = ();
+	type BlockExecutionWeight = ();
+	type ExtrinsicBaseWeight = ();
+}
+
+impl Trait for Test {
+	type Event = TestEvent;
+	type Balance = u64;
+	type AssetId = u32;
+	type Precision = u32;
+	type Cost = u64;
+	type Income = u64;
+	type AssetTrait = Assets;
+	type BridgeAssetFrom = ();
+}
+
+impl assets::Trait for Test {
+	type Event = TestEvent;
+	type Balance = u64;
+	type AssetId = u32;
+	type Price = u64;
+	type Cost = u64;
+	type Income = u64;
+	type Convert = u64;
+	type AssetRedeem = ();
+	type FetchConvertPrice = ();
+	type ClearingHandler = ();
+}
+
+mod bridge_dot {
+	pub use crate::Event;
+}
+
+impl_outer_event! {
+	pub enum TestEvent for Test {
+		system<T>,
+		bridge_dot<T>,
+		assets<T>,
+	}
+}
+
+pub type BridgeDot = Module<Test>;
+pub type BridgeDotError = Error<Test>;
+pub type Assets = assets::Module<Test>;
+pub type System = frame_system::Module<Test>;
+
+/// Asset id of DOT on Bifrost
+pub const DOT_ASSET_ID: u32 = 0;
+
+/// Multisig account of the bridge on Polkadot
+pub const BRIDGE_ACCOUNT: RelayAccountId = [0x42; 32];
+
+/// GRANDPA voters of the set, weighted equally
+pub(crate) fn voters(set_id: u8) -> Vec<ed25519::Pair> {
+	(1..=4u8).map(|i| ed25519::Pair::from_seed(&[set_id * 10 + i; 32])).collect()
+}
+
+pub(crate) fn authority_list(voters: &[ed25519::Pair]) -> AuthorityList {
+	voters.iter().map(|pair| (AuthorityId::from(pair.public()), 1)).collect()
+}
+
+pub(crate) fn relay_header(number: RelayBlockNumber, parent_hash: H256, state_root: H256) -> RelayHeader {
+	RelayHeader::new(number, Default::default(), state_root, parent_hash, Digest::default())
+}
+
+/// Header which schedules the authority set change after the delay
+pub(crate) fn relay_header_with_change(
+	number: RelayBlockNumber,
+	parent_hash: H256,
+	next_authorities: AuthorityList,
+	delay: RelayBlockNumber,
+) -> RelayHeader {
+	let change = sp_finality_grandpa::ScheduledChange { next_authorities, delay };
+	relay_header_with_log(number, parent_hash, sp_finality_grandpa::ConsensusLog::ScheduledChange(change))
+}
+
+/// Header which forces the authority set change after the delay
+pub(crate) fn relay_header_with_forced_change(
+	number: RelayBlockNumber,
+	parent_hash: H256,
+	next_authorities: AuthorityList,
+	delay: RelayBlockNumber,
+) -> RelayHeader {
+	let change = sp_finality_grandpa::ScheduledChange { next_authorities, delay };
+	relay_header_with_log(number, parent_hash, sp_finality_grandpa::ConsensusLog::ForcedChange(0, change))
+}
+
+fn relay_header_with_log(
+	number: RelayBlockNumber,
+	parent_hash: H256,
+	log: sp_finality_grandpa::ConsensusLog<RelayBlockNumber>,
+) -> RelayHeader {
+	let mut header = relay_header(number, parent_hash, Default::default());
+	header.digest.push(DigestItem::Consensus(sp_finality_grandpa::GRANDPA_ENGINE_ID, log.encode()));
+	header
+}
+
+/// Precommit of the voter on the target in the round of the authority set
+pub(crate) fn signed_precommit(voter: &ed25519::Pair, target: &RelayHeader, round: u64, set_id: SetId) -> SignedPrecommit {
+	let precommit = Precommit { target_hash: target.hash(), target_number: *target.number() };
+	let signature = voter.sign(&grandpa::precommit_payload(&precommit, round, set_id));
+	SignedPrecommit {
+		precommit,
+		signature: AuthoritySignature::from(signature),
+		id: AuthorityId::from(voter.public()),
+	}
+}
+
+/// Justification of the header, all voters precommit on the header
+pub(crate) fn justify(header: &RelayHeader, set_id: SetId, voters: &[ed25519::Pair]) -> Vec<u8> {
+	let round = 1;
+	GrandpaJustification {
+		round,
+		commit: Commit {
+			target_hash: header.hash(),
+			target_number: *header.number(),
+			precommits: voters.iter().map(|voter| signed_precommit(voter, header, round, set_id)).collect(),
+		},
+		votes_ancestries: vec![],
+	}.encode()
+}
+
+/// State root of relay chain storage where the account has the free balance, and the proof of it
+pub(crate) fn relay_state(account: &RelayAccountId, free: u128) -> (H256, Vec<Vec<u8>>) {
+	let info = relay::RelayAccountInfo {
+		nonce: 0,
+		refcount: 0,
+		data: relay::RelayAccountData { free, ..Default::default() },
+	};
+
+	let mut db = MemoryDB::<BlakeTwo256>::default();
+	let mut root = H256::default();
+	{
+		let mut trie = TrieDBMut::<Layout<BlakeTwo256>>::new(&mut db, &mut root);
+		trie.insert(&relay::account_storage_key(account), &info.encode()).unwrap();
+		trie.insert(&relay::account_storage_key(&BRIDGE_ACCOUNT), &relay::RelayAccountInfo::default().encode()).unwrap();
+	}
+	let proof = db.drain().into_iter().map(|(_, (node, _))| node).collect();
+
+	(root, proof)
+}
+
+/// Initialize Polkadot light client at genesis with authority set 0
+pub(crate) fn init_polkadot() -> RelayHeader {
+	let genesis = relay_header(0, Default::default(), Default::default());
+	assert_ok!(BridgeDot::init_relay(
+		Origin::ROOT,
+		BlockchainType::POLKADOT,
+		DOT_ASSET_ID,
+		BRIDGE_ACCOUNT,
+		genesis.clone(),
+		authority_list(&voters(0)),
+		0,
+	));
+	genesis
+}
+
+pub(crate) fn new_test_ext() -> sp_io::TestExternalities {
+	let mut ext: sp_io::TestExternalities = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap().into();
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
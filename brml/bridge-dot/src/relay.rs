@@ -0,0 +1,109 @@
+// Copyright 2019-2020 Liebi Technologies.
+// This file is part of Bifrost.
+
+// Bifrost is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Bifrost is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Bifrost.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Accounts and storage layout of relay chains.
+
+use codec::{Decode, Encode};
+use sp_core::H256;
+use sp_io::hashing::{blake2_128, blake2_256, twox_128};
+use sp_runtime::traits::BlakeTwo256;
+use sp_std::prelude::*;
+use sp_trie::{read_trie_value, Layout, StorageProof};
+
+/// Account id on relay chains
+pub type RelayAccountId = [u8; 32];
+
+const SS58_PREFIX: &[u8] = b"SS58PRE";
+const SS58_CHECKSUM_LEN: usize = 2;
+
+/// Balances of `pallet_balances::AccountData` on relay chains
+#[derive(Encode, Decode, Clone, Default, PartialEq, Eq, Debug)]
+pub struct RelayAccountData {
+	pub free: u128,
+	pub reserved: u128,
+	pub misc_frozen: u128,
+	pub fee_frozen: u128,
+}
+
+/// Value of `System::Account` on relay chains
+#[derive(Encode, Decode, Clone, Default, PartialEq, Eq, Debug)]
+pub struct RelayAccountInfo {
+	pub nonce: u32,
+	pub refcount: u8,
+	pub data: RelayAccountData,
+}
+
+fn ss58_checksum(data: &[u8]) -> [u8; SS58_CHECKSUM_LEN] {
+	let mut preimage = SS58_PREFIX.to_vec();
+	preimage.extend_from_slice(data);
+	let hash = blake2_rfc::blake2b::blake2b(64, &[], &preimage);
+
+	let mut checksum = [0u8; SS58_CHECKSUM_LEN];
+	checksum.copy_from_slice(&hash.as_bytes()[..SS58_CHECKSUM_LEN]);
+	checksum
+}
+
+/// Decode SS58 address of the network into account id
+pub fn decode_ss58(address: &[u8], network: u8) -> Option<RelayAccountId> {
+	let address = core::str::from_utf8(address).ok()?;
+	let data = bs58::decode(address).into_vec().ok()?;
+	// network prefix, account id and checksum
+	if data.len() != 1 + 32 + SS58_CHECKSUM_LEN || data[0] != network {
+		return None;
+	}
+	if ss58_checksum(&data[..33])[..] != data[33..] {
+		return None;
+	}
+
+	let mut account = [0u8; 32];
+	account.copy_from_slice(&data[1..33]);
+	Some(account)
+}
+
+/// Encode account id into SS58 address of the network
+pub fn encode_ss58(account: &RelayAccountId, network: u8) -> Vec<u8> {
+	let mut data = Vec::with_capacity(1 + 32 + SS58_CHECKSUM_LEN);
+	data.push(network);
+	data.extend_from_slice(account);
+	let checksum = ss58_checksum(&data);
+	data.extend_from_slice(&checksum);
+	bs58::encode(data).into_string().into_bytes()
+}
+
+/// Account dispatching as derivative of the account by `utility.as_derivative`
+pub fn derivative_account(account: &RelayAccountId, index: u16) -> RelayAccountId {
+	(b"modlpy/utilisuba", account, index).using_encoded(blake2_256)
+}
+
+/// Storage key of `System::Account` of the account
+pub fn account_storage_key(account: &RelayAccountId) -> Vec<u8> {
+	let mut key = twox_128(b"System").to_vec();
+	key.extend_from_slice(&twox_128(b"Account"));
+	key.extend_from_slice(&blake2_128(account));
+	key.extend_from_slice(account);
+	key
+}
+
+/// Read free balance of the account from storage proof, the account which doesn't exist has none
+pub fn read_free_balance(state_root: H256, account: &RelayAccountId, proof: Vec<Vec<u8>>) -> Option<u128> {
+	let db = StorageProof::new(proof).into_memory_db::<BlakeTwo256>();
+	let value = read_trie_value::<Layout<BlakeTwo256>, _>(&db, &state_root, &account_storage_key(account)).ok()?;
+
+	match value {
+		Some(value) => RelayAccountInfo::decode(&mut &value[..]).ok().map(|info| info.data.free),
+		None => Some(0),
+	}
+}
@@ -0,0 +1,506 @@
+// Copyright 2019-2020 Liebi Technologies.
+// This file is part of Bifrost.
+
+// Bifrost is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Bifrost is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Bifrost.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Tests for the module.
+
+#![cfg(test)]
+
+use crate::*;
+use crate::grandpa::{Commit, GrandpaJustification, JustificationError};
+use crate::mock::*;
+use frame_support::{assert_ok, assert_noop};
+use hex_literal::hex;
+use sp_core::{sr25519, Pair};
+
+fn has_event(event: RawEvent<u64, u64>) -> bool {
+	System::events().iter().any(|record| record.event == TestEvent::bridge_dot(event.clone()))
+}
+
+/// Import the header finalized by the voters of authority set
+fn finalize(header: &RelayHeader, set_id: SetId, voters: &[sp_core::ed25519::Pair]) {
+	assert_ok!(BridgeDot::submit_finalized_header(
+		Origin::signed(1),
+		BlockchainType::POLKADOT,
+		header.clone(),
+		vec![],
+		justify(header, set_id, voters),
+	));
+}
+
+/// Create DOT asset and issue 100 DOT to the account
+fn issue_dot(who: u64) {
+	assert_ok!(Assets::create(Origin::ROOT, b"DOT".to_vec(), 10));
+	assert_ok!(Assets::issue(Origin::ROOT, node_primitives::AssetSymbol::DOT, TokenType::Token, who, 100));
+}
+
+#[test]
+fn init_relay_should_work() {
+	new_test_ext().execute_with(|| {
+		let genesis = relay_header(0, Default::default(), Default::default());
+		assert_noop!(
+			BridgeDot::init_relay(
+				Origin::ROOT, BlockchainType::EOS, DOT_ASSET_ID, BRIDGE_ACCOUNT, genesis.clone(), vec![], 0
+			),
+			BridgeDotError::NotRelayChain
+		);
+
+		let genesis = init_polkadot();
+		let relay = BridgeDot::relay_chain(BlockchainType::POLKADOT).unwrap();
+		assert_eq!(relay.best_finalized, (0, genesis.hash()));
+		assert_eq!(relay.authorities, authority_list(&voters(0)));
+		assert_eq!(BridgeDot::finalized_header(BlockchainType::POLKADOT, genesis.hash()), Some((0, *genesis.state_root())));
+		assert!(has_event(RawEvent::RelayInitialized(BlockchainType::POLKADOT, 0, genesis.hash())));
+
+		assert_noop!(
+			BridgeDot::init_relay(
+				Origin::ROOT, BlockchainType::POLKADOT, DOT_ASSET_ID, BRIDGE_ACCOUNT, genesis, vec![], 0
+			),
+			BridgeDotError::RelayAlreadyInitialized
+		);
+	});
+}
+
+#[test]
+fn submit_finalized_header_should_work() {
+	new_test_ext().execute_with(|| {
+		let genesis = init_polkadot();
+		let voters = voters(0);
+
+		let header1 = relay_header(1, genesis.hash(), H256::repeat_byte(1));
+		// 3 of 4 voters are more than 2/3
+		finalize(&header1, 0, &voters[..3]);
+		assert_eq!(BridgeDot::relay_chain(BlockchainType::POLKADOT).unwrap().best_finalized, (1, header1.hash()));
+		assert_eq!(BridgeDot::finalized_header(BlockchainType::POLKADOT, header1.hash()), Some((1, H256::repeat_byte(1))));
+		assert!(has_event(RawEvent::HeaderFinalized(BlockchainType::POLKADOT, 1, header1.hash())));
+
+		// headers can be skipped along with all the headers between them, but not go backwards
+		let header2 = relay_header(2, header1.hash(), Default::default());
+		let header3 = relay_header(3, header2.hash(), Default::default());
+		let header4 = relay_header(4, header3.hash(), Default::default());
+		let header5 = relay_header(5, header4.hash(), Default::default());
+		assert_noop!(
+			BridgeDot::submit_finalized_header(
+				Origin::signed(1),
+				BlockchainType::POLKADOT,
+				header5.clone(),
+				vec![header2.clone(), header4.clone()],
+				justify(&header5, 0, &voters),
+			),
+			BridgeDotError::InvalidAncestry
+		);
+		assert_ok!(BridgeDot::submit_finalized_header(
+			Origin::signed(1),
+			BlockchainType::POLKADOT,
+			header5.clone(),
+			vec![header2, header3, header4],
+			justify(&header5, 0, &voters),
+		));
+		assert_eq!(BridgeDot::relay_chain(BlockchainType::POLKADOT).unwrap().best_finalized, (5, header5.hash()));
+		assert_noop!(
+			BridgeDot::submit_finalized_header(
+				Origin::signed(1), BlockchainType::POLKADOT, header1.clone(), vec![], justify(&header1, 0, &voters)
+			),
+			BridgeDotError::OldHeader
+		);
+	});
+}
+
+#[test]
+fn invalid_justification_should_be_rejected() {
+	new_test_ext().execute_with(|| {
+		let genesis = init_polkadot();
+		let header = relay_header(1, genesis.hash(), Default::default());
+		let authorities = authority_list(&voters(0));
+		let verify = |justification: Vec<u8>| {
+			grandpa::verify_justification(header.hash(), 1, 0, &authorities, &justification)
+		};
+
+		assert_eq!(verify(justify(&header, 0, &voters(0)[..2])), Err(JustificationError::InsufficientWeight));
+		assert_eq!(verify(justify(&header, 1, &voters(0))), Err(JustificationError::InvalidSignature));
+		assert_eq!(verify(justify(&header, 0, &voters(1))), Err(JustificationError::UnknownAuthority));
+		assert_eq!(verify(vec![1, 2, 3]), Err(JustificationError::InvalidEncoding));
+
+		let other = relay_header(1, H256::repeat_byte(1), Default::default());
+		assert_eq!(verify(justify(&other, 0, &voters(0))), Err(JustificationError::TargetMismatch));
+
+		let signers = voters(0);
+		let duplicated = GrandpaJustification {
+			round: 1,
+			commit: Commit {
+				target_hash: header.hash(),
+				target_number: 1,
+				precommits: vec![signed_precommit(&signers[0], &header, 1, 0); 3],
+			},
+			votes_ancestries: vec![],
+		};
+		assert_eq!(verify(duplicated.encode()), Err(JustificationError::DuplicateVote));
+
+		assert_noop!(
+			BridgeDot::submit_finalized_header(
+				Origin::signed(1), BlockchainType::POLKADOT, header.clone(), vec![], justify(&header, 0, &voters(0)[..2])
+			),
+			BridgeDotError::InvalidJustification
+		);
+	});
+}
+
+#[test]
+fn precommits_on_descendants_should_be_accepted() {
+	new_test_ext().execute_with(|| {
+		let genesis = init_polkadot();
+		let voters = voters(0);
+		let header1 = relay_header(1, genesis.hash(), Default::default());
+		let header2 = relay_header(2, header1.hash(), Default::default());
+		let header3 = relay_header(3, header2.hash(), Default::default());
+
+		// voters precommit on header 1, 2 and 3, which finalize header 1
+		let justification = |votes_ancestries: Vec<RelayHeader>| GrandpaJustification {
+			round: 1,
+			commit: Commit {
+				target_hash: header1.hash(),
+				target_number: 1,
+				precommits: vec![
+					signed_precommit(&voters[0], &header1, 1, 0),
+					signed_precommit(&voters[1], &header2, 1, 0),
+					signed_precommit(&voters[2], &header3, 1, 0),
+				],
+			},
+			votes_ancestries,
+		}.encode();
+
+		let authorities = authority_list(&voters);
+		assert_eq!(
+			grandpa::verify_justification(header1.hash(), 1, 0, &authorities, &justification(vec![header3.clone()])),
+			Err(JustificationError::NotDescendant)
+		);
+		assert_ok!(BridgeDot::submit_finalized_header(
+			Origin::signed(1),
+			BlockchainType::POLKADOT,
+			header1.clone(),
+			vec![],
+			justification(vec![header2, header3]),
+		));
+	});
+}
+
+#[test]
+fn authority_set_change_should_be_enacted() {
+	new_test_ext().execute_with(|| {
+		let genesis = init_polkadot();
+		let (old_voters, new_voters) = (voters(0), voters(1));
+
+		// change is scheduled at header 1, and enacted after header 3
+		let header1 = relay_header_with_change(1, genesis.hash(), authority_list(&new_voters), 2);
+		finalize(&header1, 0, &old_voters);
+		assert_eq!(
+			BridgeDot::relay_chain(BlockchainType::POLKADOT).unwrap().pending_change,
+			Some(PendingChange { enacted_at: 3, next_authorities: authority_list(&new_voters), forced: false })
+		);
+
+		let another_change = relay_header_with_change(2, header1.hash(), authority_list(&old_voters), 0);
+		assert_noop!(
+			BridgeDot::submit_finalized_header(
+				Origin::signed(1),
+				BlockchainType::POLKADOT,
+				another_change.clone(),
+				vec![],
+				justify(&another_change, 0, &old_voters),
+			),
+			BridgeDotError::ChangeAlreadyPending
+		);
+
+		let header2 = relay_header(2, header1.hash(), Default::default());
+		let header3 = relay_header(3, header2.hash(), Default::default());
+		let header4 = relay_header(4, header3.hash(), Default::default());
+		finalize(&header2, 0, &old_voters);
+
+		// the change block is finalized by the old authority set, it can't be skipped
+		assert_noop!(
+			BridgeDot::submit_finalized_header(
+				Origin::signed(1), BlockchainType::POLKADOT, header4.clone(), vec![header3.clone()], justify(&header4, 1, &new_voters)
+			),
+			BridgeDotError::ChangeHeaderSkipped
+		);
+		finalize(&header3, 0, &old_voters);
+
+		assert_noop!(
+			BridgeDot::submit_finalized_header(
+				Origin::signed(1), BlockchainType::POLKADOT, header4.clone(), vec![], justify(&header4, 0, &old_voters)
+			),
+			BridgeDotError::InvalidJustification
+		);
+		finalize(&header4, 1, &new_voters);
+
+		let relay = BridgeDot::relay_chain(BlockchainType::POLKADOT).unwrap();
+		assert_eq!((relay.set_id, relay.authorities, relay.pending_change), (1, authority_list(&new_voters), None));
+		assert!(has_event(RawEvent::AuthoritySetChanged(BlockchainType::POLKADOT, 1)));
+	});
+}
+
+#[test]
+fn changes_signalled_by_skipped_headers_should_be_followed() {
+	new_test_ext().execute_with(|| {
+		let genesis = init_polkadot();
+		let (old_voters, new_voters) = (voters(0), voters(1));
+
+		// forced change is signalled at header 1, and enacted after header 3
+		let header1 = relay_header_with_forced_change(1, genesis.hash(), authority_list(&new_voters), 2);
+		let header2 = relay_header(2, header1.hash(), Default::default());
+		let header3 = relay_header(3, header2.hash(), Default::default());
+		let header4 = relay_header(4, header3.hash(), Default::default());
+
+		assert_noop!(
+			BridgeDot::submit_finalized_header(
+				Origin::signed(1),
+				BlockchainType::POLKADOT,
+				header4.clone(),
+				vec![header1.clone(), header2.clone(), header3.clone()],
+				justify(&header4, 0, &old_voters),
+			),
+			BridgeDotError::ChangeHeaderSkipped
+		);
+		assert_ok!(BridgeDot::submit_finalized_header(
+			Origin::signed(1),
+			BlockchainType::POLKADOT,
+			header2.clone(),
+			vec![header1],
+			justify(&header2, 0, &old_voters),
+		));
+		assert_eq!(
+			BridgeDot::relay_chain(BlockchainType::POLKADOT).unwrap().pending_change,
+			Some(PendingChange { enacted_at: 3, next_authorities: authority_list(&new_voters), forced: true })
+		);
+
+		// the old authority set doesn't finalize the change block of a forced change
+		assert_ok!(BridgeDot::submit_finalized_header(
+			Origin::signed(1),
+			BlockchainType::POLKADOT,
+			header4.clone(),
+			vec![header3],
+			justify(&header4, 1, &new_voters),
+		));
+		let relay = BridgeDot::relay_chain(BlockchainType::POLKADOT).unwrap();
+		assert_eq!((relay.set_id, relay.best_finalized, relay.pending_change), (1, (4, header4.hash()), None));
+	});
+}
+
+#[test]
+fn finalized_headers_should_be_pruned() {
+	new_test_ext().execute_with(|| {
+		let genesis = init_polkadot();
+		let voters = voters(0);
+
+		let mut parent = genesis.hash();
+		for number in 1..=FINALIZED_HEADERS_HISTORY as RelayBlockNumber {
+			let header = relay_header(number, parent, Default::default());
+			finalize(&header, 0, &voters);
+			parent = header.hash();
+		}
+
+		assert_eq!(BridgeDot::finalized_hashes(BlockchainType::POLKADOT).len(), FINALIZED_HEADERS_HISTORY);
+		assert_eq!(BridgeDot::finalized_header(BlockchainType::POLKADOT, genesis.hash()), None);
+		assert!(BridgeDot::finalized_header(BlockchainType::POLKADOT, parent).is_some());
+	});
+}
+
+#[test]
+fn deposit_should_be_proved_and_issued() {
+	new_test_ext().execute_with(|| {
+		let genesis = init_polkadot();
+		let voters = voters(0);
+		assert_ok!(Assets::create(Origin::ROOT, b"DOT".to_vec(), 10));
+
+		assert_ok!(BridgeDot::assign_deposit_account(Origin::signed(1), BlockchainType::POLKADOT));
+		assert_ok!(BridgeDot::assign_deposit_account(Origin::signed(2), BlockchainType::POLKADOT));
+		assert_noop!(
+			BridgeDot::assign_deposit_account(Origin::signed(1), BlockchainType::POLKADOT),
+			BridgeDotError::DepositAccountAssigned
+		);
+		let account = BridgeDot::deposit_account_of(BlockchainType::POLKADOT, &1).unwrap();
+		assert_eq!(account, relay::derivative_account(&BRIDGE_ACCOUNT, 0));
+		assert_ne!(BridgeDot::deposit_account_of(BlockchainType::POLKADOT, &2), Some(account));
+		assert!(has_event(RawEvent::DepositAccountAssigned(BlockchainType::POLKADOT, 1, account)));
+
+		let (root, proof) = relay_state(&account, 30);
+		let header1 = relay_header(1, genesis.hash(), root);
+		finalize(&header1, 0, &voters);
+
+		assert_noop!(
+			BridgeDot::prove_deposit(Origin::signed(3), BlockchainType::POLKADOT, H256::repeat_byte(1), account, proof.clone()),
+			BridgeDotError::UnknownHeader
+		);
+		assert_noop!(
+			BridgeDot::prove_deposit(Origin::signed(3), BlockchainType::POLKADOT, header1.hash(), [1; 32], proof.clone()),
+			BridgeDotError::UnknownDepositAccount
+		);
+		assert_noop!(
+			BridgeDot::prove_deposit(Origin::signed(3), BlockchainType::POLKADOT, header1.hash(), account, vec![]),
+			BridgeDotError::InvalidStorageProof
+		);
+
+		// anyone proves the deposit for the owner
+		assert_ok!(BridgeDot::prove_deposit(Origin::signed(3), BlockchainType::POLKADOT, header1.hash(), account, proof.clone()));
+		assert_eq!(Assets::asset_balances(DOT_ASSET_ID, TokenType::Token, 1), 30);
+		assert!(has_event(RawEvent::DepositReceived(BlockchainType::POLKADOT, 1, 30)));
+		assert_noop!(
+			BridgeDot::prove_deposit(Origin::signed(3), BlockchainType::POLKADOT, header1.hash(), account, proof),
+			BridgeDotError::OldHeader
+		);
+
+		// only what's received since the last proof is issued
+		let (root, proof) = relay_state(&account, 50);
+		let header2 = relay_header(2, header1.hash(), root);
+		finalize(&header2, 0, &voters);
+		assert_ok!(BridgeDot::prove_deposit(Origin::signed(3), BlockchainType::POLKADOT, header2.hash(), account, proof));
+		assert_eq!(Assets::asset_balances(DOT_ASSET_ID, TokenType::Token, 1), 50);
+		assert_eq!(BridgeDot::deposit_account(BlockchainType::POLKADOT, account).unwrap().credited, 50);
+	});
+}
+
+#[test]
+fn redeem_should_be_approved_by_notaries() {
+	new_test_ext().execute_with(|| {
+		init_polkadot();
+		issue_dot(1);
+		assert_ok!(BridgeDot::set_notaries(Origin::ROOT, BlockchainType::POLKADOT, vec![10, 11, 12], 2));
+
+		let alice = hex!["d43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d"];
+		let to = relay::encode_ss58(&alice, 0);
+		let bridge_asset = |amount: u64, token_type: TokenType| BridgeAssetBalance {
+			symbol: BridgeDot::native_symbol(BlockchainType::POLKADOT),
+			amount,
+			memo: vec![],
+			from: 1,
+			token_type,
+		};
+		let send_asset = |target: Vec<u8>, asset| <BridgeDot as ChainBridge<u64, u32, u64>>::send_asset(target, asset);
+
+		assert_noop!(send_asset(to.clone(), bridge_asset(30, TokenType::VToken)), BridgeDotError::VTokenNotWithdrawable);
+		assert_noop!(send_asset(b"alice".to_vec(), bridge_asset(30, TokenType::Token)), BridgeDotError::InvalidRelayAccount);
+		assert_noop!(send_asset(to.clone(), bridge_asset(101, TokenType::Token)), BridgeDotError::InsufficientBalance);
+
+		assert_ok!(send_asset(to.clone(), bridge_asset(30, TokenType::Token)));
+		assert_ok!(send_asset(to, bridge_asset(20, TokenType::Token)));
+		assert_eq!(Assets::asset_balances(DOT_ASSET_ID, TokenType::Token, 1), 50);
+		let withdrawal = BridgeDot::withdrawal(0).unwrap();
+		assert_eq!(
+			(withdrawal.kind, withdrawal.to, withdrawal.amount, withdrawal.status),
+			(WithdrawalKind::Redeem(1, 30), alice, 30, WithdrawalStatus::Pending)
+		);
+
+		assert_noop!(BridgeDot::approve_withdrawal(Origin::signed(1), 0), BridgeDotError::NotNotary);
+		assert_noop!(BridgeDot::report_withdrawal(Origin::signed(10), 0, true, 1), BridgeDotError::InvalidWithdrawalStatus);
+		assert_ok!(BridgeDot::approve_withdrawal(Origin::signed(10), 0));
+		assert_noop!(BridgeDot::approve_withdrawal(Origin::signed(10), 0), BridgeDotError::AlreadyApproved);
+		assert_ok!(BridgeDot::approve_withdrawal(Origin::signed(11), 0));
+		assert_eq!(BridgeDot::withdrawal(0).unwrap().status, WithdrawalStatus::Approved);
+		assert!(has_event(RawEvent::WithdrawalApproved(BlockchainType::POLKADOT, 0)));
+
+		assert_ok!(BridgeDot::report_withdrawal(Origin::signed(12), 0, true, 1));
+		assert_eq!(BridgeDot::withdrawal(0).unwrap().status, WithdrawalStatus::Completed);
+
+		// failed withdrawal is refunded
+		assert_ok!(BridgeDot::approve_withdrawal(Origin::signed(10), 1));
+		assert_ok!(BridgeDot::approve_withdrawal(Origin::signed(12), 1));
+		assert_ok!(BridgeDot::report_withdrawal(Origin::signed(11), 1, false, 1));
+		assert_eq!(BridgeDot::withdrawal(1).unwrap().status, WithdrawalStatus::Failed);
+		assert_eq!(Assets::asset_balances(DOT_ASSET_ID, TokenType::Token, 1), 70);
+	});
+}
+
+#[test]
+fn swept_deposit_should_not_be_issued_again() {
+	new_test_ext().execute_with(|| {
+		let genesis = init_polkadot();
+		let voters = voters(0);
+		assert_ok!(Assets::create(Origin::ROOT, b"DOT".to_vec(), 10));
+		assert_ok!(BridgeDot::set_notaries(Origin::ROOT, BlockchainType::POLKADOT, vec![10, 11], 1));
+		assert_ok!(BridgeDot::assign_deposit_account(Origin::signed(1), BlockchainType::POLKADOT));
+		let account = BridgeDot::deposit_account_of(BlockchainType::POLKADOT, &1).unwrap();
+
+		let (root, proof) = relay_state(&account, 30);
+		let header1 = relay_header(1, genesis.hash(), root);
+		finalize(&header1, 0, &voters);
+		assert_ok!(BridgeDot::prove_deposit(Origin::signed(1), BlockchainType::POLKADOT, header1.hash(), account, proof));
+
+		assert_noop!(
+			BridgeDot::propose_sweep(Origin::signed(10), BlockchainType::POLKADOT, account, 31),
+			BridgeDotError::InsufficientDeposit
+		);
+		assert_ok!(BridgeDot::propose_sweep(Origin::signed(10), BlockchainType::POLKADOT, account, 30));
+		// approved by the proposer, as the threshold is 1
+		let withdrawal = BridgeDot::withdrawal(0).unwrap();
+		assert_eq!(
+			(withdrawal.kind, withdrawal.to, withdrawal.status),
+			(WithdrawalKind::Sweep(account), BRIDGE_ACCOUNT, WithdrawalStatus::Approved)
+		);
+		assert_noop!(
+			BridgeDot::propose_sweep(Origin::signed(11), BlockchainType::POLKADOT, account, 1),
+			BridgeDotError::InsufficientDeposit
+		);
+		// the sweep is executed at header 3
+		assert_ok!(BridgeDot::report_withdrawal(Origin::signed(11), 0, true, 3));
+
+		// balance before the sweep isn't received again
+		let (root, proof) = relay_state(&account, 30);
+		let header2 = relay_header(2, header1.hash(), root);
+		finalize(&header2, 0, &voters);
+		assert_noop!(
+			BridgeDot::prove_deposit(Origin::signed(1), BlockchainType::POLKADOT, header2.hash(), account, proof),
+			BridgeDotError::HeaderBeforeSweep
+		);
+
+		// 30 was swept and 5 more was received
+		let (root, proof) = relay_state(&account, 5);
+		let header3 = relay_header(3, header2.hash(), root);
+		finalize(&header3, 0, &voters);
+		assert_ok!(BridgeDot::prove_deposit(Origin::signed(1), BlockchainType::POLKADOT, header3.hash(), account, proof));
+		assert_eq!(Assets::asset_balances(DOT_ASSET_ID, TokenType::Token, 1), 35);
+
+		let deposit = BridgeDot::deposit_account(BlockchainType::POLKADOT, account).unwrap();
+		assert_eq!((deposit.credited, deposit.swept, deposit.swept_at, deposit.sweeping), (35, 30, 3, 0));
+	});
+}
+
+#[test]
+fn relay_accounts_should_be_verified() {
+	new_test_ext().execute_with(|| {
+		let alice = hex!["d43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d"];
+		let polkadot_alice = b"15oF4uVJwmo4TdGW7VfQxNLavjCXviqxT9S1MgbjMNHr6Sp5";
+		assert_eq!(relay::decode_ss58(polkadot_alice, 0), Some(alice));
+		assert_eq!(relay::encode_ss58(&alice, 0), polkadot_alice.to_vec());
+		assert_eq!(relay::decode_ss58(polkadot_alice, 2), None);
+		assert_eq!(relay::decode_ss58(&relay::encode_ss58(&alice, 2), 2), Some(alice));
+
+		assert!(<BridgeDot as ChainBridge<u64, u32, u64>>::is_valid_account(BlockchainType::POLKADOT, polkadot_alice));
+		assert!(!<BridgeDot as ChainBridge<u64, u32, u64>>::is_valid_account(BlockchainType::KUSAMA, polkadot_alice));
+		let mut corrupted = polkadot_alice.to_vec();
+		corrupted[10] = b'z';
+		assert!(!<BridgeDot as ChainBridge<u64, u32, u64>>::is_valid_account(BlockchainType::POLKADOT, &corrupted));
+
+		// the proof is signature of the message by the relay account
+		let pair = sr25519::Pair::from_seed(&[7; 32]);
+		let address = relay::encode_ss58(&pair.public().0, 0);
+		let message = b"bind account";
+		let signature = pair.sign(message);
+		let verify = |proof: Option<&[u8]>| {
+			<BridgeDot as ChainBridge<u64, u32, u64>>::verify_account_proof(BlockchainType::POLKADOT, &address, message, proof)
+		};
+		assert!(verify(Some(signature.as_ref())));
+		assert!(!verify(Some(pair.sign(b"another message").as_ref())));
+		assert!(!verify(None));
+	});
+}
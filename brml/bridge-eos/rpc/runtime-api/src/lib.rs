@@ -0,0 +1,49 @@
+// Copyright 2019-2020 Liebi Technologies.
+// This file is part of Bifrost.
+
+// Bifrost is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Bifrost is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Bifrost.  If not, see <http://www.gnu.org/licenses/>.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+use sp_api::decl_runtime_apis;
+use sp_std::vec::Vec;
+
+#[allow(unused_doc_comments)]
+/// Create bridge-eos runtime api for rpc call
+decl_runtime_apis! {
+	pub trait BridgeEosApi<AccountId, TxOutId, TxOutStatus, ActionReceipt, ProducerSchedule, NotaryStatus> where
+		AccountId: Codec,
+		TxOutId: Codec,
+		TxOutStatus: Codec,
+		ActionReceipt: Codec,
+		ProducerSchedule: Codec,
+		NotaryStatus: Codec
+	{
+		/// get status of a transaction sent to EOS
+		fn tx_out_status(id: TxOutId) -> Option<TxOutStatus>;
+
+		/// get current and pending producer schedules of EOS
+		fn producer_schedules() -> (Option<ProducerSchedule>, Option<ProducerSchedule>);
+
+		/// whether the action receipt has been proven
+		fn is_action_proven(action_receipt: ActionReceipt) -> bool;
+
+		/// get transactions sent to EOS by the account
+		fn account_tx_outs(who: AccountId) -> Vec<(TxOutId, TxOutStatus)>;
+
+		/// get whether the account is a notary, and transactions waiting for its signature
+		fn notary_status(who: AccountId) -> NotaryStatus;
+	}
+}
@@ -0,0 +1,134 @@
+// Copyright 2019-2020 Liebi Technologies.
+// This file is part of Bifrost.
+
+// Bifrost is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Bifrost is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Bifrost.  If not, see <http://www.gnu.org/licenses/>.
+
+use codec::Codec;
+use jsonrpc_derive::rpc;
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result as JsonRpcResult};
+use std::sync::Arc;
+use std::marker::PhantomData;
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+pub use self::gen_client::Client as BridgeEosClient;
+pub use bridge_eos_rpc_runtime_api::{self as runtime_api, BridgeEosApi as BridgeEosRuntimeApi};
+
+#[derive(Clone, Debug)]
+pub struct BridgeEos<C, Block> {
+	client: Arc<C>,
+	_marker: PhantomData<Block>
+}
+
+impl<C, Block> BridgeEos<C, Block> {
+	pub fn new(client: Arc<C>) -> Self {
+		Self {
+			client,
+			_marker: PhantomData
+		}
+	}
+}
+
+#[rpc]
+pub trait BridgeEosApi<BlockHash, AccountId, TxOutId, TxOutStatus, ActionReceipt, ProducerSchedule, NotaryStatus> {
+	/// rpc method for getting status of a withdrawal to EOS
+	#[rpc(name = "bridgeEos_getTxOutStatus")]
+	fn get_tx_out_status(&self, id: TxOutId, at: Option<BlockHash>) -> JsonRpcResult<Option<TxOutStatus>>;
+
+	/// rpc method for getting current and pending producer schedules of EOS
+	#[rpc(name = "bridgeEos_getProducerSchedules")]
+	fn get_producer_schedules(&self, at: Option<BlockHash>) -> JsonRpcResult<(Option<ProducerSchedule>, Option<ProducerSchedule>)>;
+
+	/// rpc method for checking whether an action receipt has been proven
+	#[rpc(name = "bridgeEos_isActionProven")]
+	fn is_action_proven(&self, action_receipt: ActionReceipt, at: Option<BlockHash>) -> JsonRpcResult<bool>;
+
+	/// rpc method for getting pending and finished withdrawals of an account
+	#[rpc(name = "bridgeEos_getAccountTxOuts")]
+	fn get_account_tx_outs(&self, who: AccountId, at: Option<BlockHash>) -> JsonRpcResult<Vec<(TxOutId, TxOutStatus)>>;
+
+	/// rpc method for getting status of a notary
+	#[rpc(name = "bridgeEos_getNotaryStatus")]
+	fn get_notary_status(&self, who: AccountId, at: Option<BlockHash>) -> JsonRpcResult<NotaryStatus>;
+}
+
+fn runtime_error<E: std::fmt::Debug>(message: &str, e: E) -> RpcError {
+	RpcError {
+		code: ErrorCode::InternalError,
+		message: message.to_owned(),
+		data: Some(format!("{:?}", e).into()),
+	}
+}
+
+impl<C, Block, AccountId, TxOutId, TxOutStatus, ActionReceipt, ProducerSchedule, NotaryStatus>
+BridgeEosApi<<Block as BlockT>::Hash, AccountId, TxOutId, TxOutStatus, ActionReceipt, ProducerSchedule, NotaryStatus>
+for BridgeEos<C, Block>
+	where
+		Block: BlockT,
+		C: Send + Sync + 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block>,
+		C::Api: BridgeEosRuntimeApi<Block, AccountId, TxOutId, TxOutStatus, ActionReceipt, ProducerSchedule, NotaryStatus>,
+		AccountId: Codec,
+		TxOutId: Codec,
+		TxOutStatus: Codec,
+		ActionReceipt: Codec,
+		ProducerSchedule: Codec,
+		NotaryStatus: Codec,
+{
+	fn get_tx_out_status(&self, id: TxOutId, at: Option<<Block as BlockT>::Hash>) -> JsonRpcResult<Option<TxOutStatus>> {
+		let bridge_eos_rpc_api = self.client.runtime_api();
+		let at = BlockId::<Block>::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+		bridge_eos_rpc_api.tx_out_status(&at, id)
+			.map_err(|e| runtime_error("Failed to get transaction status.", e))
+	}
+
+	fn get_producer_schedules(
+		&self,
+		at: Option<<Block as BlockT>::Hash>
+	) -> JsonRpcResult<(Option<ProducerSchedule>, Option<ProducerSchedule>)> {
+		let bridge_eos_rpc_api = self.client.runtime_api();
+		let at = BlockId::<Block>::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+		bridge_eos_rpc_api.producer_schedules(&at)
+			.map_err(|e| runtime_error("Failed to get producer schedules.", e))
+	}
+
+	fn is_action_proven(&self, action_receipt: ActionReceipt, at: Option<<Block as BlockT>::Hash>) -> JsonRpcResult<bool> {
+		let bridge_eos_rpc_api = self.client.runtime_api();
+		let at = BlockId::<Block>::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+		bridge_eos_rpc_api.is_action_proven(&at, action_receipt)
+			.map_err(|e| runtime_error("Failed to check action receipt.", e))
+	}
+
+	fn get_account_tx_outs(
+		&self,
+		who: AccountId,
+		at: Option<<Block as BlockT>::Hash>
+	) -> JsonRpcResult<Vec<(TxOutId, TxOutStatus)>> {
+		let bridge_eos_rpc_api = self.client.runtime_api();
+		let at = BlockId::<Block>::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+		bridge_eos_rpc_api.account_tx_outs(&at, who)
+			.map_err(|e| runtime_error("Failed to get transactions of account.", e))
+	}
+
+	fn get_notary_status(&self, who: AccountId, at: Option<<Block as BlockT>::Hash>) -> JsonRpcResult<NotaryStatus> {
+		let bridge_eos_rpc_api = self.client.runtime_api();
+		let at = BlockId::<Block>::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+		bridge_eos_rpc_api.notary_status(&at, who)
+			.map_err(|e| runtime_error("Failed to get notary status.", e))
+	}
+}
@@ -0,0 +1,500 @@
+// Copyright 2019-2020 Liebi Technologies.
+// This file is part of Bifrost.
+
+// Bifrost is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Bifrost is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Bifrost.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Benchmarks of brml_bridge_eos, EOS block headers and proofs come from the fixtures in `test_data`.
+
+use super::*;
+use eos_chain::{
+	get_proof, BlockSigningAuthority, BlockSigningAuthorityV0, KeyWeight, Transaction,
+};
+use frame_benchmarking::{account, benchmarks};
+use frame_system::RawOrigin;
+
+const SEED: u32 = 0;
+
+fn read_fixture<V: serde::de::DeserializeOwned>(json: &str) -> V {
+	serde_json::from_str(json).expect("fixtures are valid json; qed")
+}
+
+fn read_block_ids(json: &str) -> Vec<Vec<Checksum256>> {
+	let ids: Vec<Vec<String>> = read_fixture(json);
+	ids.iter().map(|ids| {
+		ids.iter().map(|id| Checksum256::from_str(id).expect("fixtures are valid block ids; qed")).collect()
+	}).collect()
+}
+
+/// Fixtures are legacy schedules, each producer signs blocks with a single key.
+fn authority_schedule(ps: ProducerSchedule) -> ProducerAuthoritySchedule {
+	let producers = ps.producers.into_iter().map(|producer| ProducerAuthority {
+		producer_name: producer.producer_name,
+		authority: BlockSigningAuthority(0u32.into(), BlockSigningAuthorityV0 {
+			threshold: 1,
+			keys: vec![KeyWeight { key: producer.block_signing_key, weight: 1 }],
+		}),
+	}).collect();
+
+	ProducerAuthoritySchedule::new(ps.version, producers)
+}
+
+fn save_schedule<T: Trait>(json: &str) {
+	let schedule: ProducerSchedule = read_fixture(json);
+	let _ = Module::<T>::save_producer_schedule(RawOrigin::Root.into(), authority_schedule(schedule));
+}
+
+/// Action, receipt and merkle path of the transfer in block 10776
+fn action_proof_10776() -> (Action, ActionReceipt, Vec<Checksum256>) {
+	let actions: Vec<Action> = read_fixture(include_str!("../test_data/actions_from_10776.json"));
+	let action_receipt: ActionReceipt = read_fixture(r#"{
+		"receiver": "megasuper333",
+		"act_digest": "eaa3b4bf845a1b41668ab7ca49fb5644fc91a6c0156dfd33911b4ec69d2e41d6",
+		"global_sequence": 3040972,
+		"recv_sequence": 1,
+		"auth_sequence": [["junglefaucet", 21]],
+		"code_sequence": 2,
+		"abi_sequence": 2
+	}"#);
+	let paths: Vec<String> = read_fixture(include_str!("../test_data/action_merkle_paths.json"));
+	let paths = paths.iter().map(|path| Checksum256::from_str(path).expect("fixtures are valid paths; qed")).collect();
+	let merkle_paths = get_proof(15, paths).expect("fixtures are valid proof; qed");
+
+	(actions[3].clone(), action_receipt, merkle_paths)
+}
+
+/// Block root merkle for block 10776, which doesn't include the previous block
+fn merkle_10776() -> IncrementalMerkle {
+	IncrementalMerkle::new(10774, vec![
+		"45c2c1cbc4b049d72a627124b05f5c476ae1cc87955fbea70bc8dbe549cf395a".into(),
+		"d96747605aaed959630b23a28e0004f42a87eae93f51d5fe241735644a0c3921".into(),
+		"937a489eea576d74a3d091cc4dcf1cb867f01e314ac7f1334f6cec00dfcee476".into(),
+		"36cbf5d9c35b2538181bf7f8af4ee57c55c17e516eedd992a73bace9ca14a5c3".into(),
+		"40e8bb864481e7bb01674ec3517c84e557869fea8160c4b2762d3e83d71d6034".into(),
+		"afa502d408f5bdf1660fa9fe3a1fcb432462467e7eb403a8499392ee5297d8d1".into(),
+		"f1329d3ee84040279460cbc87b6769b7363e477a832f73d639e0692a4042f093".into(),
+	])
+}
+
+/// Map EOS to a new asset, and issue tokens to the account
+fn setup_withdraw<T: Trait>(who: &T::AccountId) -> T::AssetId {
+	let (asset_id, _) = T::AssetTrait::asset_create(b"EOS".to_vec(), 4);
+	T::AssetTrait::asset_issue(asset_id, TokenType::Token, who.clone(), T::Balance::from(u32::max_value()));
+	let _ = Module::<T>::set_bridge_token(
+		RawOrigin::Root.into(), asset_id, b"eosio.token".to_vec(), b"EOS".to_vec(), 4
+	);
+	BridgeContractAccount::put((b"bifrostcross".to_vec(), 2));
+	BridgeReserves::<T>::insert(asset_id, BridgeReserve {
+		locked: T::Balance::from(u32::max_value()),
+		minted: T::Balance::from(u32::max_value()),
+		withdrawing: Zero::zero(),
+		staked: Zero::zero(),
+		refunding: Zero::zero(),
+	});
+
+	asset_id
+}
+
+/// Withdraw n times, each transaction is generated and waits for signatures of notaries
+fn setup_generated_tx_outs<T: Trait>(who: &T::AccountId, n: u32) {
+	let asset_id = setup_withdraw::<T>(who);
+	let amount = T::Balance::from(10u32.pow(4)) * T::Balance::from(10u32.pow(8));
+	for id in 0..n as TxOutId {
+		let _ = Module::<T>::asset_redeem(
+			RawOrigin::Signed(who.clone()).into(), asset_id, b"alice".to_vec(), TokenType::Token, amount, vec![]
+		);
+		if let Some(TxOut::Initial(multi_sig_tx)) = BridgeTxOuts::<T>::get(id) {
+			let raw_tx = Transaction::new(0, 0, 0, multi_sig_tx.actions().to_vec())
+				.to_serialize_data()
+				.expect("transaction is serializable; qed");
+			BridgeTxOuts::<T>::insert(id, multi_sig_tx.into_generated(vec![0u8; 32], raw_tx));
+		}
+	}
+}
+
+/// Make the notary sign with the key of account testa
+fn set_notary_eos_key<T: Trait>(notary: &T::AccountId) {
+	let key = Module::<T>::parse_eos_public_key(b"EOS6hMjoWRF2L8x9YpeqtUEcsDKAyxSuM1APicxgRU1E3oyV5sDEg")
+		.expect("fixture is a valid key; qed");
+	NotaryEosKeys::<T>::insert(notary, key);
+}
+
+/// Signatures of the generated transactions by account testa
+fn eos_signatures<T: Trait>(n: u32) -> Vec<(TxOutId, Vec<u8>)> {
+	let sk = SecretKey::from_wif("5JgbL2ZnoEAhTudReWH1RnMuQS6DBeLZt4ucV6t8aymVEuYg7sr").expect("fixture is a valid key; qed");
+	(0..n as TxOutId)
+		.filter_map(|id| {
+			let tx_out = BridgeTxOuts::<T>::get(id)?;
+			tx_out.eos_signature::<T>(sk.clone()).ok().map(|signature| (id, signature))
+		})
+		.collect()
+}
+
+/// Start header relay from block 10776, which is signed by producers of schedule v2
+fn init_relay_10776<T: Trait>() -> SignedBlockHeader {
+	let schedule: ProducerSchedule = read_fixture(include_str!("../test_data/schedule_v2.json"));
+	let schedule_hash = schedule.schedule_hash().expect("fixture has valid schedule; qed");
+	let version = schedule.version;
+	ProducerSchedules::insert(version, (authority_schedule(schedule).producers, schedule_hash));
+
+	let block_headers: Vec<SignedBlockHeader> = read_fixture(include_str!("../test_data/actions_verification_10776.json"));
+	let mut merkle = merkle_10776();
+	merkle.append(block_headers[0].block_header.previous).expect("merkle isn't full; qed");
+	let _ = Module::<T>::init_relay(RawOrigin::Root.into(), block_headers[0].clone(), merkle);
+
+	block_headers[0].clone()
+}
+
+benchmarks! {
+	_ { }
+
+	change_schedule {
+		let caller: T::AccountId = account("relayer", 0, SEED);
+		save_schedule::<T>(include_str!("../test_data/schedule_v1.json"));
+
+		let block_headers: Vec<SignedBlockHeader> = read_fixture(include_str!("../test_data/change_schedule_9313.json"));
+		let block_ids_list = read_block_ids(include_str!("../test_data/block_ids_list.json"));
+		let legacy_schedule = block_headers[0].block_header.new_producers.clone().expect("fixture has new producers; qed");
+		let legacy_schedule_hash = legacy_schedule.schedule_hash().expect("fixture has valid schedule; qed");
+		let new_schedule = authority_schedule(legacy_schedule);
+		let merkle = IncrementalMerkle::new(9311, vec![
+			"0000245f60aa338bd246cb7598a14796ee0210f669f9c9b37f6ddad0b5765649".into(),
+			"9d41d4581cab233fe68c4510cacd05d0cc979c53ae317ce9364040578037de6a".into(),
+			"a397d1a6dc90389dc592ea144b1801c4b323c12b0b2f066aa55faa5892803317".into(),
+			"0cf502411e185ea7e3cc790e0b757807987e767a81c463c3e4ee5970b7fd1c67".into(),
+			"9f774a35e86ddb2d293da1bfe2e25b7b447fd3d9372ee580fce230a87fefa586".into(),
+			"4d018eda9a22334ac0492489fdf79118d696eea52af3871a7e4bf0e2d5ab5945".into(),
+			"acba7c7ee5c1d8ba97ea1a841707fbb2147e883b56544ba821814aebe086383e".into(),
+			"afa502d408f5bdf1660fa9fe3a1fcb432462467e7eb403a8499392ee5297d8d1".into(),
+			"4d723385cad26cf80c2db366f9666a3ef77679c098e07d1af48d523b64b1d460".into(),
+		]);
+	}: _(RawOrigin::Signed(caller), legacy_schedule_hash, new_schedule, merkle, block_headers, block_ids_list)
+	verify {
+		assert_eq!(PendingScheduleVersion::get(), 2);
+	}
+
+	prove_action {
+		let caller: T::AccountId = account("relayer", 0, SEED);
+		save_schedule::<T>(include_str!("../test_data/schedule_v2.json"));
+
+		let block_headers: Vec<SignedBlockHeader> = read_fixture(include_str!("../test_data/actions_verification_10776.json"));
+		let block_ids_list = read_block_ids(include_str!("../test_data/block_ids_list_10776.json"));
+		let (action, action_receipt, merkle_paths) = action_proof_10776();
+	}: _(
+		RawOrigin::Signed(caller),
+		action.clone(),
+		action_receipt.clone(),
+		merkle_paths,
+		merkle_10776(),
+		block_headers,
+		block_ids_list
+	)
+	verify {
+		assert_eq!(BridgeActionReceipt::get(&action_receipt), action);
+	}
+
+	init_relay {
+		let block_headers: Vec<SignedBlockHeader> = read_fixture(include_str!("../test_data/actions_verification_10776.json"));
+		let mut merkle = merkle_10776();
+		merkle.append(block_headers[0].block_header.previous).expect("merkle isn't full; qed");
+	}: _(RawOrigin::Root, block_headers[0].clone(), merkle)
+	verify {
+		assert!(RelayHead::get().is_some());
+	}
+
+	prove_action_by_relay {
+		let caller: T::AccountId = account("relayer", 0, SEED);
+		let block_headers: Vec<SignedBlockHeader> = read_fixture(include_str!("../test_data/actions_verification_10776.json"));
+		let mut merkle = merkle_10776();
+		merkle.append(block_headers[0].block_header.previous).expect("merkle isn't full; qed");
+		let _ = Module::<T>::init_relay(RawOrigin::Root.into(), block_headers[0].clone(), merkle);
+
+		let (action, action_receipt, merkle_paths) = action_proof_10776();
+		let block_num = block_headers[0].block_header.block_num();
+	}: _(RawOrigin::Signed(caller), action.clone(), action_receipt.clone(), merkle_paths, block_num)
+	verify {
+		assert_eq!(BridgeActionReceipt::get(&action_receipt), action);
+	}
+
+	asset_redeem {
+		let m in 0 .. 256;
+
+		let caller: T::AccountId = account("caller", 0, SEED);
+		let asset_id = setup_withdraw::<T>(&caller);
+		let amount = T::Balance::from(10u32.pow(4)) * T::Balance::from(10u32.pow(8));
+		let memo = vec![b'a'; m as usize];
+	}: _(RawOrigin::Signed(caller), asset_id, b"alice".to_vec(), TokenType::Token, amount, memo)
+	verify {
+		assert_eq!(NextTxOutId::get(), 1);
+	}
+
+	bridge_tx_report {
+		let n in 1 .. 100;
+
+		let caller: T::AccountId = account("caller", 0, SEED);
+		let asset_id = setup_withdraw::<T>(&caller);
+		let amount = T::Balance::from(10u32.pow(4)) * T::Balance::from(10u32.pow(8));
+		for _ in 0..n {
+			let _ = Module::<T>::asset_redeem(
+				RawOrigin::Signed(caller.clone()).into(), asset_id, b"alice".to_vec(), TokenType::Token, amount, vec![]
+			);
+		}
+
+		// failed transactions release the escrows, which is the heaviest report
+		let tx_list = (0..n as TxOutId).filter_map(|id| {
+			BridgeTxOuts::<T>::get(id).map(|tx_out| (id, tx_out.fail(b"benchmark")))
+		}).collect::<Vec<_>>();
+		let notary: T::AccountId = account("notary", 0, SEED);
+		NotaryKeys::<T>::put(vec![notary.clone()]);
+	}: _(RawOrigin::Signed(notary), tx_list)
+	verify {
+		assert!(PendingTxOuts::get().is_empty());
+	}
+
+	relay_block_headers {
+		let caller: T::AccountId = account("relayer", 0, SEED);
+		init_relay_10776::<T>();
+
+		// block 10777 is the only fixture which follows a relayed block
+		let block_headers: Vec<SignedBlockHeader> = read_fixture(include_str!("../test_data/blocks_10777.json"));
+	}: _(RawOrigin::Signed(caller), vec![block_headers[0].clone()])
+	verify {
+		assert_eq!(RelayHead::get().map(|(num, _)| num), Some(10777));
+	}
+
+	prove_tx_out_expired {
+		let caller: T::AccountId = account("caller", 0, SEED);
+		setup_generated_tx_outs::<T>(&caller, 1);
+		if let Some(TxOut::Generated(multi_sig_tx)) = BridgeTxOuts::<T>::get(0) {
+			BridgeTxOuts::<T>::insert(0, TxOut::Signed(multi_sig_tx));
+		}
+		// the transaction expires at 0, before the relayed block
+		let block_num = init_relay_10776::<T>().block_header.block_num();
+	}: _(RawOrigin::Signed(caller), 0, block_num)
+	verify {
+		assert!(ExpiredTxOuts::<T>::contains_key(0));
+	}
+
+	submit_signatures {
+		let s in 1 .. 100;
+
+		let caller: T::AccountId = account("caller", 0, SEED);
+		setup_generated_tx_outs::<T>(&caller, s);
+		let notary: T::AccountId = account("notary", 0, SEED);
+		NotaryKeys::<T>::put(vec![notary.clone()]);
+		set_notary_eos_key::<T>(&notary);
+
+		let signatures = eos_signatures::<T>(s);
+	}: _(RawOrigin::Signed(notary), signatures)
+	verify {
+		assert_eq!(BridgeTxOuts::<T>::get(0).map(|tx_out| tx_out.signature_count()), Some(1));
+	}
+
+	set_notary_keys {
+		let n in 1 .. 21;
+		let p in 1 .. MAX_PENDING_TX_OUTS as u32;
+
+		// every pending transaction is signed by the notary who will be removed
+		let caller: T::AccountId = account("caller", 0, SEED);
+		setup_generated_tx_outs::<T>(&caller, p);
+		let removed: T::AccountId = account("notary", 0, SEED);
+		NotaryKeys::<T>::put(vec![removed.clone()]);
+		set_notary_eos_key::<T>(&removed);
+		let _ = Module::<T>::submit_signatures(RawOrigin::Signed(removed).into(), eos_signatures::<T>(p));
+
+		let notaries = (1..=n).map(|i| account("notary", i, SEED)).collect::<Vec<T::AccountId>>();
+	}: _(RawOrigin::Root, notaries)
+	verify {
+		assert_eq!(BridgeTxOuts::<T>::get(0).map(|tx_out| tx_out.signature_count()), Some(0));
+	}
+
+	set_notary_eos_key {
+		let n in 1 .. 21;
+
+		let notaries = (0..n).map(|i| account("notary", i, SEED)).collect::<Vec<T::AccountId>>();
+		NotaryKeys::<T>::put(&notaries);
+		let notary = notaries[0].clone();
+	}: _(RawOrigin::Root, notary.clone(), b"EOS6hMjoWRF2L8x9YpeqtUEcsDKAyxSuM1APicxgRU1E3oyV5sDEg".to_vec())
+	verify {
+		assert!(NotaryEosKeys::<T>::contains_key(&notary));
+	}
+
+	save_producer_schedule {
+		let schedule: ProducerSchedule = read_fixture(include_str!("../test_data/schedule_v2.json"));
+	}: _(RawOrigin::Root, authority_schedule(schedule))
+	verify {
+		assert_eq!(PendingScheduleVersion::get(), 2);
+	}
+
+	init_schedule {
+		// schedule is initialized only once
+		PendingScheduleVersion::kill();
+		let schedule: ProducerSchedule = read_fixture(include_str!("../test_data/schedule_v2.json"));
+	}: _(RawOrigin::Root, authority_schedule(schedule))
+	verify {
+		assert_eq!(PendingScheduleVersion::get(), 2);
+	}
+
+	bridge_enable {
+	}: _(RawOrigin::Root, false)
+	verify {
+		assert!(!BridgeEnable::get());
+	}
+
+	grant_crosschain_privilege {
+		let target: T::AccountId = account("target", 0, SEED);
+	}: _(RawOrigin::Root, target.clone())
+	verify {
+		assert!(CrossChainPrivilege::<T>::get(&target));
+	}
+
+	remove_crosschain_privilege {
+		let target: T::AccountId = account("target", 0, SEED);
+		let _ = Module::<T>::grant_crosschain_privilege(RawOrigin::Root.into(), target.clone());
+	}: _(RawOrigin::Root, target.clone())
+	verify {
+		assert!(!CrossChainPrivilege::<T>::get(&target));
+	}
+
+	set_contract_accounts {
+	}: _(RawOrigin::Root, b"bifrostcross".to_vec(), 3)
+	verify {
+		assert_eq!(BridgeContractAccount::get().1, 3);
+	}
+
+	set_bridge_paused {
+	}: _(RawOrigin::Root, true, true)
+	verify {
+		assert!(WithdrawPaused::get());
+	}
+
+	set_withdraw_limit {
+		let caller: T::AccountId = account("caller", 0, SEED);
+		let asset_id = setup_withdraw::<T>(&caller);
+		let limit = WithdrawLimit {
+			window: T::BlockNumber::from(100u32),
+			global_cap: T::Balance::from(10u32.pow(8)),
+			account_cap: T::Balance::from(10u32.pow(6)),
+			delay_threshold: T::Balance::from(10u32.pow(5)),
+			delay: T::BlockNumber::from(10u32),
+		};
+	}: _(RawOrigin::Root, asset_id, Some(limit))
+	verify {
+		assert!(WithdrawLimits::<T>::get(asset_id).is_some());
+	}
+
+	reset_bridge_reserve {
+		let caller: T::AccountId = account("caller", 0, SEED);
+		let asset_id = setup_withdraw::<T>(&caller);
+		let amount = T::Balance::from(10u32.pow(8));
+	}: _(RawOrigin::Root, asset_id, amount, amount)
+	verify {
+		assert_eq!(BridgeReserves::<T>::get(asset_id).locked, amount);
+	}
+
+	report_reserve_deficit {
+		let caller: T::AccountId = account("caller", 0, SEED);
+		let asset_id = setup_withdraw::<T>(&caller);
+		let notary: T::AccountId = account("notary", 0, SEED);
+		NotaryKeys::<T>::put(vec![notary.clone()]);
+	}: _(RawOrigin::Signed(notary), asset_id, Zero::zero())
+	verify {
+		assert!(WithdrawPaused::get());
+	}
+
+	set_bridge_token {
+		let (asset_id, _) = T::AssetTrait::asset_create(b"EOS".to_vec(), 4);
+	}: _(RawOrigin::Root, asset_id, b"eosio.token".to_vec(), b"EOS".to_vec(), 4)
+	verify {
+		assert!(BridgeTokens::<T>::contains_key(asset_id));
+	}
+
+	remove_bridge_token {
+		let caller: T::AccountId = account("caller", 0, SEED);
+		let asset_id = setup_withdraw::<T>(&caller);
+	}: _(RawOrigin::Root, asset_id)
+	verify {
+		assert!(!BridgeTokens::<T>::contains_key(asset_id));
+	}
+
+	set_bridge_token_enabled {
+		let caller: T::AccountId = account("caller", 0, SEED);
+		let asset_id = setup_withdraw::<T>(&caller);
+	}: _(RawOrigin::Root, asset_id, false)
+	verify {
+		assert_eq!(BridgeTokens::<T>::get(asset_id).map(|token| token.enabled), Some(false));
+	}
+
+	set_schedule_config {
+	}: _(RawOrigin::Root, 10, 5, 4)
+	verify {
+		assert_eq!(ProveHeadersCount::get(), 10);
+	}
+
+	set_relay_fee {
+	}: _(RawOrigin::Root, 100u32.into())
+	verify {
+		assert_eq!(RelayFee::<T>::get(), RelayBalanceOf::<T>::from(100u32));
+	}
+
+	set_relay_reward {
+	}: _(RawOrigin::Root, RelayKind::ProveAction, 10u32.into())
+	verify {
+		assert_eq!(RelayRewards::<T>::get(RelayKind::ProveAction), RelayBalanceOf::<T>::from(10u32));
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::mock::{new_test_ext, Test};
+	use frame_support::assert_ok;
+
+	#[test]
+	fn test_benchmarks() {
+		let benchmarks: Vec<fn() -> Result<(), &'static str>> = vec![
+			test_benchmark_change_schedule::<Test>,
+			test_benchmark_prove_action::<Test>,
+			test_benchmark_init_relay::<Test>,
+			test_benchmark_prove_action_by_relay::<Test>,
+			test_benchmark_asset_redeem::<Test>,
+			test_benchmark_bridge_tx_report::<Test>,
+			test_benchmark_relay_block_headers::<Test>,
+			test_benchmark_prove_tx_out_expired::<Test>,
+			test_benchmark_submit_signatures::<Test>,
+			test_benchmark_set_notary_keys::<Test>,
+			test_benchmark_set_notary_eos_key::<Test>,
+			test_benchmark_save_producer_schedule::<Test>,
+			test_benchmark_init_schedule::<Test>,
+			test_benchmark_bridge_enable::<Test>,
+			test_benchmark_grant_crosschain_privilege::<Test>,
+			test_benchmark_remove_crosschain_privilege::<Test>,
+			test_benchmark_set_contract_accounts::<Test>,
+			test_benchmark_set_bridge_paused::<Test>,
+			test_benchmark_set_withdraw_limit::<Test>,
+			test_benchmark_reset_bridge_reserve::<Test>,
+			test_benchmark_report_reserve_deficit::<Test>,
+			test_benchmark_set_bridge_token::<Test>,
+			test_benchmark_remove_bridge_token::<Test>,
+			test_benchmark_set_bridge_token_enabled::<Test>,
+			test_benchmark_set_schedule_config::<Test>,
+			test_benchmark_set_relay_fee::<Test>,
+			test_benchmark_set_relay_reward::<Test>,
+		];
+
+		// each benchmark starts from the genesis
+		for benchmark in benchmarks {
+			new_test_ext().execute_with(|| assert_ok!(benchmark()));
+		}
+	}
+}
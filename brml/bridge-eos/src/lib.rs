@@ -19,51 +19,59 @@
 extern crate alloc;
 
 use alloc::string::{String, ToString};
-use core::{convert::TryFrom, ops::Div, str::FromStr, fmt::Debug};
+use core::{convert::TryFrom, str::FromStr, fmt::Debug};
 
 use codec::{Decode, Encode};
 use eos_chain::{
-	Action, ActionTransfer, ActionReceipt, Asset, Checksum256, Digest, IncrementalMerkle,
-	ProducerSchedule, SignedBlockHeader, Symbol, SymbolCode, Read, verify_proof, ActionName,
-	ProducerAuthoritySchedule, ProducerAuthority,
+	Action, ActionTransfer, Asset, Checksum256, Digest, IncrementalMerkle,
+	ProducerSchedule, SignedBlockHeader, Symbol, SymbolCode, Read, SerializeData, verify_proof, ActionName,
+	ProducerAuthoritySchedule, ProducerAuthority, AccountName,
 };
 use eos_keys::secret::SecretKey;
 use sp_std::prelude::*;
 use sp_core::offchain::StorageKind;
 use sp_runtime::{
-	traits::{Member, SaturatedConversion, AtLeast32Bit},
-	transaction_validity::{
-		InvalidTransaction, TransactionLongevity, TransactionPriority,
-		TransactionValidity, ValidTransaction, TransactionSource
-	},
+	ModuleId,
+	traits::{AccountIdConversion, IdentifyAccount, Member, SaturatedConversion, AtLeast32Bit, Zero},
 };
-use frame_support::traits::{Get};
+use frame_support::traits::{Currency, ExistenceRequirement, Get, ReservableCurrency};
 use frame_support::{
-	decl_event, decl_module, decl_storage, decl_error, debug, ensure, Parameter,
+	decl_event, decl_module, decl_storage, decl_error, debug, ensure, Parameter, IterableStorageMap,
 	dispatch::{DispatchResult, DispatchError},
 	weights::{FunctionOf, DispatchClass, Weight, Pays}
 };
 use frame_system::{
-	self as system, ensure_root, ensure_none, ensure_signed,
-	offchain::{SubmitTransaction, CreateSignedTransaction}
+	self as system, ensure_root, ensure_signed,
+	offchain::{AppCrypto, CreateSignedTransaction, SendSignedTransaction, Signer}
 };
 
 use node_primitives::{
 	AssetTrait, BridgeAssetBalance, BridgeAssetFrom,
-	BridgeAssetTo, BridgeAssetSymbol, BlockchainType, TokenType,
+	BridgeAssetTo, BridgeAssetSymbol, BlockchainType, ChainBridge, TokenType,
 };
-use transaction::TxOut;
+pub use eos_chain::ActionReceipt;
+pub use transaction::{TxOut, TxOutStatus};
+use transaction::EosNodes;
+pub use memo::{DepositMemo, MemoAction, MemoError};
 use sp_application_crypto::RuntimeAppPublic;
 
 mod transaction;
+mod memo;
+mod staking;
+pub mod weights;
+#[cfg(all(feature = "runtime-benchmarks", feature = "std"))]
+mod benchmarking;
 mod mock;
 mod tests;
 
 lazy_static::lazy_static! {
-	pub static ref ACTION_NAMES: [ActionName; 1] = {
-		let name = ActionName::from_str("transfer").unwrap();
-		[name]
-	};
+	/// Transfer, and actions of staking transactions sent by the bridge
+	pub static ref ACTION_NAMES: [ActionName; 4] = [
+		ActionName::from_str("transfer").unwrap(),
+		ActionName::from_str("delegatebw").unwrap(),
+		ActionName::from_str("voteproducer").unwrap(),
+		ActionName::from_str("undelegatebw").unwrap(),
+	];
 }
 
 #[derive(Encode, Decode, Clone, Copy, Eq, PartialEq, Debug)]
@@ -98,6 +106,15 @@ pub mod sr25519 {
 
 	/// An bridge-eos identifier using sr25519 as its crypto.
 	pub type AuthorityId = app_sr25519::Public;
+
+	/// Identity used by offchain worker of notaries to submit signatures of transactions sent to EOS.
+	pub struct BridgeEosAuthId;
+
+	impl frame_system::offchain::AppCrypto<sp_runtime::MultiSigner, sp_runtime::MultiSignature> for BridgeEosAuthId {
+		type RuntimeAppPublic = AuthorityId;
+		type GenericSignature = sp_core::sr25519::Signature;
+		type GenericPublic = sp_core::sr25519::Public;
+	}
 }
 
 pub mod ed25519 {
@@ -118,7 +135,19 @@ pub mod ed25519 {
 }
 
 const EOS_NODE_URL: &[u8] = b"EOS_NODE_URL";
+/// Local storage of the EOS secret key of each notary, followed by the encoded notary account
 const EOS_SECRET_KEY: &[u8] = b"EOS_SECRET_KEY";
+const TX_OUT_RETRY_PREFIX: &[u8] = b"bridge-eos::tx-out-retry::";
+const TX_OUT_SIGNED_PREFIX: &[u8] = b"bridge-eos::tx-out-signed::";
+
+/// How many times offchain worker tries to generate or send a transaction before failing it
+const MAX_TX_OUT_RETRIES: u32 = 5;
+/// Offchain worker waits `TX_OUT_RETRY_BACKOFF * 2^attempts` blocks before next retry
+const TX_OUT_RETRY_BACKOFF: u64 = 2;
+/// Notary submits its signature again if the signature isn't on chain after these blocks
+const SIGNATURE_RESUBMIT_INTERVAL: u64 = 10;
+/// How often (in blocks) offchain worker compares reserves with balances on EOS
+const RESERVE_CHECK_INTERVAL: u64 = 600;
 
 decl_error! {
 	pub enum Error for Module<T: Trait> {
@@ -146,8 +175,10 @@ decl_error! {
 		SignatureVerificationFailure,
 		/// Fail to verify merkle tree
 		MerkleRootVerificationFailure,
-		/// The length of block headers don't meet the size 15
+		/// The length of block headers doesn't match the configured headers count
 		InvalidBlockHeadersLength,
+		/// Block ids before each header don't match the configured confirmation depth
+		InvalidBlockIdsLength,
 		/// Invalid transaction
 		InvalidTxOutType,
 		/// Error from eos-chain crate
@@ -170,21 +201,264 @@ decl_error! {
 		OffchainHttpError,
 		/// EOS node response a error after send a request
 		EOSRpcError,
+		/// No EOS node is configured, or all of them are failing
+		NoEosNodeAvailable,
 		/// Error from lite-json while serializing or deserializing
 		LiteJsonError,
 		/// Invalid checksum
 		InvalidChecksum256,
 		/// Initialze producer schedule multiple times
 		InitMultiTimeProducerSchedules,
+		/// Transaction sent to EOS doesn't exist
+		TxOutNotFound,
+		/// Transaction sent to EOS cannot be changed to this status
+		InvalidTxOutTransition,
+		/// Bridge asset amount is too small to be sent to EOS
+		ZeroWithdrawAmount,
+		/// The asset or EOS token isn't mapped by bridge
+		TokenNotMapped,
+		/// Bridging this token is disabled
+		TokenDisabled,
+		/// The EOS token has been mapped to another asset
+		TokenAlreadyMapped,
+		/// Precision of EOS token doesn't match the mapping
+		InvalidTokenPrecision,
+		/// The asset doesn't exist
+		AssetNotExists,
+		/// The token is transferred by another contract on EOS
+		InvalidTokenContract,
+		/// Suspended deposit doesn't exist
+		SuspendedDepositNotFound,
+		/// Header relay hasn't been initialized
+		RelayNotInitialized,
+		/// Block header doesn't follow the last relayed block
+		UnlinkedBlockHeader,
+		/// Producer schedule of block header hasn't been saved
+		UnknownProducerSchedule,
+		/// Too many reversible blocks are relayed
+		TooManyReversibleBlocks,
+		/// Block hasn't been relayed, or has been pruned
+		BlockNotRelayed,
+		/// Block hasn't been irreversible
+		BlockNotIrreversible,
+		/// Action isn't included in the block
+		ActionNotInBlock,
+		/// Deposits are paused
+		DepositPaused,
+		/// Withdrawals are paused
+		WithdrawPaused,
+		/// Withdrawals of all accounts exceed the cap in the window
+		GlobalWithdrawLimitExceeded,
+		/// Withdrawals of the account exceed the cap in the window
+		AccountWithdrawLimitExceeded,
+		/// Delayed withdrawal doesn't exist, or has been executed
+		DelayedWithdrawNotFound,
+		/// Too many withdrawals are delayed, new large one should be sent after some are executed
+		TooManyDelayedWithdraws,
+		/// Tokens locked on EOS aren't enough for the withdrawal
+		InsufficientReserve,
+		/// Balance on EOS covers the reserve
+		NoReserveDeficit,
+		/// Only notaries can sign transactions sent to EOS
+		NotNotary,
+		/// Signature cannot be decoded as an EOS signature
+		InvalidEosSignature,
+		/// Signature isn't made by the EOS key of the notary
+		EosSignerMismatch,
+		/// EOS public key cannot be decoded
+		InvalidEosPublicKey,
+		/// Notary hasn't got an EOS key to sign with
+		NotaryEosKeyNotSet,
+		/// EOS key is used by another notary
+		EosKeyInUse,
+		/// New producer schedule must be the current version plus one
+		InvalidScheduleVersion,
+		/// Block headers aren't produced by the expected producer schedule
+		ScheduleVersionMismatch,
+		/// Headers count and history window must be greater than zero
+		InvalidScheduleConfig,
+		/// Only EOS of the system token contract can be staked
+		StakingTokenNotSupported,
+		/// Bridge account cannot vote for more producers on EOS
+		TooManyVotedProducers,
+		/// Un-staking is more than what's staked to the producer
+		InsufficientStake,
+		/// Amount is too small to be staked on EOS
+		ZeroStakeAmount,
+		/// Raw transaction generated for EOS cannot be decoded
+		InvalidRawTransaction,
+		/// Reported transaction doesn't match the one on chain
+		TxOutMismatch,
+		/// Transaction isn't sent to EOS, or hasn't expired on EOS at the block
+		TxOutNotExpired,
+		/// Too many transactions are pending, new one should be sent after some are finished
+		TooManyPendingTxOuts,
 	}
 }
 
 pub type VersionId = u32;
+pub type BlockNum = u32;
+
+/// How many relayed blocks are kept behind the last irreversible block
+pub const RELAYED_BLOCKS_KEPT: BlockNum = 7200;
+/// How many reversible blocks can be relayed ahead of the last irreversible block
+pub const MAX_REVERSIBLE_BLOCKS: usize = 1000;
+/// How many transactions can be pending, they're all signed again when notaries are changed
+pub const MAX_PENDING_TX_OUTS: usize = 1000;
+/// How many withdrawals can be delayed
+pub const MAX_DELAYED_WITHDRAWS: u32 = 1000;
+/// How many delayed withdrawals are sent to EOS in a block
+pub const MAX_DELAYED_WITHDRAWS_PER_BLOCK: usize = 50;
+/// Default count of signed block headers proving a producer schedule or an action
+pub const DEFAULT_PROVE_HEADERS: u32 = 15;
+/// Default count of block ids appended to the merkle before each header except the first one
+pub const DEFAULT_CONFIRMATION_DEPTH: u32 = 10;
+/// Default count of the latest producer schedules kept in storage
+pub const DEFAULT_SCHEDULE_HISTORY: u32 = 3;
+/// EOS block timestamp counts half seconds since 2000-01-01T00:00:00, which is this unix time
+const EOS_BLOCK_TIMESTAMP_EPOCH: u32 = 946_684_800;
+
+/// EOS block header which is verified and stored by bridge
+#[derive(Encode, Decode, Clone, PartialEq, Debug)]
+pub struct RelayedBlock {
+	pub id: Checksum256,
+	/// Merkle root of action receipts in this block
+	pub action_mroot: Checksum256,
+	pub producer: AccountName,
+	pub schedule_version: VersionId,
+	/// Time of this block in unix seconds
+	pub timestamp: u32,
+}
+
+/// Identifier of a transaction sent to EOS
+pub type TxOutId = u64;
+
+/// Producer schedule of EOS, which is returned by RPC
+#[derive(Encode, Decode, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+pub struct ProducerScheduleInfo {
+	pub version: VersionId,
+	/// Account names of producers
+	pub producers: Vec<String>,
+	pub schedule_hash: String,
+}
+
+/// Status of a notary, which is returned by RPC
+#[derive(Encode, Decode, Clone, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+pub struct NotaryStatus {
+	pub is_notary: bool,
+	/// How many signatures a transaction sent to EOS requires
+	pub threshold: u8,
+	/// Generated transactions which haven't been signed by the notary
+	pub unsigned_tx_outs: Vec<TxOutId>,
+}
+
+/// EOS token which is mapped to a Bifrost asset
+#[derive(Encode, Decode, Clone, PartialEq, Debug)]
+pub struct BridgeToken {
+	/// Account where the token contract deployed on EOS, like eosio.token
+	pub contract: Vec<u8>,
+	/// Symbol code of the token, like EOS
+	pub symbol: Vec<u8>,
+	/// Precision of the token on EOS
+	pub precision: u8,
+	/// Whether the token can be bridged
+	pub enabled: bool,
+}
+
+/// Deposit from EOS with an invalid memo, which is kept until it's claimed
+#[derive(Encode, Decode, Clone, PartialEq, Debug)]
+pub struct SuspendedDeposit<AssetId, Balance> {
+	/// EOS account who sent the deposit
+	pub eos_from: Vec<u8>,
+	pub asset_id: AssetId,
+	/// Amount in the precision of token
+	pub amount: Balance,
+	pub memo: Vec<u8>,
+}
+
+pub type SuspendedDepositId = u64;
+
+type RelayBalanceOf<T> = <<T as Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::Balance;
+
+/// Kinds of relaying which are rewarded
+#[derive(Encode, Decode, Clone, Copy, Eq, PartialEq, Debug)]
+pub enum RelayKind {
+	ProveAction,
+	ChangeSchedule,
+	RelayBlockHeaders,
+}
+
+/// Withdrawal limits of an asset, amounts are in the precision of the token on EOS.
+/// Zero amount means no limit.
+#[derive(Encode, Decode, Clone, PartialEq, Debug, Default)]
+pub struct WithdrawLimit<Balance, BlockNumber> {
+	/// Length of the rolling window
+	pub window: BlockNumber,
+	/// How much can be withdrawn by all accounts in a window
+	pub global_cap: Balance,
+	/// How much can be withdrawn by an account in a window
+	pub account_cap: Balance,
+	/// Withdrawal of this amount or more is delayed, and can be vetoed by governance
+	pub delay_threshold: Balance,
+	/// How many blocks a large withdrawal is delayed
+	pub delay: BlockNumber,
+}
+
+pub type DelayedWithdrawId = u64;
+
+/// Large withdrawal waiting to be sent to EOS, tokens have been escrowed
+#[derive(Encode, Decode, Clone, PartialEq, Debug)]
+pub struct DelayedWithdraw<AccountId, AssetId, Balance, BlockNumber> {
+	pub tx_out: TxOut<AccountId>,
+	pub escrow: WithdrawEscrow<AccountId, AssetId, Balance>,
+	/// When the withdrawal is sent to EOS
+	pub execute_at: BlockNumber,
+}
+
+/// Tokens are kept by bridge until the withdrawal is finished
+#[derive(Encode, Decode, Clone, PartialEq, Debug)]
+pub struct WithdrawEscrow<AccountId, AssetId, Balance> {
+	/// Who withdraws the tokens
+	pub who: AccountId,
+	pub asset_id: AssetId,
+	pub token_type: TokenType,
+	pub amount: Balance,
+	/// Amount sent to EOS, in the precision of the token on EOS
+	pub eos_amount: Balance,
+}
+
+/// Reserve of a bridged token, amounts are in the precision of the token on EOS
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Debug, Default)]
+pub struct BridgeReserve<Balance> {
+	/// Tokens locked in the bridge contract account on EOS
+	pub locked: Balance,
+	/// Tokens minted on Bifrost by deposits, which haven't been withdrawn
+	pub minted: Balance,
+	/// Tokens being withdrawn, they're still locked until the withdrawal is proven
+	pub withdrawing: Balance,
+	/// Locked tokens staked to producers by the bridge contract account, which cannot be withdrawn
+	pub staked: Balance,
+	/// Un-staked tokens which EOS hasn't refunded to the bridge contract account yet
+	pub refunding: Balance,
+}
+
+/// Staking or un-staking sent to EOS, which is reverted if the transaction fails
+#[derive(Encode, Decode, Clone, PartialEq, Debug)]
+pub struct StakeTxOut<AssetId, Balance> {
+	pub asset_id: AssetId,
+	/// EOS account of the producer
+	pub producer: Vec<u8>,
+	/// Amount in the precision of the token on EOS
+	pub eos_amount: Balance,
+	/// Whether it's staking or un-staking
+	pub staking: bool,
+}
 
 pub trait Trait: CreateSignedTransaction<Call<Self>> + pallet_authorship::Trait {
-	/// The identifier type for an authority.
-	type AuthorityId: Member + Parameter + RuntimeAppPublic + Default + Ord
-		+ From<<Self as frame_system::Trait>::AccountId>;
+	/// The identifier type for notaries to sign transactions sent to EOS.
+	type AuthorityId: AppCrypto<Self::Public, Self::Signature>;
 
 	type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
 
@@ -210,16 +484,35 @@ pub trait Trait: CreateSignedTransaction<Call<Self>> + pallet_authorship::Trait
 
 	/// A dispatchable call type.
 	type Call: From<Call<Self>>;
+
+	/// How many blocks a finished transaction sent to EOS is kept before being pruned.
+	type TxOutRetention: Get<Self::BlockNumber>;
+
+	/// How many blocks a transaction can stay unsent to EOS before it's failed and refunded.
+	type TxOutExpiry: Get<Self::BlockNumber>;
+
+	/// Currency for relay fees and rewards.
+	type Currency: ReservableCurrency<Self::AccountId>;
+
+	/// Account which pays relay rewards and receives penalties.
+	type RelayPotId: Get<ModuleId>;
 }
 
 decl_event! {
 	pub enum Event<T>
 		where <T as system::Trait>::AccountId,
+			<T as system::Trait>::BlockNumber,
+			<T as Trait>::Balance,
+			<T as Trait>::AssetId,
+			RelayBalance = RelayBalanceOf<T>,
 	{
 		InitSchedule(VersionId),
 		ChangeSchedule(VersionId, VersionId), // ChangeSchedule(older, newer)
 		ProveAction,
-		RelayBlock,
+		/// EOS block header is verified and stored
+		RelayBlock(BlockNum),
+		/// Last irreversible block of relayed EOS blocks is changed
+		IrreversibleBlock(BlockNum),
 		Deposit(Vec<u8>, AccountId), // EOS account => Bifrost AccountId
 		DepositFail,
 		Withdraw(AccountId, Vec<u8>), // Bifrost AccountId => EOS account
@@ -228,6 +521,71 @@ decl_event! {
 		SendTransactionFailure,
 		GrantedCrossChainPrivilege(AccountId),
 		RemovedCrossChainPrivilege(AccountId),
+		/// A transaction to EOS is created for the account
+		TxOutCreated(TxOutId, AccountId),
+		/// Status of a transaction to EOS is changed
+		TxOutStatusChanged(TxOutId, TxOutStatus),
+		/// Notary submitted its signature of a transaction to EOS
+		TxOutSigned(TxOutId, AccountId),
+		/// Notaries are changed, pending transactions are signed again by new notaries
+		NotaryKeysChanged(Vec<AccountId>),
+		/// EOS public key which the notary signs with is set
+		NotaryEosKeySet(AccountId, Vec<u8>),
+		/// Tokens are kept by bridge for the withdrawal
+		WithdrawEscrowed(TxOutId, AccountId, Balance),
+		/// Withdrawal is done, escrowed tokens are burned
+		WithdrawCompleted(TxOutId, AccountId, Balance),
+		/// Withdrawal failed, escrowed tokens are given back
+		WithdrawRefunded(TxOutId, AccountId, Balance),
+		/// Withdrawal isn't sent to EOS in time
+		WithdrawExpired(TxOutId),
+		/// Large withdrawal is delayed until the block, (id, who, amount on EOS, block)
+		WithdrawDelayed(DelayedWithdrawId, AccountId, Balance, BlockNumber),
+		/// Delayed withdrawal is sent to EOS as the transaction
+		DelayedWithdrawExecuted(DelayedWithdrawId, TxOutId),
+		/// Delayed withdrawal is vetoed, escrowed tokens are given back
+		DelayedWithdrawVetoed(DelayedWithdrawId),
+		/// Withdrawal limits of the asset are changed
+		WithdrawLimitSet(AssetId),
+		/// Deposits and withdrawals are paused or resumed, (deposit paused, withdraw paused)
+		BridgePaused(bool, bool),
+		/// Balance of bridge contract account on EOS is less than the reserve, (asset, locked, balance on EOS)
+		ReserveDeficit(AssetId, Balance, Balance),
+		/// Reserve of the asset is reset, (asset, locked, minted)
+		BridgeReserveReset(AssetId, Balance, Balance),
+		/// EOS token is mapped to the asset, (asset, contract, symbol, precision)
+		BridgeTokenSet(AssetId, Vec<u8>, Vec<u8>, u8),
+		/// Mapping of the asset is removed
+		BridgeTokenRemoved(AssetId),
+		/// Bridging the asset is enabled or disabled
+		BridgeTokenEnabled(AssetId, bool),
+		/// Deposit with invalid memo is suspended, (id, EOS account, memo)
+		DepositSuspended(SuspendedDepositId, Vec<u8>, Vec<u8>),
+		/// Suspended deposit is claimed to the account
+		SuspendedDepositClaimed(SuspendedDepositId, AccountId),
+		/// Deposit is referred by the account, (receiver, referrer, asset, amount)
+		DepositReferred(AccountId, AccountId, AssetId, Balance),
+		/// Relayer gets the fee back and the reward
+		RelayerRewarded(AccountId, RelayKind, RelayBalance),
+		/// Relayer submitted an invalid or duplicated proof, and the fee is forfeited
+		RelayerPenalised(AccountId, RelayKind, RelayBalance),
+		/// Relay fee is changed
+		RelayFeeSet(RelayBalance),
+		/// Reward of relaying is changed
+		RelayRewardSet(RelayKind, RelayBalance),
+		/// Schedule verification is changed, (headers count, confirmation depth, history window)
+		ScheduleConfigSet(u32, u32, u32),
+		/// Producer schedule is out of the history window and removed
+		ProducerSchedulePruned(VersionId),
+		/// Transaction staking to or un-staking from the producer is created, (id, producer, amount on EOS, is staking)
+		StakeTxOutCreated(TxOutId, Vec<u8>, Balance, bool),
+		/// Staking transaction failed, and the stake is reverted
+		StakeReverted(TxOutId),
+		/// Un-staked tokens are refunded to the bridge contract account by EOS, (asset, amount on EOS)
+		StakeRefunded(AssetId, Balance),
+		/// Transaction sent to EOS is proven expired by the irreversible block, it fails unless
+		/// its action is proven before the block, (id, EOS block, block)
+		TxOutExpirationProven(TxOutId, BlockNum, BlockNumber),
 	}
 }
 
@@ -236,9 +594,46 @@ decl_storage! {
 		/// The current set of notary keys that may send bridge transactions to Eos chain.
 		NotaryKeys get(fn notary_keys) config(): Vec<T::AccountId>;
 
+		/// Compressed EOS public key of each notary, its signatures are checked against it
+		NotaryEosKeys get(fn notary_eos_key): map hasher(blake2_128_concat) T::AccountId => Option<Vec<u8>>;
+
 		/// Config to enable/disable this runtime
 		BridgeEnable get(fn is_bridge_enable): bool = true;
 
+		/// Deposits from EOS are paused
+		DepositPaused get(fn is_deposit_paused): bool;
+
+		/// Withdrawals to EOS are paused, delayed withdrawals wait until they're resumed
+		WithdrawPaused get(fn is_withdraw_paused): bool;
+
+		/// Withdrawal limits of each asset
+		WithdrawLimits get(fn withdraw_limit): map hasher(blake2_128_concat) T::AssetId => Option<WithdrawLimit<T::Balance, T::BlockNumber>>;
+
+		/// Amount withdrawn by all accounts in the rolling window, and when it's updated
+		GlobalWithdrawn get(fn global_withdrawn): map hasher(blake2_128_concat) T::AssetId => (T::Balance, T::BlockNumber);
+
+		/// Amount withdrawn by the account in the rolling window, and when it's updated
+		AccountWithdrawn get(fn account_withdrawn): double_map hasher(blake2_128_concat) T::AssetId, hasher(blake2_128_concat) T::AccountId => (T::Balance, T::BlockNumber);
+
+		/// Large withdrawals waiting to be sent to EOS
+		DelayedWithdraws get(fn delayed_withdraw): map hasher(twox_64_concat) DelayedWithdrawId => Option<DelayedWithdraw<T::AccountId, T::AssetId, T::Balance, T::BlockNumber>>;
+
+		/// Id of next delayed withdrawal
+		NextDelayedWithdrawId get(fn next_delayed_withdraw_id): DelayedWithdrawId;
+
+		/// Delayed withdrawals will be sent to EOS at the block
+		DelayedWithdrawsToExecute: map hasher(twox_64_concat) T::BlockNumber => Vec<DelayedWithdrawId>;
+
+		/// Count of delayed withdrawals which haven't been executed or vetoed
+		DelayedWithdrawCount get(fn delayed_withdraw_count): u32;
+
+		/// Delayed withdrawals which are due, sent to EOS in order while withdrawals aren't paused
+		/// and pending transactions aren't full
+		ReadyDelayedWithdraws get(fn ready_delayed_withdraws): Vec<DelayedWithdrawId>;
+
+		/// Tokens locked on EOS and minted on Bifrost of each bridged asset
+		BridgeReserves get(fn bridge_reserve): map hasher(blake2_128_concat) T::AssetId => BridgeReserve<T::Balance>;
+
 		/// Eos producer list and hash which in specific version id
 		ProducerSchedules: map hasher(blake2_128_concat) VersionId => (Vec<ProducerAuthority>, Checksum256);
 
@@ -252,8 +647,81 @@ decl_storage! {
 		/// Current pending schedule version
 		PendingScheduleVersion: VersionId;
 
-		/// Transaction sent to Eos blockchain
-		BridgeTxOuts get(fn bridge_tx_outs): Vec<TxOut<T::AccountId>>;
+		/// Versions of producer schedules kept in storage, in ascending order
+		ScheduleVersions get(fn schedule_versions): Vec<VersionId>;
+
+		/// How many signed block headers are required to prove a producer schedule or an action
+		ProveHeadersCount get(fn prove_headers_count): u32 = DEFAULT_PROVE_HEADERS;
+
+		/// How many block ids are appended to the merkle before each header except the first one
+		ConfirmationDepth get(fn confirmation_depth): u32 = DEFAULT_CONFIRMATION_DEPTH;
+
+		/// How many latest producer schedules are kept, older ones are pruned
+		ScheduleHistoryWindow get(fn schedule_history_window): u32 = DEFAULT_SCHEDULE_HISTORY;
+
+		/// EOS blocks verified by header relay
+		RelayedBlocks get(fn relayed_block): map hasher(twox_64_concat) BlockNum => Option<RelayedBlock>;
+
+		/// The last relayed block, (block number, block id)
+		RelayHead get(fn relay_head): Option<(BlockNum, Checksum256)>;
+
+		/// Block root merkle including the last relayed block, for verifying the next header
+		RelayMerkle: Option<IncrementalMerkle>;
+
+		/// Blocks after the last irreversible block, (block number, producer, schedule version)
+		ReversibleBlocks: Vec<(BlockNum, AccountName, VersionId)>;
+
+		/// Block number of the last irreversible relayed block
+		LastIrreversibleBlock get(fn last_irreversible_block): BlockNum;
+
+		/// Fee reserved from relayer for each relaying, it's refunded if the relaying succeeds
+		RelayFee get(fn relay_fee): RelayBalanceOf<T>;
+
+		/// Reward paid to relayer for each kind of successful relaying
+		RelayRewards get(fn relay_reward): map hasher(twox_64_concat) RelayKind => RelayBalanceOf<T>;
+
+		/// Transactions sent to Eos blockchain
+		BridgeTxOuts get(fn bridge_tx_outs): map hasher(twox_64_concat) TxOutId => Option<TxOut<T::AccountId>>;
+
+		/// Id of next transaction sent to Eos blockchain
+		NextTxOutId get(fn next_tx_out_id): TxOutId;
+
+		/// Transactions haven't been finished, which are handled by offchain worker
+		PendingTxOuts get(fn pending_tx_outs): Vec<TxOutId>;
+
+		/// Transactions sent to EOS by the account, which haven't been pruned
+		AccountTxOuts: map hasher(blake2_128_concat) T::AccountId => Vec<TxOutId>;
+
+		/// Who sends the transaction to EOS
+		TxOutOwners: map hasher(twox_64_concat) TxOutId => Option<T::AccountId>;
+
+		/// Finished transactions will be pruned at the block
+		TxOutsToPrune: map hasher(twox_64_concat) T::BlockNumber => Vec<TxOutId>;
+
+		/// Transactions will be failed at the block if they haven't been sent to EOS
+		TxOutsToExpire: map hasher(twox_64_concat) T::BlockNumber => Vec<TxOutId>;
+
+		/// Transactions sent to EOS which are proven expired, they will be failed at the block
+		ExpiredTxOuts get(fn expired_tx_out): map hasher(twox_64_concat) TxOutId => Option<T::BlockNumber>;
+
+		/// Tokens kept by bridge for each withdrawal
+		WithdrawEscrows get(fn withdraw_escrow): map hasher(twox_64_concat) TxOutId => Option<WithdrawEscrow<T::AccountId, T::AssetId, T::Balance>>;
+
+		/// EOS staked to each producer by the bridge contract account, in the precision of EOS
+		StakedProducers get(fn staked_producer): map hasher(blake2_128_concat) Vec<u8> => T::Balance;
+
+		/// Staking transactions which haven't been finished
+		StakeTxOuts get(fn stake_tx_out): map hasher(twox_64_concat) TxOutId => Option<StakeTxOut<T::AssetId, T::Balance>>;
+
+		/// EOS token which the asset is mapped to
+		BridgeTokens get(fn bridge_token): map hasher(blake2_128_concat) T::AssetId => Option<BridgeToken>;
+
+		/// Asset which EOS token (contract, symbol) is mapped to
+		EosTokenAssets get(fn eos_token_asset): map hasher(blake2_128_concat) (Vec<u8>, Vec<u8>) => Option<T::AssetId>;
+
+		/// Deposits with invalid memo, they can be claimed by root
+		SuspendedDeposits get(fn suspended_deposit): map hasher(twox_64_concat) SuspendedDepositId => Option<SuspendedDeposit<T::AssetId, T::Balance>>;
+		NextSuspendedDepositId get(fn next_suspended_deposit_id): SuspendedDepositId;
 
 		/// Account where Eos bridge contract deployed, (Account, Signature threshold)
 		BridgeContractAccount get(fn bridge_contract_account) config(): (Vec<u8>, u8);
@@ -264,16 +732,23 @@ decl_storage! {
 		AllAddressesHaveCrossChainPrivilege get(fn all_crosschain_privilege) config(): Vec<T::AccountId>;
 	}
 	add_extra_genesis {
+		/// Map EOS tokens to assets, (asset, contract, symbol, precision)
+		config(bridge_tokens): Vec<(T::AssetId, Vec<u8>, Vec<u8>, u8)>;
+		/// EOS public keys of notaries, like `EOS6MRy...`
+		config(notary_eos_keys): Vec<(T::AccountId, Vec<u8>)>;
 		build(|config: &GenesisConfig<T>| {
 			BridgeContractAccount::put(config.bridge_contract_account.clone());
 
 			NotaryKeys::<T>::put(config.notary_keys.clone());
+			for (notary, eos_key) in config.notary_eos_keys.iter() {
+				let eos_key = <Module<T>>::parse_eos_public_key(eos_key).expect("notary EOS key is invalid");
+				<NotaryEosKeys<T>>::insert(notary, eos_key);
+			}
 
 			let schedule = ProducerAuthoritySchedule::default();
 			let schedule_hash = schedule.schedule_hash();
 			assert!(schedule_hash.is_ok());
-			ProducerSchedules::insert(schedule.version, (schedule.producers, schedule_hash.unwrap()));
-			PendingScheduleVersion::put(schedule.version);
+			<Module<T>>::insert_producer_schedule(schedule.version, schedule.producers, schedule_hash.unwrap());
 
 			// grant privilege to sign transaction between EOS and Bifrost
 			for (who, privilege) in config.cross_chain_privilege.iter() {
@@ -284,6 +759,16 @@ decl_storage! {
 			<AllAddressesHaveCrossChainPrivilege<T>>::mutate(move |all| {
 				all.extend(all_addresses.into_iter());
 			});
+
+			for (asset_id, contract, symbol, precision) in config.bridge_tokens.iter() {
+				<EosTokenAssets<T>>::insert((contract, symbol), asset_id);
+				<BridgeTokens<T>>::insert(asset_id, BridgeToken {
+					contract: contract.clone(),
+					symbol: symbol.clone(),
+					precision: *precision,
+					enabled: true,
+				});
+			}
 		});
 	}
 }
@@ -294,29 +779,28 @@ decl_module! {
 
 		fn deposit_event() = default;
 
-		#[weight = T::DbWeight::get().writes(1)]
+		#[weight = weights::bridge_enable::<T>()]
 		fn bridge_enable(origin, enable: bool) {
 			ensure_root(origin)?;
 
 			BridgeEnable::put(enable);
 		}
 
-		#[weight = T::DbWeight::get().reads_writes(1, 2)]
+		#[weight = weights::save_producer_schedule::<T>(ps.producers.len() as u32)]
 		fn save_producer_schedule(origin, ps: ProducerAuthoritySchedule) -> DispatchResult {
 			ensure_root(origin)?;
 
 			let schedule_hash = ps.schedule_hash().map_err(|_| Error::<T>::InvalidScheduleHash)?;
 
 			// calculate schedule hash just one time, instead of calculating it multiple times.
-			ProducerSchedules::insert(ps.version, (ps.producers, schedule_hash));
-			PendingScheduleVersion::put(ps.version);
+			Self::insert_producer_schedule(ps.version, ps.producers, schedule_hash);
 
 			Self::deposit_event(RawEvent::InitSchedule(ps.version));
 
 			Ok(())
 		}
 
-		#[weight = T::DbWeight::get().reads_writes(1, 1)]
+		#[weight = weights::init_schedule::<T>(ps.producers.len() as u32)]
 		fn init_schedule(origin, ps: ProducerAuthoritySchedule) {
 			ensure_root(origin)?;
 
@@ -326,13 +810,12 @@ decl_module! {
 			let schedule_hash = ps.schedule_hash().map_err(|_| Error::<T>::InvalidScheduleHash)?;
 
 			// calculate schedule hash just one time, instead of calculating it multiple times.
-			ProducerSchedules::insert(ps.version, (ps.producers, schedule_hash));
-			PendingScheduleVersion::put(ps.version);
+			Self::insert_producer_schedule(ps.version, ps.producers, schedule_hash);
 
 			Self::deposit_event(RawEvent::InitSchedule(ps.version));
 		}
 
-		#[weight = T::DbWeight::get().reads_writes(1, 1)]
+		#[weight = weights::grant_crosschain_privilege::<T>()]
 		fn grant_crosschain_privilege(origin, target: T::AccountId) {
 			ensure_root(origin)?;
 
@@ -349,7 +832,7 @@ decl_module! {
 			Self::deposit_event(RawEvent::GrantedCrossChainPrivilege(target));
 		}
 
-		#[weight = (0, DispatchClass::Normal, Pays::No)]
+		#[weight = (weights::remove_crosschain_privilege::<T>(), DispatchClass::Normal, Pays::No)]
 		fn remove_crosschain_privilege(origin, target: T::AccountId) {
 			ensure_root(origin)?;
 
@@ -364,66 +847,347 @@ decl_module! {
 			Self::deposit_event(RawEvent::RemovedCrossChainPrivilege(target));
 		}
 
-		#[weight = (0, DispatchClass::Normal, Pays::No)]
+		#[weight = (weights::set_contract_accounts::<T>(), DispatchClass::Normal, Pays::No)]
 		fn set_contract_accounts(origin, account: Vec<u8>, threthold: u8) {
 			ensure_root(origin)?;
 			BridgeContractAccount::put((account, threthold));
 		}
 
-		// 1. block_headers length must be 15.
-		// 2. the first block_header's new_producers cannot be none.
-		// 3. compare current schedules version with pending_schedules'.
-		// 4. verify incoming 180 block_headers to prove this new_producers list is valid.
-		// 5. save the new_producers list.
-		#[weight = (0, DispatchClass::Normal, Pays::No)]
-		fn change_schedule(
+		/// Pause or resume deposits and withdrawals separately.
+		#[weight = (weights::set_bridge_paused::<T>(), DispatchClass::Operational)]
+		fn set_bridge_paused(origin, deposit_paused: bool, withdraw_paused: bool) {
+			ensure_root(origin)?;
+
+			DepositPaused::put(deposit_paused);
+			WithdrawPaused::put(withdraw_paused);
+
+			Self::deposit_event(RawEvent::BridgePaused(deposit_paused, withdraw_paused));
+		}
+
+		/// Set withdrawal limits of the asset, or remove them by none.
+		#[weight = weights::set_withdraw_limit::<T>()]
+		fn set_withdraw_limit(origin, asset_id: T::AssetId, limit: Option<WithdrawLimit<T::Balance, T::BlockNumber>>) {
+			ensure_root(origin)?;
+
+			match limit {
+				Some(limit) => WithdrawLimits::<T>::insert(asset_id, limit),
+				None => WithdrawLimits::<T>::remove(asset_id),
+			}
+
+			Self::deposit_event(RawEvent::WithdrawLimitSet(asset_id));
+		}
+
+		/// Veto a delayed withdrawal before it's sent to EOS, escrowed tokens are given back.
+		#[weight = weights::veto_delayed_withdraw::<T>()]
+		fn veto_delayed_withdraw(origin, id: DelayedWithdrawId) {
+			ensure_root(origin)?;
+
+			let delayed = DelayedWithdraws::<T>::take(id).ok_or(Error::<T>::DelayedWithdrawNotFound)?;
+			DelayedWithdrawCount::mutate(|count| *count = count.saturating_sub(1));
+			ReadyDelayedWithdraws::mutate(|ready| ready.retain(|ready_id| *ready_id != id));
+
+			let escrow = delayed.escrow;
+			T::AssetTrait::asset_issue(escrow.asset_id, escrow.token_type, escrow.who.clone(), escrow.amount);
+			Self::release_reserve(&escrow, true);
+			Self::release_withdraw(escrow.asset_id, &escrow.who, escrow.eos_amount);
+
+			Self::deposit_event(RawEvent::DelayedWithdrawVetoed(id));
+		}
+
+		/// Correct the reserve of the asset, like tokens bridged before reserves are counted.
+		#[weight = weights::reset_bridge_reserve::<T>()]
+		fn reset_bridge_reserve(
 			origin,
-			legacy_schedule_hash: Checksum256,
-			new_schedule: ProducerAuthoritySchedule,
-			merkle: IncrementalMerkle,
-			block_headers: Vec<SignedBlockHeader>,
-			block_ids_list: Vec<Vec<Checksum256>>
-		) -> DispatchResult {
-			let origin = ensure_signed(origin)?;
-			ensure!(CrossChainPrivilege::<T>::get(&origin), DispatchError::Other("You're not permitted to execute this call."));
+			asset_id: T::AssetId,
+			#[compact] locked: T::Balance,
+			#[compact] minted: T::Balance
+		) {
+			ensure_root(origin)?;
 
-			ensure!(BridgeEnable::get(), DispatchError::Other("This call is not enabled now!"));
-			ensure!(!block_headers.is_empty(), DispatchError::Other("The signed block headers cannot be empty."));
-			ensure!(block_headers[0].block_header.new_producers.is_some(), DispatchError::Other("The producers list cannot be empty."));
-			ensure!(block_ids_list.len() == block_headers.len(), DispatchError::Other("The block ids list cannot be empty."));
-			ensure!(block_ids_list.len() == 15, DispatchError::Other("The length of signed block headers must be 15."));
-			ensure!(block_ids_list[0].is_empty(), DispatchError::Other("The first block ids must be empty."));
-			ensure!(block_ids_list[1].len() ==  10, DispatchError::Other("The rest of block ids length must be 10."));
+			BridgeReserves::<T>::mutate(asset_id, |reserve| {
+				reserve.locked = locked;
+				reserve.minted = minted;
+			});
 
-			let legacy_pending_schedule = block_headers[0].block_header.new_producers.as_ref();
-			let legacy_pending_schedule_hash = legacy_pending_schedule.and_then(|ps| ps.schedule_hash().ok())
-				.ok_or(DispatchError::Other("Failed to calculate legacy schedule hash value."))?;
-			ensure!(legacy_pending_schedule_hash == legacy_schedule_hash, "invalid producers schedule");
+			Self::deposit_event(RawEvent::BridgeReserveReset(asset_id, locked, minted));
+		}
 
-			ensure!(PendingScheduleVersion::exists(), DispatchError::Other("PendingScheduleVersion has not been initialized."));
+		/// Notary reports balance of bridge contract account on EOS is less than the reserve,
+		/// withdrawals are paused until they're resumed by governance.
+		#[weight = weights::report_reserve_deficit::<T>()]
+		fn report_reserve_deficit(origin, asset_id: T::AssetId, #[compact] eos_balance: T::Balance) {
+			let notary = ensure_signed(origin)?;
+			ensure!(NotaryKeys::<T>::get().contains(&notary), Error::<T>::NotNotary);
 
-			let current_schedule_version = PendingScheduleVersion::get();
+			let reserve = BridgeReserves::<T>::get(asset_id);
+			ensure!(Self::is_reserve_deficit(&reserve, eos_balance), Error::<T>::NoReserveDeficit);
 
-			let (schedule_hash, producer_schedule) = {
-				let schedule_hash = new_schedule.schedule_hash().map_err(|_| DispatchError::Other("Failed to calculate schedule hash value."))?;
-				(schedule_hash, new_schedule)
-			};
+			WithdrawPaused::put(true);
+
+			Self::deposit_event(RawEvent::ReserveDeficit(asset_id, reserve.locked, eos_balance));
+			Self::deposit_event(RawEvent::BridgePaused(DepositPaused::get(), true));
+		}
+
+		/// Replace notaries, signatures of the removed notaries are dropped from pending transactions,
+		/// and those transactions are signed again by the new notaries.
+		#[weight = (
+			weights::set_notary_keys::<T>(notaries.len() as u32, MAX_PENDING_TX_OUTS as u32),
+			DispatchClass::Operational
+		)]
+		fn set_notary_keys(origin, notaries: Vec<T::AccountId>) {
+			ensure_root(origin)?;
+
+			NotaryKeys::<T>::put(&notaries);
+			let threshold = BridgeContractAccount::get().1;
+			for id in PendingTxOuts::get() {
+				let tx_out = match BridgeTxOuts::<T>::get(id) {
+					Some(tx_out) => tx_out,
+					None => continue,
+				};
+				let status = tx_out.status();
+				let tx_out = tx_out.retain_signatures(&notaries, threshold);
+				let new_status = tx_out.status();
+				BridgeTxOuts::<T>::insert(id, tx_out);
+
+				if new_status != status {
+					Self::deposit_event(RawEvent::TxOutStatusChanged(id, new_status));
+				}
+			}
+
+			Self::deposit_event(RawEvent::NotaryKeysChanged(notaries));
+		}
 
+		/// Notary submits its EOS signatures of generated transactions, a transaction is signed
+		/// once its signatures reach the threshold of bridge contract account.
+		#[weight = weights::submit_signatures::<T>(signatures.len() as u32)]
+		fn submit_signatures(origin, signatures: Vec<(TxOutId, Vec<u8>)>) {
+			let notary = ensure_signed(origin)?;
+			ensure!(NotaryKeys::<T>::get().contains(&notary), Error::<T>::NotNotary);
+
+			// check all signatures before changing any transaction
+			let threshold = BridgeContractAccount::get().1;
+			let eos_key = NotaryEosKeys::<T>::get(&notary).ok_or(Error::<T>::NotaryEosKeyNotSet)?;
+			let mut signed_tx_outs = Vec::with_capacity(signatures.len());
+			for (id, signature) in signatures.into_iter() {
+				ensure!(!signed_tx_outs.iter().any(|(signed_id, _)| *signed_id == id), Error::<T>::AlreadySignedByAuthor);
+				let tx_out = BridgeTxOuts::<T>::get(id).ok_or(Error::<T>::TxOutNotFound)?;
+				let tx_out = tx_out.add_signature::<T>(notary.clone(), signature, &eos_key, threshold)?;
+				signed_tx_outs.push((id, tx_out));
+			}
+
+			for (id, tx_out) in signed_tx_outs.into_iter() {
+				Self::deposit_event(RawEvent::TxOutSigned(id, notary.clone()));
+				Self::update_tx_out(id, tx_out);
+			}
+		}
+
+		/// Set the EOS public key, like `EOS6MRy...`, which the notary signs transactions with. It
+		/// should be one of the keys of the bridge contract account's active permission.
+		#[weight = (
+			weights::set_notary_eos_key::<T>(NotaryKeys::<T>::decode_len().unwrap_or(0) as u32),
+			DispatchClass::Operational
+		)]
+		fn set_notary_eos_key(origin, notary: T::AccountId, eos_key: Vec<u8>) {
+			ensure_root(origin)?;
+
+			// a key signing for two notaries would count twice for the threshold
+			let key = Self::parse_eos_public_key(&eos_key)?;
 			ensure!(
-				Self::verify_block_headers(merkle, &schedule_hash, &producer_schedule, &block_headers, block_ids_list).is_ok(),
-				"Failed to verify block."
+				!NotaryKeys::<T>::get().iter()
+					.any(|other| *other != notary && NotaryEosKeys::<T>::get(other).as_ref() == Some(&key)),
+				Error::<T>::EosKeyInUse
 			);
+			NotaryEosKeys::<T>::insert(&notary, key);
 
-			// if verification is successful, save the new producers schedule.
-			ProducerSchedules::insert(producer_schedule.version, (&producer_schedule.producers, schedule_hash));
-			PendingScheduleVersion::put(producer_schedule.version);
+			Self::deposit_event(RawEvent::NotaryEosKeySet(notary, eos_key));
+		}
+
+		/// Map an EOS token to the asset, the existing mapping of the asset is replaced.
+		#[weight = weights::set_bridge_token::<T>()]
+		fn set_bridge_token(
+			origin,
+			asset_id: T::AssetId,
+			contract: Vec<u8>,
+			symbol: Vec<u8>,
+			precision: u8
+		) {
+			ensure_root(origin)?;
 
-			Self::deposit_event(RawEvent::ChangeSchedule(current_schedule_version, producer_schedule.version));
+			ensure!(T::AssetTrait::token_exists(asset_id), Error::<T>::AssetNotExists);
+			ensure!(precision <= 12, Error::<T>::InvalidTokenPrecision);
+			let token_key = (contract.clone(), symbol.clone());
+			if let Some(mapped) = EosTokenAssets::<T>::get(&token_key) {
+				ensure!(mapped == asset_id, Error::<T>::TokenAlreadyMapped);
+			}
 
-			Ok(())
+			if let Some(old) = BridgeTokens::<T>::get(asset_id) {
+				EosTokenAssets::<T>::remove((old.contract, old.symbol));
+			}
+			EosTokenAssets::<T>::insert(&token_key, asset_id);
+			BridgeTokens::<T>::insert(asset_id, BridgeToken {
+				contract: contract.clone(),
+				symbol: symbol.clone(),
+				precision,
+				enabled: true,
+			});
+
+			Self::deposit_event(RawEvent::BridgeTokenSet(asset_id, contract, symbol, precision));
+		}
+
+		#[weight = weights::remove_bridge_token::<T>()]
+		fn remove_bridge_token(origin, asset_id: T::AssetId) {
+			ensure_root(origin)?;
+
+			let token = BridgeTokens::<T>::take(asset_id).ok_or(Error::<T>::TokenNotMapped)?;
+			EosTokenAssets::<T>::remove((token.contract, token.symbol));
+
+			Self::deposit_event(RawEvent::BridgeTokenRemoved(asset_id));
+		}
+
+		/// Set how many signed block headers prove a schedule change or an action, how many block ids
+		/// are appended before each header, and how many producer schedules are kept.
+		#[weight = weights::set_schedule_config::<T>()]
+		fn set_schedule_config(origin, headers_count: u32, confirmation_depth: u32, history_window: u32) {
+			ensure_root(origin)?;
+			ensure!(headers_count > 0 && history_window > 0, Error::<T>::InvalidScheduleConfig);
+
+			ProveHeadersCount::put(headers_count);
+			ConfirmationDepth::put(confirmation_depth);
+			ScheduleHistoryWindow::put(history_window);
+			Self::deposit_event(RawEvent::ScheduleConfigSet(headers_count, confirmation_depth, history_window));
+		}
+
+		#[weight = weights::set_relay_fee::<T>()]
+		fn set_relay_fee(origin, #[compact] fee: RelayBalanceOf<T>) {
+			ensure_root(origin)?;
+
+			RelayFee::<T>::put(fee);
+			Self::deposit_event(RawEvent::RelayFeeSet(fee));
+		}
+
+		#[weight = weights::set_relay_reward::<T>()]
+		fn set_relay_reward(origin, kind: RelayKind, #[compact] reward: RelayBalanceOf<T>) {
+			ensure_root(origin)?;
+
+			RelayRewards::<T>::insert(kind, reward);
+			Self::deposit_event(RawEvent::RelayRewardSet(kind, reward));
+		}
+
+		/// Issue a suspended deposit to the account who owns it.
+		#[weight = weights::claim_suspended_deposit::<T>()]
+		fn claim_suspended_deposit(
+			origin,
+			id: SuspendedDepositId,
+			target: T::AccountId,
+			token_type: TokenType
+		) {
+			ensure_root(origin)?;
+
+			let deposit = SuspendedDeposits::<T>::get(id).ok_or(Error::<T>::SuspendedDepositNotFound)?;
+			let token = BridgeTokens::<T>::get(deposit.asset_id).ok_or(Error::<T>::TokenNotMapped)?;
+			let amount = Self::convert_precision(
+				deposit.amount.saturated_into(),
+				token.precision,
+				Self::asset_precision(deposit.asset_id, token_type),
+			);
+			let amount = T::Balance::try_from(amount).map_err(|_| Error::<T>::ConvertBalanceError)?;
+
+			SuspendedDeposits::<T>::remove(id);
+			T::AssetTrait::asset_issue(deposit.asset_id, token_type, target.clone(), amount);
+			BridgeReserves::<T>::mutate(deposit.asset_id, |reserve| {
+				reserve.minted = reserve.minted.saturating_add(deposit.amount);
+			});
+
+			Self::deposit_event(RawEvent::SuspendedDepositClaimed(id, target));
+		}
+
+		#[weight = weights::set_bridge_token_enabled::<T>()]
+		fn set_bridge_token_enabled(origin, asset_id: T::AssetId, enabled: bool) {
+			ensure_root(origin)?;
+
+			BridgeTokens::<T>::try_mutate(asset_id, |token| -> DispatchResult {
+				let token = token.as_mut().ok_or(Error::<T>::TokenNotMapped)?;
+				token.enabled = enabled;
+				Ok(())
+			})?;
+
+			Self::deposit_event(RawEvent::BridgeTokenEnabled(asset_id, enabled));
+		}
+
+		// 1. the first block_header's new_producers cannot be none.
+		// 2. new schedule version must be current version plus one.
+		// 3. block headers must be produced by the current schedule.
+		// 4. verify the configured count of block_headers to prove this new_producers list is valid.
+		// 5. save the new_producers list, prune schedules out of the history window.
+		#[weight = FunctionOf(
+			|args: (_, &ProducerAuthoritySchedule, _, &Vec<SignedBlockHeader>, &Vec<Vec<Checksum256>>)| {
+				let ids = args.4.iter().map(|ids| ids.len() as u32).sum();
+				weights::change_schedule::<T>(args.3.len() as u32, ids, args.1.producers.len() as u32)
+			},
+			DispatchClass::Normal,
+			Pays::Yes
+		)]
+		fn change_schedule(
+			origin,
+			legacy_schedule_hash: Checksum256,
+			new_schedule: ProducerAuthoritySchedule,
+			merkle: IncrementalMerkle,
+			block_headers: Vec<SignedBlockHeader>,
+			block_ids_list: Vec<Vec<Checksum256>>
+		) -> DispatchResult {
+			let origin = ensure_signed(origin)?;
+			ensure!(BridgeEnable::get(), DispatchError::Other("This call is not enabled now!"));
+
+			Self::relay_with_incentive(&origin, RelayKind::ChangeSchedule, || {
+				ensure!(!block_headers.is_empty(), DispatchError::Other("The signed block headers cannot be empty."));
+				ensure!(block_headers[0].block_header.new_producers.is_some(), DispatchError::Other("The producers list cannot be empty."));
+				ensure!(block_ids_list.len() == block_headers.len(), DispatchError::Other("The block ids list cannot be empty."));
+
+				let legacy_pending_schedule = block_headers[0].block_header.new_producers.as_ref();
+				let legacy_pending_schedule_hash = legacy_pending_schedule.and_then(|ps| ps.schedule_hash().ok())
+					.ok_or(DispatchError::Other("Failed to calculate legacy schedule hash value."))?;
+				ensure!(legacy_pending_schedule_hash == legacy_schedule_hash, "invalid producers schedule");
+
+				ensure!(PendingScheduleVersion::exists(), DispatchError::Other("PendingScheduleVersion has not been initialized."));
+
+				let current_schedule_version = PendingScheduleVersion::get();
+				ensure!(
+					current_schedule_version.checked_add(1) == Some(new_schedule.version) &&
+						legacy_pending_schedule.map(|ps| ps.version) == Some(new_schedule.version),
+					Error::<T>::InvalidScheduleVersion
+				);
+				ensure!(
+					block_headers.iter().all(|header| header.block_header.schedule_version == current_schedule_version),
+					Error::<T>::ScheduleVersionMismatch
+				);
+
+				let (schedule_hash, producer_schedule) = {
+					let schedule_hash = new_schedule.schedule_hash().map_err(|_| DispatchError::Other("Failed to calculate schedule hash value."))?;
+					(schedule_hash, new_schedule)
+				};
+
+				ensure!(
+					Self::verify_block_headers(merkle, &schedule_hash, &producer_schedule, &block_headers, block_ids_list).is_ok(),
+					"Failed to verify block."
+				);
+
+				// if verification is successful, save the new producers schedule.
+				Self::insert_producer_schedule(producer_schedule.version, producer_schedule.producers, schedule_hash);
+
+				Self::deposit_event(RawEvent::ChangeSchedule(current_schedule_version, producer_schedule.version));
+
+				Ok(())
+			})
 		}
 
-		#[weight = (0, DispatchClass::Normal, Pays::No)]
+		#[weight = FunctionOf(
+			|args: (_, _, &Vec<Checksum256>, _, &Vec<SignedBlockHeader>, &Vec<Vec<Checksum256>>)| {
+				let ids = args.5.iter().map(|ids| ids.len() as u32).sum();
+				weights::prove_action::<T>(args.4.len() as u32, ids, args.2.len() as u32, weights::ACTIVE_PRODUCERS)
+			},
+			DispatchClass::Normal,
+			Pays::Yes
+		)]
 		fn prove_action(
 			origin,
 			action: Action,
@@ -431,152 +1195,303 @@ decl_module! {
 			action_merkle_paths: Vec<Checksum256>,
 			merkle: IncrementalMerkle,
 			block_headers: Vec<SignedBlockHeader>,
-			block_ids_list: Vec<Vec<Checksum256>>,
-			trx_id: Checksum256
+			block_ids_list: Vec<Vec<Checksum256>>
 		) -> DispatchResult {
 			let origin = ensure_signed(origin)?;
-			ensure!(CrossChainPrivilege::<T>::get(&origin), DispatchError::Other("You're not permitted to execute this call."));
+			ensure!(BridgeEnable::get(), DispatchError::Other("This call is not enabled now!"));
+			Self::ensure_deposit_not_paused(&action)?;
+
+			Self::relay_with_incentive(&origin, RelayKind::ProveAction, || {
+				// ensure this transaction is unique, and ensure no duplicated transaction
+				ensure!(BridgeActionReceipt::get(&action_receipt).ne(&action), "This is a duplicated transaction");
+
+				// ensure action is what we want
+				ensure!(ACTION_NAMES.contains(&action.name), "This is an invalid action to Bifrost");
+
+				ensure!(
+					!block_headers.is_empty(),
+					"The signed block headers cannot be empty."
+				);
+				ensure!(
+					block_ids_list.len() ==  block_headers.len(),
+					"The block ids list cannot be empty."
+				);
+
+				let action_hash = action.digest().map_err(|_| Error::<T>::ErrorOnCalculationActionHash)?;
+				ensure!(
+					action_hash == action_receipt.act_digest,
+					"current action hash isn't equal to act_digest from action_receipt."
+				);
+
+				let leaf = action_receipt.digest().map_err(|_| Error::<T>::ErrorOnCalculationActionReceiptHash)?;
+
+				let block_under_verification = &block_headers[0];
+				ensure!(
+					verify_proof(&action_merkle_paths, leaf, block_under_verification.block_header.action_mroot),
+					"failed to prove action."
+				);
+
+				let (schedule_hash, producer_schedule) = Self::get_schedule_hash_and_public_key(block_headers[0].block_header.new_producers.as_ref())?;
+				// this is for testing due to there's a default producer schedule on standalone eos node.
+				let schedule_hash = {
+					if producer_schedule.version == 0 {
+						ProducerSchedule::default().schedule_hash().map_err(|_| Error::<T>::InvalidScheduleHash)?
+					} else {
+						schedule_hash
+					}
+				};
 
-			// ensure this transaction is unique, and ensure no duplicated transaction
-			ensure!(BridgeActionReceipt::get(&action_receipt).ne(&action), "This is a duplicated transaction");
+				ensure!(
+					Self::verify_block_headers(merkle, &schedule_hash, &producer_schedule, &block_headers, block_ids_list).is_ok(),
+					"Failed to verify blocks."
+				);
 
-			// ensure action is what we want
-			ensure!(action.name == ACTION_NAMES[0], "This is an invalid action to Bifrost");
+				Self::apply_proven_action(action, action_receipt)
+			})
+		}
 
-			ensure!(BridgeEnable::get(), "This call is not enable now!");
-			ensure!(
-				!block_headers.is_empty(),
-				"The signed block headers cannot be empty."
-			);
-			ensure!(
-				block_ids_list.len() ==  block_headers.len(),
-				"The block ids list cannot be empty."
-			);
+		/// Trust the EOS block header as the start of header relay, merkle is the block root merkle
+		/// for this header, which doesn't include the block itself.
+		#[weight = weights::init_relay::<T>()]
+		fn init_relay(origin, block_header: SignedBlockHeader, merkle: IncrementalMerkle) -> DispatchResult {
+			ensure_root(origin)?;
 
-			let action_hash = action.digest().map_err(|_| Error::<T>::ErrorOnCalculationActionHash)?;
-			ensure!(
-				action_hash == action_receipt.act_digest,
-				"current action hash isn't equal to act_digest from action_receipt."
-			);
+			let block_num = block_header.block_header.block_num();
+			let block_id = block_header.id().map_err(|_| Error::<T>::FailureOnGetBlockId)?;
+			let mut merkle = merkle;
+			merkle.append(block_id).map_err(|_| Error::<T>::AppendIncreMerkleError)?;
 
-			let leaf = action_receipt.digest().map_err(|_| Error::<T>::ErrorOnCalculationActionReceiptHash)?;
+			let timestamp = Self::block_timestamp(&block_header)?;
+			RelayedBlocks::insert(block_num, RelayedBlock {
+				id: block_id,
+				action_mroot: block_header.block_header.action_mroot,
+				producer: block_header.block_header.producer,
+				schedule_version: block_header.block_header.schedule_version,
+				timestamp,
+			});
+			RelayHead::put((block_num, block_id));
+			RelayMerkle::put(merkle);
+			ReversibleBlocks::kill();
+			LastIrreversibleBlock::put(block_num);
 
-			let block_under_verification = &block_headers[0];
-			ensure!(
-				verify_proof(&action_merkle_paths, leaf, block_under_verification.block_header.action_mroot),
-				"failed to prove action."
-			);
+			Self::deposit_event(RawEvent::RelayBlock(block_num));
+			Self::deposit_event(RawEvent::IrreversibleBlock(block_num));
 
-			let (schedule_hash, producer_schedule) = Self::get_schedule_hash_and_public_key(block_headers[0].block_header.new_producers.as_ref())?;
-			// this is for testing due to there's a default producer schedule on standalone eos node.
-			let schedule_hash = {
-				if producer_schedule.version == 0 {
-					ProducerSchedule::default().schedule_hash().map_err(|_| Error::<T>::InvalidScheduleHash)?
-				} else {
-					schedule_hash
+			Ok(())
+		}
+
+		/// Relay EOS block headers following the last relayed block, each header is verified by
+		/// signature of its producer, then stored.
+		#[weight = FunctionOf(
+			|args: (&Vec<SignedBlockHeader>,)| weights::relay_block_headers::<T>(args.0.len() as u32),
+			DispatchClass::Normal,
+			Pays::Yes
+		)]
+		fn relay_block_headers(origin, block_headers: Vec<SignedBlockHeader>) -> DispatchResult {
+			let origin = ensure_signed(origin)?;
+			ensure!(BridgeEnable::get(), DispatchError::Other("This call is not enabled now!"));
+
+			Self::relay_with_incentive(&origin, RelayKind::RelayBlockHeaders, || {
+				let (mut head_num, mut head_id) = RelayHead::get().ok_or(Error::<T>::RelayNotInitialized)?;
+				let mut merkle = RelayMerkle::get().ok_or(Error::<T>::RelayNotInitialized)?;
+				let mut reversible_blocks = ReversibleBlocks::get();
+				ensure!(
+					reversible_blocks.len() + block_headers.len() <= MAX_REVERSIBLE_BLOCKS,
+					Error::<T>::TooManyReversibleBlocks
+				);
+				let mut head_version = RelayedBlocks::get(head_num).ok_or(Error::<T>::BlockNotRelayed)?.schedule_version;
+
+				let mut relayed = Vec::with_capacity(block_headers.len());
+				for block_header in block_headers.iter() {
+					let header = &block_header.block_header;
+					ensure!(header.previous == head_id, Error::<T>::UnlinkedBlockHeader);
+					ensure!(header.block_num() == head_num + 1, Error::<T>::UnlinkedBlockHeader);
+					// producer schedule never goes back, and only moves to the next version
+					ensure!(
+						header.schedule_version == head_version || Some(header.schedule_version) == head_version.checked_add(1),
+						Error::<T>::ScheduleVersionMismatch
+					);
+
+					// header is signed with the block root merkle which includes the previous block
+					ensure!(ProducerSchedules::contains_key(header.schedule_version), Error::<T>::UnknownProducerSchedule);
+					let (producers, schedule_hash) = ProducerSchedules::get(header.schedule_version);
+					let producer_schedule = ProducerAuthoritySchedule::new(header.schedule_version, producers);
+					Self::verify_block_header_signature(&schedule_hash, &producer_schedule, block_header, &merkle.get_root())?;
+
+					let block_id = block_header.id().map_err(|_| Error::<T>::FailureOnGetBlockId)?;
+					merkle.append(block_id).map_err(|_| Error::<T>::AppendIncreMerkleError)?;
+
+					head_num = header.block_num();
+					head_id = block_id;
+					head_version = header.schedule_version;
+					reversible_blocks.push((head_num, header.producer, header.schedule_version));
+					relayed.push((head_num, RelayedBlock {
+						id: block_id,
+						action_mroot: header.action_mroot,
+						producer: header.producer,
+						schedule_version: header.schedule_version,
+						timestamp: Self::block_timestamp(block_header)?,
+					}));
 				}
-			};
 
-			ensure!(
-				Self::verify_block_headers(merkle, &schedule_hash, &producer_schedule, &block_headers, block_ids_list).is_ok(),
-				"Failed to verify blocks."
-			);
+				// all headers are verified
+				for (block_num, block) in relayed.into_iter() {
+					RelayedBlocks::insert(block_num, block);
+					Self::deposit_event(RawEvent::RelayBlock(block_num));
+				}
+				RelayHead::put((head_num, head_id));
+				RelayMerkle::put(merkle);
+				Self::update_irreversible_block(reversible_blocks);
+
+				Ok(())
+			})
+		}
+
+		/// Prove an action by merkle path against an irreversible block relayed before.
+		#[weight = FunctionOf(
+			|args: (_, _, &Vec<Checksum256>, _)| weights::prove_action_by_relay::<T>(args.2.len() as u32),
+			DispatchClass::Normal,
+			Pays::Yes
+		)]
+		fn prove_action_by_relay(
+			origin,
+			action: Action,
+			action_receipt: ActionReceipt,
+			action_merkle_paths: Vec<Checksum256>,
+			block_num: BlockNum
+		) -> DispatchResult {
+			let origin = ensure_signed(origin)?;
+			ensure!(BridgeEnable::get(), DispatchError::Other("This call is not enabled now!"));
+			Self::ensure_deposit_not_paused(&action)?;
 
-			// save proves for this transaction
-			BridgeActionReceipt::insert(&action_receipt, &action);
+			Self::relay_with_incentive(&origin, RelayKind::ProveAction, || {
+				ensure!(BridgeActionReceipt::get(&action_receipt).ne(&action), "This is a duplicated transaction");
+				ensure!(ACTION_NAMES.contains(&action.name), "This is an invalid action to Bifrost");
 
-			Self::deposit_event(RawEvent::ProveAction);
+				let block = RelayedBlocks::get(block_num).ok_or(Error::<T>::BlockNotRelayed)?;
+				ensure!(block_num <= LastIrreversibleBlock::get(), Error::<T>::BlockNotIrreversible);
 
-			let action_transfer = Self::get_action_transfer_from_action(&action)?;
+				let action_hash = action.digest().map_err(|_| Error::<T>::ErrorOnCalculationActionHash)?;
+				ensure!(action_hash == action_receipt.act_digest, Error::<T>::ActionNotInBlock);
+				let leaf = action_receipt.digest().map_err(|_| Error::<T>::ErrorOnCalculationActionReceiptHash)?;
+				ensure!(verify_proof(&action_merkle_paths, leaf, block.action_mroot), Error::<T>::ActionNotInBlock);
 
-			let cross_account = BridgeContractAccount::get().0;
-			// withdraw operation, Bifrost => EOS
-			if cross_account == action_transfer.from.to_string().into_bytes() {
-				match Self::transaction_from_bifrost_to_eos(trx_id, &action_transfer) {
-					Ok(target) => Self::deposit_event(RawEvent::Withdraw(target, action_transfer.to.to_string().into_bytes())),
-					Err(e) => {
-						debug::info!("Bifrost => EOS failed due to {:?}", e);
-						Self::deposit_event(RawEvent::WithdrawFail);
-					}
-				}
+				Self::apply_proven_action(action, action_receipt)
+			})
+		}
+
+		/// Notary reports transactions generated, sent or failed by its offchain worker.
+		#[weight = (weights::bridge_tx_report::<T>(tx_list.len() as u32), DispatchClass::Normal, Pays::No)]
+		fn bridge_tx_report(origin, tx_list: Vec<(TxOutId, TxOut<T::AccountId>)>) -> DispatchResult {
+			let notary = ensure_signed(origin)?;
+			ensure!(NotaryKeys::<T>::get().contains(&notary), Error::<T>::NotNotary);
+
+			// check all transactions before changing any of them
+			for (id, tx_out) in tx_list.iter() {
+				Self::ensure_tx_out_transition(*id, tx_out)?;
 			}
 
-			// deposit operation, EOS => Bifrost
-			if cross_account == action_transfer.to.to_string().into_bytes() {
-				match Self::transaction_from_eos_to_bifrost(&action_transfer) {
-					Ok(target) => Self::deposit_event(RawEvent::Deposit(action_transfer.from.to_string().into_bytes(), target)),
-					Err(e) => {
-						debug::info!("EOS => Bifrost failed due to {:?}", e);
-						Self::deposit_event(RawEvent::DepositFail);
-					}
-				}
+			for (id, tx_out) in tx_list.into_iter() {
+				Self::update_tx_out(id, tx_out);
 			}
 
 			Ok(())
 		}
 
-		#[weight = (0, DispatchClass::Normal, Pays::No)]
-		fn bridge_tx_report(origin, tx_list: Vec<TxOut<T::AccountId>>) -> DispatchResult {
-			ensure_none(origin)?;
+		/// Prove the transaction sent to EOS can no longer be executed, since the irreversible
+		/// block is after its expiration. It fails after `TxOutExpiry` blocks, in which its action
+		/// can still be proven if it was executed before expired.
+		#[weight = weights::prove_tx_out_expired::<T>()]
+		fn prove_tx_out_expired(origin, id: TxOutId, block_num: BlockNum) -> DispatchResult {
+			ensure_signed(origin)?;
+
+			let tx_out = BridgeTxOuts::<T>::get(id).ok_or(Error::<T>::TxOutNotFound)?;
+			let expiration = tx_out.eos_expiration().ok_or(Error::<T>::TxOutNotExpired)?;
+			ensure!(!ExpiredTxOuts::<T>::contains_key(id), Error::<T>::TxOutNotExpired);
+
+			let block = RelayedBlocks::get(block_num).ok_or(Error::<T>::BlockNotRelayed)?;
+			ensure!(block_num <= LastIrreversibleBlock::get(), Error::<T>::BlockNotIrreversible);
+			ensure!(block.timestamp > expiration, Error::<T>::TxOutNotExpired);
+
+			let fail_at = <system::Module<T>>::block_number() + T::TxOutExpiry::get();
+			ExpiredTxOuts::<T>::insert(id, fail_at);
+			TxOutsToExpire::<T>::append(fail_at, id);
 
-			BridgeTxOuts::<T>::put(tx_list);
+			Self::deposit_event(RawEvent::TxOutExpirationProven(id, block_num, fail_at));
 
 			Ok(())
 		}
 
 		#[weight = FunctionOf(
-			|args: (_, _, _, &Vec<u8>)| (args.3.len() * 10000usize) as Weight,
+			|args: (_, _, _, _, &Vec<u8>)| weights::asset_redeem::<T>(args.4.len() as u32),
 			DispatchClass::Normal,
 			Pays::Yes
 		)]
 		fn asset_redeem(
 			origin,
+			asset_id: T::AssetId,
 			to: Vec<u8>,
 			token_type: TokenType,
 			#[compact] amount: T::Balance,
 			memo: Vec<u8>
 		) {
 			let origin = system::ensure_signed(origin)?;
-			let eos_amount = amount;
 
-			let token_id = T::AssetId::from(2);
+			let token = BridgeTokens::<T>::get(asset_id).ok_or(Error::<T>::TokenNotMapped)?;
+			ensure!(token.enabled, Error::<T>::TokenDisabled);
 
-			// check vtoken id exist or not
-			ensure!(T::AssetTrait::token_exists(token_id), "this token doesn't exist.");
-
-			let token = T::AssetTrait::get_token(&token_id).token;
-			let symbol_code = token.symbol;
-			let symbol_precise = token.precision;
-
-			let balance = T::AssetTrait::get_account_asset(&token_id, TokenType::VToken, &origin).balance;
-			ensure!(symbol_precise <= 12, "symbol precise cannot bigger than 12.");
-			let amount = amount.div(T::Balance::from(10u32.pow(12u32 - symbol_precise as u32)));
-			ensure!(balance >= amount, "amount should be less than or equal to origin balance");
-
-			let asset_symbol = BridgeAssetSymbol::new(BlockchainType::EOS, symbol_code, T::Precision::from(symbol_precise.into()));
+			// amount is in 12 decimals
+			let asset_symbol = BridgeAssetSymbol::new(BlockchainType::EOS, token.symbol, T::Precision::from(token.precision.into()));
 			let bridge_asset = BridgeAssetBalance {
 				symbol: asset_symbol,
-				amount: eos_amount,
+				amount,
 				memo,
-				from: origin.clone(),
+				from: origin,
 				token_type
 			};
 
-			if Self::bridge_asset_to(to, bridge_asset).is_ok() {
-				debug::info!("sent transaction to EOS node.");
-				Self::deposit_event(RawEvent::SendTransactionSuccess);
-			} else {
-				debug::warn!("failed to send transaction to EOS node.");
-				Self::deposit_event(RawEvent::SendTransactionFailure);
-			}
+			// tokens are escrowed, and will be refunded if this withdrawal fails
+			Self::tx_transfer_to(to, asset_id, bridge_asset)?;
+			Self::deposit_event(RawEvent::SendTransactionSuccess);
 		}
 
-		// Runs after every block.
-		fn offchain_worker(now_block: T::BlockNumber) {
-			debug::RuntimeLogger::init();
+		fn on_finalize(now_block: T::BlockNumber) {
+			// signed transaction may be executed by EOS, so it only expires once proven expired on EOS
+			for id in TxOutsToExpire::<T>::take(now_block) {
+				let proven_expired = ExpiredTxOuts::<T>::get(id) == Some(now_block);
+				match BridgeTxOuts::<T>::get(id) {
+					Some(tx_out @ TxOut::Initial(_)) |
+					Some(tx_out @ TxOut::Generated(_)) => {
+						Self::update_tx_out(id, tx_out.fail(b"expired"));
+						Self::deposit_event(RawEvent::WithdrawExpired(id));
+					}
+					Some(tx_out @ TxOut::Signed(_)) |
+					Some(tx_out @ TxOut::Processing { .. }) if proven_expired => {
+						Self::update_tx_out(id, tx_out.fail(b"expired on EOS"));
+						Self::deposit_event(RawEvent::WithdrawExpired(id));
+					}
+					_ => {}
+				}
+			}
 
-			// It's no nessesary to start offchain worker if no any task in queue
-			if !BridgeTxOuts::<T>::get().is_empty() {
+			for id in TxOutsToPrune::<T>::take(now_block) {
+				BridgeTxOuts::<T>::remove(id);
+				if let Some(owner) = TxOutOwners::<T>::take(id) {
+					AccountTxOuts::<T>::mutate(owner, |ids| ids.retain(|owned_id| *owned_id != id));
+				}
+			}
+		}
+
+		fn on_initialize(now_block: T::BlockNumber) -> Weight {
+			Self::execute_delayed_withdraws(now_block)
+		}
+
+		// Runs after every block.
+		fn offchain_worker(now_block: T::BlockNumber) {
+			debug::RuntimeLogger::init();
+
+			// It's no nessesary to start offchain worker if no any task in queue, or withdrawals are paused
+			if !PendingTxOuts::get().is_empty() && !WithdrawPaused::get() {
 				// Only send messages if we are a potential validator.
 				if sp_io::offchain::is_validator() {
 					debug::info!(target: "bridge-eos", "Is validator at {:?}.", now_block);
@@ -590,11 +1505,202 @@ decl_module! {
 			} else {
 				debug::info!("There's no offchain worker started.");
 			}
+
+			if now_block.saturated_into::<u64>() % RESERVE_CHECK_INTERVAL == 0 && sp_io::offchain::is_validator() {
+				if let Err(e) = Self::check_reserves(now_block.saturated_into::<u64>()) {
+					debug::warn!(target: "bridge-eos", "failed to check reserves due to: {:?}", e);
+				}
+			}
 		}
 	}
 }
 
 impl<T: Trait> Module<T> {
+	/// Save the proven action, then deposit to Bifrost or finish the withdrawal or staking
+	/// Proven action is applied, and saved only if it succeeds. A failed action changes nothing,
+	/// so it can be proven again.
+	fn apply_proven_action(action: Action, action_receipt: ActionReceipt) -> DispatchResult {
+		if action.name == ACTION_NAMES[0] {
+			Self::apply_proven_transfer(&action)?;
+		} else {
+			// other actions are only executed by staking transactions
+			Self::stake_from_bifrost_to_eos(&action)
+				.map_err(|e| {
+					debug::info!("staking on EOS failed due to {:?}", e);
+					e
+				})?;
+		}
+
+		// save proves for this transaction
+		BridgeActionReceipt::insert(&action_receipt, &action);
+
+		Self::deposit_event(RawEvent::ProveAction);
+
+		Ok(())
+	}
+
+	/// Proven transfer finishes the withdrawal, or it's a deposit or refund of un-staked tokens
+	fn apply_proven_transfer(action: &Action) -> DispatchResult {
+		let action_transfer = Self::get_action_transfer_from_action(action)?;
+
+		// account of the token contract, like eosio.token
+		let token_contract = action.account.to_string().into_bytes();
+
+		let cross_account = BridgeContractAccount::get().0;
+		// withdraw operation, Bifrost => EOS
+		if cross_account == action_transfer.from.to_string().into_bytes() {
+			let target = Self::transaction_from_bifrost_to_eos(&token_contract, action, &action_transfer)
+				.map_err(|e| {
+					debug::info!("Bifrost => EOS failed due to {:?}", e);
+					e
+				})?;
+			Self::deposit_event(RawEvent::Withdraw(target, action_transfer.to.to_string().into_bytes()));
+		}
+
+		// deposit operation, EOS => Bifrost
+		if cross_account == action_transfer.to.to_string().into_bytes() {
+			// un-staked tokens refunded by EOS are locked as before, they're not deposits
+			if Self::is_stake_refund(&token_contract, &action_transfer) {
+				return Self::receive_stake_refund(&action_transfer).map_err(Into::into);
+			}
+
+			let target = Self::transaction_from_eos_to_bifrost(&token_contract, &action_transfer)
+				.map_err(|e| {
+					debug::info!("EOS => Bifrost failed due to {:?}", e);
+					e
+				})?;
+			// memo is invalid if there's no target, the deposit is suspended
+			if let Some(target) = target {
+				Self::deposit_event(RawEvent::Deposit(action_transfer.from.to_string().into_bytes(), target));
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Account which pays relay rewards
+	pub fn relay_pot() -> T::AccountId {
+		T::RelayPotId::get().into_account()
+	}
+
+	/// Anyone can relay, relay fee is reserved from relayer before relaying. The fee is refunded
+	/// with reward if relaying succeeds, otherwise it's forfeited to relay pot.
+	///
+	/// Penalty must be kept, so failed relaying doesn't return error. Since storage changed by a
+	/// failed `relay` is kept as well, `relay` must check everything before changing any storage.
+	fn relay_with_incentive(
+		relayer: &T::AccountId,
+		kind: RelayKind,
+		relay: impl FnOnce() -> DispatchResult
+	) -> DispatchResult {
+		let fee = RelayFee::<T>::get();
+		T::Currency::reserve(relayer, fee)?;
+
+		match relay() {
+			Ok(()) => {
+				T::Currency::unreserve(relayer, fee);
+
+				let pot = Self::relay_pot();
+				let reward = RelayRewards::<T>::get(kind).min(T::Currency::free_balance(&pot));
+				let reward = T::Currency::transfer(&pot, relayer, reward, ExistenceRequirement::AllowDeath)
+					.map(|_| reward)
+					.unwrap_or_else(|_| Zero::zero());
+
+				Self::deposit_event(RawEvent::RelayerRewarded(relayer.clone(), kind, reward));
+			}
+			Err(e) => {
+				debug::info!("relaying {:?} failed due to {:?}", kind, e);
+
+				let (penalty, _) = T::Currency::slash_reserved(relayer, fee);
+				T::Currency::resolve_creating(&Self::relay_pot(), penalty);
+
+				Self::deposit_event(RawEvent::RelayerPenalised(relayer.clone(), kind, fee));
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Save the producer schedule as the pending one, then prune schedules out of the history window.
+	fn insert_producer_schedule(version: VersionId, producers: Vec<ProducerAuthority>, schedule_hash: Checksum256) {
+		ProducerSchedules::insert(version, (producers, schedule_hash));
+		PendingScheduleVersion::put(version);
+		ScheduleVersions::mutate(|versions| {
+			if let Err(index) = versions.binary_search(&version) {
+				versions.insert(index, version);
+			}
+		});
+
+		Self::prune_producer_schedules();
+	}
+
+	/// Schedules used by the relay head and reversible blocks are kept even if they're out of
+	/// the window, irreversibility of relayed blocks is still counted with them.
+	fn prune_producer_schedules() {
+		let window = ScheduleHistoryWindow::get() as usize;
+		let mut versions = ScheduleVersions::get();
+		if versions.len() <= window {
+			return;
+		}
+
+		let head_version = RelayHead::get()
+			.and_then(|(head_num, _)| RelayedBlocks::get(head_num))
+			.map(|block| block.schedule_version);
+		let oldest_in_use = ReversibleBlocks::get().iter()
+			.map(|(_, _, version)| *version)
+			.chain(head_version)
+			.chain(Some(PendingScheduleVersion::get()))
+			.min()
+			.unwrap_or_default();
+
+		while versions.len() > window && versions[0] < oldest_in_use {
+			let version = versions.remove(0);
+			ProducerSchedules::remove(version);
+			Self::deposit_event(RawEvent::ProducerSchedulePruned(version));
+		}
+		ScheduleVersions::put(versions);
+	}
+
+	/// A block is irreversible once blocks after it are produced by more than 2/3 producers
+	/// of its schedule. Schedule changes are respected by only counting producers from the
+	/// same schedule version.
+	fn update_irreversible_block(mut reversible_blocks: Vec<(BlockNum, AccountName, VersionId)>) {
+		let mut confirmations: Vec<(VersionId, Vec<AccountName>)> = Vec::new();
+		let mut irreversible_index = None;
+
+		for (index, (_, producer, version)) in reversible_blocks.iter().enumerate().rev() {
+			let confirmed_by = confirmations.iter()
+				.find(|(v, _)| v == version)
+				.map(|(_, producers)| producers.len())
+				.unwrap_or_default();
+			let threshold = ProducerSchedules::get(version).0.len() * 2 / 3 + 1;
+			if confirmed_by >= threshold {
+				irreversible_index = Some(index);
+				break;
+			}
+
+			match confirmations.iter_mut().find(|(v, _)| v == version) {
+				Some((_, producers)) if !producers.contains(producer) => producers.push(*producer),
+				Some(_) => {}
+				None => confirmations.push((*version, vec![*producer])),
+			}
+		}
+
+		if let Some(index) = irreversible_index {
+			let last_irreversible = reversible_blocks[index].0;
+			let pruned_before = LastIrreversibleBlock::get().saturating_sub(RELAYED_BLOCKS_KEPT);
+			for block_num in pruned_before..last_irreversible.saturating_sub(RELAYED_BLOCKS_KEPT) {
+				RelayedBlocks::remove(block_num);
+			}
+
+			reversible_blocks.drain(..=index);
+			LastIrreversibleBlock::put(last_irreversible);
+			Self::deposit_event(RawEvent::IrreversibleBlock(last_irreversible));
+		}
+
+		ReversibleBlocks::put(reversible_blocks);
+	}
+
 	fn verify_block_headers(
 		mut merkle: IncrementalMerkle,
 		schedule_hash: &Checksum256,
@@ -602,8 +1708,16 @@ impl<T: Trait> Module<T> {
 		block_headers: &[SignedBlockHeader],
 		block_ids_list: Vec<Vec<Checksum256>>,
 	) -> Result<(), Error<T>> {
-		ensure!(block_headers.len() == 15, Error::<T>::InvalidBlockHeadersLength);
-		ensure!(block_ids_list.len() == 15, Error::<T>::InvalidBlockHeadersLength);
+		let headers_count = ProveHeadersCount::get() as usize;
+		ensure!(block_headers.len() == headers_count, Error::<T>::InvalidBlockHeadersLength);
+		ensure!(block_ids_list.len() == headers_count, Error::<T>::InvalidBlockHeadersLength);
+
+		// the first header follows the merkle directly, others are confirmed by the blocks between
+		let confirmation_depth = ConfirmationDepth::get() as usize;
+		ensure!(
+			block_ids_list.iter().enumerate().all(|(i, ids)| ids.len() == if i == 0 { 0 } else { confirmation_depth }),
+			Error::<T>::InvalidBlockIdsLength
+		);
 
 		for (block_header, block_ids) in block_headers.iter().zip(block_ids_list.iter()) {
 			// calculate merkle root
@@ -620,6 +1734,14 @@ impl<T: Trait> Module<T> {
 		Ok(())
 	}
 
+	/// Time of the block in unix seconds
+	fn block_timestamp(block_header: &SignedBlockHeader) -> Result<u32, Error<T>> {
+		let slot = block_header.block_header.timestamp.to_serialize_data().map_err(|_| Error::<T>::EosChainError)?;
+		let slot = <[u8; 4]>::try_from(slot.as_slice()).map_err(|_| Error::<T>::EosChainError)?;
+
+		Ok(u32::from_le_bytes(slot) / 2 + EOS_BLOCK_TIMESTAMP_EPOCH)
+	}
+
 	fn verify_block_header_signature(
 		schedule_hash: &Checksum256,
 		producer_schedule: &ProducerAuthoritySchedule,
@@ -679,210 +1801,908 @@ impl<T: Trait> Module<T> {
 		Ok(action_transfer)
 	}
 
-	fn transaction_from_eos_to_bifrost(action_transfer: &ActionTransfer) -> Result<T::AccountId, Error<T>> {
-		// check memo, example like "alice@bifrost:EOS", the formatter: {receiver}@{chain}:{token_type}
-		let split_memo = action_transfer.memo.as_str().split(|c| c == '@' || c == ':').collect::<Vec<_>>();
-
-		// the length should be 2, either 3.
-		if split_memo.len().gt(&3) || split_memo.len().lt(&2) {
-			return Err(Error::<T>::InvalidMemo);
-		}
-
-		// get account
-		let account_data = Self::get_account_data(split_memo[0])?;
-		let target = Self::into_account(account_data)?;
-
-		let token_type = {
-			match split_memo.len() {
-				2 => TokenType::VToken,
-				3 => {
-					match split_memo[2] {
-						"" | "vEOS" => TokenType::VToken,
-						"EOS" => TokenType::Token,
-						_ => {
-							debug::error!("A invalid token type, default token type will be vtoken");
-							TokenType::Token
-						}
-					}
-				}
-				_ => unreachable!("previous step checked he length of split_memo.")
-			}
-		};
-
+	/// Deposit is issued to the receiver in memo, or suspended if memo is invalid
+	fn transaction_from_eos_to_bifrost(
+		token_contract: &[u8],
+		action_transfer: &ActionTransfer
+	) -> Result<Option<T::AccountId>, Error<T>> {
 		let symbol = action_transfer.quantity.symbol;
-		let symbol_code = symbol.code().to_string().into_bytes();
+		let symbol_code = symbol.code().to_string();
 		let symbol_precise = symbol.precision();
 
-		let token_balances = action_transfer.quantity.amount as usize;
-		let vtoken_balances = T::Balance::try_from(token_balances).map_err(|_| Error::<T>::ConvertBalanceError)?;
-
-		// check vtoken id exists not not, if it doesn't exist, create a vtoken for it
-		let token_id = match T::AssetTrait::asset_id_exists(&target, &symbol_code, symbol_precise.into()) {
-			Some(id) => id,
-			None => {
-				T::AssetTrait::asset_create(symbol_code, symbol_precise.into()).0
+		// only mapped tokens can be deposited
+		let token_key = (token_contract.to_vec(), symbol_code.clone().into_bytes());
+		let token_id = EosTokenAssets::<T>::get(&token_key).ok_or(Error::<T>::TokenNotMapped)?;
+		let token = BridgeTokens::<T>::get(token_id).ok_or(Error::<T>::TokenNotMapped)?;
+		ensure!(token.enabled, Error::<T>::TokenDisabled);
+		ensure!(token.precision == symbol_precise, Error::<T>::InvalidTokenPrecision);
+		let deposit_amount = u128::try_from(action_transfer.quantity.amount).ok()
+			.and_then(|amount| T::Balance::try_from(amount).ok())
+			.ok_or(Error::<T>::ConvertBalanceError)?;
+
+		let parsed = DepositMemo::parse(action_transfer.memo.as_str())
+			.map_err(|e| {
+				debug::info!("invalid deposit memo: {:?}", e);
+				Error::<T>::InvalidMemo
+			})
+			.and_then(|memo| {
+				let token_type = Self::memo_token_type(memo.token_type.as_deref(), &symbol_code)?;
+				Ok((memo, token_type))
+			});
+		let (memo, token_type) = match parsed {
+			Ok(parsed) => parsed,
+			Err(_) => {
+				Self::suspend_deposit(token_id, action_transfer)?;
+				BridgeReserves::<T>::mutate(token_id, |reserve| {
+					reserve.locked = reserve.locked.saturating_add(deposit_amount);
+				});
+				return Ok(None);
 			}
 		};
+		let target = Self::into_account(memo.receiver)?;
+		let referrer = memo.referrer.map(Self::into_account).transpose()?;
+
+		let eos_amount = u128::try_from(action_transfer.quantity.amount).map_err(|_| Error::<T>::ConvertBalanceError)?;
+		let token_balances = Self::convert_precision(
+			eos_amount,
+			symbol_precise,
+			Self::asset_precision(token_id, token_type),
+		);
+		let vtoken_balances = T::Balance::try_from(token_balances).map_err(|_| Error::<T>::ConvertBalanceError)?;
 
 		// issue asset to target
 		T::AssetTrait::asset_issue(token_id, token_type, target.clone(), vtoken_balances);
+		BridgeReserves::<T>::mutate(token_id, |reserve| {
+			reserve.locked = reserve.locked.saturating_add(deposit_amount);
+			reserve.minted = reserve.minted.saturating_add(deposit_amount);
+		});
+
+		if let Some(referrer) = referrer {
+			Self::deposit_event(RawEvent::DepositReferred(target.clone(), referrer, token_id, vtoken_balances));
+		}
 
-		Ok(target)
+		if memo.action == MemoAction::AutoConvert {
+			let bridge_asset = BridgeAssetBalance {
+				symbol: BridgeAssetSymbol::new(BlockchainType::EOS, token.symbol, T::Precision::from(token.precision.into())),
+				amount: vtoken_balances,
+				memo: action_transfer.memo.clone().into_bytes(),
+				from: target.clone(),
+				token_type,
+			};
+			T::BridgeAssetFrom::bridge_asset_from(target.clone(), bridge_asset);
+		}
+
+		Ok(Some(target))
 	}
 
-	fn transaction_from_bifrost_to_eos(pending_trx_id: Checksum256, action_transfer: &ActionTransfer) -> Result<T::AccountId, Error<T>> {
-		let bridge_tx_outs = BridgeTxOuts::<T>::get();
+	/// token type is the symbol for token, or the symbol with prefix v for vtoken, like EOS and vEOS
+	fn memo_token_type(token_type: Option<&str>, symbol_code: &str) -> Result<TokenType, Error<T>> {
+		match token_type {
+			None => Ok(TokenType::VToken),
+			Some(token_type) if token_type == symbol_code => Ok(TokenType::Token),
+			Some(token_type) if token_type.strip_prefix('v') == Some(symbol_code) => Ok(TokenType::VToken),
+			Some(_) => Err(Error::<T>::InvalidMemo),
+		}
+	}
 
-		for trx in bridge_tx_outs.iter() {
-			match trx {
-				TxOut::Processing{ tx_id, multi_sig_tx } if pending_trx_id.eq(tx_id) => {
-					let target = &multi_sig_tx.from;
-					let token_id = T::AssetId::from(2);
-					let token_type = multi_sig_tx.token_type;
+	fn suspend_deposit(token_id: T::AssetId, action_transfer: &ActionTransfer) -> Result<SuspendedDepositId, Error<T>> {
+		let amount = u128::try_from(action_transfer.quantity.amount).ok()
+			.and_then(|amount| T::Balance::try_from(amount).ok())
+			.ok_or(Error::<T>::ConvertBalanceError)?;
+		let eos_from = action_transfer.from.to_string().into_bytes();
+		let memo = action_transfer.memo.clone().into_bytes();
+
+		let id = NextSuspendedDepositId::get();
+		NextSuspendedDepositId::put(id + 1);
+		SuspendedDeposits::<T>::insert(id, SuspendedDeposit {
+			eos_from: eos_from.clone(),
+			asset_id: token_id,
+			amount,
+			memo: memo.clone(),
+		});
 
-					let all_vtoken_balances = T::AssetTrait::get_account_asset(&token_id, token_type, &target).balance;
-					let token_balances = action_transfer.quantity.amount as usize;
+		Self::deposit_event(RawEvent::DepositSuspended(id, eos_from, memo));
+
+		Ok(id)
+	}
+
+	/// Precision of the asset on Bifrost
+	fn asset_precision(asset_id: T::AssetId, token_type: TokenType) -> u8 {
+		let token_pair = T::AssetTrait::get_token(&asset_id);
+		let precision = match token_type {
+			TokenType::Token => token_pair.token.precision,
+			TokenType::VToken => token_pair.vtoken.precision,
+		};
+
+		precision.saturated_into()
+	}
+
+	fn convert_precision(amount: u128, from: u8, to: u8) -> u128 {
+		if to >= from {
+			amount.saturating_mul(10u128.saturating_pow((to - from) as u32))
+		} else {
+			amount / 10u128.saturating_pow((from - to) as u32)
+		}
+	}
+
+	/// Withdrawal is done by the oldest transaction sent to EOS which executes the action
+	fn transaction_from_bifrost_to_eos(
+		token_contract: &[u8],
+		action: &Action,
+		action_transfer: &ActionTransfer
+	) -> Result<T::AccountId, Error<T>> {
+		for id in PendingTxOuts::get() {
+			match BridgeTxOuts::<T>::get(id) {
+				Some(TxOut::Signed(multi_sig_tx)) |
+				Some(TxOut::Processing { multi_sig_tx, .. }) if multi_sig_tx.actions().contains(action) => {
+					let target = multi_sig_tx.from.clone();
+					let escrow = WithdrawEscrows::<T>::get(id).ok_or(Error::<T>::InsufficientBalance)?;
+					let token = BridgeTokens::<T>::get(escrow.asset_id).ok_or(Error::<T>::TokenNotMapped)?;
+					ensure!(token.contract.as_slice() == token_contract, Error::<T>::InvalidTokenContract);
+
+					let eos_amount = u128::try_from(action_transfer.quantity.amount).map_err(|_| Error::<T>::ConvertBalanceError)?;
+					let token_balances = Self::convert_precision(
+						eos_amount,
+						action_transfer.quantity.symbol.precision(),
+						Self::asset_precision(escrow.asset_id, escrow.token_type),
+					);
 					let vtoken_balances = T::Balance::try_from(token_balances).map_err(|_| Error::<T>::ConvertBalanceError)?;
 
-					if all_vtoken_balances.lt(&vtoken_balances) {
-						debug::warn!("origin account balance must be greater than or equal to the transfer amount.");
+					// tokens have been escrowed while withdrawing, ensure EOS sent what's escrowed
+					if escrow.amount.ne(&vtoken_balances) {
+						debug::warn!("transferred amount on EOS doesn't match the escrowed amount.");
 						return Err(Error::<T>::InsufficientBalance);
 					}
 
-					T::AssetTrait::asset_redeem(token_id, token_type, target.clone(), vtoken_balances);
-					return Ok(target.clone());
+					Self::update_tx_out(id, TxOut::Success(multi_sig_tx.eos_tx_id().to_string().into_bytes()));
+
+					return Ok(target);
 				}
 				_ => continue,
 			}
 		}
 
-		Err(Error::<T>::InvalidAccountId)
+		Err(Error::<T>::TxOutNotFound)
 	}
 
-	/// check receiver account format
-	/// https://github.com/paritytech/substrate/wiki/External-Address-Format-(SS58)
-	fn get_account_data(receiver: &str) -> Result<[u8; 32], Error<T>> {
-		let decoded_ss58 = bs58::decode(receiver).into_vec().map_err(|_| Error::<T>::InvalidAccountId)?;
+	/// Staking is done by the oldest staking transaction sent to EOS which executes the action
+	fn stake_from_bifrost_to_eos(action: &Action) -> Result<TxOutId, Error<T>> {
+		for id in PendingTxOuts::get() {
+			if !StakeTxOuts::<T>::contains_key(id) {
+				continue;
+			}
+			match BridgeTxOuts::<T>::get(id) {
+				Some(TxOut::Signed(multi_sig_tx)) |
+				Some(TxOut::Processing { multi_sig_tx, .. }) if multi_sig_tx.actions().contains(action) => {
+					Self::update_tx_out(id, TxOut::Success(multi_sig_tx.eos_tx_id().to_string().into_bytes()));
 
-		if decoded_ss58.len() == 35 && decoded_ss58.first() == Some(&42) {
-			let mut data = [0u8; 32];
-			data.copy_from_slice(&decoded_ss58[1..33]);
-			Ok(data)
-		} else {
-			Err(Error::<T>::InvalidAccountId)
+					return Ok(id);
+				}
+				_ => continue,
+			}
+		}
+
+		Err(Error::<T>::TxOutNotFound)
+	}
+
+	/// EOS refunds un-staked tokens from `eosio.stake` after the refund delay
+	fn is_stake_refund(token_contract: &[u8], action_transfer: &ActionTransfer) -> bool {
+		token_contract == staking::EOS_TOKEN_CONTRACT
+			&& action_transfer.quantity.symbol.code().to_string().as_bytes() == staking::EOS_TOKEN_SYMBOL
+			&& action_transfer.from.to_string().as_bytes() == staking::EOS_STAKE_ACCOUNT
+	}
+
+	/// Refunded tokens are back in the balance of bridge contract account
+	fn receive_stake_refund(action_transfer: &ActionTransfer) -> Result<(), Error<T>> {
+		let token_key = (staking::EOS_TOKEN_CONTRACT.to_vec(), staking::EOS_TOKEN_SYMBOL.to_vec());
+		let token_id = EosTokenAssets::<T>::get(&token_key).ok_or(Error::<T>::TokenNotMapped)?;
+		let amount = u128::try_from(action_transfer.quantity.amount).ok()
+			.and_then(|amount| T::Balance::try_from(amount).ok())
+			.ok_or(Error::<T>::ConvertBalanceError)?;
+
+		BridgeReserves::<T>::mutate(token_id, |reserve| {
+			reserve.refunding = reserve.refunding.saturating_sub(amount);
+		});
+		Self::deposit_event(RawEvent::StakeRefunded(token_id, amount));
+
+		Ok(())
+	}
+
+	/// Get status of a transaction sent to EOS, none if it doesn't exist or has been pruned.
+	pub fn tx_out_status(id: TxOutId) -> Option<TxOutStatus> {
+		BridgeTxOuts::<T>::get(id).map(|tx_out| tx_out.status())
+	}
+
+	/// Get the current producer schedule, which is used by the last relayed block or the latest
+	/// saved one if relaying hasn't started, and the pending one which is newer than it.
+	pub fn producer_schedules() -> (Option<ProducerScheduleInfo>, Option<ProducerScheduleInfo>) {
+		let latest_version = PendingScheduleVersion::get();
+		let current_version = RelayHead::get()
+			.and_then(|(head_num, _)| RelayedBlocks::get(head_num))
+			.map_or(latest_version, |block| block.schedule_version);
+
+		let schedule_info = |version: VersionId| {
+			if !ProducerSchedules::contains_key(version) {
+				return None;
+			}
+			let (producers, schedule_hash) = ProducerSchedules::get(version);
+
+			Some(ProducerScheduleInfo {
+				version,
+				producers: producers.iter().map(|producer| producer.producer_name.to_string()).collect(),
+				schedule_hash: schedule_hash.to_string(),
+			})
+		};
+		let pending = if latest_version > current_version { schedule_info(latest_version) } else { None };
+
+		(schedule_info(current_version), pending)
+	}
+
+	/// Whether the action receipt has been proven
+	pub fn is_action_proven(action_receipt: &ActionReceipt) -> bool {
+		BridgeActionReceipt::contains_key(action_receipt)
+	}
+
+	/// Get transactions sent to EOS by the account, which haven't been pruned
+	pub fn account_tx_outs(who: &T::AccountId) -> Vec<(TxOutId, TxOutStatus)> {
+		AccountTxOuts::<T>::get(who).into_iter()
+			.filter_map(|id| Self::tx_out_status(id).map(|status| (id, status)))
+			.collect()
+	}
+
+	/// Get whether the account is a notary, and transactions waiting for its signature
+	pub fn notary_status(who: &T::AccountId) -> NotaryStatus {
+		if !NotaryKeys::<T>::get().contains(who) {
+			return NotaryStatus::default();
+		}
+
+		NotaryStatus {
+			is_notary: true,
+			threshold: BridgeContractAccount::get().1,
+			unsigned_tx_outs: PendingTxOuts::get().into_iter()
+				.filter(|id| match BridgeTxOuts::<T>::get(id) {
+					Some(tx_out @ TxOut::Generated(_)) => !tx_out.has_signed(who),
+					_ => false,
+				})
+				.collect(),
+		}
+	}
+
+	/// Signatures are only added through `submit_signatures`, and success is only proven by the
+	/// action executed on EOS, so reports cannot change them.
+	fn ensure_tx_out_transition(id: TxOutId, tx_out: &TxOut<T::AccountId>) -> Result<(), Error<T>> {
+		let current = BridgeTxOuts::<T>::get(id).ok_or(Error::<T>::TxOutNotFound)?;
+
+		ensure!(current.status().can_transit_to(tx_out.status()), Error::<T>::InvalidTxOutTransition);
+		current.ensure_reported::<T>(tx_out)
+	}
+
+	fn update_tx_out(id: TxOutId, tx_out: TxOut<T::AccountId>) {
+		let status = tx_out.status();
+		BridgeTxOuts::<T>::insert(id, tx_out);
+
+		if status.is_finished() {
+			PendingTxOuts::mutate(|pending| pending.retain(|pending_id| *pending_id != id));
+			ExpiredTxOuts::<T>::remove(id);
+
+			let prune_at = <system::Module<T>>::block_number() + T::TxOutRetention::get();
+			TxOutsToPrune::<T>::append(prune_at, id);
+		}
+
+		Self::deposit_event(RawEvent::TxOutStatusChanged(id, status));
+
+		match status {
+			TxOutStatus::Success => {
+				Self::release_escrow(id, false);
+				StakeTxOuts::<T>::remove(id);
+			},
+			TxOutStatus::Fail => {
+				Self::release_escrow(id, true);
+				Self::revert_stake(id);
+			},
+			_ => {}
+		}
+	}
+
+	/// Stake of a failed staking transaction is given back, and un-staked amount is staked again
+	fn revert_stake(id: TxOutId) {
+		if let Some(stake) = StakeTxOuts::<T>::take(id) {
+			StakedProducers::<T>::mutate(&stake.producer, |staked| {
+				*staked = if stake.staking {
+					staked.saturating_sub(stake.eos_amount)
+				} else {
+					staked.saturating_add(stake.eos_amount)
+				};
+			});
+			BridgeReserves::<T>::mutate(stake.asset_id, |reserve| {
+				if stake.staking {
+					reserve.staked = reserve.staked.saturating_sub(stake.eos_amount);
+				} else {
+					reserve.staked = reserve.staked.saturating_add(stake.eos_amount);
+					reserve.refunding = reserve.refunding.saturating_sub(stake.eos_amount);
+				}
+			});
+			Self::deposit_event(RawEvent::StakeReverted(id));
+		}
+	}
+
+	/// Escrowed tokens are burned once the withdrawal succeeds, otherwise they're given back
+	fn release_escrow(id: TxOutId, refund: bool) {
+		if let Some(escrow) = WithdrawEscrows::<T>::take(id) {
+			Self::release_reserve(&escrow, refund);
+			if refund {
+				T::AssetTrait::asset_issue(escrow.asset_id, escrow.token_type, escrow.who.clone(), escrow.amount);
+				Self::deposit_event(RawEvent::WithdrawRefunded(id, escrow.who, escrow.amount));
+			} else {
+				Self::deposit_event(RawEvent::WithdrawCompleted(id, escrow.who, escrow.amount));
+			}
 		}
 	}
 
+	/// Refunded withdrawal is minted again, otherwise it's unlocked from EOS
+	fn release_reserve(escrow: &WithdrawEscrow<T::AccountId, T::AssetId, T::Balance>, refund: bool) {
+		BridgeReserves::<T>::mutate(escrow.asset_id, |reserve| {
+			reserve.withdrawing = reserve.withdrawing.saturating_sub(escrow.eos_amount);
+			if refund {
+				reserve.minted = reserve.minted.saturating_add(escrow.eos_amount);
+			} else {
+				reserve.locked = reserve.locked.saturating_sub(escrow.eos_amount);
+			}
+		});
+	}
+
+	/// Tokens being withdrawn may have been sent by EOS, so they're not counted in the deficit.
+	/// Staked tokens and those being refunded aren't in the balance of bridge contract account either.
+	fn is_reserve_deficit(reserve: &BridgeReserve<T::Balance>, eos_balance: T::Balance) -> bool {
+		Self::unavailable_reserve(reserve).saturating_add(eos_balance) < reserve.locked
+	}
+
+	/// Locked tokens which cannot be withdrawn or staked, since they're not in the balance of
+	/// bridge contract account or will leave it
+	fn unavailable_reserve(reserve: &BridgeReserve<T::Balance>) -> T::Balance {
+		reserve.withdrawing.saturating_add(reserve.staked).saturating_add(reserve.refunding)
+	}
+
+	/// check receiver account format
+	/// https://github.com/paritytech/substrate/wiki/External-Address-Format-(SS58)
 	fn into_account(data: [u8; 32]) -> Result<T::AccountId, Error<T>> {
 		T::AccountId::decode(&mut &data[..]).map_err(|_| Error::<T>::InvalidAccountId)
 	}
 
-	/// generate transaction for transfer amount to
-	fn tx_transfer_to<P, B>(
+	/// generate transaction for transfer amount to, the amount is escrowed until it's finished.
+	/// Large withdrawal is delayed before it's sent to EOS.
+	fn tx_transfer_to(
 		raw_to: Vec<u8>,
-		bridge_asset: BridgeAssetBalance<T::AccountId, P, B>,
-	) -> Result<TxOut<T::AccountId>, Error<T>>
-		where
-			P: AtLeast32Bit + Copy,
-			B: AtLeast32Bit + Copy,
-	{
+		token_id: T::AssetId,
+		bridge_asset: BridgeAssetBalance<T::AccountId, T::Precision, T::Balance>,
+	) -> Result<(), Error<T>> {
+		ensure!(!WithdrawPaused::get(), Error::<T>::WithdrawPaused);
+		ensure!(Self::can_push_tx_out(), Error::<T>::TooManyPendingTxOuts);
+		let token = BridgeTokens::<T>::get(token_id).ok_or(Error::<T>::TokenNotMapped)?;
+		ensure!(token.enabled, Error::<T>::TokenDisabled);
+		ensure!(bridge_asset.symbol.symbol == token.symbol, Error::<T>::TokenNotMapped);
+		ensure!(bridge_asset.symbol.precision.saturated_into::<u8>() == token.precision, Error::<T>::InvalidTokenPrecision);
+
 		let (raw_from, threshold) = BridgeContractAccount::get();
 		let memo = core::str::from_utf8(&bridge_asset.memo).map_err(|_| Error::<T>::ParseUtf8Error)?.to_string();
-		let amount = Self::convert_to_eos_asset::<T::AccountId, P, B>(&bridge_asset)?;
+		let amount = Self::convert_to_eos_asset::<T::AccountId, T::Precision, T::Balance>(&bridge_asset)?;
+
+		let token_type = bridge_asset.token_type;
+		let escrow_amount: T::Balance = Self::convert_precision(
+			amount.amount as u128,
+			token.precision,
+			Self::asset_precision(token_id, token_type),
+		).saturated_into();
+		ensure!(!escrow_amount.is_zero(), Error::<T>::ZeroWithdrawAmount);
+		let balance = T::AssetTrait::get_account_asset(&token_id, token_type, &bridge_asset.from).balance;
+		ensure!(balance >= escrow_amount, Error::<T>::InsufficientBalance);
+
+		let eos_amount: T::Balance = (amount.amount as u128).saturated_into();
+		let tx_out = TxOut::<T::AccountId>::init(
+			raw_from,
+			raw_to,
+			&token.contract,
+			amount,
+			threshold,
+			&memo,
+			bridge_asset.from.clone(),
+			token_type
+		)?;
+
+		// withdrawals cannot take more than what's locked on EOS and not staked or being refunded
+		let reserve = BridgeReserves::<T>::get(token_id);
+		ensure!(
+			Self::unavailable_reserve(&reserve).saturating_add(eos_amount) <= reserve.locked,
+			Error::<T>::InsufficientReserve
+		);
+
+		let limit = WithdrawLimits::<T>::get(token_id);
+		let delayed = limit.as_ref()
+			.map_or(false, |limit| !limit.delay_threshold.is_zero() && eos_amount >= limit.delay_threshold);
+		ensure!(
+			!delayed || DelayedWithdrawCount::get() < MAX_DELAYED_WITHDRAWS,
+			Error::<T>::TooManyDelayedWithdraws
+		);
+		if let Some(ref limit) = limit {
+			Self::record_withdraw(token_id, &bridge_asset.from, eos_amount, limit)?;
+		}
 
-		let tx_out = TxOut::<T::AccountId>::init(raw_from, raw_to, amount, threshold, &memo, bridge_asset.from, bridge_asset.token_type)?;
-		BridgeTxOuts::<T>::append(&tx_out);
+		T::AssetTrait::asset_destroy(token_id, token_type, bridge_asset.from.clone(), escrow_amount);
+		BridgeReserves::<T>::insert(token_id, BridgeReserve {
+			minted: reserve.minted.saturating_sub(eos_amount),
+			withdrawing: reserve.withdrawing.saturating_add(eos_amount),
+			..reserve
+		});
+		let escrow = WithdrawEscrow {
+			who: bridge_asset.from.clone(),
+			asset_id: token_id,
+			token_type,
+			amount: escrow_amount,
+			eos_amount,
+		};
 
-		Ok(tx_out)
+		match limit {
+			Some(limit) if delayed => {
+				let execute_at = <system::Module<T>>::block_number() + limit.delay;
+				let id = NextDelayedWithdrawId::mutate(|id| {
+					let current = *id;
+					*id += 1;
+					current
+				});
+				DelayedWithdraws::<T>::insert(id, DelayedWithdraw { tx_out, escrow, execute_at });
+				DelayedWithdrawsToExecute::<T>::append(execute_at, id);
+				DelayedWithdrawCount::mutate(|count| *count += 1);
+
+				Self::deposit_event(RawEvent::WithdrawDelayed(id, bridge_asset.from, eos_amount, execute_at));
+			}
+			_ => {
+				Self::send_tx_out(tx_out, escrow);
+			}
+		}
+
+		Ok(())
 	}
 
-	fn offchain(_now_block: T::BlockNumber) -> Result<(), Error<T>> {
-		//  avoid borrow checker issue if use has_change: bool
-		let has_change = core::cell::Cell::new(false);
+	/// Add the transaction to be handled by offchain worker, and keep the escrow until it's finished
+	fn send_tx_out(
+		tx_out: TxOut<T::AccountId>,
+		escrow: WithdrawEscrow<T::AccountId, T::AssetId, T::Balance>
+	) -> TxOutId {
+		let id = Self::push_tx_out(tx_out, &escrow.who);
+
+		let (who, amount) = (escrow.who.clone(), escrow.amount);
+		WithdrawEscrows::<T>::insert(id, escrow);
 
-		let bridge_tx_outs = BridgeTxOuts::<T>::get();
+		Self::deposit_event(RawEvent::WithdrawEscrowed(id, who, amount));
 
-		let node_url = Self::get_offchain_storage(EOS_NODE_URL)?;
-		let sk_str = Self::get_offchain_storage(EOS_SECRET_KEY)?;
+		id
+	}
 
-		let sk = SecretKey::from_wif(&sk_str).map_err(|_| Error::<T>::ParseSecretKeyError)?;
+	/// Delayed withdrawals due at the block are queued as ready, and sent to EOS in order. While
+	/// withdrawals are paused or pending transactions are full, they're kept in the queue and the
+	/// next block resumes from the first one.
+	fn execute_delayed_withdraws(now: T::BlockNumber) -> Weight {
+		let due = DelayedWithdrawsToExecute::<T>::take(now);
+		let due_count = due.len() as u32;
+		let mut ready = ReadyDelayedWithdraws::get();
+		ready.extend(due);
+		if ready.is_empty() {
+			return weights::execute_delayed_withdraws::<T>(0, 0);
+		}
 
-		let bridge_tx_outs = bridge_tx_outs.into_iter()
-			.map(|bto| {
-				match bto {
-					// generate raw transactions
-					TxOut::<T::AccountId>::Initial(_) => {
-						match bto.clone().generate::<T>(node_url.as_str()) {
-							Ok(generated_bto) => {
-								has_change.set(true);
-								debug::info!(target: "bridge-eos", "bto.generate {:?}",generated_bto);
-								debug::info!("bto.generate");
-								generated_bto
-							}
+		let mut executed = 0usize;
+		if !WithdrawPaused::get() {
+			let mut handled = 0usize;
+			for &id in ready.iter() {
+				if executed >= MAX_DELAYED_WITHDRAWS_PER_BLOCK || !Self::can_push_tx_out() {
+					break;
+				}
+				handled += 1;
+				// vetoed withdrawal has been removed
+				if let Some(delayed) = DelayedWithdraws::<T>::take(id) {
+					DelayedWithdrawCount::mutate(|count| *count = count.saturating_sub(1));
+					let tx_out_id = Self::send_tx_out(delayed.tx_out, delayed.escrow);
+					Self::deposit_event(RawEvent::DelayedWithdrawExecuted(id, tx_out_id));
+					executed += 1;
+				}
+			}
+			ready.drain(..handled);
+		}
+
+		if ready.is_empty() {
+			ReadyDelayedWithdraws::kill();
+		} else {
+			ReadyDelayedWithdraws::put(ready);
+		}
+
+		weights::execute_delayed_withdraws::<T>(due_count, executed as u32)
+	}
+
+	/// Pending transactions are bounded by `MAX_PENDING_TX_OUTS`
+	fn can_push_tx_out() -> bool {
+		PendingTxOuts::decode_len().unwrap_or(0) < MAX_PENDING_TX_OUTS
+	}
+
+	/// Add the transaction sent by the account to be handled by offchain worker
+	fn push_tx_out(tx_out: TxOut<T::AccountId>, who: &T::AccountId) -> TxOutId {
+		let id = NextTxOutId::mutate(|id| {
+			let current = *id;
+			*id += 1;
+			current
+		});
+		BridgeTxOuts::<T>::insert(id, &tx_out);
+		PendingTxOuts::append(id);
+		TxOutsToExpire::<T>::append(<system::Module<T>>::block_number() + T::TxOutExpiry::get(), id);
+		AccountTxOuts::<T>::append(who, id);
+		TxOutOwners::<T>::insert(id, who);
+
+		Self::deposit_event(RawEvent::TxOutCreated(id, who.clone()));
+
+		id
+	}
+
+	/// Generate transaction staking locked EOS to or un-staking it from the producer, on behalf of
+	/// the account. Bridge contract account votes for all producers it has staked to.
+	fn tx_stake(
+		producer: Vec<u8>,
+		bridge_asset: BridgeAssetBalance<T::AccountId, T::Precision, T::Balance>,
+		is_stake: bool,
+	) -> Result<TxOutId, Error<T>> {
+		ensure!(!WithdrawPaused::get(), Error::<T>::WithdrawPaused);
+		ensure!(Self::can_push_tx_out(), Error::<T>::TooManyPendingTxOuts);
+		let token_id = Self::bridge_token_id(&bridge_asset.symbol.symbol)?;
+		let token = BridgeTokens::<T>::get(token_id).ok_or(Error::<T>::TokenNotMapped)?;
+		ensure!(token.enabled, Error::<T>::TokenDisabled);
+		ensure!(
+			token.contract == staking::EOS_TOKEN_CONTRACT && token.symbol == staking::EOS_TOKEN_SYMBOL,
+			Error::<T>::StakingTokenNotSupported
+		);
+		ensure!(bridge_asset.symbol.precision.saturated_into::<u8>() == token.precision, Error::<T>::InvalidTokenPrecision);
+
+		let amount = Self::convert_to_eos_asset::<T::AccountId, T::Precision, T::Balance>(&bridge_asset)?;
+		let eos_amount: T::Balance = (amount.amount as u128).saturated_into();
+		ensure!(!eos_amount.is_zero(), Error::<T>::ZeroStakeAmount);
+
+		let reserve = BridgeReserves::<T>::get(token_id);
+		let staked = StakedProducers::<T>::get(&producer);
+		let (staked, reserve_staked) = if is_stake {
+			// only locked tokens which aren't being withdrawn or refunded can be staked
+			ensure!(
+				Self::unavailable_reserve(&reserve).saturating_add(eos_amount) <= reserve.locked,
+				Error::<T>::InsufficientReserve
+			);
+			(staked.saturating_add(eos_amount), reserve.staked.saturating_add(eos_amount))
+		} else {
+			ensure!(staked >= eos_amount, Error::<T>::InsufficientStake);
+			(staked - eos_amount, reserve.staked.saturating_sub(eos_amount))
+		};
+		// un-staked tokens are being refunded until EOS sends them back
+		let reserve_refunding = if is_stake { reserve.refunding } else { reserve.refunding.saturating_add(eos_amount) };
+
+		let mut producers: Vec<Vec<u8>> = StakedProducers::<T>::iter()
+			.filter(|(voted, stake)| *voted != producer && !stake.is_zero())
+			.map(|(voted, _)| voted)
+			.collect();
+		if !staked.is_zero() {
+			producers.push(producer.clone());
+		}
+
+		let (raw_bridge, threshold) = BridgeContractAccount::get();
+		let actions = if is_stake {
+			staking::stake_actions::<T>(&raw_bridge, amount, &producers)?
+		} else {
+			staking::unstake_actions::<T>(&raw_bridge, amount, &producers)?
+		};
+		let tx_out = TxOut::<T::AccountId>::init_actions(actions, threshold, bridge_asset.from.clone(), TokenType::Token);
+
+		if staked.is_zero() {
+			StakedProducers::<T>::remove(&producer);
+		} else {
+			StakedProducers::<T>::insert(&producer, staked);
+		}
+		BridgeReserves::<T>::insert(token_id, BridgeReserve { staked: reserve_staked, refunding: reserve_refunding, ..reserve });
+
+		let id = Self::push_tx_out(tx_out, &bridge_asset.from);
+		StakeTxOuts::<T>::insert(id, StakeTxOut { asset_id: token_id, producer: producer.clone(), eos_amount, staking: is_stake });
+		Self::deposit_event(RawEvent::StakeTxOutCreated(id, producer, eos_amount, is_stake));
+
+		Ok(id)
+	}
+
+	/// Asset which is mapped to EOS token of the symbol
+	fn bridge_token_id(symbol: &[u8]) -> Result<T::AssetId, Error<T>> {
+		BridgeTokens::<T>::iter()
+			.find(|(_, token)| token.symbol == symbol)
+			.map(|(token_id, _)| token_id)
+			.ok_or(Error::<T>::TokenNotMapped)
+	}
+
+	/// Check the withdrawal against caps of the rolling window, and count it in
+	fn record_withdraw(
+		token_id: T::AssetId,
+		who: &T::AccountId,
+		amount: T::Balance,
+		limit: &WithdrawLimit<T::Balance, T::BlockNumber>
+	) -> Result<(), Error<T>> {
+		let now = <system::Module<T>>::block_number();
+
+		let (used, updated_at) = GlobalWithdrawn::<T>::get(token_id);
+		let global_used = Self::rolling_usage(used, updated_at, now, limit.global_cap, limit.window)
+			.saturating_add(amount);
+		ensure!(limit.global_cap.is_zero() || global_used <= limit.global_cap, Error::<T>::GlobalWithdrawLimitExceeded);
+
+		let (used, updated_at) = AccountWithdrawn::<T>::get(token_id, who);
+		let account_used = Self::rolling_usage(used, updated_at, now, limit.account_cap, limit.window)
+			.saturating_add(amount);
+		ensure!(limit.account_cap.is_zero() || account_used <= limit.account_cap, Error::<T>::AccountWithdrawLimitExceeded);
+
+		GlobalWithdrawn::<T>::insert(token_id, (global_used, now));
+		AccountWithdrawn::<T>::insert(token_id, who, (account_used, now));
+
+		Ok(())
+	}
+
+	/// Take back the amount counted in the rolling window, like a vetoed withdrawal which isn't
+	/// sent to EOS
+	fn release_withdraw(token_id: T::AssetId, who: &T::AccountId, amount: T::Balance) {
+		GlobalWithdrawn::<T>::mutate(token_id, |(used, _)| *used = used.saturating_sub(amount));
+		AccountWithdrawn::<T>::mutate(token_id, who, |(used, _)| *used = used.saturating_sub(amount));
+	}
+
+	/// Usage is released linearly in the window, so it works as a rolling window without keeping
+	/// every withdrawal.
+	fn rolling_usage(
+		used: T::Balance,
+		updated_at: T::BlockNumber,
+		now: T::BlockNumber,
+		cap: T::Balance,
+		window: T::BlockNumber
+	) -> T::Balance {
+		let elapsed = now.saturating_sub(updated_at);
+		if window.is_zero() || elapsed >= window {
+			return Zero::zero();
+		}
+
+		let released = cap.saturated_into::<u128>()
+			.saturating_mul(elapsed.saturated_into::<u128>())
+			/ window.saturated_into::<u128>();
+
+		used.saturating_sub(released.saturated_into())
+	}
+
+	/// Deposits cannot be proven while they're paused, so the action can be proven after resuming
+	fn ensure_deposit_not_paused(action: &Action) -> DispatchResult {
+		if DepositPaused::get() && action.name == ACTION_NAMES[0] {
+			let action_transfer = Self::get_action_transfer_from_action(action)?;
+			ensure!(
+				BridgeContractAccount::get().0 != action_transfer.to.to_string().into_bytes(),
+				Error::<T>::DepositPaused
+			);
+		}
+
+		Ok(())
+	}
+
+	fn offchain(now_block: T::BlockNumber) -> Result<(), Error<T>> {
+		let now = now_block.saturated_into::<u64>();
+		let eos_nodes = EosNodes::parse(&Self::get_offchain_storage(EOS_NODE_URL)?, now);
+		let local_notaries = Self::local_notaries();
+
+		// only report transactions changed by this worker
+		let mut changed_tx_outs = Vec::new();
+		let mut notary_signatures = Vec::new();
+		for id in PendingTxOuts::get() {
+			let bto = match BridgeTxOuts::<T>::get(id) {
+				Some(bto) => bto,
+				None => continue,
+			};
+			let status = bto.status();
+
+			let processed_bto = match bto {
+				// generate raw transactions
+				TxOut::<T::AccountId>::Initial(_) if Self::is_retry_due(id, status, now) => {
+					match bto.clone().generate::<T>(&eos_nodes) {
+						Ok(generated_bto) => {
+							debug::info!(target: "bridge-eos", "bto.generate {:?}", generated_bto);
+							Some(generated_bto)
+						}
+						Err(e) => {
+							debug::info!("failed to get latest block due to: {:?}", e);
+							Self::retry_later(id, status, now, bto)
+						}
+					}
+				},
+				TxOut::<T::AccountId>::Generated(_) => {
+					// every local notary signs it with its own EOS key, and submits the signature by itself
+					for (notary, _) in local_notaries.iter() {
+						if bto.has_signed(notary) || !Self::is_signature_due(id, notary, now) {
+							continue;
+						}
+						let sk = match Self::notary_secret_key(notary) {
+							Ok(sk) => sk,
 							Err(e) => {
-								debug::info!("failed to get latest block due to: {:?}", e);
-								bto
+								debug::warn!("notary {:?} cannot sign due to: {:?}", notary, e);
+								continue;
 							}
-						}
-					},
-					_ => bto,
-				}
-			})
-			.map(|bto| {
-				match bto {
-					TxOut::<T::AccountId>::Generated(_) => {
-						let author = <pallet_authorship::Module<T>>::author();
-						let mut ret = bto.clone();
-						if let Some(_) = Self::local_authority_keys()
-							.find(|key| *key == author.clone().into())
-						{
-							match bto.sign::<T>(sk.clone(), author) {
-								Ok(signed_bto) => {
-									has_change.set(true);
-									debug::info!(target: "bridge-eos", "bto.sign {:?}", signed_bto);
-									debug::info!("bto.sign with: {:?}", sk.to_string());
-									ret = signed_bto;
-								}
-								Err(e) => debug::warn!("bto.sign with failure: {:?}", e),
+						};
+						match bto.eos_signature::<T>(sk) {
+							Ok(signature) => {
+								debug::info!(target: "bridge-eos", "notary {:?} signed transaction {}", notary, id);
+								notary_signatures.push((notary.clone(), id, signature));
 							}
+							Err(e) => debug::warn!("bto.eos_signature with failure: {:?}", e),
 						}
-						ret
-					},
-					_ => bto,
+					}
+					None
+				},
+				TxOut::<T::AccountId>::Signed(_) if Self::is_retry_due(id, status, now) => {
+					match bto.clone().send::<T>(&eos_nodes) {
+						Ok(sent_bto) => {
+							debug::info!(target: "bridge-eos", "bto.send {:?}", sent_bto);
+							Some(sent_bto)
+						}
+						Err(e) => {
+							debug::warn!("error happened while pushing transaction: {:?}", e);
+							Self::retry_later(id, status, now, bto)
+						}
+					}
+				},
+				_ => None,
+			};
+
+			if let Some(processed_bto) = processed_bto {
+				changed_tx_outs.push((id, processed_bto));
+			}
+		}
+
+		// reports are submitted by the first local notary
+		match local_notaries.first() {
+			Some((_, public)) if !changed_tx_outs.is_empty() => {
+				let results = Signer::<T, T::AuthorityId>::all_accounts()
+					.with_filter(vec![public.clone()])
+					.send_signed_transaction(|_account| Call::bridge_tx_report(changed_tx_outs.clone()));
+				for (account, result) in results.iter() {
+					match result {
+						Ok(_) => debug::info!(target: "bridge-eos", "Call::bridge_tx_report {:?}", changed_tx_outs),
+						Err(_) => debug::warn!("[{:?}] failed to report transactions", account.id),
+					}
 				}
-			})
-			.map(|bto| {
-				match bto {
-					TxOut::<T::AccountId>::Signed(_) => {
-						match bto.clone().send::<T>(node_url.as_str()) {
-							Ok(sent_bto) => {
-								has_change.set(true);
-								debug::info!(target: "bridge-eos", "bto.send {:?}", sent_bto,);
-								debug::info!("bto.send");
-								sent_bto
-							}
-							Err(e) => {
-								debug::warn!("error happened while pushing transaction: {:?}", e);
-								bto
-							}
+			}
+			_ => {}
+		}
+
+		// each notary submits all of its signatures in one signed transaction
+		for (notary, public) in local_notaries.into_iter() {
+			let signatures = notary_signatures.iter()
+				.filter(|(signer, _, _)| *signer == notary)
+				.map(|(_, id, signature)| (*id, signature.clone()))
+				.collect::<Vec<_>>();
+			if signatures.is_empty() {
+				continue;
+			}
+
+			let results = Signer::<T, T::AuthorityId>::all_accounts()
+				.with_filter(vec![public])
+				.send_signed_transaction(|_account| Call::submit_signatures(signatures.clone()));
+			for (account, result) in results.iter() {
+				match result {
+					Ok(_) => {
+						debug::info!(target: "bridge-eos", "Call::submit_signatures by {:?}", account.id);
+						for (id, _) in signatures.iter() {
+							sp_io::offchain::local_storage_set(
+								StorageKind::PERSISTENT,
+								&Self::tx_out_signed_key(*id, &notary),
+								&now.encode()
+							);
 						}
-					},
-					_ => bto,
+					}
+					Err(_) => debug::warn!("[{:?}] failed to submit signatures", account.id),
 				}
-			}).collect::<Vec<_>>();
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Compare reserves with balances of bridge contract account on EOS, a local notary reports
+	/// the deficit if there's any.
+	fn check_reserves(now: u64) -> Result<(), Error<T>> {
+		let public = match Self::local_notaries().into_iter().next() {
+			Some((_, public)) => public,
+			None => return Ok(()),
+		};
+		let eos_nodes = EosNodes::parse(&Self::get_offchain_storage(EOS_NODE_URL)?, now);
+		let (raw_account, _) = BridgeContractAccount::get();
+		let account = core::str::from_utf8(&raw_account).map_err(|_| Error::<T>::ParseUtf8Error)?;
+
+		for (asset_id, token) in BridgeTokens::<T>::iter() {
+			let contract = core::str::from_utf8(&token.contract).map_err(|_| Error::<T>::ParseUtf8Error)?;
+			let symbol = core::str::from_utf8(&token.symbol).map_err(|_| Error::<T>::ParseUtf8Error)?;
+			let eos_balance = match transaction::eos_rpc::get_currency_balance::<T>(&eos_nodes, contract, account, symbol) {
+				Ok(balance) => (balance.max(0) as u128).saturated_into::<T::Balance>(),
+				Err(e) => {
+					debug::warn!(target: "bridge-eos", "failed to get balance of {} due to: {:?}", symbol, e);
+					continue;
+				}
+			};
+
+			let reserve = BridgeReserves::<T>::get(asset_id);
+			if !Self::is_reserve_deficit(&reserve, eos_balance) {
+				continue;
+			}
 
-		if has_change.get() {
-			BridgeTxOuts::<T>::put(bridge_tx_outs.clone()); // update transaction list
-			let call = Call::bridge_tx_report(bridge_tx_outs.clone());
-			match SubmitTransaction::<T, Call<T>>::submit_unsigned_transaction(call.into()) {
-				Ok(_) => debug::info!(target: "bridge-eos", "Call::bridge_tx_report {:?}", bridge_tx_outs),
-				Err(e) => debug::warn!("submit transaction with failure: {:?}", e),
+			debug::warn!(target: "bridge-eos", "reserve of {} is {:?}, but balance on EOS is {:?}", symbol, reserve, eos_balance);
+			let results = Signer::<T, T::AuthorityId>::all_accounts()
+				.with_filter(vec![public.clone()])
+				.send_signed_transaction(|_account| Call::report_reserve_deficit(asset_id, eos_balance));
+			for (account, result) in results.iter() {
+				if result.is_err() {
+					debug::warn!("[{:?}] failed to report reserve deficit", account.id);
+				}
 			}
 		}
 
 		Ok(())
 	}
 
+	fn tx_out_signed_key(id: TxOutId, notary: &T::AccountId) -> Vec<u8> {
+		let mut key = TX_OUT_SIGNED_PREFIX.to_vec();
+		key.extend((id, notary).encode());
+		key
+	}
+
+	/// Signature which has been submitted is submitted again only if it isn't on chain for a while
+	fn is_signature_due(id: TxOutId, notary: &T::AccountId, now: u64) -> bool {
+		sp_io::offchain::local_storage_get(StorageKind::PERSISTENT, &Self::tx_out_signed_key(id, notary))
+			.and_then(|signed_at| u64::decode(&mut &signed_at[..]).ok())
+			.map_or(true, |signed_at| now >= signed_at.saturating_add(SIGNATURE_RESUBMIT_INTERVAL))
+	}
+
+	fn tx_out_retry_key(id: TxOutId, status: TxOutStatus) -> Vec<u8> {
+		let mut key = TX_OUT_RETRY_PREFIX.to_vec();
+		key.extend((id, status).encode());
+		key
+	}
+
+	/// Retry state (attempts, next retry block) of a transaction in the status, kept in offchain storage
+	fn tx_out_retry_state(id: TxOutId, status: TxOutStatus) -> (u32, u64) {
+		sp_io::offchain::local_storage_get(StorageKind::PERSISTENT, &Self::tx_out_retry_key(id, status))
+			.and_then(|state| Decode::decode(&mut &state[..]).ok())
+			.unwrap_or_default()
+	}
+
+	fn is_retry_due(id: TxOutId, status: TxOutStatus, now: u64) -> bool {
+		let (_, next_retry_at) = Self::tx_out_retry_state(id, status);
+		now >= next_retry_at
+	}
+
+	/// Back off after a failure, fail the transaction if it has been retried too many times
+	fn retry_later(
+		id: TxOutId,
+		status: TxOutStatus,
+		now: u64,
+		bto: TxOut<T::AccountId>
+	) -> Option<TxOut<T::AccountId>> {
+		let (attempts, _) = Self::tx_out_retry_state(id, status);
+		let attempts = attempts.saturating_add(1);
+
+		// signed transaction may have been pushed to EOS, it's retried until proven expired
+		if attempts >= MAX_TX_OUT_RETRIES && status != TxOutStatus::Signed {
+			debug::warn!(target: "bridge-eos", "transaction {} failed after {} attempts", id, attempts);
+			return Some(bto.fail(b"exceeded max retries"));
+		}
+
+		let backoff = 1u64 << attempts.min(MAX_TX_OUT_RETRIES);
+		let next_retry_at = now.saturating_add(TX_OUT_RETRY_BACKOFF.saturating_mul(backoff));
+		sp_io::offchain::local_storage_set(
+			StorageKind::PERSISTENT,
+			&Self::tx_out_retry_key(id, status),
+			&(attempts, next_retry_at).encode()
+		);
+
+		None
+	}
+
 	fn convert_to_eos_asset<A, P, B>(
 		bridge_asset: &BridgeAssetBalance<A, P, B>
 	) -> Result<Asset, Error<T>>
@@ -895,57 +2715,98 @@ impl<T: Trait> Module<T> {
 		let symbol_code = SymbolCode::try_from(symbol_str).map_err(|_| Error::<T>::ParseUtf8Error)?;
 		let symbol = Symbol::new_with_code(precision, symbol_code);
 
-		let amount = (bridge_asset.amount.saturated_into::<u128>() / (10u128.pow(12 - precision as u32))) as i64;
+		// balances have 12 decimals, more than any EOS asset
+		let divisor = 12u32.checked_sub(precision as u32)
+			.and_then(|exp| 10u128.checked_pow(exp))
+			.ok_or(Error::<T>::InvalidTokenPrecision)?;
+		let amount = i64::try_from(bridge_asset.amount.saturated_into::<u128>() / divisor)
+			.map_err(|_| Error::<T>::ConvertBalanceError)?;
 
 		Ok(Asset::new(amount, symbol))
 	}
 
+	/// EOS secret key of the notary in local storage
+	fn notary_secret_key(notary: &T::AccountId) -> Result<SecretKey, Error<T>> {
+		let sk_str = Self::get_offchain_storage(&[EOS_SECRET_KEY, &notary.encode()[..]].concat())?;
+
+		SecretKey::from_wif(&sk_str).map_err(|_| Error::<T>::ParseSecretKeyError)
+	}
+
+	/// Compressed public key of an EOS public key like `EOS6MRy...`. Its checksum is ripemd160,
+	/// which isn't available in runtime, the key is set by root and checked by the signatures anyway.
+	fn parse_eos_public_key(eos_key: &[u8]) -> Result<Vec<u8>, Error<T>> {
+		ensure!(eos_key.starts_with(b"EOS"), Error::<T>::InvalidEosPublicKey);
+		let decoded = bs58::decode(&eos_key[3..]).into_vec().map_err(|_| Error::<T>::InvalidEosPublicKey)?;
+		// compressed key followed by 4 bytes of checksum
+		ensure!(decoded.len() == 37 && (decoded[0] == 2 || decoded[0] == 3), Error::<T>::InvalidEosPublicKey);
+
+		Ok(decoded[..33].to_vec())
+	}
+
 	fn get_offchain_storage(key: &[u8]) -> Result<String, Error<T>> {
 		let value = sp_io::offchain::local_storage_get(StorageKind::PERSISTENT, key, ).ok_or(Error::<T>::NoLocalStorage)?;
 
 		Ok(String::from_utf8(value).map_err(|_| Error::<T>::ParseUtf8Error)?)
 	}
 
-	fn local_authority_keys() -> impl Iterator<Item=T::AuthorityId> {
-		let authorities = NotaryKeys::<T>::get();
-		let mut local_keys = T::AuthorityId::all();
-		local_keys.sort();
+	/// Notaries whose keys are in the local keystore
+	fn local_notaries() -> Vec<(T::AccountId, T::Public)> {
+		let notaries = NotaryKeys::<T>::get();
+
+		<T::AuthorityId as AppCrypto<T::Public, T::Signature>>::RuntimeAppPublic::all()
+			.into_iter()
+			.filter_map(|key| {
+				let generic_public = <T::AuthorityId as AppCrypto<T::Public, T::Signature>>::GenericPublic::from(key);
+				let public: T::Public = generic_public.into();
+				let account = public.clone().into_account();
 
-		authorities.into_iter()
-			.enumerate()
-			.filter_map(move |(_, authority)| {
-				local_keys.binary_search(&authority.into())
-					.ok()
-					.map(|location| local_keys[location].clone())
+				if notaries.contains(&account) {
+					Some((account, public))
+				} else {
+					None
+				}
 			})
+			.collect()
 	}
 }
 
 impl<T: Trait> BridgeAssetTo<T::AccountId, T::Precision, T::Balance> for Module<T> {
 	type Error = crate::Error<T>;
 	fn bridge_asset_to(target: Vec<u8>, bridge_asset: BridgeAssetBalance<T::AccountId, T::Precision, T::Balance>) -> Result<(), Self::Error> {
-		let _ = Self::tx_transfer_to(target, bridge_asset)?;
-
-		Ok(())
+		let precision = bridge_asset.symbol.precision.saturated_into::<u16>();
+		let token_id = T::AssetTrait::asset_id_exists(&bridge_asset.from, &bridge_asset.symbol.symbol, precision)
+			.ok_or(Error::<T>::TokenNotMapped)?;
+		Self::tx_transfer_to(target, token_id, bridge_asset)
 	}
 }
 
-#[allow(deprecated)]
-impl<T: Trait> frame_support::unsigned::ValidateUnsigned for Module<T> {
-	type Call = Call<T>;
-
-	fn validate_unsigned(_source: TransactionSource, call: &Self::Call) -> TransactionValidity {
-		if let Call::bridge_tx_report(_tx_list) = call {
-			let now_block = <frame_system::Module<T>>::block_number().saturated_into::<u64>();
-			Ok(ValidTransaction {
-				priority: TransactionPriority::max_value(),
-				requires: vec![],
-				provides: vec![(now_block).encode()],
-				longevity: TransactionLongevity::max_value(),
-				propagate: true,
-			})
-		} else {
-			InvalidTransaction::Call.into()
-		}
+impl<T: Trait> ChainBridge<T::AccountId, T::Precision, T::Balance> for Module<T> {
+	fn connects(blockchain: BlockchainType) -> bool {
+		blockchain == BlockchainType::EOS
+	}
+
+	fn is_valid_account(blockchain: BlockchainType, account: &[u8]) -> bool {
+		// EOS account name is up to 12 characters of a-z, 1-5 and '.'
+		blockchain == BlockchainType::EOS && !account.is_empty() && account.len() <= 12 &&
+			core::str::from_utf8(account).map_or(false, |name| AccountName::from_str(name).is_ok())
+	}
+
+	fn verify_account_proof(blockchain: BlockchainType, _: &[u8], _: &[u8], proof: Option<&[u8]>) -> bool {
+		// permissions of EOS accounts are kept on EOS, the ownership cannot be proved here, so EOS
+		// accounts are only bound without proof
+		blockchain == BlockchainType::EOS && proof.is_none()
+	}
+
+	fn send_asset(target: Vec<u8>, bridge_asset: BridgeAssetBalance<T::AccountId, T::Precision, T::Balance>) -> DispatchResult {
+		let token_id = Self::bridge_token_id(&bridge_asset.symbol.symbol)?;
+		Self::tx_transfer_to(target, token_id, bridge_asset).map_err(Into::into)
+	}
+
+	fn stake(validator: Vec<u8>, bridge_asset: BridgeAssetBalance<T::AccountId, T::Precision, T::Balance>) -> DispatchResult {
+		Self::tx_stake(validator, bridge_asset, true).map(|_| ()).map_err(Into::into)
+	}
+
+	fn unstake(validator: Vec<u8>, bridge_asset: BridgeAssetBalance<T::AccountId, T::Precision, T::Balance>) -> DispatchResult {
+		Self::tx_stake(validator, bridge_asset, false).map(|_| ()).map_err(Into::into)
 	}
 }
@@ -0,0 +1,168 @@
+// Copyright 2019-2020 Liebi Technologies.
+// This file is part of Bifrost.
+
+// Bifrost is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Bifrost is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Bifrost.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Grammar of the memo carried by deposits from EOS.
+//!
+//! Legacy memo (version 0): `{receiver}@{chain}[:{token_type}]`, like `alice@bifrost:EOS`.
+//!
+//! Version 1: `v1:{receiver}@{chain}:{token_type}[:{key}={value}]*`, supported keys are
+//! `ref` for the referrer account and `act` for the action, like
+//! `v1:alice@bifrost:vEOS:ref=bob:act=convert`.
+//!
+//! Receiver and referrer are SS58 addresses of any prefix, or `0x` prefixed hex account id.
+
+use alloc::string::{String, ToString};
+use sp_std::prelude::*;
+
+/// The chain name a deposit memo should point to
+pub const MEMO_CHAIN: &str = "bifrost";
+/// The latest version of memo grammar
+pub const MEMO_LATEST_VERSION: u8 = 1;
+
+const SS58_PREFIX: &[u8] = b"SS58PRE";
+const ACCOUNT_LENGTH: usize = 32;
+const CHECKSUM_LENGTH: usize = 2;
+
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum MemoError {
+	/// Fields are missing or not in the grammar
+	InvalidFormat,
+	/// The version isn't supported
+	UnknownVersion,
+	/// The memo doesn't point to Bifrost
+	InvalidChain,
+	/// The account is neither a SS58 address nor a hex account id
+	InvalidAccount,
+	/// The key or action isn't supported
+	UnknownField,
+}
+
+/// What to do with the deposit after it arrives
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum MemoAction {
+	/// Issue the tokens to receiver
+	Deposit,
+	/// Issue the tokens to receiver, then convert them
+	AutoConvert,
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct DepositMemo {
+	pub version: u8,
+	pub receiver: [u8; 32],
+	/// Token type, like EOS or vEOS, vtoken is deposited if it's not specified
+	pub token_type: Option<String>,
+	pub referrer: Option<[u8; 32]>,
+	pub action: MemoAction,
+}
+
+impl DepositMemo {
+	pub fn parse(memo: &str) -> Result<Self, MemoError> {
+		let memo = memo.trim();
+		let (version, body) = match memo.split_at(memo.find(':').unwrap_or(0)) {
+			(head, rest) if head.starts_with('v') && !head.contains('@') => {
+				let version = head[1..].parse::<u8>().map_err(|_| MemoError::UnknownVersion)?;
+				(version, &rest[1..])
+			}
+			_ => (0, memo),
+		};
+
+		match version {
+			0 => Self::parse_v0(body),
+			1 => Self::parse_v1(body),
+			_ => Err(MemoError::UnknownVersion),
+		}
+	}
+
+	fn parse_v0(body: &str) -> Result<Self, MemoError> {
+		let mut fields = body.splitn(2, ':');
+		let receiver = Self::parse_receiver(fields.next().unwrap_or_default())?;
+		let token_type = fields.next().filter(|t| !t.is_empty()).map(ToString::to_string);
+
+		Ok(Self { version: 0, receiver, token_type, referrer: None, action: MemoAction::Deposit })
+	}
+
+	fn parse_v1(body: &str) -> Result<Self, MemoError> {
+		let mut fields = body.split(':');
+		let receiver = Self::parse_receiver(fields.next().unwrap_or_default())?;
+		let token_type = fields.next().filter(|t| !t.is_empty()).ok_or(MemoError::InvalidFormat)?;
+
+		let mut memo = Self {
+			version: 1,
+			receiver,
+			token_type: Some(token_type.to_string()),
+			referrer: None,
+			action: MemoAction::Deposit,
+		};
+		for field in fields {
+			let mut pair = field.splitn(2, '=');
+			match (pair.next(), pair.next()) {
+				(Some("ref"), Some(referrer)) if memo.referrer.is_none() => {
+					memo.referrer = Some(decode_account(referrer)?);
+				}
+				(Some("act"), Some("convert")) => memo.action = MemoAction::AutoConvert,
+				(Some(_), Some(_)) => return Err(MemoError::UnknownField),
+				_ => return Err(MemoError::InvalidFormat),
+			}
+		}
+
+		Ok(memo)
+	}
+
+	/// receiver is like {account}@{chain}
+	fn parse_receiver(receiver: &str) -> Result<[u8; 32], MemoError> {
+		let mut parts = receiver.split('@');
+		match (parts.next(), parts.next(), parts.next()) {
+			(Some(account), Some(chain), None) => {
+				if !chain.eq_ignore_ascii_case(MEMO_CHAIN) {
+					return Err(MemoError::InvalidChain);
+				}
+				decode_account(account)
+			}
+			_ => Err(MemoError::InvalidFormat),
+		}
+	}
+}
+
+/// Decode account from SS58 address with any prefix, or hex account id like 0x1234...
+pub fn decode_account(account: &str) -> Result<[u8; 32], MemoError> {
+	let mut data = [0u8; ACCOUNT_LENGTH];
+
+	if account.starts_with("0x") {
+		hex::decode_to_slice(&account[2..], &mut data).map_err(|_| MemoError::InvalidAccount)?;
+		return Ok(data);
+	}
+
+	let decoded = bs58::decode(account).into_vec().map_err(|_| MemoError::InvalidAccount)?;
+	// simple prefix takes 1 byte and full prefix takes 2 bytes
+	let prefix_len = match decoded.first() {
+		Some(0..=63) => 1,
+		Some(64..=127) => 2,
+		_ => return Err(MemoError::InvalidAccount),
+	};
+	if decoded.len() != prefix_len + ACCOUNT_LENGTH + CHECKSUM_LENGTH {
+		return Err(MemoError::InvalidAccount);
+	}
+
+	let (payload, checksum) = decoded.split_at(prefix_len + ACCOUNT_LENGTH);
+	let hash = sp_io::hashing::blake2_512(&[SS58_PREFIX, payload].concat());
+	if hash[..CHECKSUM_LENGTH] != *checksum {
+		return Err(MemoError::InvalidAccount);
+	}
+
+	data.copy_from_slice(&payload[prefix_len..]);
+	Ok(data)
+}
@@ -22,13 +22,12 @@ use frame_support::{
 	traits::{OnInitialize, OnFinalize, FindAuthor}
 };
 //use node_primitives::{AccountId, Signature};
-use sp_core::{H256, sr25519::Signature};
+use sp_core::H256;
 use sp_runtime::{
-	Perbill,
+	ModuleId, Perbill,
 	testing::{Header, TestXt, TestSignature, UintAuthorityId},
-	traits::{BlakeTwo256, IdentityLookup, Extrinsic as ExtrinsicT, Verify, IdentifyAccount},
+	traits::{BlakeTwo256, IdentityLookup, Extrinsic as ExtrinsicT},
 };
-use frame_system::offchain::{SubmitTransaction, CreateSignedTransaction};
 use super::*;
 
 impl_outer_dispatch! {
@@ -40,7 +39,6 @@ impl_outer_dispatch! {
 /// An extrinsic type used for tests.
 pub type Extrinsic = TestXt<Call, ()>;
 //type SubmitTransaction = frame_system::offchain::TransactionSubmitter<(), Call, Extrinsic>;
-type AccountId = <<Signature as Verify>::Signer as IdentifyAccount>::AccountId;
 
 #[derive(Clone, Eq, PartialEq, Encode, Decode)]
 pub struct Test;
@@ -52,8 +50,9 @@ impl_outer_origin! {
 impl_outer_event! {
 	pub enum TestEvent for Test {
 		system<T>,
-		bridge_eos,
+		bridge_eos<T>,
 		assets<T>,
+		pallet_balances<T>,
 	}
 }
 
@@ -67,6 +66,10 @@ parameter_types! {
 	pub const MaximumBlockLength: u32 = 4 * 1024 * 1024;
 	pub const AvailableBlockRatio: Perbill = Perbill::from_percent(75);
 	pub const UncleGenerations: u32 = 5;
+	pub const TxOutRetention: u64 = 10;
+	pub const TxOutExpiry: u64 = 5;
+	pub const ExistentialDeposit: u64 = 1;
+	pub const RelayPotId: ModuleId = ModuleId(*b"bf/eosrl");
 }
 
 impl frame_system::Trait for Test {
@@ -86,7 +89,7 @@ impl frame_system::Trait for Test {
 	type MaximumBlockLength = MaximumBlockLength;
 	type AvailableBlockRatio = AvailableBlockRatio;
 	type Version = ();
-	type AccountData = ();
+	type AccountData = pallet_balances::AccountData<u64>;
 	type OnNewAccount = ();
 	type OnKilledAccount = ();
 	type DbWeight = ();
@@ -120,9 +123,10 @@ impl pallet_authorship::Trait for Test {
 	type EventHandler = ();
 }
 
+/// Keys of local notaries are set by `UintAuthorityId::set_all_keys`, each key is the notary account
 impl frame_system::offchain::SigningTypes for Test {
-	type Public = u64;
-	type Signature = Signature;
+	type Public = UintAuthorityId;
+	type Signature = TestSignature;
 }
 
 impl<LocalCall> frame_system::offchain::SendTransactionTypes<LocalCall> for Test where
@@ -137,31 +141,23 @@ impl<LocalCall> frame_system::offchain::CreateSignedTransaction<LocalCall> for T
 {
 	fn create_transaction<C: frame_system::offchain::AppCrypto<Self::Public, Self::Signature>>(
 		call: Call,
-		_public: <Signature as Verify>::Signer,
-		_account: AccountId,
+		_public: UintAuthorityId,
+		_account: u64,
 		nonce: u64,
 	) -> Option<(Call, <Extrinsic as ExtrinsicT>::SignaturePayload)> {
 		Some((call, (nonce, ())))
 	}
 }
 
-#[derive(Clone, Debug, Default, Eq, PartialEq, Encode, Decode, PartialOrd, Ord)]
-//pub struct TestAuthId;
-//impl frame_system::offchain::AppCrypto<<Signature as Verify>::Signer, Signature> for TestAuthId {
-//	type RuntimeAppPublic = sp_runtime::app_crypto::RuntimeAppPublic;
-//	type GenericSignature = sp_core::sr25519::Signature;
-//	type GenericPublic = sp_core::sr25519::Public;
-//}
-
 pub struct TestAuthorityId;
-impl frame_system::offchain::AppCrypto<<Signature as Verify>::Signer, Signature> for TestAuthorityId {
-	type RuntimeAppPublic = crate::sr25519::app_sr25519::Public;
-	type GenericSignature = sp_core::sr25519::Signature;
-	type GenericPublic = sp_core::sr25519::Public;
+impl frame_system::offchain::AppCrypto<UintAuthorityId, TestSignature> for TestAuthorityId {
+	type RuntimeAppPublic = UintAuthorityId;
+	type GenericSignature = TestSignature;
+	type GenericPublic = UintAuthorityId;
 }
 
 impl crate::Trait for Test {
-	type AuthorityId = Self::AuthorityId;
+	type AuthorityId = TestAuthorityId;
 	type Event = TestEvent;
 	type Balance = u64;
 	type AssetId = u32;
@@ -172,6 +168,18 @@ impl crate::Trait for Test {
 	type Call = Call;
 	//	type SubmitTransaction = SubmitTransaction;
 	type AssetTrait = Assets;
+	type TxOutRetention = TxOutRetention;
+	type TxOutExpiry = TxOutExpiry;
+	type Currency = Balances;
+	type RelayPotId = RelayPotId;
+}
+
+impl pallet_balances::Trait for Test {
+	type Balance = u64;
+	type DustRemoval = ();
+	type Event = TestEvent;
+	type ExistentialDeposit = ExistentialDeposit;
+	type AccountStore = System;
 }
 
 impl assets::Trait for Test {
@@ -184,12 +192,13 @@ impl assets::Trait for Test {
 	type Convert = u64;
 	type AssetRedeem = ();
 	type FetchConvertPrice = ();
+	type ClearingHandler = ();
 }
 
 pub type BridgeEos = crate::Module<Test>;
-pub type Authorship = pallet_authorship::Module<Test>;
 pub type System = frame_system::Module<Test>;
 pub type Assets = assets::Module<Test>;
+pub type Balances = pallet_balances::Module<Test>;
 
 // simulate block production
 pub(crate) fn run_to_block(n: u64) {
@@ -202,13 +211,35 @@ pub(crate) fn run_to_block(n: u64) {
 	}
 }
 
+/// EOS secret keys of notary 1, 2 and 3, which are accounts testa, testb and eosio
+pub(crate) const NOTARY_SECRET_KEYS: [&str; 3] = [
+	"5JgbL2ZnoEAhTudReWH1RnMuQS6DBeLZt4ucV6t8aymVEuYg7sr",
+	"5J6vV6xbVV2UEwBYYDRQQ8yTDcSmHJw67XqRriF4EkEzWKUFNKj",
+	"5KQwrPbwdL6PhXujxW37FSSQZ1JiwsST4cqQzDeyXtP79zkvFD3",
+];
+
+/// EOS public keys of notary 1, 2 and 3
+pub(crate) const NOTARY_EOS_KEYS: [&str; 3] = [
+	"EOS6hMjoWRF2L8x9YpeqtUEcsDKAyxSuM1APicxgRU1E3oyV5sDEg",
+	"EOS7QwKWXsfJvcB7XXnosJMFoQKh89eBtLy1e19ZLSYRcpd3n2JwL",
+	"EOS6MRyAjQq8ud7hVNYcfnVPJqcVpscN5So8BhtHuGYqET5GDW5CV",
+];
+
 // mockup runtime
 pub(crate) fn new_test_ext() -> sp_io::TestExternalities {
 	let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_balances::GenesisConfig::<Test> {
+		balances: vec![(1, 1_000), (2, 1_000), (BridgeEos::relay_pot(), 1_000)],
+	}.assimilate_storage(&mut t).unwrap();
 	GenesisConfig::<Test> {
-		producer_schedule: eos_chain::ProducerSchedule::default(),
 		bridge_contract_account: (b"bifrost".to_vec(), 2),
 		notary_keys: vec![1u64, 2u64],
+		cross_chain_privilege: vec![(1u64, true)],
+		all_crosschain_privilege: vec![],
+		bridge_tokens: vec![],
+		notary_eos_keys: (0..NOTARY_EOS_KEYS.len())
+			.map(|i| (i as u64 + 1, NOTARY_EOS_KEYS[i].as_bytes().to_vec()))
+			.collect(),
 	}.assimilate_storage(&mut t).unwrap();
 	t.into()
 }
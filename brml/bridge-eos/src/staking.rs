@@ -0,0 +1,203 @@
+// Copyright 2019-2020 Liebi Technologies.
+// This file is part of Bifrost.
+
+// Bifrost is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Bifrost is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Bifrost.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Actions of EOS system contract for staking the bridged EOS to block producers.
+//!
+//! Staking is done by the bridge contract account itself, `delegatebw` stakes half of the amount
+//! for NET and the other half for CPU, then `voteproducer` votes for all producers the bridge
+//! has staked to, since EOS counts the whole stake of an account in its votes.
+
+use core::str::FromStr;
+use crate::Error;
+use eos_chain::{AccountName, Action, Asset, NumBytes, PermissionLevel, SerializeData, Write, WriteError};
+use sp_std::prelude::*;
+
+/// Account of EOS system contract
+pub const EOS_SYSTEM_CONTRACT: &str = "eosio";
+/// Account of EOS system token contract, only its EOS can be staked
+pub const EOS_TOKEN_CONTRACT: &[u8] = b"eosio.token";
+/// Symbol of EOS system token
+pub const EOS_TOKEN_SYMBOL: &[u8] = b"EOS";
+/// Account of EOS system contract holding staked tokens, which refunds un-staked tokens
+pub const EOS_STAKE_ACCOUNT: &[u8] = b"eosio.stake";
+/// An account can vote for at most 30 producers on EOS
+pub const MAX_VOTED_PRODUCERS: usize = 30;
+
+/// Data of `eosio::delegatebw`
+#[derive(Clone, Debug)]
+pub struct ActionDelegatebw {
+	pub from: AccountName,
+	pub receiver: AccountName,
+	pub stake_net_quantity: Asset,
+	pub stake_cpu_quantity: Asset,
+	/// Whether the staked tokens are given to the receiver
+	pub transfer: bool,
+}
+
+impl NumBytes for ActionDelegatebw {
+	fn num_bytes(&self) -> usize {
+		self.from.num_bytes() + self.receiver.num_bytes() + self.stake_net_quantity.num_bytes()
+			+ self.stake_cpu_quantity.num_bytes() + self.transfer.num_bytes()
+	}
+}
+
+impl Write for ActionDelegatebw {
+	fn write(&self, bytes: &mut [u8], pos: &mut usize) -> Result<(), WriteError> {
+		self.from.write(bytes, pos)?;
+		self.receiver.write(bytes, pos)?;
+		self.stake_net_quantity.write(bytes, pos)?;
+		self.stake_cpu_quantity.write(bytes, pos)?;
+		self.transfer.write(bytes, pos)
+	}
+}
+
+impl SerializeData for ActionDelegatebw {}
+
+/// Data of `eosio::undelegatebw`
+#[derive(Clone, Debug)]
+pub struct ActionUndelegatebw {
+	pub from: AccountName,
+	pub receiver: AccountName,
+	pub unstake_net_quantity: Asset,
+	pub unstake_cpu_quantity: Asset,
+}
+
+impl NumBytes for ActionUndelegatebw {
+	fn num_bytes(&self) -> usize {
+		self.from.num_bytes() + self.receiver.num_bytes() + self.unstake_net_quantity.num_bytes()
+			+ self.unstake_cpu_quantity.num_bytes()
+	}
+}
+
+impl Write for ActionUndelegatebw {
+	fn write(&self, bytes: &mut [u8], pos: &mut usize) -> Result<(), WriteError> {
+		self.from.write(bytes, pos)?;
+		self.receiver.write(bytes, pos)?;
+		self.unstake_net_quantity.write(bytes, pos)?;
+		self.unstake_cpu_quantity.write(bytes, pos)
+	}
+}
+
+impl SerializeData for ActionUndelegatebw {}
+
+/// Data of `eosio::voteproducer`
+#[derive(Clone, Debug)]
+pub struct ActionVoteproducer {
+	pub voter: AccountName,
+	/// Empty name if the voter doesn't vote through a proxy
+	pub proxy: AccountName,
+	/// Producers in ascending order, as required by the system contract
+	pub producers: Vec<AccountName>,
+}
+
+impl NumBytes for ActionVoteproducer {
+	fn num_bytes(&self) -> usize {
+		self.voter.num_bytes() + self.proxy.num_bytes() + self.producers.num_bytes()
+	}
+}
+
+impl Write for ActionVoteproducer {
+	fn write(&self, bytes: &mut [u8], pos: &mut usize) -> Result<(), WriteError> {
+		self.voter.write(bytes, pos)?;
+		self.proxy.write(bytes, pos)?;
+		self.producers.write(bytes, pos)
+	}
+}
+
+impl SerializeData for ActionVoteproducer {}
+
+/// Actions staking the amount from the bridge account, and voting for the producers
+pub fn stake_actions<T: crate::Trait>(
+	raw_bridge: &[u8],
+	amount: Asset,
+	producers: &[Vec<u8>],
+) -> Result<Vec<Action>, Error<T>> {
+	let bridge = parse_account::<T>(raw_bridge)?;
+	let (net, cpu) = split_amount(amount);
+	let delegate = ActionDelegatebw {
+		from: bridge,
+		receiver: bridge,
+		stake_net_quantity: net,
+		stake_cpu_quantity: cpu,
+		transfer: false,
+	};
+
+	Ok(vec![
+		system_action::<T, _>(raw_bridge, "delegatebw", delegate)?,
+		vote_action::<T>(raw_bridge, producers)?,
+	])
+}
+
+/// Actions voting for the rest producers, and un-staking the amount from the bridge account.
+/// Un-staked tokens are refunded to the bridge account by EOS after the refund delay.
+pub fn unstake_actions<T: crate::Trait>(
+	raw_bridge: &[u8],
+	amount: Asset,
+	producers: &[Vec<u8>],
+) -> Result<Vec<Action>, Error<T>> {
+	let bridge = parse_account::<T>(raw_bridge)?;
+	let (net, cpu) = split_amount(amount);
+	let undelegate = ActionUndelegatebw {
+		from: bridge,
+		receiver: bridge,
+		unstake_net_quantity: net,
+		unstake_cpu_quantity: cpu,
+	};
+
+	Ok(vec![
+		vote_action::<T>(raw_bridge, producers)?,
+		system_action::<T, _>(raw_bridge, "undelegatebw", undelegate)?,
+	])
+}
+
+fn vote_action<T: crate::Trait>(raw_bridge: &[u8], producers: &[Vec<u8>]) -> Result<Action, Error<T>> {
+	let mut producers = producers.iter()
+		.map(|producer| parse_account::<T>(producer))
+		.collect::<Result<Vec<_>, _>>()?;
+	producers.sort();
+	producers.dedup();
+	if producers.len() > MAX_VOTED_PRODUCERS {
+		return Err(Error::<T>::TooManyVotedProducers);
+	}
+
+	let vote = ActionVoteproducer {
+		voter: parse_account::<T>(raw_bridge)?,
+		proxy: Default::default(),
+		producers,
+	};
+
+	system_action::<T, _>(raw_bridge, "voteproducer", vote)
+}
+
+fn system_action<T: crate::Trait, D: SerializeData>(raw_bridge: &[u8], name: &str, data: D) -> Result<Action, Error<T>> {
+	let bridge = core::str::from_utf8(raw_bridge).map_err(|_| Error::<T>::ParseUtf8Error)?;
+	let permission_level = PermissionLevel::from_str(bridge, "active").map_err(|_| Error::<T>::EosChainError)?;
+
+	Action::from_str(EOS_SYSTEM_CONTRACT, name, vec![permission_level], data).map_err(|_| Error::<T>::EosChainError)
+}
+
+fn parse_account<T: crate::Trait>(raw_account: &[u8]) -> Result<AccountName, Error<T>> {
+	let account = core::str::from_utf8(raw_account).map_err(|_| Error::<T>::ParseUtf8Error)?;
+
+	AccountName::from_str(account).map_err(|_| Error::<T>::EosChainError)
+}
+
+/// Half of the amount is for NET, and the rest is for CPU
+fn split_amount(amount: Asset) -> (Asset, Asset) {
+	let net = amount.amount / 2;
+
+	(Asset::new(net, amount.symbol), Asset::new(amount.amount - net, amount.symbol))
+}
@@ -20,25 +20,25 @@ use crate::*;
 use crate::mock::*;
 use core::{convert::From, str::FromStr};
 use eos_chain::{
-	Action, ActionReceipt, Checksum256, get_proof,
-	IncrementalMerkle, ProducerSchedule, SignedBlockHeader
+	AccountName, Action, ActionReceipt, BlockSigningAuthority, BlockSigningAuthorityV0, Checksum256, get_proof,
+	IncrementalMerkle, KeyWeight, PermissionLevel, ProducerAuthority, ProducerAuthoritySchedule, ProducerSchedule,
+	SerializeData, SignedBlockHeader, Transaction
 };
 #[cfg(feature = "std")]
 use std::{
-	error::Error,
+	error::Error as StdError,
 	fs::File,
 	io::Read as StdRead,
 	path::Path,
 };
-use sp_core::H256;
 use sp_core::offchain::{
 	OffchainExt, TransactionPoolExt,
 	testing::{TestOffchainExt, TestTransactionPoolExt},
 };
-use sp_runtime::traits::Header as HeaderT;
-use sp_runtime::{generic::DigestItem, testing::Header};
+use sp_core::{testing::KeyStore, traits::KeystoreExt};
+use sp_runtime::testing::UintAuthorityId;
 use node_primitives::{BridgeAssetSymbol, BlockchainType};
-use frame_support::dispatch;
+use frame_support::{assert_noop, assert_ok, dispatch};
 
 #[test]
 fn get_latest_schedule_version_should_work() {
@@ -63,6 +63,7 @@ fn get_producer_schedules_should_work() {
 		let signed_blocks_headers = signed_blocks.unwrap();
 
 		let schedule = signed_blocks_headers.first().as_ref().unwrap().block_header.new_producers.as_ref().unwrap().clone();
+		let schedule = authority_schedule(schedule);
 		let pending_schedule_hash = schedule.schedule_hash();
 		assert!(pending_schedule_hash.is_ok());
 
@@ -202,49 +203,97 @@ fn verify_block_headers_should_succeed() {
 fn change_schedule_should_work() {
 	new_test_ext().execute_with(|| {
 		// insert producers schedule v1 in advance.
-		let shedule_json = "schedule_v1.json";
-		let v1_producers_str = read_json_from_file(shedule_json);
-		assert!(v1_producers_str.is_ok());
-		let v1_producers: Result<ProducerSchedule, _> = serde_json::from_str(&v1_producers_str.unwrap());
-		assert!(v1_producers.is_ok());
-		let v1_producers = v1_producers.unwrap();
-
-		let v1_schedule_hash = v1_producers.schedule_hash();
-		assert!(v1_schedule_hash.is_ok());
-
-		PendingScheduleVersion::put(v1_producers.version);
-		ProducerSchedules::insert(v1_producers.version, (&v1_producers.producers, v1_schedule_hash.unwrap()));
-
-		let block_headers_json = "change_schedule_9313.json";
-		let signed_blocks_str = read_json_from_file(block_headers_json);
-		let signed_blocks: Result<Vec<SignedBlockHeader>, _> = serde_json::from_str(&signed_blocks_str.unwrap());
-		assert!(signed_blocks.is_ok());
-		let signed_blocks_headers = signed_blocks.unwrap();
-
-		let ids_json = "block_ids_list.json";
-		let ids_str = read_json_from_file(ids_json).unwrap();
-		let block_ids_list: Result<Vec<Vec<String>>, _> = serde_json::from_str(&ids_str);
-		assert!(block_ids_list.is_ok());
+		let v1_producers: ProducerSchedule = serde_json::from_str(&read_json_from_file("schedule_v1.json").unwrap()).unwrap();
+		assert_ok!(BridgeEos::save_producer_schedule(Origin::system(frame_system::RawOrigin::Root), authority_schedule(v1_producers)));
+		assert_eq!(BridgeEos::schedule_versions(), vec![0, 1]);
+
+		let (signed_blocks_headers, block_ids_list, merkle) = change_schedule_9313();
+		let legacy_schedule = signed_blocks_headers[0].block_header.new_producers.clone().unwrap();
+		let legacy_schedule_hash = legacy_schedule.schedule_hash().unwrap();
+
+		assert_ok!(BridgeEos::change_schedule(
+			Origin::signed(2), legacy_schedule_hash, authority_schedule(legacy_schedule), merkle, signed_blocks_headers, block_ids_list
+		));
+		assert_eq!(PendingScheduleVersion::get(), 2);
+		assert_eq!(BridgeEos::schedule_versions(), vec![0, 1, 2]);
+		assert!(ProducerSchedules::contains_key(2));
+	});
+}
 
-		let block_ids_list: Vec<Vec<Checksum256>> = block_ids_list.as_ref().unwrap().iter().map(|ids| {
-			ids.iter().map(|id| Checksum256::from_str(id).unwrap()).collect::<Vec<_>>()
-		}).collect::<Vec<_>>();
+#[test]
+fn change_schedule_should_reject_skipped_and_duplicated_versions() {
+	new_test_ext().execute_with(|| {
+		let root = || Origin::system(frame_system::RawOrigin::Root);
+		assert_ok!(BridgeEos::set_relay_fee(root(), 100));
+
+		let v1_producers: ProducerSchedule = serde_json::from_str(&read_json_from_file("schedule_v1.json").unwrap()).unwrap();
+		let v2_producers: ProducerSchedule = serde_json::from_str(&read_json_from_file("schedule_v2.json").unwrap()).unwrap();
+		let (signed_blocks_headers, block_ids_list, merkle) = change_schedule_9313();
+		let legacy_schedule = signed_blocks_headers[0].block_header.new_producers.clone().unwrap();
+		let legacy_schedule_hash = legacy_schedule.schedule_hash().unwrap();
+		let change_schedule = |new_schedule: ProducerAuthoritySchedule| BridgeEos::change_schedule(
+			Origin::signed(2), legacy_schedule_hash, new_schedule, merkle.clone(), signed_blocks_headers.clone(), block_ids_list.clone()
+		);
+
+		// schedule v2 is already saved, headers produced by v1 cannot change it again
+		assert_ok!(BridgeEos::save_producer_schedule(root(), authority_schedule(v2_producers)));
+		assert_ok!(change_schedule(authority_schedule(legacy_schedule.clone())));
+		assert_eq!(PendingScheduleVersion::get(), 2);
+		assert_eq!(Balances::free_balance(2), 900);
+
+		// version 3 skips version 2
+		assert_ok!(BridgeEos::save_producer_schedule(root(), authority_schedule(v1_producers)));
+		let mut skipped = authority_schedule(legacy_schedule.clone());
+		skipped.version = 3;
+		assert_ok!(change_schedule(skipped));
+		assert_eq!(PendingScheduleVersion::get(), 1);
+		assert_eq!(Balances::free_balance(2), 800);
+
+		// schedule change is proven by the configured count of headers
+		assert_ok!(BridgeEos::set_schedule_config(root(), 14, 10, 3));
+		assert_ok!(change_schedule(authority_schedule(legacy_schedule.clone())));
+		assert_eq!(PendingScheduleVersion::get(), 1);
+		assert_eq!(Balances::free_balance(2), 700);
+
+		assert_ok!(BridgeEos::set_schedule_config(root(), 15, 10, 3));
+		assert_ok!(change_schedule(authority_schedule(legacy_schedule)));
+		assert_eq!(PendingScheduleVersion::get(), 2);
+		assert_eq!(Balances::free_balance(2), 700);
+	});
+}
 
-		let node_count = 9311;
-		let active_nodes: Vec<Checksum256> = vec![
-			"0000245f60aa338bd246cb7598a14796ee0210f669f9c9b37f6ddad0b5765649".into(),
-			"9d41d4581cab233fe68c4510cacd05d0cc979c53ae317ce9364040578037de6a".into(),
-			"a397d1a6dc90389dc592ea144b1801c4b323c12b0b2f066aa55faa5892803317".into(),
-			"0cf502411e185ea7e3cc790e0b757807987e767a81c463c3e4ee5970b7fd1c67".into(),
-			"9f774a35e86ddb2d293da1bfe2e25b7b447fd3d9372ee580fce230a87fefa586".into(),
-			"4d018eda9a22334ac0492489fdf79118d696eea52af3871a7e4bf0e2d5ab5945".into(),
-			"acba7c7ee5c1d8ba97ea1a841707fbb2147e883b56544ba821814aebe086383e".into(),
-			"afa502d408f5bdf1660fa9fe3a1fcb432462467e7eb403a8499392ee5297d8d1".into(),
-			"4d723385cad26cf80c2db366f9666a3ef77679c098e07d1af48d523b64b1d460".into()
-		];
+#[test]
+fn producer_schedules_should_be_pruned_out_of_window() {
+	new_test_ext().execute_with(|| {
+		let root = || Origin::system(frame_system::RawOrigin::Root);
+		assert_noop!(BridgeEos::set_schedule_config(root(), 0, 10, 2), Error::<Test>::InvalidScheduleConfig);
+		assert_noop!(BridgeEos::set_schedule_config(root(), 15, 10, 0), Error::<Test>::InvalidScheduleConfig);
+		assert!(BridgeEos::set_schedule_config(Origin::signed(1), 15, 10, 2).is_err());
+		assert_ok!(BridgeEos::set_schedule_config(root(), 15, 10, 2));
+		assert_eq!(BridgeEos::schedule_history_window(), 2);
+
+		let v1_producers: ProducerSchedule = serde_json::from_str(&read_json_from_file("schedule_v1.json").unwrap()).unwrap();
+		let v2_producers: ProducerSchedule = serde_json::from_str(&read_json_from_file("schedule_v2.json").unwrap()).unwrap();
+		assert_ok!(BridgeEos::save_producer_schedule(root(), authority_schedule(v1_producers.clone())));
+		assert_eq!(BridgeEos::schedule_versions(), vec![0, 1]);
+
+		assert_ok!(BridgeEos::save_producer_schedule(root(), authority_schedule(v2_producers.clone())));
+		assert_eq!(BridgeEos::schedule_versions(), vec![1, 2]);
+		assert!(!ProducerSchedules::contains_key(0));
 
-		let merkle = IncrementalMerkle::new(node_count, active_nodes);
-		assert!(BridgeEos::change_schedule(Origin::ROOT, merkle, signed_blocks_headers, block_ids_list).is_ok());
+		// schedule of reversible blocks is kept until they're irreversible
+		ReversibleBlocks::put(vec![(100, v1_producers.producers[0].producer_name, 1)]);
+		let mut v3_producers = authority_schedule(v2_producers);
+		v3_producers.version = 3;
+		assert_ok!(BridgeEos::save_producer_schedule(root(), v3_producers));
+		assert_eq!(BridgeEos::schedule_versions(), vec![1, 2, 3]);
+		assert!(ProducerSchedules::contains_key(1));
+
+		ReversibleBlocks::kill();
+		BridgeEos::prune_producer_schedules();
+		assert_eq!(BridgeEos::schedule_versions(), vec![2, 3]);
+		assert!(!ProducerSchedules::contains_key(1));
+		assert_eq!(BridgeEos::producer_schedules().0.map(|ps| ps.version), Some(3));
 	});
 }
 
@@ -259,6 +308,7 @@ fn prove_action_should_be_ok() {
 		assert!(v2_producers.is_ok());
 		let v2_producers = v2_producers.unwrap();
 
+		let v2_producers = authority_schedule(v2_producers);
 		let v2_schedule_hash = v2_producers.schedule_hash();
 		assert!(v2_schedule_hash.is_ok());
 
@@ -342,7 +392,7 @@ fn prove_action_should_be_ok() {
 		assert!(action_receipt.is_ok());
 		let action_receipt = action_receipt.unwrap();
 
-		assert!(BridgeEos::prove_action(Origin::ROOT, action.clone(), action_receipt.clone(), actual_merkle_paths, merkle, signed_blocks_headers, block_ids_list).is_ok());
+		assert!(BridgeEos::prove_action(Origin::signed(1), action.clone(), action_receipt.clone(), actual_merkle_paths, merkle, signed_blocks_headers, block_ids_list).is_ok());
 
 		// ensure action_receipt is saved after proved action
 		assert_eq!(BridgeActionReceipt::get(&action_receipt), action);
@@ -354,47 +404,89 @@ fn bridge_eos_offchain_should_work() {
 	let mut ext = new_test_ext();
 	let (offchain, _state) = TestOffchainExt::new();
 	let (pool, pool_state) = TestTransactionPoolExt::new();
+	let mut eos_nodes = MockEosNodes::new(&offchain);
 	ext.register_extension(OffchainExt::new(offchain));
 	ext.register_extension(TransactionPoolExt::new(pool));
+	ext.register_extension(KeystoreExt(KeyStore::new()));
+	UintAuthorityId::set_all_keys(vec![1u64, 2u64]);
+
+	eos_nodes.expect_chain_head("http://127.0.0.1:8888");
 
 	ext.execute_with(|| {
 		System::set_block_number(1);
-		sp_io::offchain::local_storage_set(StorageKind::PERSISTENT, b"EOS_NODE_URL", b"http://127.0.0.1:8888/");
+		let (eos_id, _) = Assets::asset_create(b"EOS".to_vec(), 4);
+		Assets::asset_issue(eos_id, TokenType::Token, 1u64, 10u64.pow(5));
+		assert_ok!(BridgeEos::set_bridge_token(
+			Origin::system(frame_system::RawOrigin::Root), eos_id, b"eosio.token".to_vec(), b"EOS".to_vec(), 4
+		));
+		assert_ok!(BridgeEos::reset_bridge_reserve(
+			Origin::system(frame_system::RawOrigin::Root), eos_id, 10u64.pow(6), 10u64.pow(6)
+		));
+		assert_ok!(BridgeEos::bridge_asset_to(b"alice".to_vec(), BridgeAssetBalance {
+			symbol: BridgeAssetSymbol::new(BlockchainType::EOS, b"EOS".to_vec(), 4u32),
+			amount: 1 * 10u64.pow(12),
+			memo: vec![],
+			from: 1u64,
+			token_type: TokenType::Token,
+		}));
 
-		// EOS secret key of account testa
-		sp_io::offchain::local_storage_set(StorageKind::PERSISTENT, b"EOS_SECRET_KEY", b"5JgbL2ZnoEAhTudReWH1RnMuQS6DBeLZt4ucV6t8aymVEuYg7sr");
+		sp_io::offchain::local_storage_set(StorageKind::PERSISTENT, b"EOS_NODE_URL", b"http://127.0.0.1:8888/");
+		for notary in 1..=2u64 {
+			let key = [&b"EOS_SECRET_KEY"[..], &notary.encode()[..]].concat();
+			sp_io::offchain::local_storage_set(StorageKind::PERSISTENT, &key, NOTARY_SECRET_KEYS[notary as usize - 1].as_bytes());
+		}
 
-		let raw_to = b"alice".to_vec();
-		let raw_symbol = b"EOS".to_vec();
-		let asset_symbol = BridgeAssetSymbol::new(BlockchainType::EOS, raw_symbol, 4u32);
-		let bridge_asset = BridgeAssetBalance {
-			symbol: asset_symbol.clone(),
-			amount: 1 * 10u64.pow(8),
+		let pool_calls = || {
+			pool_state.write().transactions.drain(..).map(|transaction| {
+				let ex: Extrinsic = codec::Decode::decode(&mut &*transaction).unwrap();
+				ex.call
+			}).collect::<Vec<_>>()
 		};
-		assert!(BridgeEos::bridge_asset_to(raw_to.clone(), bridge_asset).is_ok());
-		BridgeEos::offchain(1);
-
-		// EOS secret key of account testb
-		sp_io::offchain::local_storage_set(StorageKind::PERSISTENT, b"EOS_SECRET_KEY", b"5J6vV6xbVV2UEwBYYDRQQ8yTDcSmHJw67XqRriF4EkEzWKUFNKj");
-
-		rotate_author(2);
-		BridgeEos::offchain(2);
-
-		use codec::Decode;
-		let transaction = pool_state.write().transactions.pop().unwrap();
-		assert_eq!(pool_state.read().transactions.len(), 1);
-		let ex: Extrinsic = Decode::decode(&mut &*transaction).unwrap();
-		let tx_outs = match ex.call {
-			crate::mock::Call::BridgeEos(crate::Call::bridge_tx_report(tx_outs)) => tx_outs,
-			e => panic!("Unexpected call: {:?}", e),
+		let reported_tx_outs = |calls: Vec<crate::mock::Call>| match calls.as_slice() {
+			[crate::mock::Call::BridgeEos(crate::Call::bridge_tx_report(tx_outs))] => tx_outs.clone(),
+			e => panic!("Unexpected calls: {:?}", e),
 		};
 
-		assert_eq!(tx_outs.iter().filter(|out| {
-			match out {
-				TxOut::Processing{ .. } => true,
-				_ => false,
+		// raw transaction is generated by the node
+		assert_ok!(BridgeEos::offchain(1));
+		let tx_outs = reported_tx_outs(pool_calls());
+		assert_eq!(tx_outs.iter().map(|(id, out)| (*id, out.status())).collect::<Vec<_>>(), vec![(0, TxOutStatus::Generated)]);
+		assert_ok!(BridgeEos::bridge_tx_report(Origin::signed(1), tx_outs));
+
+		// notaries sign it with their own EOS keys
+		assert_ok!(BridgeEos::offchain(2));
+		for (notary, call) in (1..=2u64).zip(pool_calls()) {
+			match call {
+				crate::mock::Call::BridgeEos(crate::Call::submit_signatures(signatures)) => {
+					assert_ok!(BridgeEos::submit_signatures(Origin::signed(notary), signatures));
+				}
+				e => panic!("Unexpected call: {:?}", e),
 			}
-		}).count(), 1);
+		}
+		assert_eq!(BridgeEos::tx_out_status(0), Some(TxOutStatus::Signed));
+
+		// signed transaction is pushed to the node
+		let signed_trx = match BridgeEos::bridge_tx_outs(0) {
+			Some(TxOut::Signed(multi_sig_tx)) => {
+				crate::transaction::eos_rpc::serialize_push_transaction_params::<Test, u64>(&multi_sig_tx).unwrap()
+			}
+			_ => panic!("transaction should be signed"),
+		};
+		eos_nodes.expect(
+			"http://127.0.0.1:8888",
+			"/v1/chain/push_transaction",
+			&signed_trx,
+			br#"{"transaction_id":"58e71de1c3f1a93417addbf1fc79e58e4f57a0930ec9c4f294b4ad64375c9dc6","processed":{}}"#
+		);
+		assert_ok!(BridgeEos::offchain(3));
+		let tx_outs = reported_tx_outs(pool_calls());
+		assert!(matches!(
+			tx_outs.as_slice(),
+			[(0, TxOut::Processing { tx_id, .. })]
+				if *tx_id == Checksum256::from_str("58e71de1c3f1a93417addbf1fc79e58e4f57a0930ec9c4f294b4ad64375c9dc6").unwrap()
+		));
+		assert_ok!(BridgeEos::bridge_tx_report(Origin::signed(1), tx_outs));
+		assert_eq!(BridgeEos::tx_out_status(0), Some(TxOutStatus::Processing));
 	});
 }
 
@@ -403,7 +495,7 @@ fn bridge_eos_genesis_config_should_work() {
 	new_test_ext().execute_with(|| {
 		assert_eq!(BridgeContractAccount::get(), (b"bifrost".to_vec(), 2));
 
-		let producer_schedule = eos_chain::ProducerSchedule::default();
+		let producer_schedule = ProducerAuthoritySchedule::default();
 		let version = producer_schedule.clone().version;
 		let producers = producer_schedule.clone().producers;
 		let schedule_hash = producer_schedule.schedule_hash();
@@ -423,7 +515,7 @@ fn chech_receiver_is_ss58_format() {
 		92, 143, 26, 236, 159, 180, 112, 61
 	];
 
-	let data = BridgeEos::get_account_data(alice_key);
+	let data = crate::memo::decode_account(alice_key);
 	assert!(data.is_ok());
 	let data = data.unwrap();
 	assert_eq!(data, expected_alice);
@@ -446,10 +538,1229 @@ fn chech_receiver_is_ss58_format() {
 	let decoded_ss58 = decoded_ss58.unwrap();
 	assert_eq!(decoded_ss58.len(), 35);
 	assert_ne!(decoded_ss58[0], BIFROST_PREFIX);
+
+	// addresses of all prefixes are accepted, this one is with prefix 100
+	assert_eq!(crate::memo::decode_account("gCPYbXEHYRkA4e8KhEp1y6tTx5jEapTDdREtwE8WDARvwrbBX"), Ok(expected_alice));
+	assert!(crate::memo::decode_account("gg2XUSNDsdmYR28YRVMZ7qeWqPpaKtG5PefahS4yKwshda2").is_ok());
+	// hex account id
+	let alice_hex = "0x0816fe0689322e26cd2aa9c0dccb6c44851345e96f969ae85c8f1aec9fb4703d";
+	assert_eq!(crate::memo::decode_account(alice_hex), Ok(expected_alice));
+	// checksum mismatches
+	assert_eq!(
+		crate::memo::decode_account("5CFK52zU59zUhC3s6mRobEJ3zm7JeXQZaS6ybvcuCDDhWwGH"),
+		Err(MemoError::InvalidAccount)
+	);
+}
+
+#[test]
+fn deposit_memo_should_be_parsed() {
+	let alice = "5CFK52zU59zUhC3s6mRobEJ3zm7JeXQZaS6ybvcuCDDhWwGG";
+	let bob = "gg2XUSNDsdmYR28YRVMZ7qeWqPpaKtG5PefahS4yKwshda2";
+	let alice_data = crate::memo::decode_account(alice).unwrap();
+	let bob_data = crate::memo::decode_account(bob).unwrap();
+
+	// legacy memo
+	let memo = DepositMemo::parse(&format!("{}@bifrost:EOS", alice)).unwrap();
+	assert_eq!(memo, DepositMemo {
+		version: 0,
+		receiver: alice_data,
+		token_type: Some("EOS".to_string()),
+		referrer: None,
+		action: MemoAction::Deposit,
+	});
+	let memo = DepositMemo::parse(&format!("{}@bifrost", alice)).unwrap();
+	assert_eq!(memo.token_type, None);
+
+	// version 1
+	let memo = DepositMemo::parse(&format!("v1:{}@bifrost:vEOS:ref={}:act=convert", alice, bob)).unwrap();
+	assert_eq!(memo, DepositMemo {
+		version: 1,
+		receiver: alice_data,
+		token_type: Some("vEOS".to_string()),
+		referrer: Some(bob_data),
+		action: MemoAction::AutoConvert,
+	});
+
+	assert_eq!(DepositMemo::parse(&format!("v1:{}@bifrost", alice)), Err(MemoError::InvalidFormat));
+	assert_eq!(DepositMemo::parse(&format!("v2:{}@bifrost:EOS", alice)), Err(MemoError::UnknownVersion));
+	assert_eq!(DepositMemo::parse(&format!("{}@ethereum:EOS", alice)), Err(MemoError::InvalidChain));
+	assert_eq!(DepositMemo::parse(&format!("v1:{}@bifrost:EOS:foo=bar", alice)), Err(MemoError::UnknownField));
+	assert_eq!(DepositMemo::parse("alice@bifrost:EOS"), Err(MemoError::InvalidAccount));
+	assert_eq!(DepositMemo::parse(""), Err(MemoError::InvalidFormat));
+}
+
+#[test]
+fn tx_out_status_transition_should_work() {
+	use TxOutStatus::*;
+
+	assert!(Initial.can_transit_to(Generated));
+	assert!(Generated.can_transit_to(Generated));
+	assert!(Generated.can_transit_to(Signed));
+	assert!(Signed.can_transit_to(Processing));
+	assert!(Processing.can_transit_to(Success));
+	assert!(Initial.can_transit_to(Fail));
+	assert!(Generated.can_transit_to(Fail));
+
+	// signed transaction may have been executed by EOS
+	assert!(!Signed.can_transit_to(Fail));
+	assert!(!Processing.can_transit_to(Fail));
+
+	assert!(!Initial.can_transit_to(Signed));
+	assert!(!Signed.can_transit_to(Generated));
+	assert!(!Processing.can_transit_to(Initial));
+	assert!(!Success.can_transit_to(Fail));
+	assert!(!Fail.can_transit_to(Initial));
+}
+
+#[test]
+fn bridge_tx_report_should_enforce_transition_and_prune() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		let (eos_id, _) = Assets::asset_create(b"EOS".to_vec(), 4);
+		Assets::asset_issue(eos_id, TokenType::Token, 1u64, 10u64.pow(5));
+		assert_ok!(BridgeEos::set_bridge_token(
+			Origin::system(frame_system::RawOrigin::Root), eos_id, b"eosio.token".to_vec(), b"EOS".to_vec(), 4
+		));
+		assert_ok!(BridgeEos::reset_bridge_reserve(
+			Origin::system(frame_system::RawOrigin::Root), eos_id, 10u64.pow(6), 10u64.pow(6)
+		));
+
+		let bridge_asset = BridgeAssetBalance {
+			symbol: BridgeAssetSymbol::new(BlockchainType::EOS, b"EOS".to_vec(), 4u32),
+			amount: 1 * 10u64.pow(12),
+			memo: vec![],
+			from: 1u64,
+			token_type: TokenType::Token,
+		};
+		assert_ok!(BridgeEos::bridge_asset_to(b"alice".to_vec(), bridge_asset));
+		assert_eq!(BridgeEos::tx_out_status(0), Some(TxOutStatus::Initial));
+		assert_eq!(BridgeEos::pending_tx_outs(), vec![0]);
+		assert_eq!(BridgeEos::next_tx_out_id(), 1);
+
+		let multi_sig_tx = match BridgeEos::bridge_tx_outs(0) {
+			Some(TxOut::Initial(multi_sig_tx)) => multi_sig_tx,
+			_ => panic!("transaction should be initial"),
+		};
+
+		// only notaries can report transactions
+		let raw_tx = Transaction::new(0, 0, 0, multi_sig_tx.actions().to_vec()).to_serialize_data().unwrap();
+		let generated = multi_sig_tx.clone().into_generated(vec![0u8; 32], raw_tx);
+		assert_noop!(
+			BridgeEos::bridge_tx_report(Origin::signed(3), vec![(0, generated)]),
+			Error::<Test>::NotNotary
+		);
+
+		// generated transaction must carry the stored actions
+		let raw_tx = Transaction::new(0, 0, 0, vec![]).to_serialize_data().unwrap();
+		let generated = multi_sig_tx.clone().into_generated(vec![0u8; 32], raw_tx);
+		assert_noop!(
+			BridgeEos::bridge_tx_report(Origin::signed(1), vec![(0, generated)]),
+			Error::<Test>::TxOutMismatch
+		);
+		let generated = multi_sig_tx.clone().into_generated(vec![0u8; 32], vec![1, 2, 3]);
+		assert_noop!(
+			BridgeEos::bridge_tx_report(Origin::signed(1), vec![(0, generated)]),
+			Error::<Test>::InvalidRawTransaction
+		);
+		let mut tampered = multi_sig_tx.clone();
+		tampered.from = 2u64;
+		let raw_tx = Transaction::new(0, 0, 0, multi_sig_tx.actions().to_vec()).to_serialize_data().unwrap();
+		assert_noop!(
+			BridgeEos::bridge_tx_report(Origin::signed(1), vec![(0, tampered.into_generated(vec![0u8; 32], raw_tx))]),
+			Error::<Test>::TxOutMismatch
+		);
+
+		// an initial transaction cannot be sent before being signed
+		let processing = TxOut::Processing { tx_id: Default::default(), multi_sig_tx: multi_sig_tx.clone() };
+		assert_noop!(
+			BridgeEos::bridge_tx_report(Origin::signed(1), vec![(0, processing)]),
+			Error::<Test>::InvalidTxOutTransition
+		);
+		// unknown transaction
+		let fail = TxOut::Fail { tx_id: vec![], reason: vec![], tx: multi_sig_tx };
+		assert_noop!(
+			BridgeEos::bridge_tx_report(Origin::signed(1), vec![(1, fail.clone())]),
+			Error::<Test>::TxOutNotFound
+		);
+
+		assert_ok!(BridgeEos::bridge_tx_report(Origin::signed(1), vec![(0, fail)]));
+		assert_eq!(BridgeEos::tx_out_status(0), Some(TxOutStatus::Fail));
+		assert!(BridgeEos::pending_tx_outs().is_empty());
+		// escrowed tokens are refunded
+		assert!(BridgeEos::withdraw_escrow(0).is_none());
+		assert_eq!(Assets::get_account_asset(&eos_id, TokenType::Token, &1u64).balance, 10u64.pow(5));
+
+		// finished transaction is kept for TxOutRetention blocks
+		run_to_block(1 + TxOutRetention::get());
+		assert_eq!(BridgeEos::tx_out_status(0), Some(TxOutStatus::Fail));
+		run_to_block(2 + TxOutRetention::get());
+		assert_eq!(BridgeEos::tx_out_status(0), None);
+		assert!(BridgeEos::account_tx_outs(&1).is_empty());
+	});
+}
+
+#[test]
+fn notaries_should_sign_tx_out_until_threshold() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		let (eos_id, _) = Assets::asset_create(b"EOS".to_vec(), 4);
+		Assets::asset_issue(eos_id, TokenType::Token, 1u64, 10u64.pow(5));
+		assert_ok!(BridgeEos::set_bridge_token(
+			Origin::system(frame_system::RawOrigin::Root), eos_id, b"eosio.token".to_vec(), b"EOS".to_vec(), 4
+		));
+		assert_ok!(BridgeEos::reset_bridge_reserve(
+			Origin::system(frame_system::RawOrigin::Root), eos_id, 10u64.pow(6), 10u64.pow(6)
+		));
+
+		let bridge_asset = BridgeAssetBalance {
+			symbol: BridgeAssetSymbol::new(BlockchainType::EOS, b"EOS".to_vec(), 4u32),
+			amount: 1 * 10u64.pow(12),
+			memo: vec![],
+			from: 1u64,
+			token_type: TokenType::Token,
+		};
+		assert_ok!(BridgeEos::bridge_asset_to(b"alice".to_vec(), bridge_asset));
+
+		// notaries cannot sign a transaction before it's generated
+		let signature = {
+			let sk = SecretKey::from_wif("5JgbL2ZnoEAhTudReWH1RnMuQS6DBeLZt4ucV6t8aymVEuYg7sr").unwrap();
+			let trx = Transaction::new(0, 0, 0, vec![]);
+			trx.sign(sk, vec![0u8; 32]).unwrap().to_serialize_data().unwrap()
+		};
+		assert_noop!(
+			BridgeEos::submit_signatures(Origin::signed(1), vec![(0, signature.clone())]),
+			Error::<Test>::InvalidTxOutType
+		);
+
+		assert_ok!(report_generated(0));
+
+		// only notaries can sign, with a valid EOS signature made by their own keys
+		assert_noop!(
+			BridgeEos::submit_signatures(Origin::signed(3), vec![(0, notary_signature(0, 3))]),
+			Error::<Test>::NotNotary
+		);
+		assert_noop!(
+			BridgeEos::submit_signatures(Origin::signed(1), vec![(0, vec![1, 2, 3])]),
+			Error::<Test>::InvalidEosSignature
+		);
+		assert_noop!(
+			BridgeEos::submit_signatures(Origin::signed(1), vec![(0, signature)]),
+			Error::<Test>::EosSignerMismatch
+		);
+		assert_noop!(
+			BridgeEos::submit_signatures(Origin::signed(1), vec![(0, notary_signature(0, 2))]),
+			Error::<Test>::EosSignerMismatch
+		);
+
+		assert_eq!(BridgeEos::notary_status(&1).unsigned_tx_outs, vec![0]);
+		assert_eq!(BridgeEos::notary_status(&3), NotaryStatus::default());
+		assert_eq!(BridgeEos::account_tx_outs(&1), vec![(0, TxOutStatus::Generated)]);
+
+		assert_ok!(BridgeEos::submit_signatures(Origin::signed(1), vec![(0, notary_signature(0, 1))]));
+		assert_eq!(BridgeEos::tx_out_status(0), Some(TxOutStatus::Generated));
+		assert!(BridgeEos::notary_status(&1).unsigned_tx_outs.is_empty());
+		assert_eq!(BridgeEos::notary_status(&2), NotaryStatus { is_notary: true, threshold: 2, unsigned_tx_outs: vec![0] });
+		assert_noop!(
+			BridgeEos::submit_signatures(Origin::signed(1), vec![(0, notary_signature(0, 1))]),
+			Error::<Test>::AlreadySignedByAuthor
+		);
+
+		// signature of the removed notary is dropped
+		assert_ok!(BridgeEos::set_notary_keys(Origin::system(frame_system::RawOrigin::Root), vec![2u64, 3u64]));
+		assert_eq!(BridgeEos::bridge_tx_outs(0).map(|tx_out| tx_out.signature_count()), Some(0));
+
+		assert_ok!(BridgeEos::submit_signatures(Origin::signed(2), vec![(0, notary_signature(0, 2))]));
+		assert_eq!(BridgeEos::tx_out_status(0), Some(TxOutStatus::Generated));
+		assert_ok!(BridgeEos::submit_signatures(Origin::signed(3), vec![(0, notary_signature(0, 3))]));
+		assert_eq!(BridgeEos::tx_out_status(0), Some(TxOutStatus::Signed));
+
+		// signed transaction may have been sent to EOS, so rotating out a signer keeps it signed
+		assert_ok!(BridgeEos::set_notary_keys(Origin::system(frame_system::RawOrigin::Root), vec![1u64, 2u64]));
+		assert_eq!(BridgeEos::tx_out_status(0), Some(TxOutStatus::Signed));
+		assert_eq!(BridgeEos::bridge_tx_outs(0).map(|tx_out| tx_out.signature_count()), Some(2));
+	});
+}
+
+#[test]
+fn withdraw_should_be_escrowed_and_refunded_on_expiry() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		let (eos_id, _) = Assets::asset_create(b"EOS".to_vec(), 4);
+		Assets::asset_issue(eos_id, TokenType::Token, 1u64, 10u64.pow(5));
+		assert_ok!(BridgeEos::set_bridge_token(
+			Origin::system(frame_system::RawOrigin::Root), eos_id, b"eosio.token".to_vec(), b"EOS".to_vec(), 4
+		));
+		assert_ok!(BridgeEos::reset_bridge_reserve(
+			Origin::system(frame_system::RawOrigin::Root), eos_id, 10u64.pow(6), 10u64.pow(6)
+		));
+
+		let bridge_asset = BridgeAssetBalance {
+			symbol: BridgeAssetSymbol::new(BlockchainType::EOS, b"EOS".to_vec(), 4u32),
+			amount: 2 * 10u64.pow(12),
+			memo: vec![],
+			from: 1u64,
+			token_type: TokenType::Token,
+		};
+		assert_ok!(BridgeEos::bridge_asset_to(b"alice".to_vec(), bridge_asset.clone()));
+
+		// 2 EOS are escrowed
+		assert_eq!(Assets::get_account_asset(&eos_id, TokenType::Token, &1u64).balance, 8 * 10u64.pow(4));
+		assert_eq!(
+			BridgeEos::withdraw_escrow(0),
+			Some(WithdrawEscrow {
+				who: 1u64, asset_id: eos_id, token_type: TokenType::Token, amount: 2 * 10u64.pow(4), eos_amount: 2 * 10u64.pow(4)
+			})
+		);
+
+		// cannot withdraw more than the balance
+		let too_much = BridgeAssetBalance { amount: 9 * 10u64.pow(12), ..bridge_asset };
+		assert!(BridgeEos::bridge_asset_to(b"alice".to_vec(), too_much).is_err());
+
+		// the transaction isn't sent to EOS in TxOutExpiry blocks
+		run_to_block(2 + TxOutExpiry::get());
+		assert_eq!(BridgeEos::tx_out_status(0), Some(TxOutStatus::Fail));
+		assert!(BridgeEos::withdraw_escrow(0).is_none());
+		assert_eq!(Assets::get_account_asset(&eos_id, TokenType::Token, &1u64).balance, 10u64.pow(5));
+	});
+}
+
+#[test]
+fn signed_tx_out_should_fail_only_after_proven_expired() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		let (eos_id, _) = Assets::asset_create(b"EOS".to_vec(), 4);
+		Assets::asset_issue(eos_id, TokenType::Token, 1u64, 10u64.pow(5));
+		assert_ok!(BridgeEos::set_bridge_token(
+			Origin::system(frame_system::RawOrigin::Root), eos_id, b"eosio.token".to_vec(), b"EOS".to_vec(), 4
+		));
+		assert_ok!(BridgeEos::reset_bridge_reserve(
+			Origin::system(frame_system::RawOrigin::Root), eos_id, 10u64.pow(6), 10u64.pow(6)
+		));
+		assert_ok!(BridgeEos::bridge_asset_to(b"alice".to_vec(), BridgeAssetBalance {
+			symbol: BridgeAssetSymbol::new(BlockchainType::EOS, b"EOS".to_vec(), 4u32),
+			amount: 1 * 10u64.pow(12),
+			memo: vec![],
+			from: 1u64,
+			token_type: TokenType::Token,
+		}));
+
+		// the transaction expires on EOS at 1_600_000_000
+		let multi_sig_tx = match BridgeEos::bridge_tx_outs(0) {
+			Some(TxOut::Initial(multi_sig_tx)) => multi_sig_tx,
+			_ => panic!("transaction should be initial"),
+		};
+		let expiration = 1_600_000_000u32;
+		let raw_tx = Transaction::new(expiration, 0, 0, multi_sig_tx.actions().to_vec()).to_serialize_data().unwrap();
+		assert_ok!(BridgeEos::bridge_tx_report(Origin::signed(1), vec![(0, multi_sig_tx.into_generated(vec![0u8; 32], raw_tx))]));
+		// expiration of an unsigned transaction doesn't need to be proven
+		assert_noop!(BridgeEos::prove_tx_out_expired(Origin::signed(3), 0, 100), Error::<Test>::TxOutNotExpired);
+
+		assert_ok!(BridgeEos::submit_signatures(Origin::signed(1), vec![(0, notary_signature(0, 1))]));
+		assert_ok!(BridgeEos::submit_signatures(Origin::signed(2), vec![(0, notary_signature(0, 2))]));
+		assert_eq!(BridgeEos::tx_out_status(0), Some(TxOutStatus::Signed));
+		assert_eq!(BridgeEos::bridge_tx_outs(0).and_then(|tx_out| tx_out.eos_expiration()), Some(expiration));
+
+		// signed transaction cannot be failed by notaries, and doesn't expire by itself
+		let fail = BridgeEos::bridge_tx_outs(0).unwrap().fail(b"failed");
+		assert_noop!(
+			BridgeEos::bridge_tx_report(Origin::signed(1), vec![(0, fail)]),
+			Error::<Test>::InvalidTxOutTransition
+		);
+		run_to_block(2 + TxOutExpiry::get());
+		assert_eq!(BridgeEos::tx_out_status(0), Some(TxOutStatus::Signed));
+		assert!(BridgeEos::withdraw_escrow(0).is_some());
+
+		// EOS block 100 is at the expiration, and block 101 is after it
+		let producer = AccountName::from_str("eosio").unwrap();
+		for (block_num, timestamp) in vec![(100, expiration), (101, expiration + 1)] {
+			RelayedBlocks::insert(block_num, RelayedBlock {
+				id: Default::default(),
+				action_mroot: Default::default(),
+				producer,
+				schedule_version: 0,
+				timestamp,
+			});
+		}
+		LastIrreversibleBlock::put(100);
+
+		assert_noop!(BridgeEos::prove_tx_out_expired(Origin::signed(3), 0, 102), Error::<Test>::BlockNotRelayed);
+		assert_noop!(BridgeEos::prove_tx_out_expired(Origin::signed(3), 0, 101), Error::<Test>::BlockNotIrreversible);
+		assert_noop!(BridgeEos::prove_tx_out_expired(Origin::signed(3), 0, 100), Error::<Test>::TxOutNotExpired);
+
+		LastIrreversibleBlock::put(101);
+		let fail_at = System::block_number() + TxOutExpiry::get();
+		assert_ok!(BridgeEos::prove_tx_out_expired(Origin::signed(3), 0, 101));
+		assert_eq!(BridgeEos::expired_tx_out(0), Some(fail_at));
+		assert_noop!(BridgeEos::prove_tx_out_expired(Origin::signed(3), 0, 101), Error::<Test>::TxOutNotExpired);
+
+		// action of the transaction can be proven until it fails, then escrowed tokens are refunded
+		run_to_block(fail_at);
+		assert_eq!(BridgeEos::tx_out_status(0), Some(TxOutStatus::Signed));
+		run_to_block(fail_at + 1);
+		assert_eq!(BridgeEos::tx_out_status(0), Some(TxOutStatus::Fail));
+		assert!(BridgeEos::expired_tx_out(0).is_none());
+		assert!(BridgeEos::withdraw_escrow(0).is_none());
+		assert_eq!(Assets::get_account_asset(&eos_id, TokenType::Token, &1u64).balance, 10u64.pow(5));
+	});
+}
+
+#[test]
+fn withdrawal_should_be_done_by_proven_action() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		let (eos_id, _) = Assets::asset_create(b"EOS".to_vec(), 4);
+		Assets::asset_issue(eos_id, TokenType::Token, 1u64, 10u64.pow(5));
+		assert_ok!(BridgeEos::set_bridge_token(
+			Origin::system(frame_system::RawOrigin::Root), eos_id, b"eosio.token".to_vec(), b"EOS".to_vec(), 4
+		));
+		assert_ok!(BridgeEos::reset_bridge_reserve(
+			Origin::system(frame_system::RawOrigin::Root), eos_id, 10u64.pow(6), 10u64.pow(6)
+		));
+		let withdraw = |to: &[u8], amount: u64| BridgeEos::bridge_asset_to(to.to_vec(), BridgeAssetBalance {
+			symbol: BridgeAssetSymbol::new(BlockchainType::EOS, b"EOS".to_vec(), 4u32),
+			amount: amount * 10u64.pow(12),
+			memo: vec![],
+			from: 1u64,
+			token_type: TokenType::Token,
+		});
+		assert_ok!(withdraw(b"alice", 1));
+		assert_ok!(withdraw(b"bob", 2));
+
+		assert_ok!(report_generated(0));
+		assert_ok!(BridgeEos::submit_signatures(Origin::signed(1), vec![(0, notary_signature(0, 1))]));
+		assert_ok!(BridgeEos::submit_signatures(Origin::signed(2), vec![(0, notary_signature(0, 2))]));
+		let (alice_action, eos_tx_id) = match BridgeEos::bridge_tx_outs(0) {
+			Some(TxOut::Signed(multi_sig_tx)) => (multi_sig_tx.actions()[0].clone(), multi_sig_tx.eos_tx_id()),
+			_ => panic!("transaction should be signed"),
+		};
+		let bob_action = match BridgeEos::bridge_tx_outs(1) {
+			Some(TxOut::Initial(multi_sig_tx)) => multi_sig_tx.actions()[0].clone(),
+			_ => panic!("transaction should be initial"),
+		};
+		let action_receipt: ActionReceipt = serde_json::from_str(r#"{
+			"receiver": "bifrost",
+			"act_digest": "eaa3b4bf845a1b41668ab7ca49fb5644fc91a6c0156dfd33911b4ec69d2e41d6",
+			"global_sequence": 3040972,
+			"recv_sequence": 1,
+			"auth_sequence": [["bifrost", 21]],
+			"code_sequence": 2,
+			"abi_sequence": 2
+		}"#).unwrap();
+
+		// action isn't executed by any transaction sent to EOS, nothing is changed
+		assert_eq!(
+			BridgeEos::apply_proven_action(bob_action, action_receipt.clone()),
+			Err(Error::<Test>::TxOutNotFound.into())
+		);
+		assert!(!BridgeEos::is_action_proven(&action_receipt));
+		assert_eq!(BridgeEos::tx_out_status(1), Some(TxOutStatus::Initial));
+
+		// the transaction is done with its id on EOS, and escrowed tokens are burned
+		assert_ok!(BridgeEos::apply_proven_action(alice_action.clone(), action_receipt.clone()));
+		assert_eq!(BridgeEos::bridge_tx_outs(0), Some(TxOut::Success(eos_tx_id.to_string().into_bytes())));
+		assert_eq!(BridgeActionReceipt::get(&action_receipt), alice_action);
+		assert!(BridgeEos::withdraw_escrow(0).is_none());
+		assert_eq!(Assets::get_account_asset(&eos_id, TokenType::Token, &1u64).balance, 7 * 10u64.pow(4));
+	});
+}
+
+#[test]
+fn withdrawals_should_be_covered_by_reserve() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		let root = || Origin::system(frame_system::RawOrigin::Root);
+		let (eos_id, _) = Assets::asset_create(b"EOS".to_vec(), 4);
+		Assets::asset_issue(eos_id, TokenType::Token, 1u64, 10u64.pow(5));
+		assert_ok!(BridgeEos::set_bridge_token(root(), eos_id, b"eosio.token".to_vec(), b"EOS".to_vec(), 4));
+		assert_ok!(BridgeEos::reset_bridge_reserve(root(), eos_id, 3 * 10u64.pow(4), 3 * 10u64.pow(4)));
+
+		let bridge_asset = BridgeAssetBalance {
+			symbol: BridgeAssetSymbol::new(BlockchainType::EOS, b"EOS".to_vec(), 4u32),
+			amount: 2 * 10u64.pow(12),
+			memo: vec![],
+			from: 1u64,
+			token_type: TokenType::Token,
+		};
+		assert_ok!(BridgeEos::bridge_asset_to(b"alice".to_vec(), bridge_asset.clone()));
+		assert_eq!(
+			BridgeEos::bridge_reserve(eos_id),
+			BridgeReserve { locked: 3 * 10u64.pow(4), minted: 10u64.pow(4), withdrawing: 2 * 10u64.pow(4), staked: 0, refunding: 0 }
+		);
+		assert_noop!(
+			BridgeEos::bridge_asset_to(b"alice".to_vec(), bridge_asset).map_err(dispatch::DispatchError::from),
+			Error::<Test>::InsufficientReserve
+		);
+
+		// tokens being withdrawn may have been sent by EOS
+		assert_noop!(
+			BridgeEos::report_reserve_deficit(Origin::signed(3), eos_id, 0),
+			Error::<Test>::NotNotary
+		);
+		assert_noop!(
+			BridgeEos::report_reserve_deficit(Origin::signed(1), eos_id, 10u64.pow(4)),
+			Error::<Test>::NoReserveDeficit
+		);
+		assert_ok!(BridgeEos::report_reserve_deficit(Origin::signed(1), eos_id, 10u64.pow(4) - 1));
+		assert!(BridgeEos::is_withdraw_paused());
+
+		// failed withdrawal is minted again
+		let fail = BridgeEos::bridge_tx_outs(0).unwrap().fail(b"failed");
+		assert_ok!(BridgeEos::bridge_tx_report(Origin::signed(1), vec![(0, fail)]));
+		assert_eq!(
+			BridgeEos::bridge_reserve(eos_id),
+			BridgeReserve { locked: 3 * 10u64.pow(4), minted: 3 * 10u64.pow(4), withdrawing: 0, staked: 0, refunding: 0 }
+		);
+	});
+}
+
+#[test]
+fn staking_should_be_covered_by_reserve_and_reverted_on_failure() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		let root = || Origin::system(frame_system::RawOrigin::Root);
+		let (eos_id, _) = Assets::asset_create(b"EOS".to_vec(), 4);
+		assert_ok!(BridgeEos::set_bridge_token(root(), eos_id, b"eosio.token".to_vec(), b"EOS".to_vec(), 4));
+		assert_ok!(BridgeEos::reset_bridge_reserve(root(), eos_id, 3 * 10u64.pow(4), 3 * 10u64.pow(4)));
+
+		let bridge_asset = BridgeAssetBalance {
+			symbol: BridgeAssetSymbol::new(BlockchainType::EOS, b"EOS".to_vec(), 4u32),
+			amount: 2 * 10u64.pow(12),
+			memo: vec![],
+			from: 1u64,
+			token_type: TokenType::Token,
+		};
+		assert_ok!(BridgeEos::stake(b"producer1".to_vec(), bridge_asset.clone()));
+		assert_eq!(BridgeEos::staked_producer(b"producer1".to_vec()), 2 * 10u64.pow(4));
+		assert_eq!(BridgeEos::bridge_reserve(eos_id).staked, 2 * 10u64.pow(4));
+		assert_eq!(
+			BridgeEos::stake_tx_out(0),
+			Some(StakeTxOut { asset_id: eos_id, producer: b"producer1".to_vec(), eos_amount: 2 * 10u64.pow(4), staking: true })
+		);
+		assert_eq!(BridgeEos::tx_out_status(0), Some(TxOutStatus::Initial));
+
+		// staked tokens cannot be staked again or withdrawn
+		assert_noop!(BridgeEos::stake(b"producer2".to_vec(), bridge_asset.clone()), Error::<Test>::InsufficientReserve);
+		assert_noop!(
+			BridgeEos::unstake(b"producer2".to_vec(), bridge_asset.clone()),
+			Error::<Test>::InsufficientStake
+		);
+		// balance on EOS only has tokens which aren't staked
+		assert_noop!(
+			BridgeEos::report_reserve_deficit(Origin::signed(1), eos_id, 10u64.pow(4)),
+			Error::<Test>::NoReserveDeficit
+		);
+
+		assert_ok!(BridgeEos::unstake(b"producer1".to_vec(), bridge_asset.clone()));
+		assert!(!StakedProducers::<Test>::contains_key(b"producer1".to_vec()));
+		assert_eq!(BridgeEos::bridge_reserve(eos_id).staked, 0);
+		assert_eq!(BridgeEos::bridge_reserve(eos_id).refunding, 2 * 10u64.pow(4));
+
+		// failed un-staking stakes the amount again
+		let fail = BridgeEos::bridge_tx_outs(1).unwrap().fail(b"failed");
+		assert_ok!(BridgeEos::bridge_tx_report(Origin::signed(1), vec![(1, fail)]));
+		assert_eq!(BridgeEos::staked_producer(b"producer1".to_vec()), 2 * 10u64.pow(4));
+		assert_eq!(BridgeEos::bridge_reserve(eos_id).staked, 2 * 10u64.pow(4));
+		assert_eq!(BridgeEos::bridge_reserve(eos_id).refunding, 0);
+		assert!(BridgeEos::stake_tx_out(1).is_none());
+
+		// only EOS of the system token contract can be staked
+		let (usd_id, _) = Assets::asset_create(b"USD".to_vec(), 4);
+		assert_ok!(BridgeEos::set_bridge_token(root(), usd_id, b"usd.token".to_vec(), b"USD".to_vec(), 4));
+		let usd_asset = BridgeAssetBalance {
+			symbol: BridgeAssetSymbol::new(BlockchainType::EOS, b"USD".to_vec(), 4u32),
+			..bridge_asset
+		};
+		assert_noop!(BridgeEos::stake(b"producer1".to_vec(), usd_asset), Error::<Test>::StakingTokenNotSupported);
+	});
+}
+
+#[test]
+fn staking_should_be_done_by_proven_action_and_refunded_later() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		let root = || Origin::system(frame_system::RawOrigin::Root);
+		let (eos_id, _) = Assets::asset_create(b"EOS".to_vec(), 4);
+		Assets::asset_issue(eos_id, TokenType::Token, 1u64, 10u64.pow(5));
+		assert_ok!(BridgeEos::set_bridge_token(root(), eos_id, b"eosio.token".to_vec(), b"EOS".to_vec(), 4));
+		assert_ok!(BridgeEos::reset_bridge_reserve(root(), eos_id, 3 * 10u64.pow(4), 3 * 10u64.pow(4)));
+
+		let bridge_asset = BridgeAssetBalance {
+			symbol: BridgeAssetSymbol::new(BlockchainType::EOS, b"EOS".to_vec(), 4u32),
+			amount: 2 * 10u64.pow(12),
+			memo: vec![],
+			from: 1u64,
+			token_type: TokenType::Token,
+		};
+		assert_ok!(BridgeEos::stake(b"producer1".to_vec(), bridge_asset.clone()));
+		assert_ok!(report_generated(0));
+		assert_ok!(BridgeEos::submit_signatures(Origin::signed(1), vec![(0, notary_signature(0, 1))]));
+		assert_ok!(BridgeEos::submit_signatures(Origin::signed(2), vec![(0, notary_signature(0, 2))]));
+		let (delegate_action, eos_tx_id) = match BridgeEos::bridge_tx_outs(0) {
+			Some(TxOut::Signed(multi_sig_tx)) => (multi_sig_tx.actions()[0].clone(), multi_sig_tx.eos_tx_id()),
+			_ => panic!("transaction should be signed"),
+		};
+		assert_eq!(delegate_action.name, ACTION_NAMES[1]);
+		let action_receipt: ActionReceipt = serde_json::from_str(r#"{
+			"receiver": "eosio",
+			"act_digest": "eaa3b4bf845a1b41668ab7ca49fb5644fc91a6c0156dfd33911b4ec69d2e41d6",
+			"global_sequence": 3040973,
+			"recv_sequence": 1,
+			"auth_sequence": [["bifrost", 22]],
+			"code_sequence": 2,
+			"abi_sequence": 2
+		}"#).unwrap();
+
+		// the staking transaction is done, and the stake is kept
+		assert_ok!(BridgeEos::apply_proven_action(delegate_action, action_receipt));
+		assert_eq!(BridgeEos::bridge_tx_outs(0), Some(TxOut::Success(eos_tx_id.to_string().into_bytes())));
+		assert!(BridgeEos::stake_tx_out(0).is_none());
+		assert_eq!(BridgeEos::staked_producer(b"producer1".to_vec()), 2 * 10u64.pow(4));
+		assert_eq!(BridgeEos::bridge_reserve(eos_id).staked, 2 * 10u64.pow(4));
+
+		// un-staked tokens aren't in the balance on EOS until they're refunded
+		assert_ok!(BridgeEos::unstake(b"producer1".to_vec(), bridge_asset.clone()));
+		assert_eq!(BridgeEos::bridge_reserve(eos_id).refunding, 2 * 10u64.pow(4));
+		assert_noop!(
+			BridgeEos::report_reserve_deficit(Origin::signed(1), eos_id, 10u64.pow(4)),
+			Error::<Test>::NoReserveDeficit
+		);
+		assert_noop!(
+			BridgeEos::bridge_asset_to(b"alice".to_vec(), bridge_asset).map_err(dispatch::DispatchError::from),
+			Error::<Test>::InsufficientReserve
+		);
+
+		// refund from EOS is locked as before, it isn't a deposit
+		let refund = ActionTransfer::from_str("eosio.stake", "bifrost", "2.0000 EOS", "unstake").unwrap();
+		let permission_level = PermissionLevel::from_str("eosio.stake", "active").unwrap();
+		let refund_action = Action::from_str("eosio.token", "transfer", vec![permission_level], refund).unwrap();
+		let refund_receipt: ActionReceipt = serde_json::from_str(r#"{
+			"receiver": "eosio.token",
+			"act_digest": "eaa3b4bf845a1b41668ab7ca49fb5644fc91a6c0156dfd33911b4ec69d2e41d6",
+			"global_sequence": 3040974,
+			"recv_sequence": 1,
+			"auth_sequence": [["eosio.stake", 1]],
+			"code_sequence": 2,
+			"abi_sequence": 2
+		}"#).unwrap();
+		assert_ok!(BridgeEos::apply_proven_action(refund_action, refund_receipt));
+		assert_eq!(
+			BridgeEos::bridge_reserve(eos_id),
+			BridgeReserve { locked: 3 * 10u64.pow(4), minted: 3 * 10u64.pow(4), withdrawing: 0, staked: 0, refunding: 0 }
+		);
+		assert_eq!(BridgeEos::next_suspended_deposit_id(), 0);
+	});
+}
+
+#[test]
+fn currency_balance_should_be_fetched_from_eos_node() {
+	let (offchain, _state) = TestOffchainExt::new();
+	let mut eos_nodes = MockEosNodes::new(&offchain);
+	let mut t = sp_io::TestExternalities::default();
+	t.register_extension(OffchainExt::new(offchain));
+
+	let balance_request = br#"{"code":"eosio.token","account":"bifrostcross","symbol":"EOS"}"#;
+	// error replied by node isn't retried on other nodes
+	eos_nodes.expect(
+		"http://127.0.0.1:8888", "/v1/chain/get_currency_balance", balance_request,
+		br#"{"code":500,"message":"Internal Service Error","error":{"code":3010001,"name":"name_type_exception","what":"Invalid name","details":[]}}"#
+	);
+	eos_nodes.expect("http://127.0.0.1:8888", "/v1/chain/get_currency_balance", balance_request, br#"["1.2345 EOS"]"#);
+
+	t.execute_with(|| {
+		let nodes = EosNodes::parse("http://127.0.0.1:8888/, http://127.0.0.1:8889/", 1);
+		assert_eq!(nodes.urls(), &["http://127.0.0.1:8888".to_string(), "http://127.0.0.1:8889".to_string()][..]);
+
+		let balance = crate::transaction::eos_rpc::get_currency_balance::<Test>(&nodes, "eosio.token", "bifrostcross", "EOS");
+		assert!(matches!(balance, Err(Error::<Test>::EOSRpcError)));
+		assert_eq!(crate::transaction::eos_rpc::node_health("http://127.0.0.1:8888"), (0, 0));
+
+		let balance = crate::transaction::eos_rpc::get_currency_balance::<Test>(&nodes, "eosio.token", "bifrostcross", "EOS");
+		assert_eq!(balance.ok(), Some(12345));
+	});
+}
+
+#[test]
+fn offchain_worker_should_fail_over_eos_nodes() {
+	let mut ext = new_test_ext();
+	let (offchain, _state) = TestOffchainExt::new();
+	let (pool, pool_state) = TestTransactionPoolExt::new();
+	let mut eos_nodes = MockEosNodes::new(&offchain);
+	ext.register_extension(OffchainExt::new(offchain));
+	ext.register_extension(TransactionPoolExt::new(pool));
+	ext.register_extension(KeystoreExt(KeyStore::new()));
+	UintAuthorityId::set_all_keys(vec![1u64]);
+
+	// the first node is down at block 1, and comes back at block 3
+	eos_nodes.expect("http://127.0.0.1:8888", "/v1/chain/get_info", b"{}", b"<html>502 Bad Gateway</html>");
+	eos_nodes.expect_chain_head("http://127.0.0.1:8889");
+	eos_nodes.expect_chain_head("http://127.0.0.1:8889");
+	eos_nodes.expect_chain_head("http://127.0.0.1:8888");
+
+	ext.execute_with(|| {
+		System::set_block_number(1);
+		let (eos_id, _) = Assets::asset_create(b"EOS".to_vec(), 4);
+		Assets::asset_issue(eos_id, TokenType::Token, 1u64, 10u64.pow(5));
+		assert_ok!(BridgeEos::set_bridge_token(
+			Origin::system(frame_system::RawOrigin::Root), eos_id, b"eosio.token".to_vec(), b"EOS".to_vec(), 4
+		));
+		assert_ok!(BridgeEos::reset_bridge_reserve(
+			Origin::system(frame_system::RawOrigin::Root), eos_id, 10u64.pow(6), 10u64.pow(6)
+		));
+		assert_ok!(BridgeEos::bridge_asset_to(b"alice".to_vec(), BridgeAssetBalance {
+			symbol: BridgeAssetSymbol::new(BlockchainType::EOS, b"EOS".to_vec(), 4u32),
+			amount: 2 * 10u64.pow(12),
+			memo: vec![],
+			from: 1u64,
+			token_type: TokenType::Token,
+		}));
+
+		sp_io::offchain::local_storage_set(StorageKind::PERSISTENT, b"EOS_NODE_URL", b"http://127.0.0.1:8888/,http://127.0.0.1:8889/");
+
+		let reported_status = || {
+			let transaction = pool_state.write().transactions.pop().unwrap();
+			let ex: Extrinsic = codec::Decode::decode(&mut &*transaction).unwrap();
+			match ex.call {
+				crate::mock::Call::BridgeEos(crate::Call::bridge_tx_report(tx_outs)) => {
+					tx_outs.iter().map(|(id, out)| (*id, out.status())).collect::<Vec<_>>()
+				}
+				e => panic!("Unexpected call: {:?}", e),
+			}
+		};
+
+		// transaction is generated by the second node
+		assert_ok!(BridgeEos::offchain(1));
+		assert_eq!(reported_status(), vec![(0, TxOutStatus::Generated)]);
+		assert_eq!(crate::transaction::eos_rpc::node_health("http://127.0.0.1:8888"), (1, 3));
+
+		// the failing node is skipped in its backoff
+		assert_ok!(BridgeEos::offchain(2));
+		assert_eq!(reported_status(), vec![(0, TxOutStatus::Generated)]);
+
+		// the node is healthy once it serves a request
+		assert_ok!(BridgeEos::offchain(3));
+		assert_eq!(reported_status(), vec![(0, TxOutStatus::Generated)]);
+		assert_eq!(crate::transaction::eos_rpc::node_health("http://127.0.0.1:8888"), (0, 0));
+		assert!(pool_state.read().transactions.is_empty());
+	});
+}
+
+#[test]
+fn local_notaries_should_sign_with_their_own_eos_keys() {
+	let mut ext = new_test_ext();
+	let (offchain, _state) = TestOffchainExt::new();
+	let (pool, pool_state) = TestTransactionPoolExt::new();
+	ext.register_extension(OffchainExt::new(offchain));
+	ext.register_extension(TransactionPoolExt::new(pool));
+	ext.register_extension(KeystoreExt(KeyStore::new()));
+	UintAuthorityId::set_all_keys(vec![1u64, 2u64]);
+
+	ext.execute_with(|| {
+		System::set_block_number(1);
+		let (eos_id, _) = Assets::asset_create(b"EOS".to_vec(), 4);
+		Assets::asset_issue(eos_id, TokenType::Token, 1u64, 10u64.pow(5));
+		assert_ok!(BridgeEos::set_bridge_token(
+			Origin::system(frame_system::RawOrigin::Root), eos_id, b"eosio.token".to_vec(), b"EOS".to_vec(), 4
+		));
+		assert_ok!(BridgeEos::reset_bridge_reserve(
+			Origin::system(frame_system::RawOrigin::Root), eos_id, 10u64.pow(6), 10u64.pow(6)
+		));
+		assert_ok!(BridgeEos::bridge_asset_to(b"alice".to_vec(), BridgeAssetBalance {
+			symbol: BridgeAssetSymbol::new(BlockchainType::EOS, b"EOS".to_vec(), 4u32),
+			amount: 2 * 10u64.pow(12),
+			memo: vec![],
+			from: 1u64,
+			token_type: TokenType::Token,
+		}));
+		assert_ok!(report_generated(0));
+
+		sp_io::offchain::local_storage_set(StorageKind::PERSISTENT, b"EOS_NODE_URL", b"http://127.0.0.1:8888/");
+		for notary in 1..=2u64 {
+			let key = [&b"EOS_SECRET_KEY"[..], &notary.encode()[..]].concat();
+			sp_io::offchain::local_storage_set(StorageKind::PERSISTENT, &key, NOTARY_SECRET_KEYS[notary as usize - 1].as_bytes());
+		}
+		assert_ok!(BridgeEos::offchain(1));
+
+		// each notary submits the signature made by its own key, in the order of local notaries
+		let transactions = pool_state.write().transactions.drain(..).collect::<Vec<_>>();
+		assert_eq!(transactions.len(), 2);
+		for (notary, transaction) in (1..=2u64).zip(transactions) {
+			let ex: Extrinsic = codec::Decode::decode(&mut &*transaction).unwrap();
+			let signatures = match ex.call {
+				crate::mock::Call::BridgeEos(crate::Call::submit_signatures(signatures)) => signatures,
+				e => panic!("Unexpected call: {:?}", e),
+			};
+			assert_eq!(signatures, vec![(0, notary_signature(0, notary))]);
+			assert_ok!(BridgeEos::submit_signatures(Origin::signed(notary), signatures));
+		}
+		assert_eq!(BridgeEos::tx_out_status(0), Some(TxOutStatus::Signed));
+	});
+}
+
+#[test]
+fn notary_eos_keys_should_be_set_by_root() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(
+			BridgeEos::notary_eos_key(&1).map(|key| key.len()),
+			Some(33)
+		);
+
+		let root = || Origin::system(frame_system::RawOrigin::Root);
+		assert_noop!(
+			BridgeEos::set_notary_eos_key(Origin::signed(1), 1, NOTARY_EOS_KEYS[2].as_bytes().to_vec()),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_noop!(
+			BridgeEos::set_notary_eos_key(root(), 1, b"EOS123".to_vec()),
+			Error::<Test>::InvalidEosPublicKey
+		);
+		assert_noop!(
+			BridgeEos::set_notary_eos_key(root(), 1, NOTARY_EOS_KEYS[1].as_bytes().to_vec()),
+			Error::<Test>::EosKeyInUse
+		);
+		assert_ok!(BridgeEos::set_notary_eos_key(root(), 1, NOTARY_EOS_KEYS[2].as_bytes().to_vec()));
+		assert_eq!(BridgeEos::notary_eos_key(&1), BridgeEos::notary_eos_key(&3));
+
+		// notary cannot sign without an EOS key
+		assert_ok!(BridgeEos::set_notary_keys(root(), vec![4u64]));
+		assert_noop!(
+			BridgeEos::submit_signatures(Origin::signed(4), vec![(0, vec![])]),
+			Error::<Test>::NotaryEosKeyNotSet
+		);
+	});
+}
+
+#[test]
+fn withdraw_limits_and_delay_should_work() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		let (eos_id, _) = Assets::asset_create(b"EOS".to_vec(), 4);
+		Assets::asset_issue(eos_id, TokenType::Token, 1u64, 10u64.pow(5));
+		Assets::asset_issue(eos_id, TokenType::Token, 2u64, 10u64.pow(5));
+		assert_ok!(BridgeEos::set_bridge_token(
+			Origin::system(frame_system::RawOrigin::Root), eos_id, b"eosio.token".to_vec(), b"EOS".to_vec(), 4
+		));
+		assert_ok!(BridgeEos::reset_bridge_reserve(
+			Origin::system(frame_system::RawOrigin::Root), eos_id, 10u64.pow(6), 10u64.pow(6)
+		));
+		assert_ok!(BridgeEos::set_withdraw_limit(
+			Origin::system(frame_system::RawOrigin::Root),
+			eos_id,
+			Some(WithdrawLimit {
+				window: 10,
+				global_cap: 3 * 10u64.pow(4),
+				account_cap: 2 * 10u64.pow(4),
+				delay_threshold: 2 * 10u64.pow(4),
+				delay: 3,
+			})
+		));
+
+		let withdraw = |from: u64, amount: u64| BridgeAssetBalance {
+			symbol: BridgeAssetSymbol::new(BlockchainType::EOS, b"EOS".to_vec(), 4u32),
+			amount: amount * 10u64.pow(12),
+			memo: vec![],
+			from,
+			token_type: TokenType::Token,
+		};
+
+		assert_ok!(BridgeEos::bridge_asset_to(b"alice".to_vec(), withdraw(1, 1)));
+		assert_eq!(BridgeEos::tx_out_status(0), Some(TxOutStatus::Initial));
+		assert_noop!(
+			BridgeEos::bridge_asset_to(b"alice".to_vec(), withdraw(1, 2)).map_err(dispatch::DispatchError::from),
+			Error::<Test>::AccountWithdrawLimitExceeded
+		);
+
+		// large withdrawal is escrowed and delayed
+		assert_ok!(BridgeEos::bridge_asset_to(b"alice".to_vec(), withdraw(2, 2)));
+		assert_eq!(BridgeEos::tx_out_status(1), None);
+		assert_eq!(BridgeEos::delayed_withdraw(0).map(|delayed| delayed.execute_at), Some(4));
+		assert_eq!(Assets::get_account_asset(&eos_id, TokenType::Token, &2u64).balance, 8 * 10u64.pow(4));
+		assert_noop!(
+			BridgeEos::bridge_asset_to(b"alice".to_vec(), withdraw(2, 1)).map_err(dispatch::DispatchError::from),
+			Error::<Test>::GlobalWithdrawLimitExceeded
+		);
+
+		run_to_block(3);
+		assert_eq!(BridgeEos::tx_out_status(1), None);
+		run_to_block(4);
+		assert_eq!(BridgeEos::tx_out_status(1), Some(TxOutStatus::Initial));
+		assert!(BridgeEos::delayed_withdraw(0).is_none());
+		assert_eq!(BridgeEos::delayed_withdraw_count(), 0);
+		assert_eq!(BridgeEos::withdraw_escrow(1).map(|escrow| escrow.who), Some(2u64));
+
+		// usage is released after the window, and the expired withdrawals are refunded
+		run_to_block(20);
+		assert_eq!(Assets::get_account_asset(&eos_id, TokenType::Token, &2u64).balance, 10u64.pow(5));
+		assert_ok!(BridgeEos::bridge_asset_to(b"alice".to_vec(), withdraw(2, 2)));
+		assert_eq!(Assets::get_account_asset(&eos_id, TokenType::Token, &2u64).balance, 8 * 10u64.pow(4));
+
+		// governance vetoes the delayed withdrawal, and its usage is released
+		assert_ok!(BridgeEos::veto_delayed_withdraw(Origin::system(frame_system::RawOrigin::Root), 1));
+		assert_eq!(Assets::get_account_asset(&eos_id, TokenType::Token, &2u64).balance, 10u64.pow(5));
+		assert_eq!(BridgeEos::account_withdrawn(eos_id, 2u64).0, 0);
+		assert_eq!(BridgeEos::global_withdrawn(eos_id).0, 0);
+		assert_ok!(BridgeEos::bridge_asset_to(b"alice".to_vec(), withdraw(2, 1)));
+		run_to_block(30);
+		assert_eq!(BridgeEos::next_tx_out_id(), 3);
+	});
+}
+
+#[test]
+fn delayed_withdrawals_should_wait_while_paused() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		let (eos_id, _) = Assets::asset_create(b"EOS".to_vec(), 4);
+		Assets::asset_issue(eos_id, TokenType::Token, 1u64, 10u64.pow(5));
+		assert_ok!(BridgeEos::set_bridge_token(
+			Origin::system(frame_system::RawOrigin::Root), eos_id, b"eosio.token".to_vec(), b"EOS".to_vec(), 4
+		));
+		assert_ok!(BridgeEos::reset_bridge_reserve(
+			Origin::system(frame_system::RawOrigin::Root), eos_id, 10u64.pow(6), 10u64.pow(6)
+		));
+		assert_ok!(BridgeEos::set_withdraw_limit(
+			Origin::system(frame_system::RawOrigin::Root),
+			eos_id,
+			Some(WithdrawLimit {
+				window: 10,
+				global_cap: 0,
+				account_cap: 0,
+				delay_threshold: 10u64.pow(4),
+				delay: 2,
+			})
+		));
+
+		let bridge_asset = BridgeAssetBalance {
+			symbol: BridgeAssetSymbol::new(BlockchainType::EOS, b"EOS".to_vec(), 4u32),
+			amount: 1 * 10u64.pow(12),
+			memo: vec![],
+			from: 1u64,
+			token_type: TokenType::Token,
+		};
+		assert_ok!(BridgeEos::bridge_asset_to(b"alice".to_vec(), bridge_asset.clone()));
+		assert_ok!(BridgeEos::bridge_asset_to(b"alice".to_vec(), bridge_asset.clone()));
+		assert_eq!(BridgeEos::delayed_withdraw_count(), 2);
+
+		// due withdrawals are kept in the queue while paused, not rescheduled every block
+		assert_ok!(BridgeEos::set_bridge_paused(Origin::system(frame_system::RawOrigin::Root), false, true));
+		run_to_block(10);
+		assert_eq!(BridgeEos::ready_delayed_withdraws(), vec![0, 1]);
+		assert!((3..=10).all(|n| DelayedWithdrawsToExecute::<Test>::get(n).is_empty()));
+		assert_eq!(BridgeEos::next_tx_out_id(), 0);
+
+		// vetoed withdrawal leaves the queue
+		assert_ok!(BridgeEos::veto_delayed_withdraw(Origin::system(frame_system::RawOrigin::Root), 0));
+		assert_eq!(BridgeEos::ready_delayed_withdraws(), vec![1]);
+
+		// resumed from the queue once withdrawals are resumed
+		assert_ok!(BridgeEos::set_bridge_paused(Origin::system(frame_system::RawOrigin::Root), false, false));
+		run_to_block(11);
+		assert!(BridgeEos::ready_delayed_withdraws().is_empty());
+		assert_eq!(BridgeEos::tx_out_status(0), Some(TxOutStatus::Initial));
+		assert_eq!(BridgeEos::delayed_withdraw_count(), 0);
+
+		// delayed withdrawals are bounded
+		DelayedWithdrawCount::put(MAX_DELAYED_WITHDRAWS);
+		assert_noop!(
+			BridgeEos::bridge_asset_to(b"alice".to_vec(), bridge_asset).map_err(dispatch::DispatchError::from),
+			Error::<Test>::TooManyDelayedWithdraws
+		);
+	});
+}
+
+#[test]
+fn paused_withdrawals_should_be_rejected() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		let (eos_id, _) = Assets::asset_create(b"EOS".to_vec(), 4);
+		Assets::asset_issue(eos_id, TokenType::Token, 1u64, 10u64.pow(5));
+		assert_ok!(BridgeEos::set_bridge_token(
+			Origin::system(frame_system::RawOrigin::Root), eos_id, b"eosio.token".to_vec(), b"EOS".to_vec(), 4
+		));
+		assert_ok!(BridgeEos::reset_bridge_reserve(
+			Origin::system(frame_system::RawOrigin::Root), eos_id, 10u64.pow(6), 10u64.pow(6)
+		));
+
+		let bridge_asset = BridgeAssetBalance {
+			symbol: BridgeAssetSymbol::new(BlockchainType::EOS, b"EOS".to_vec(), 4u32),
+			amount: 1 * 10u64.pow(12),
+			memo: vec![],
+			from: 1u64,
+			token_type: TokenType::Token,
+		};
+
+		// pausing deposits doesn't affect withdrawals
+		assert_ok!(BridgeEos::set_bridge_paused(Origin::system(frame_system::RawOrigin::Root), true, false));
+		assert_ok!(BridgeEos::bridge_asset_to(b"alice".to_vec(), bridge_asset.clone()));
+
+		assert_ok!(BridgeEos::set_bridge_paused(Origin::system(frame_system::RawOrigin::Root), false, true));
+		assert!(!BridgeEos::is_deposit_paused());
+		assert_noop!(
+			BridgeEos::bridge_asset_to(b"alice".to_vec(), bridge_asset.clone()).map_err(dispatch::DispatchError::from),
+			Error::<Test>::WithdrawPaused
+		);
+
+		// pending transactions are bounded
+		assert_ok!(BridgeEos::set_bridge_paused(Origin::system(frame_system::RawOrigin::Root), false, false));
+		PendingTxOuts::put((0..MAX_PENDING_TX_OUTS as TxOutId).collect::<Vec<_>>());
+		assert_noop!(
+			BridgeEos::bridge_asset_to(b"alice".to_vec(), bridge_asset).map_err(dispatch::DispatchError::from),
+			Error::<Test>::TooManyPendingTxOuts
+		);
+	});
+}
+
+#[test]
+fn bridge_asset_out_of_eos_range_should_be_rejected() {
+	new_test_ext().execute_with(|| {
+		let bridge_asset = |amount: u128, precision: u32| BridgeAssetBalance {
+			symbol: BridgeAssetSymbol::new(BlockchainType::EOS, b"EOS".to_vec(), precision),
+			amount,
+			memo: vec![],
+			from: 1u64,
+			token_type: TokenType::Token,
+		};
+
+		let asset = BridgeEos::convert_to_eos_asset(&bridge_asset(12_345 * 10u128.pow(8), 4)).unwrap();
+		assert_eq!(asset.amount, 12_345);
+		assert!(matches!(
+			BridgeEos::convert_to_eos_asset(&bridge_asset(u128::max_value(), 4)),
+			Err(Error::<Test>::ConvertBalanceError)
+		));
+		assert!(matches!(
+			BridgeEos::convert_to_eos_asset(&bridge_asset(1, 13)),
+			Err(Error::<Test>::InvalidTokenPrecision)
+		));
+	});
+}
+
+#[test]
+fn bridge_token_mapping_should_work() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		let root = || Origin::system(frame_system::RawOrigin::Root);
+		let (eos_id, _) = Assets::asset_create(b"EOS".to_vec(), 4);
+		let (usdt_id, _) = Assets::asset_create(b"USDT".to_vec(), 4);
+		Assets::asset_issue(usdt_id, TokenType::Token, 1u64, 10u64.pow(5));
+
+		// only root can map tokens
+		assert!(BridgeEos::set_bridge_token(Origin::signed(1), eos_id, b"eosio.token".to_vec(), b"EOS".to_vec(), 4).is_err());
+		assert_ok!(BridgeEos::set_bridge_token(root(), eos_id, b"eosio.token".to_vec(), b"EOS".to_vec(), 4));
+		assert_eq!(BridgeEos::eos_token_asset((b"eosio.token".to_vec(), b"EOS".to_vec())), Some(eos_id));
+		// an EOS token cannot be mapped to two assets
+		assert_noop!(
+			BridgeEos::set_bridge_token(root(), usdt_id, b"eosio.token".to_vec(), b"EOS".to_vec(), 4),
+			Error::<Test>::TokenAlreadyMapped
+		);
+		assert_noop!(
+			BridgeEos::set_bridge_token(root(), 100, b"tethertether".to_vec(), b"USDT".to_vec(), 4),
+			Error::<Test>::AssetNotExists
+		);
+		assert_ok!(BridgeEos::set_bridge_token(root(), usdt_id, b"tethertether".to_vec(), b"USDT".to_vec(), 4));
+		assert_ok!(BridgeEos::reset_bridge_reserve(root(), usdt_id, 10u64.pow(4), 10u64.pow(4)));
+
+		// unmapped asset cannot be withdrawn
+		assert_noop!(
+			BridgeEos::asset_redeem(Origin::signed(1), 100, b"alice".to_vec(), TokenType::Token, 10u64.pow(12), vec![]),
+			Error::<Test>::TokenNotMapped
+		);
+
+		// disabled token cannot be withdrawn
+		assert_ok!(BridgeEos::set_bridge_token_enabled(root(), usdt_id, false));
+		assert_noop!(
+			BridgeEos::asset_redeem(Origin::signed(1), usdt_id, b"alice".to_vec(), TokenType::Token, 10u64.pow(12), vec![]),
+			Error::<Test>::TokenDisabled
+		);
+		assert_ok!(BridgeEos::set_bridge_token_enabled(root(), usdt_id, true));
+
+		// withdrawal of the mapped token is escrowed
+		assert_ok!(BridgeEos::asset_redeem(Origin::signed(1), usdt_id, b"alice".to_vec(), TokenType::Token, 10u64.pow(12), vec![]));
+		assert_eq!(BridgeEos::tx_out_status(0), Some(TxOutStatus::Initial));
+		assert_eq!(
+			BridgeEos::withdraw_escrow(0),
+			Some(WithdrawEscrow {
+				who: 1u64, asset_id: usdt_id, token_type: TokenType::Token, amount: 10u64.pow(4), eos_amount: 10u64.pow(4)
+			})
+		);
+
+		// remapping the asset releases the previous EOS token
+		assert_ok!(BridgeEos::remove_bridge_token(root(), eos_id));
+		assert_eq!(BridgeEos::bridge_token(eos_id), None);
+		assert_eq!(BridgeEos::eos_token_asset((b"eosio.token".to_vec(), b"EOS".to_vec())), None);
+	});
+}
+
+#[test]
+fn deposit_with_invalid_memo_should_be_suspended() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		let root = || Origin::system(frame_system::RawOrigin::Root);
+		let (eos_id, _) = Assets::asset_create(b"EOS".to_vec(), 4);
+		assert_ok!(BridgeEos::set_bridge_token(root(), eos_id, b"eosio.token".to_vec(), b"EOS".to_vec(), 4));
+
+		let alice = "5CFK52zU59zUhC3s6mRobEJ3zm7JeXQZaS6ybvcuCDDhWwGG";
+		let transfer = ActionTransfer::from_str("testa", "bifrost", "1.0000 EOS", &format!("{}@bifrost:EOS", alice)).unwrap();
+		let target = BridgeEos::transaction_from_eos_to_bifrost(b"eosio.token", &transfer).unwrap();
+		assert!(target.is_some());
+		assert_eq!(Assets::get_account_asset(&eos_id, TokenType::Token, &target.unwrap()).balance, 10u64.pow(4));
+
+		// unknown token type
+		let transfer = ActionTransfer::from_str("testa", "bifrost", "2.0000 EOS", &format!("{}@bifrost:USDT", alice)).unwrap();
+		assert_eq!(BridgeEos::transaction_from_eos_to_bifrost(b"eosio.token", &transfer).ok(), Some(None));
+		assert_eq!(BridgeEos::suspended_deposit(0), Some(SuspendedDeposit {
+			eos_from: b"testa".to_vec(),
+			asset_id: eos_id,
+			amount: 2 * 10u64.pow(4),
+			memo: format!("{}@bifrost:USDT", alice).into_bytes(),
+		}));
+
+		// unmapped token cannot be suspended
+		let transfer = ActionTransfer::from_str("testa", "bifrost", "2.0000 EOS", "invalid").unwrap();
+		assert!(BridgeEos::transaction_from_eos_to_bifrost(b"fake.token", &transfer).is_err());
+		assert_eq!(BridgeEos::next_suspended_deposit_id(), 1);
+		// suspended deposit is locked, but not minted until it's claimed
+		assert_eq!(BridgeEos::bridge_reserve(eos_id), BridgeReserve {
+			locked: 3 * 10u64.pow(4), minted: 10u64.pow(4), withdrawing: 0, staked: 0, refunding: 0
+		});
+
+		assert!(BridgeEos::claim_suspended_deposit(Origin::signed(1), 0, 2u64, TokenType::Token).is_err());
+		assert_ok!(BridgeEos::claim_suspended_deposit(root(), 0, 2u64, TokenType::Token));
+		assert_eq!(Assets::get_account_asset(&eos_id, TokenType::Token, &2u64).balance, 2 * 10u64.pow(4));
+		assert_eq!(BridgeEos::bridge_reserve(eos_id).minted, 3 * 10u64.pow(4));
+		assert_noop!(
+			BridgeEos::claim_suspended_deposit(root(), 0, 2u64, TokenType::Token),
+			Error::<Test>::SuspendedDepositNotFound
+		);
+	});
+}
+
+#[test]
+fn relayed_block_should_prove_action() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		let v2_producers: ProducerSchedule = serde_json::from_str(&read_json_from_file("schedule_v2.json").unwrap()).unwrap();
+		let v2_producers = authority_schedule(v2_producers);
+		let v2_schedule_hash = v2_producers.schedule_hash().unwrap();
+		PendingScheduleVersion::put(v2_producers.version);
+		ProducerSchedules::insert(v2_producers.version, (&v2_producers.producers, v2_schedule_hash));
+
+		let signed_blocks_str = read_json_from_file("actions_verification_10776.json").unwrap();
+		let signed_blocks_headers: Vec<SignedBlockHeader> = serde_json::from_str(&signed_blocks_str).unwrap();
+
+		// block root merkle for block 10776
+		let active_nodes: Vec<Checksum256> = vec![
+			"45c2c1cbc4b049d72a627124b05f5c476ae1cc87955fbea70bc8dbe549cf395a".into(),
+			"d96747605aaed959630b23a28e0004f42a87eae93f51d5fe241735644a0c3921".into(),
+			"937a489eea576d74a3d091cc4dcf1cb867f01e314ac7f1334f6cec00dfcee476".into(),
+			"36cbf5d9c35b2538181bf7f8af4ee57c55c17e516eedd992a73bace9ca14a5c3".into(),
+			"40e8bb864481e7bb01674ec3517c84e557869fea8160c4b2762d3e83d71d6034".into(),
+			"afa502d408f5bdf1660fa9fe3a1fcb432462467e7eb403a8499392ee5297d8d1".into(),
+			"f1329d3ee84040279460cbc87b6769b7363e477a832f73d639e0692a4042f093".into()
+		];
+		let mut merkle = IncrementalMerkle::new(10774, active_nodes);
+		assert!(merkle.append(signed_blocks_headers[0].block_header.previous).is_ok());
+
+		// only root can start header relay
+		assert!(BridgeEos::init_relay(Origin::signed(1), signed_blocks_headers[0].clone(), merkle.clone()).is_err());
+		assert_ok!(BridgeEos::init_relay(Origin::system(frame_system::RawOrigin::Root), signed_blocks_headers[0].clone(), merkle));
+		let block_num = signed_blocks_headers[0].block_header.block_num();
+		assert_eq!(BridgeEos::relay_head().map(|(num, _)| num), Some(block_num));
+		assert_eq!(BridgeEos::last_irreversible_block(), block_num);
+		// 2018-11-23T17:54:53.500
+		assert_eq!(BridgeEos::relayed_block(block_num).map(|block| block.timestamp), Some(1_542_995_693));
+
+		// headers must be continuous
+		assert_ok!(BridgeEos::relay_block_headers(Origin::signed(1), vec![signed_blocks_headers[1].clone()]));
+		assert_eq!(BridgeEos::relay_head().map(|(num, _)| num), Some(block_num));
+
+		let action_merkle_paths: Vec<String> = serde_json::from_str(&read_json_from_file("action_merkle_paths.json").unwrap()).unwrap();
+		let action_merkle_paths = action_merkle_paths.iter().map(|path| Checksum256::from_str(path).unwrap()).collect::<Vec<_>>();
+		let actual_merkle_paths = get_proof(15, action_merkle_paths).unwrap();
+
+		let actions: Vec<Action> = serde_json::from_str(&read_json_from_file("actions_from_10776.json").unwrap()).unwrap();
+		let action = actions[3].clone();
+		let action_receipt: ActionReceipt = serde_json::from_str(r#"{
+			"receiver": "megasuper333",
+			"act_digest": "eaa3b4bf845a1b41668ab7ca49fb5644fc91a6c0156dfd33911b4ec69d2e41d6",
+			"global_sequence": 3040972,
+			"recv_sequence": 1,
+			"auth_sequence": [["junglefaucet", 21]],
+			"code_sequence": 2,
+			"abi_sequence": 2
+		}"#).unwrap();
+
+		// governance sets relay fee and reward
+		let root = || Origin::system(frame_system::RawOrigin::Root);
+		assert!(BridgeEos::set_relay_fee(Origin::signed(2), 100).is_err());
+		assert_ok!(BridgeEos::set_relay_fee(root(), 100));
+		assert_ok!(BridgeEos::set_relay_reward(root(), RelayKind::ProveAction, 10));
+
+		// the action isn't in another block, relayer is penalised
+		assert_ok!(BridgeEos::prove_action_by_relay(
+			Origin::signed(2), action.clone(), action_receipt.clone(), actual_merkle_paths.clone(), block_num + 1
+		));
+		assert_ne!(BridgeActionReceipt::get(&action_receipt), action);
+		assert!(!BridgeEos::is_action_proven(&action_receipt));
+		assert_eq!(Balances::free_balance(2), 900);
+		assert_eq!(Balances::free_balance(BridgeEos::relay_pot()), 1_100);
+
+		// anyone can prove the action, the fee is refunded with reward
+		assert_ok!(BridgeEos::prove_action_by_relay(
+			Origin::signed(2), action.clone(), action_receipt.clone(), actual_merkle_paths.clone(), block_num
+		));
+		assert_eq!(BridgeActionReceipt::get(&action_receipt), action);
+		assert!(BridgeEos::is_action_proven(&action_receipt));
+		assert_eq!(Balances::free_balance(2), 910);
+		assert_eq!(Balances::reserved_balance(2), 0);
+		assert_eq!(Balances::free_balance(BridgeEos::relay_pot()), 1_090);
+
+		// duplicated proof is penalised
+		assert_ok!(BridgeEos::prove_action_by_relay(
+			Origin::signed(2), action, action_receipt, actual_merkle_paths, block_num
+		));
+		assert_eq!(Balances::free_balance(2), 810);
+		assert_eq!(Balances::free_balance(BridgeEos::relay_pot()), 1_190);
+
+		// relayer must be able to pay the fee
+		assert_ok!(BridgeEos::set_relay_fee(root(), 2_000));
+		assert!(BridgeEos::relay_block_headers(Origin::signed(2), vec![signed_blocks_headers[1].clone()]).is_err());
+	});
+}
+
+#[test]
+fn relayed_blocks_should_be_irreversible_by_two_thirds_producers() {
+	new_test_ext().execute_with(|| {
+		let v2_producers: ProducerSchedule = serde_json::from_str(&read_json_from_file("schedule_v2.json").unwrap()).unwrap();
+		let v2_producers = authority_schedule(v2_producers);
+		let v2_schedule_hash = v2_producers.schedule_hash().unwrap();
+		let version = v2_producers.version;
+		ProducerSchedules::insert(version, (&v2_producers.producers, v2_schedule_hash));
+
+		let producers = v2_producers.producers.iter().map(|p| p.producer_name).collect::<Vec<_>>();
+		let threshold = producers.len() * 2 / 3 + 1;
+
+		// every producer produces a block in turn
+		let blocks = (0..threshold).map(|i| (100 + i as BlockNum, producers[i], version)).collect::<Vec<_>>();
+		BridgeEos::update_irreversible_block(blocks.clone());
+		// no block has been confirmed by enough producers
+		assert_eq!(BridgeEos::last_irreversible_block(), 0);
+		assert_eq!(ReversibleBlocks::get().len(), threshold);
+
+		// the first block is confirmed by the block produced by the last producer in threshold
+		let mut blocks = ReversibleBlocks::get();
+		blocks.push((100 + threshold as BlockNum, producers[threshold % producers.len()], version));
+		BridgeEos::update_irreversible_block(blocks);
+		assert_eq!(BridgeEos::last_irreversible_block(), 100);
+		assert_eq!(ReversibleBlocks::get().len(), threshold);
+
+		// blocks of the same producer confirm nothing
+		let mut blocks = ReversibleBlocks::get();
+		blocks.extend((0..threshold).map(|i| (200 + i as BlockNum, producers[0], version)));
+		BridgeEos::update_irreversible_block(blocks);
+		assert_eq!(BridgeEos::last_irreversible_block(), 100);
+	});
 }
 
 #[cfg(feature = "std")]
-fn read_json_from_file(json_name: impl AsRef<str>) -> Result<String, Box<dyn Error>> {
+fn read_json_from_file(json_name: impl AsRef<str>) -> Result<String, Box<dyn StdError>> {
 	let path = Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/test_data/")).join(json_name.as_ref());
 	let mut file = File::open(path)?;
 	let mut json_str = String::new();
@@ -457,58 +1768,113 @@ fn read_json_from_file(json_name: impl AsRef<str>) -> Result<String, Box<dyn Err
 	Ok(json_str)
 }
 
-#[allow(dead_code)]
-fn bridge_tx_report() -> dispatch::DispatchResult {
-	#[allow(deprecated)]
-	use frame_support::unsigned::ValidateUnsigned;
+/// Fixtures are legacy schedules, each producer signs blocks with a single key.
+fn authority_schedule(ps: ProducerSchedule) -> ProducerAuthoritySchedule {
+	let producers = ps.producers.into_iter().map(|producer| ProducerAuthority {
+		producer_name: producer.producer_name,
+		authority: BlockSigningAuthority(0u32.into(), BlockSigningAuthorityV0 {
+			threshold: 1,
+			keys: vec![KeyWeight { key: producer.block_signing_key, weight: 1 }],
+		}),
+	}).collect();
+
+	ProducerAuthoritySchedule::new(ps.version, producers)
+}
 
-	let tx_outs = vec![TxOut::Success(vec![])];
+/// Block headers produced by schedule v1 which propose schedule v2, with block ids and merkle
+fn change_schedule_9313() -> (Vec<SignedBlockHeader>, Vec<Vec<Checksum256>>, IncrementalMerkle) {
+	let signed_blocks_headers: Vec<SignedBlockHeader> = serde_json::from_str(&read_json_from_file("change_schedule_9313.json").unwrap()).unwrap();
+	let block_ids_list: Vec<Vec<String>> = serde_json::from_str(&read_json_from_file("block_ids_list.json").unwrap()).unwrap();
+	let block_ids_list = block_ids_list.iter().map(|ids| {
+		ids.iter().map(|id| Checksum256::from_str(id).unwrap()).collect::<Vec<_>>()
+	}).collect::<Vec<_>>();
+
+	let merkle = IncrementalMerkle::new(9311, vec![
+		"0000245f60aa338bd246cb7598a14796ee0210f669f9c9b37f6ddad0b5765649".into(),
+		"9d41d4581cab233fe68c4510cacd05d0cc979c53ae317ce9364040578037de6a".into(),
+		"a397d1a6dc90389dc592ea144b1801c4b323c12b0b2f066aa55faa5892803317".into(),
+		"0cf502411e185ea7e3cc790e0b757807987e767a81c463c3e4ee5970b7fd1c67".into(),
+		"9f774a35e86ddb2d293da1bfe2e25b7b447fd3d9372ee580fce230a87fefa586".into(),
+		"4d018eda9a22334ac0492489fdf79118d696eea52af3871a7e4bf0e2d5ab5945".into(),
+		"acba7c7ee5c1d8ba97ea1a841707fbb2147e883b56544ba821814aebe086383e".into(),
+		"afa502d408f5bdf1660fa9fe3a1fcb432462467e7eb403a8499392ee5297d8d1".into(),
+		"4d723385cad26cf80c2db366f9666a3ef77679c098e07d1af48d523b64b1d460".into(),
+	]);
 
-	#[allow(deprecated)]
-	BridgeEos::pre_dispatch(&crate::Call::bridge_tx_report(tx_outs.clone())).map_err(|e| <&'static str>::from(e))?;
+	(signed_blocks_headers, block_ids_list, merkle)
+}
 
-	BridgeEos::bridge_tx_report(
-		Origin::system(frame_system::RawOrigin::None),
-		tx_outs,
-	)
+const GET_INFO_RESPONSE: &[u8] = br#"{
+	"server_version": "7e1ad13e",
+	"chain_id": "cf057bbfb72640471fd910bcb67639c22df9f92470936cddc1ade0e2f2e7dc4f",
+	"head_block_num": 10776,
+	"last_irreversible_block_num": 10775,
+	"head_block_id": "00002a18d6f8d2f8a8a7d4bbbe5e1d0bb3b8c3f1d1f9e7a1c0e6d0f9a3b5c7d9",
+	"head_block_producer": "eosio"
+}"#;
+
+const GET_BLOCK_RESPONSE: &[u8] = br#"{
+	"timestamp": "2020-04-27T08:09:26.500",
+	"producer": "eosio",
+	"id": "00002a18d6f8d2f8a8a7d4bbbe5e1d0bb3b8c3f1d1f9e7a1c0e6d0f9a3b5c7d9",
+	"block_num": 10776,
+	"ref_block_prefix": 3155470248,
+	"transactions": []
+}"#;
+
+/// Mock EOS nodes serving requests of offchain worker, requests are expected in the order they're sent
+struct MockEosNodes {
+	offchain: TestOffchainExt,
+	next_request: u16,
 }
 
-fn seal_header(mut header: Header, author: u64) -> Header {
-	{
-		let digest = header.digest_mut();
-		digest.logs.push(DigestItem::PreRuntime(TEST_ID, author.encode()));
-		digest.logs.push(DigestItem::Seal(TEST_ID, author.encode()));
+impl MockEosNodes {
+	fn new(offchain: &TestOffchainExt) -> Self {
+		Self { offchain: offchain.clone(), next_request: 0 }
 	}
 
-	header
-}
+	fn expect(&mut self, node_url: &str, api: &str, body: &[u8], response: &[u8]) {
+		self.offchain.0.write().expect_request(self.next_request, sp_core::offchain::testing::PendingRequest {
+			method: "POST".into(),
+			uri: format!("{}{}", node_url, api),
+			headers: vec![("Content-Type".into(), "application/json".into())],
+			body: body.to_vec(),
+			response: Some(response.to_vec()),
+			sent: true,
+			..Default::default()
+		});
+		self.next_request += 1;
+	}
 
-fn create_header(number: u64, parent_hash: H256, state_root: H256) -> Header {
-	Header::new(
-		number,
-		Default::default(),
-		state_root,
-		parent_hash,
-		Default::default(),
-	)
+	/// The node serves chain info and the head block for generating a transaction
+	fn expect_chain_head(&mut self, node_url: &str) {
+		self.expect(node_url, "/v1/chain/get_info", b"{}", GET_INFO_RESPONSE);
+		self.expect(
+			node_url,
+			"/v1/chain/get_block",
+			br#"{"block_num_or_id":"00002a18d6f8d2f8a8a7d4bbbe5e1d0bb3b8c3f1d1f9e7a1c0e6d0f9a3b5c7d9"}"#,
+			GET_BLOCK_RESPONSE
+		);
+	}
 }
 
-fn rotate_author(author: u64) {
-	let mut header = seal_header(
-		create_header(1, Default::default(), [1; 32].into()),
-		author,
-	);
+/// EOS signature of the generated transaction made by the key of notary 1, 2 or 3
+fn notary_signature(id: TxOutId, notary: u64) -> Vec<u8> {
+	let sk = SecretKey::from_wif(NOTARY_SECRET_KEYS[notary as usize - 1]).unwrap();
+	BridgeEos::bridge_tx_outs(id).unwrap().eos_signature::<Test>(sk).unwrap()
+}
 
-	header.digest_mut().pop(); // pop the seal off.
-	System::initialize(
-		&1,
-		&Default::default(),
-		&Default::default(),
-		header.digest(),
-		Default::default(),
-	);
+fn report_generated(id: TxOutId) -> dispatch::DispatchResult {
+	let multi_sig_tx = match BridgeEos::bridge_tx_outs(id) {
+		Some(TxOut::Initial(multi_sig_tx)) => multi_sig_tx,
+		_ => panic!("transaction should be initial"),
+	};
+	let raw_tx = Transaction::new(0, 0, 0, multi_sig_tx.actions().to_vec()).to_serialize_data().unwrap();
 
-	assert_eq!(Authorship::author(), author);
+	BridgeEos::bridge_tx_report(
+		Origin::signed(1),
+		vec![(id, multi_sig_tx.into_generated(vec![0u8; 32], raw_tx))],
+	)
 }
 
 #[test]
@@ -15,13 +15,15 @@
 // along with Bifrost.  If not, see <http://www.gnu.org/licenses/>.
 
 use alloc::string::{String, ToString};
-use core::{iter::FromIterator, str::FromStr};
+use core::str::FromStr;
 use codec::{Decode, Encode};
 use crate::Error;
-use eos_chain::{Action, Asset, Checksum256, Read, SerializeData, Signature, Transaction};
+use eos_chain::{Action, ActionTransfer, Asset, Checksum256, PermissionLevel, Read, SerializeData, Signature, Transaction};
 use eos_keys::secret::SecretKey;
+use frame_support::ensure;
 use sp_core::offchain::Duration;
 use sp_std::prelude::*;
+pub use eos_rpc::EosNodes;
 
 #[derive(Encode, Decode, Clone, PartialEq, Debug, Default)]
 pub struct TxSig<AccountId> {
@@ -49,8 +51,8 @@ impl<AccountId: PartialEq> MultiSig<AccountId> {
 		self.signatures.len() >= self.threshold as usize
 	}
 
-	fn has_signed(&self, author: AccountId) -> bool {
-		self.signatures.iter().find(|sig| sig.author == author).is_some()
+	fn has_signed(&self, author: &AccountId) -> bool {
+		self.signatures.iter().any(|sig| sig.author == *author)
 	}
 }
 
@@ -71,14 +73,55 @@ pub struct MultiSigTx<AccountId> {
 	raw_tx: Vec<u8>,
 	/// Signatures of transaction
 	multi_sig: MultiSig<AccountId>,
-	/// EOS transaction action
-	action: Action,
+	/// EOS transaction actions
+	actions: Vec<Action>,
 	/// Who sends Transaction to EOS
 	pub from: AccountId,
 	/// token type
 	pub token_type: node_primitives::TokenType,
 }
 
+impl<AccountId> MultiSigTx<AccountId> {
+	/// Actions executed by this transaction on EOS
+	pub fn actions(&self) -> &[Action] {
+		&self.actions
+	}
+
+	/// Id of the raw transaction on EOS, which is the sha256 of it
+	pub fn eos_tx_id(&self) -> Checksum256 {
+		Checksum256::from(sp_io::hashing::sha2_256(&self.raw_tx))
+	}
+
+	/// Digest of the raw transaction which is signed by EOS keys, the context free data is empty
+	fn sig_digest(&self) -> [u8; 32] {
+		sp_io::hashing::sha2_256(&[&self.chain_id[..], &self.raw_tx[..], &[0u8; 32][..]].concat())
+	}
+
+	/// Recover the compressed public key which made the serialized EOS signature of the raw transaction
+	pub fn recover_signer<T: crate::Trait>(&self, signature: &[u8]) -> Result<Vec<u8>, Error<T>> {
+		let mut pos = 0;
+		Signature::read(signature, &mut pos).map_err(|_| Error::<T>::InvalidEosSignature)?;
+		// only K1 signatures, (type 0, header 27 + 4 + recovery id, r, s)
+		ensure!(pos == 66 && signature[0] == 0 && signature[1] >= 31, Error::<T>::InvalidEosSignature);
+
+		let mut sig = [0u8; 65];
+		sig[..64].copy_from_slice(&signature[2..66]);
+		sig[64] = signature[1] - 31;
+		let signer = sp_io::crypto::secp256k1_ecdsa_recover_compressed(&sig, &self.sig_digest())
+			.map_err(|_| Error::<T>::InvalidEosSignature)?;
+
+		Ok(signer.to_vec())
+	}
+
+	/// Transaction with the raw transaction generated for EOS, waiting for signatures
+	pub fn into_generated(mut self, chain_id: Vec<u8>, raw_tx: Vec<u8>) -> TxOut<AccountId> {
+		self.chain_id = chain_id;
+		self.raw_tx = raw_tx;
+
+		TxOut::Generated(self)
+	}
+}
+
 #[derive(Encode, Decode, Clone, PartialEq, Debug)]
 pub enum TxOut<AccountId> {
 	/// Initial Eos multi-sig transaction
@@ -102,10 +145,99 @@ pub enum TxOut<AccountId> {
 	},
 }
 
+/// Status of a transaction sent to Eos blockchain
+#[derive(Encode, Decode, Clone, Copy, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+pub enum TxOutStatus {
+	Initial,
+	Generated,
+	Signed,
+	Processing,
+	Success,
+	Fail,
+}
+
+impl TxOutStatus {
+	/// Transaction in Success or Fail won't be changed anymore
+	pub fn is_finished(&self) -> bool {
+		match self {
+			TxOutStatus::Success | TxOutStatus::Fail => true,
+			_ => false,
+		}
+	}
+
+	/// Initial => Generated => Signed => Processing => Success, and it can fail before signed.
+	/// A generated transaction stays in Generated until it gets enough signatures.
+	///
+	/// Signed transaction may have been executed by EOS, so it only fails once its expiration is
+	/// proven by relayed blocks, see `prove_tx_out_expired`.
+	pub fn can_transit_to(&self, next: TxOutStatus) -> bool {
+		use TxOutStatus::*;
+
+		match (self, next) {
+			(Initial, Generated) | (Generated, Generated) | (Generated, Signed) |
+			(Signed, Processing) | (Processing, Success) |
+			(Initial, Fail) | (Generated, Fail) => true,
+			_ => false,
+		}
+	}
+}
+
+impl<AccountId> TxOut<AccountId> {
+	pub fn status(&self) -> TxOutStatus {
+		match self {
+			TxOut::Initial(_) => TxOutStatus::Initial,
+			TxOut::Generated(_) => TxOutStatus::Generated,
+			TxOut::Signed(_) => TxOutStatus::Signed,
+			TxOut::Processing { .. } => TxOutStatus::Processing,
+			TxOut::Success(_) => TxOutStatus::Success,
+			TxOut::Fail { .. } => TxOutStatus::Fail,
+		}
+	}
+
+	/// Fail a transaction which hasn't been finished, a finished one is returned as it is
+	pub fn fail(self, reason: &[u8]) -> Self {
+		match self {
+			TxOut::Initial(tx) | TxOut::Generated(tx) | TxOut::Signed(tx) => TxOut::Fail {
+				tx_id: Vec::new(),
+				reason: reason.to_vec(),
+				tx,
+			},
+			TxOut::Processing { tx_id, multi_sig_tx } => TxOut::Fail {
+				tx_id: tx_id.to_string().into_bytes(),
+				reason: reason.to_vec(),
+				tx: multi_sig_tx,
+			},
+			finished => finished,
+		}
+	}
+
+	/// Expiration in unix seconds of a transaction signed for EOS, which is the first field of the
+	/// raw transaction
+	pub fn eos_expiration(&self) -> Option<u32> {
+		match self {
+			TxOut::Signed(tx) | TxOut::Processing { multi_sig_tx: tx, .. } => tx.raw_tx.get(0..4)
+				.map(|expiration| u32::from_le_bytes([expiration[0], expiration[1], expiration[2], expiration[3]])),
+			_ => None,
+		}
+	}
+
+	/// How many notaries have signed this transaction
+	pub fn signature_count(&self) -> usize {
+		match self {
+			TxOut::Initial(tx) | TxOut::Generated(tx) | TxOut::Signed(tx) => tx.multi_sig.signatures.len(),
+			TxOut::Processing { multi_sig_tx, .. } => multi_sig_tx.multi_sig.signatures.len(),
+			TxOut::Fail { tx, .. } => tx.multi_sig.signatures.len(),
+			TxOut::Success(_) => 0,
+		}
+	}
+}
+
 impl<AccountId: PartialEq + Clone> TxOut<AccountId> {
 	pub fn init<T: crate::Trait>(
 		raw_from: Vec<u8>,
 		raw_to: Vec<u8>,
+		raw_contract: &[u8],
 		amount: Asset,
 		threshold: u8,
 		memo: &str,
@@ -114,309 +246,180 @@ impl<AccountId: PartialEq + Clone> TxOut<AccountId> {
 	) -> Result<Self, Error<T>> {
 		let eos_from = core::str::from_utf8(&raw_from).map_err(|_| Error::<T>::ParseUtf8Error)?;
 		let eos_to = core::str::from_utf8(&raw_to).map_err(|_| Error::<T>::ParseUtf8Error)?;
+		let contract = core::str::from_utf8(raw_contract).map_err(|_| Error::<T>::ParseUtf8Error)?;
+
+		// Construct action, the transfer is executed by the token contract
+		let permission_level = PermissionLevel::from_str(eos_from, "active").map_err(|_| Error::<T>::EosChainError)?;
+		let action_transfer = ActionTransfer::from_str(eos_from, eos_to, amount.to_string().as_ref(), memo)
+			.map_err(|_| Error::<T>::EosChainError)?;
+		let action = Action::from_str(contract, "transfer", vec![permission_level], action_transfer)
+			.map_err(|_| Error::<T>::EosChainError)?;
 
-		// Construct action
-		let action = Action::transfer(eos_from, eos_to, amount.to_string().as_ref(), memo).map_err(|_| Error::<T>::EosChainError)?;
+		Ok(Self::init_actions(vec![action], threshold, from, token_type))
+	}
 
-		// Construct transaction
+	/// Initial transaction executing the actions in order
+	pub fn init_actions(
+		actions: Vec<Action>,
+		threshold: u8,
+		from: AccountId,
+		token_type: node_primitives::TokenType
+	) -> Self {
 		let multi_sig_tx = MultiSigTx {
 			chain_id: Default::default(),
 			raw_tx: Default::default(),
 			multi_sig: MultiSig::new(threshold),
-			action,
+			actions,
 			from,
 			token_type,
 		};
 
-		Ok(TxOut::Initial(multi_sig_tx))
+		TxOut::Initial(multi_sig_tx)
 	}
 
-	pub fn generate<T: crate::Trait>(self, eos_node_url: &str) -> Result<Self, Error<T>> {
+	pub fn generate<T: crate::Trait>(self, eos_nodes: &EosNodes) -> Result<Self, Error<T>> {
 		match self {
-			TxOut::Initial(mut multi_sig_tx) => {
-				// fetch info
-				let (chain_id, head_block_id) = eos_rpc::get_info(eos_node_url)?;
-				let chain_id: Vec<u8> = hex::decode(chain_id).map_err(|_| Error::<T>::DecodeHexError)?;
-
-				// fetch block
-				let (ref_block_num, ref_block_prefix) = eos_rpc::get_block(eos_node_url, head_block_id)?;
+			TxOut::Initial(multi_sig_tx) => {
+				// fetch info and the head block
+				let (info, block) = eos_rpc::get_chain_head::<T>(eos_nodes)?;
+				let chain_id: Vec<u8> = hex::decode(info.chain_id).map_err(|_| Error::<T>::DecodeHexError)?;
 
-				let actions = vec![multi_sig_tx.action.clone()];
+				let actions = multi_sig_tx.actions.clone();
 				// Construct transaction
 				let expiration = (sp_io::offchain::timestamp().add(Duration::from_millis(600 * 1000)).unix_millis() as f64 / 1000.0) as u32;
-				let tx = Transaction::new(expiration, ref_block_num, ref_block_prefix, actions);
-				multi_sig_tx.raw_tx = tx.to_serialize_data().map_err(|_| Error::<T>::EosChainError)?;
-				multi_sig_tx.chain_id = chain_id;
+				let tx = Transaction::new(expiration, block.ref_block_num, block.ref_block_prefix, actions);
+				let raw_tx = tx.to_serialize_data().map_err(|_| Error::<T>::EosChainError)?;
 
-				Ok(TxOut::Generated(multi_sig_tx))
+				Ok(multi_sig_tx.into_generated(chain_id, raw_tx))
 			},
 			_ => Err(Error::<T>::InvalidTxOutType)
 		}
 	}
 
-	pub fn sign<T: crate::Trait>(self, sk: SecretKey, author: AccountId) -> Result<Self, Error<T>> {
+	/// Check the transaction reported by offchain worker against this one. A report can only
+	/// generate the raw transaction executing the same actions, send the signed transaction with
+	/// the id of its raw transaction, or fail the transaction as it is.
+	pub fn ensure_reported<T: crate::Trait>(&self, reported: &Self) -> Result<(), Error<T>> {
+		match (self, reported) {
+			(TxOut::Initial(current), TxOut::Generated(generated)) => {
+				// only the raw transaction is generated, so from, to and amount in actions are kept
+				let expected = MultiSigTx {
+					chain_id: generated.chain_id.clone(),
+					raw_tx: generated.raw_tx.clone(),
+					..current.clone()
+				};
+				ensure!(*generated == expected, Error::<T>::TxOutMismatch);
+
+				let mut pos = 0;
+				let trx = Transaction::read(&generated.raw_tx, &mut pos).map_err(|_| Error::<T>::InvalidRawTransaction)?;
+				ensure!(pos == generated.raw_tx.len(), Error::<T>::InvalidRawTransaction);
+				ensure!(
+					trx.context_free_actions.is_empty() && trx.actions == current.actions,
+					Error::<T>::TxOutMismatch
+				);
+			}
+			(TxOut::Signed(current), TxOut::Processing { tx_id, multi_sig_tx }) => {
+				ensure!(multi_sig_tx == current, Error::<T>::TxOutMismatch);
+				ensure!(*tx_id == current.eos_tx_id(), Error::<T>::TxOutMismatch);
+			}
+			(current, TxOut::Fail { tx, .. }) => match current {
+				TxOut::Initial(current) | TxOut::Generated(current) => ensure!(tx == current, Error::<T>::TxOutMismatch),
+				_ => return Err(Error::<T>::InvalidTxOutTransition),
+			},
+			_ => return Err(Error::<T>::InvalidTxOutTransition),
+		}
+
+		Ok(())
+	}
+
+	/// Whether the notary has signed this transaction
+	pub fn has_signed(&self, author: &AccountId) -> bool {
 		match self {
-			TxOut::Generated(mut multi_sig_tx) => {
-				if multi_sig_tx.multi_sig.has_signed(author.clone()) {
-					return Err(Error::<T>::AlreadySignedByAuthor);
-				}
+			TxOut::Initial(tx) | TxOut::Generated(tx) | TxOut::Signed(tx) => tx.multi_sig.has_signed(author),
+			TxOut::Processing { multi_sig_tx, .. } => multi_sig_tx.multi_sig.has_signed(author),
+			TxOut::Fail { tx, .. } => tx.multi_sig.has_signed(author),
+			TxOut::Success(_) => false,
+		}
+	}
 
+	/// Sign the generated raw transaction with an EOS key, and return the serialized signature
+	pub fn eos_signature<T: crate::Trait>(&self, sk: SecretKey) -> Result<Vec<u8>, Error<T>> {
+		match self {
+			TxOut::Generated(multi_sig_tx) => {
 				let chain_id = &multi_sig_tx.chain_id;
 				let trx = Transaction::read(&multi_sig_tx.raw_tx, &mut 0).map_err(|_| Error::<T>::EosChainError)?;
 				let sig: Signature = trx.sign(sk, chain_id.clone()).map_err(|_| Error::<T>::EosChainError)?;
-				let sig_hex_data = sig.to_serialize_data().map_err(|_| Error::<T>::EosChainError)?;
 
-				multi_sig_tx.multi_sig.signatures.push(TxSig {author, signature: sig_hex_data});
-
-				if multi_sig_tx.multi_sig.reach_threshold() {
-					Ok(TxOut::Signed(multi_sig_tx))
-				} else {
-					Ok(TxOut::Generated(multi_sig_tx))
-				}
+				sig.to_serialize_data().map_err(|_| Error::<T>::EosChainError)
 			},
 			_ => Err(Error::<T>::InvalidTxOutType)
 		}
 	}
 
-	pub fn send<T: crate::Trait>(self, eos_node_url: &str) -> Result<Self, Error<T>> {
+	/// Add the signature of a notary made by its EOS key, the transaction is signed once signatures
+	/// reach the threshold
+	pub fn add_signature<T: crate::Trait>(
+		self,
+		author: AccountId,
+		signature: Vec<u8>,
+		eos_key: &[u8],
+		threshold: u8
+	) -> Result<Self, Error<T>> {
 		match self {
-			TxOut::Signed(multi_sig_tx) => {
-				let signed_trx = eos_rpc::serialize_push_transaction_params(&multi_sig_tx)?;
-
-				let transaction_vec = eos_rpc::push_transaction(eos_node_url, signed_trx)?;
+			TxOut::Generated(mut multi_sig_tx) => {
+				if multi_sig_tx.multi_sig.has_signed(&author) {
+					return Err(Error::<T>::AlreadySignedByAuthor);
+				}
+				ensure!(multi_sig_tx.recover_signer::<T>(&signature)? == eos_key, Error::<T>::EosSignerMismatch);
 
-				let transaction_id = core::str::from_utf8(transaction_vec.as_slice()).map_err(|_| Error::<T>::ParseUtf8Error)?;
-				let tx_id = Checksum256::from_str(&transaction_id).map_err(|_| Error::<T>::InvalidChecksum256)?;
+				multi_sig_tx.multi_sig.signatures.push(TxSig { author, signature });
+				multi_sig_tx.multi_sig.threshold = threshold;
 
-				Ok(TxOut::Processing {
-					tx_id,
-					multi_sig_tx,
-				})
+				Ok(Self::signing(multi_sig_tx))
 			},
 			_ => Err(Error::<T>::InvalidTxOutType)
 		}
 	}
-}
-
-pub(crate) mod eos_rpc {
-	use alloc::collections::btree_map::BTreeMap;
-	use alloc::string::ToString;
-	use crate::Error;
-	use lite_json::{parse_json, JsonValue, Serialize};
-	use sp_runtime::offchain::http;
-	use super::*;
-
-	const CHAIN_ID: [char; 8] = ['c', 'h', 'a', 'i', 'n', '_', 'i', 'd']; // key chain_id
-	const HEAD_BLOCK_ID: [char; 13] = ['h', 'e', 'a', 'd', '_', 'b', 'l', 'o', 'c', 'k', '_', 'i', 'd']; // key head_block_id
-	const GET_INFO_API: &'static str = "/v1/chain/get_info";
-	const GET_BLOCK_API: &'static str = "/v1/chain/get_block";
-	const PUSH_TRANSACTION_API: &'static str = "/v1/chain/push_transaction";
-
-	type ChainId = String;
-	type HeadBlockId = String;
-	type BlockNum = u16;
-	type RefBlockPrefix = u32;
-
-	pub(crate) fn get_info<T: crate::Trait>(node_url: &str) -> Result<(ChainId, HeadBlockId), Error<T>> {
-		let req_api = format!("{}{}", node_url, GET_INFO_API);
-		let pending = http::Request::post(&req_api, vec![b"{}"])
-			.add_header("Content-Type", "application/json")
-			.send().map_err(|_| Error::<T>::OffchainHttpError)?;
-		let response = pending.wait().map_err(|_| Error::<T>::OffchainHttpError)?;
-
-		let body = response.body().collect::<Vec<u8>>();
-		let body_str= core::str::from_utf8(body.as_slice()).map_err(|_| Error::<T>::ParseUtf8Error)?;
-		let node_info = parse_json(body_str).map_err(|_| Error::<T>::LiteJsonError)?;
-
-		let mut chain_id = Default::default();
-		let mut head_block_id = Default::default();
-
-		match node_info {
-			JsonValue::Object(ref json) => {
-				for item in json.iter() {
-					if item.0 == CHAIN_ID {
-						chain_id = {
-							match item.1 {
-								JsonValue::String(ref chars) => String::from_iter(chars.iter()),
-								_ => return Err(Error::<T>::EOSRpcError),
-							}
-						};
-					}
-					if item.0 == HEAD_BLOCK_ID {
-						head_block_id = {
-							match item.1 {
-								JsonValue::String(ref chars) => String::from_iter(chars.iter()),
-								_ => return Err(Error::<T>::EOSRpcError),
-							}
-						};
-					}
-				}
-			}
-			_ => return Err(Error::<T>::EOSRpcError),
-		}
-		if chain_id == String::default() || head_block_id == String::default() {
-			return Err(Error::<T>::EOSRpcError);
-		}
 
-		Ok((chain_id, head_block_id))
-	}
-
-	pub(crate) fn get_block<T: crate::Trait>(node_url: &str, head_block_id: String) -> Result<(BlockNum, RefBlockPrefix), Error<T>> {
-		let req_body = {
-			JsonValue::Object(vec![
-				(
-					"block_num_or_id".chars().collect::<Vec<_>>(),
-					JsonValue::String(head_block_id.chars().collect::<Vec<_>>()),
-				),
-			]).serialize()
-		};
-		let pending = http::Request::post(&format!("{}{}", node_url, GET_BLOCK_API), vec![req_body.as_slice()])
-			.add_header("Content-Type", "application/json")
-			.send().map_err(|_| Error::<T>::OffchainHttpError)?;
-		let response = pending.wait().map_err(|_| Error::<T>::OffchainHttpError)?;
-
-		let body = response.body().collect::<Vec<u8>>();
-		let body_str = core::str::from_utf8(body.as_slice()).map_err(|_| Error::<T>::ParseUtf8Error)?;
-
-		let maps = body_str.trim_matches(|c| c == '{' || c == '}')
-			.split(',').into_iter().filter_map(|i| {
-			if i.rfind("block_num").is_some() || i.rfind("ref_block_prefix").is_some() {
-				match i.split(':').collect::<Vec<&str>>().as_slice() {
-					[key, val] => Some((key.clone(), val.clone())),
-					_ => None
-				}
-			} else {
-				None
-			}
-		}).collect::<BTreeMap<_, _>>();
+	/// Only keep signatures of the notaries in a generated transaction. A signed transaction keeps
+	/// its signatures, since it may have been sent to EOS. Other transactions are returned as they are.
+	pub fn retain_signatures(self, notaries: &[AccountId], threshold: u8) -> Self {
+		match self {
+			TxOut::Generated(mut multi_sig_tx) => {
+				multi_sig_tx.multi_sig.signatures.retain(|sig| notaries.contains(&sig.author));
+				multi_sig_tx.multi_sig.threshold = threshold;
 
-		if maps.is_empty() {
-			return Err(Error::<T>::EOSRpcError);
+				Self::signing(multi_sig_tx)
+			},
+			other => other,
 		}
-
-		let block_num = {
-			let num_str = maps.get("\"block_num\"").ok_or(Error::<T>::ParseUtf8Error)?;
-			let block_num: u64 = num_str.parse().map_err(|_| Error::<T>::ParseUtf8Error)?;
-			(block_num & 0xffff) as u16
-		};
-		let ref_block_prefix = {
-			let prefix = maps.get("\"ref_block_prefix\"").ok_or(Error::<T>::ParseUtf8Error)?;
-			let prefix_num: u32 = prefix.parse().map_err(|_| Error::<T>::ParseUtf8Error)?;
-			prefix_num
-		};
-
-		Ok((block_num, ref_block_prefix))
-	}
-
-	pub(crate) fn push_transaction<T: crate::Trait>(node_url: &str, signed_trx: Vec<u8>) -> Result<Vec<u8>, Error<T>>{
-		let pending = http::Request::post(&format!("{}{}", node_url, PUSH_TRANSACTION_API), vec![signed_trx]).send().map_err(|_| Error::<T>::OffchainHttpError)?;
-		let response = pending.wait().map_err(|_| Error::<T>::OffchainHttpError)?;
-
-		let body = response.body().collect::<Vec<u8>>();
-		let body_str = String::from_utf8(body).map_err(|_| Error::<T>::ParseUtf8Error)?;
-		let tx_id = get_transaction_id(&body_str)?;
-
-		Ok(tx_id.into_bytes())
 	}
 
-	pub(crate) fn serialize_push_transaction_params<T: crate::Trait, AccountId>(multi_sig_tx: &MultiSigTx<AccountId>) -> Result<Vec<u8>, Error<T>> {
-		let serialized_signatures = {
-			let mut serialized_signatures = Vec::with_capacity(multi_sig_tx.multi_sig.signatures.len());
-			for tx_sig in multi_sig_tx.multi_sig.signatures.iter() {
-				let sig = Signature::read(&tx_sig.signature, &mut 0).map_err(|_| Error::<T>::EosChainError)?;
-				let val = JsonValue::String(sig.to_string().chars().collect());
-				serialized_signatures.push(val);
-			}
-			serialized_signatures
-		};
-
-		let signed_trx = JsonValue::Object(vec![
-			(
-				"signatures".chars().collect::<Vec<_>>(),
-				JsonValue::Array(serialized_signatures),
-			),
-			(
-				"compression".chars().collect::<Vec<_>>(),
-				JsonValue::String("none".chars().collect()),
-			),
-			(
-				"packed_context_free_data".chars().collect::<Vec<_>>(),
-				JsonValue::String(Vec::new()),
-			),
-			(
-				"packed_trx".chars().collect::<Vec<_>>(),
-				JsonValue::String(
-					hex::encode(&multi_sig_tx.raw_tx).chars().collect()
-				),
-			),
-		]).serialize();
-
-		Ok(signed_trx)
+	fn signing(multi_sig_tx: MultiSigTx<AccountId>) -> Self {
+		if multi_sig_tx.multi_sig.reach_threshold() {
+			TxOut::Signed(multi_sig_tx)
+		} else {
+			TxOut::Generated(multi_sig_tx)
+		}
 	}
 
-	pub(crate) fn get_transaction_id<T: crate::Trait>(trx_response: &str) -> Result<String, Error<T>> {
-		// error happens while pushing transaction to EOS node
-		if !trx_response.contains("transaction_id") && !trx_response.contains("processed") {
-			return Err(Error::<T>::EOSRpcError);
-		}
+	pub fn send<T: crate::Trait>(self, eos_nodes: &EosNodes) -> Result<Self, Error<T>> {
+		match self {
+			TxOut::Signed(multi_sig_tx) => {
+				let signed_trx = eos_rpc::serialize_push_transaction_params(&multi_sig_tx)?;
 
-		let mut trx_id = String::new();
-		let splited_strs: Vec<&str> = trx_response.trim_matches(|c| c == '{' || c == '}').split("processed").collect();
-		for s in &splited_strs {
-			if s.contains("transaction_id") {
-				trx_id = s.replace("transaction_id", "").chars().filter(|c| c.is_numeric() || c.is_alphabetic()).collect();
-				break;
-			}
-		}
+				let transaction_id = eos_rpc::push_transaction::<T>(eos_nodes, signed_trx)?;
+				let tx_id = Checksum256::from_str(&transaction_id).map_err(|_| Error::<T>::InvalidChecksum256)?;
 
-		if trx_id.eq("") {
-			return Err(Error::<T>::EOSRpcError);
+				Ok(TxOut::Processing {
+					tx_id,
+					multi_sig_tx,
+				})
+			},
+			_ => Err(Error::<T>::InvalidTxOutType)
 		}
-
-		Ok(trx_id)
 	}
 }
 
-#[cfg(test)]
-mod tests {
-	use super::*;
-	use eos_chain::Symbol;
-	use sp_std::str::FromStr;
-
-	#[test]
-	fn tx_send_with_multisig_should_work() {
-		let eos_node_url: &'static str = "http://127.0.0.1:8888/";
-		let raw_from = b"bifrost".to_vec();
-		let raw_to = b"alice".to_vec();
-		let sym = Symbol::from_str("4,EOS").unwrap();
-		let asset = Asset::new(1i64, sym);
-		let account_id_1= 1u64;
-		let account_id_2= 2u64;
-
-		// init tx
-		let tx_out = TxOut::<u64>::init(raw_from, raw_to, asset, 2);
-		assert!(tx_out.is_ok());
-
-		// generate Eos raw tx
-		let tx_out = tx_out.unwrap();
-		let tx_out = tx_out.generate(eos_node_url);
-		assert!(tx_out.is_ok());
-
-		// sign tx by account testa
-		let tx_out = tx_out.unwrap();
-		let sk = SecretKey::from_wif("5JgbL2ZnoEAhTudReWH1RnMuQS6DBeLZt4ucV6t8aymVEuYg7sr").unwrap();
-		let tx_out = tx_out.sign(sk, account_id_1);
-		assert!(tx_out.is_ok());
-
-		// tx by account testb
-		let tx_out = tx_out.unwrap();
-		let sk = SecretKey::from_wif("5J6vV6xbVV2UEwBYYDRQQ8yTDcSmHJw67XqRriF4EkEzWKUFNKj").unwrap();
-		let tx_out = tx_out.sign(sk, account_id_2);
-		assert!(tx_out.is_ok());
-
-		// send tx
-		let tx_out = tx_out.unwrap();
-		let tx_out = tx_out.send(eos_node_url);
-		assert!(tx_out.is_ok());
-	}
-}
+pub(crate) mod eos_rpc;
+
@@ -0,0 +1,373 @@
+// Copyright 2019-2020 Liebi Technologies.
+// This file is part of Bifrost.
+
+// Bifrost is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Bifrost is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Bifrost.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Client of EOS nodes for offchain worker.
+//!
+//! Nodes are configured in offchain local storage `EOS_NODE_URL`, separated by commas, and
+//! requests are sent to them in order until one responds. A node failing to respond is skipped
+//! for a while, which grows with its consecutive failures, the health is kept in offchain storage.
+
+use alloc::string::{String, ToString};
+use codec::{Decode, Encode};
+use core::{convert::TryFrom, iter::FromIterator, str::FromStr};
+use crate::Error;
+use frame_support::debug;
+use lite_json::{parse_json, JsonValue, Serialize};
+use sp_core::offchain::{Duration, StorageKind};
+use sp_runtime::offchain::http;
+use sp_std::prelude::*;
+use super::*;
+
+const GET_INFO_API: &'static str = "/v1/chain/get_info";
+const GET_BLOCK_API: &'static str = "/v1/chain/get_block";
+const PUSH_TRANSACTION_API: &'static str = "/v1/chain/push_transaction";
+const GET_CURRENCY_BALANCE_API: &'static str = "/v1/chain/get_currency_balance";
+
+/// Prefix of offchain storage key for health of each node
+const NODE_HEALTH_PREFIX: &[u8] = b"bridge-eos::node-health::";
+/// How long a request waits for the response, in milliseconds
+pub const REQUEST_TIMEOUT: u64 = 3_000;
+/// A failing node is skipped for 2^failures blocks, but no more than this
+pub const MAX_NODE_BACKOFF: u64 = 64;
+
+pub type BlockNum = u16;
+pub type RefBlockPrefix = u32;
+
+/// Error while requesting an EOS node
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum EosRpcError {
+	/// Request cannot be sent, or fails before the response
+	Http,
+	/// Node doesn't respond before the deadline
+	DeadlineReached,
+	/// Node responds with a status other than 2xx
+	HttpStatus(u16),
+	/// Response isn't utf8 encoded
+	InvalidUtf8,
+	/// Response isn't a valid json
+	InvalidJson,
+	/// Field is missing in the response
+	MissingField(&'static str),
+	/// Field in the response has an unexpected type or value
+	InvalidField(&'static str),
+	/// Node handles the request but replies an error with the code, like an expired transaction
+	NodeError(u64),
+	/// No node is configured, or all of them are skipped due to failures
+	NoAvailableNode,
+}
+
+impl EosRpcError {
+	/// Whether the node fails to serve the request, then the request goes to the next node.
+	/// An error replied by node will be the same on other nodes.
+	pub fn is_node_failure(&self) -> bool {
+		match self {
+			EosRpcError::NodeError(_) | EosRpcError::NoAvailableNode => false,
+			_ => true,
+		}
+	}
+}
+
+impl<T: crate::Trait> From<EosRpcError> for Error<T> {
+	fn from(e: EosRpcError) -> Self {
+		match e {
+			EosRpcError::Http | EosRpcError::DeadlineReached | EosRpcError::HttpStatus(_) => Error::<T>::OffchainHttpError,
+			EosRpcError::InvalidUtf8 => Error::<T>::ParseUtf8Error,
+			EosRpcError::InvalidJson => Error::<T>::LiteJsonError,
+			EosRpcError::MissingField(_) | EosRpcError::InvalidField(_) | EosRpcError::NodeError(_) => Error::<T>::EOSRpcError,
+			EosRpcError::NoAvailableNode => Error::<T>::NoEosNodeAvailable,
+		}
+	}
+}
+
+/// EOS nodes which requests are sent to, in the configured order
+#[derive(Clone, PartialEq, Debug)]
+pub struct EosNodes {
+	urls: Vec<String>,
+	/// Current block, for skipping failing nodes
+	now: u64,
+}
+
+impl EosNodes {
+	pub fn new(urls: Vec<String>, now: u64) -> Self {
+		Self { urls, now }
+	}
+
+	/// Node urls are separated by commas or whitespaces
+	pub fn parse(urls: &str, now: u64) -> Self {
+		let urls = urls.split(|c: char| c == ',' || c.is_whitespace())
+			.map(|url| url.trim_end_matches('/'))
+			.filter(|url| !url.is_empty())
+			.map(ToString::to_string)
+			.collect();
+
+		Self::new(urls, now)
+	}
+
+	pub fn urls(&self) -> &[String] {
+		&self.urls
+	}
+
+	/// Send the request to nodes in order until one of them serves it, nodes which have been
+	/// failing are skipped until their backoff ends.
+	pub fn request<R>(&self, request: impl Fn(&str) -> Result<R, EosRpcError>) -> Result<R, EosRpcError> {
+		let mut last_error = EosRpcError::NoAvailableNode;
+		for url in self.urls.iter() {
+			let (failures, retry_at) = node_health(url);
+			if self.now < retry_at {
+				continue;
+			}
+
+			match request(url) {
+				Ok(response) => {
+					if failures > 0 {
+						sp_io::offchain::local_storage_clear(StorageKind::PERSISTENT, &node_health_key(url));
+					}
+					return Ok(response);
+				}
+				Err(e) if e.is_node_failure() => {
+					debug::warn!(target: "bridge-eos", "EOS node {} failed due to {:?}", url, e);
+					let failures = failures.saturating_add(1);
+					let backoff = 2u64.saturating_pow(failures).min(MAX_NODE_BACKOFF);
+					sp_io::offchain::local_storage_set(
+						StorageKind::PERSISTENT,
+						&node_health_key(url),
+						&(failures, self.now.saturating_add(backoff)).encode()
+					);
+					last_error = e;
+				}
+				Err(e) => return Err(e),
+			}
+		}
+
+		Err(last_error)
+	}
+}
+
+fn node_health_key(url: &str) -> Vec<u8> {
+	let mut key = NODE_HEALTH_PREFIX.to_vec();
+	key.extend(url.as_bytes());
+	key
+}
+
+/// Health of the node, (consecutive failures, block when it can be requested again)
+pub fn node_health(url: &str) -> (u32, u64) {
+	sp_io::offchain::local_storage_get(StorageKind::PERSISTENT, &node_health_key(url))
+		.and_then(|health| Decode::decode(&mut &health[..]).ok())
+		.unwrap_or_default()
+}
+
+/// Post the json body to the api of the node, and decode the response as json
+fn post(url: &str, api: &str, body: Vec<u8>) -> Result<JsonValue, EosRpcError> {
+	let deadline = sp_io::offchain::timestamp().add(Duration::from_millis(REQUEST_TIMEOUT));
+	let pending = http::Request::post(&format!("{}{}", url, api), vec![body])
+		.add_header("Content-Type", "application/json")
+		.deadline(deadline)
+		.send().map_err(|_| EosRpcError::Http)?;
+	let response = pending.try_wait(deadline)
+		.map_err(|_| EosRpcError::DeadlineReached)?
+		.map_err(|_| EosRpcError::Http)?;
+
+	let code = response.code;
+	let body = response.body().collect::<Vec<u8>>();
+	let json = core::str::from_utf8(&body).map_err(|_| EosRpcError::InvalidUtf8)
+		.and_then(|body| parse_json(body).map_err(|_| EosRpcError::InvalidJson));
+
+	// EOS node replies errors like {"code":500,"message":"...","error":{"code":3040005,...}}
+	if let Some(e) = json.as_ref().ok().and_then(node_error) {
+		return Err(e);
+	}
+	if code / 100 != 2 {
+		return Err(EosRpcError::HttpStatus(code));
+	}
+
+	json
+}
+
+fn node_error(json: &JsonValue) -> Option<EosRpcError> {
+	let error = field(json, "error").ok()?;
+	let code = number_field(error, "code").unwrap_or_default();
+
+	Some(EosRpcError::NodeError(code))
+}
+
+fn field<'a>(json: &'a JsonValue, name: &'static str) -> Result<&'a JsonValue, EosRpcError> {
+	match json {
+		JsonValue::Object(ref fields) => fields.iter()
+			.find(|(key, _)| key.iter().copied().eq(name.chars()))
+			.map(|(_, value)| value)
+			.ok_or(EosRpcError::MissingField(name)),
+		_ => Err(EosRpcError::InvalidField(name)),
+	}
+}
+
+fn string_field(json: &JsonValue, name: &'static str) -> Result<String, EosRpcError> {
+	match field(json, name)? {
+		JsonValue::String(ref chars) if !chars.is_empty() => Ok(String::from_iter(chars.iter())),
+		_ => Err(EosRpcError::InvalidField(name)),
+	}
+}
+
+fn number_field(json: &JsonValue, name: &'static str) -> Result<u64, EosRpcError> {
+	match field(json, name)? {
+		JsonValue::Number(ref number) if number.fraction == 0 => {
+			u64::try_from(number.integer).map_err(|_| EosRpcError::InvalidField(name))
+		}
+		_ => Err(EosRpcError::InvalidField(name)),
+	}
+}
+
+/// Chain id and head block of the node, from `get_info`
+#[derive(Clone, PartialEq, Debug)]
+pub struct ChainInfo {
+	pub chain_id: String,
+	pub head_block_id: String,
+}
+
+impl ChainInfo {
+	fn decode(json: &JsonValue) -> Result<Self, EosRpcError> {
+		Ok(Self {
+			chain_id: string_field(json, "chain_id")?,
+			head_block_id: string_field(json, "head_block_id")?,
+		})
+	}
+}
+
+/// Reference block of a transaction, from `get_block`
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct BlockRef {
+	pub ref_block_num: BlockNum,
+	pub ref_block_prefix: RefBlockPrefix,
+}
+
+impl BlockRef {
+	fn decode(json: &JsonValue) -> Result<Self, EosRpcError> {
+		let block_num = number_field(json, "block_num")?;
+		let ref_block_prefix = number_field(json, "ref_block_prefix")?;
+
+		Ok(Self {
+			ref_block_num: (block_num & 0xffff) as BlockNum,
+			ref_block_prefix: RefBlockPrefix::try_from(ref_block_prefix)
+				.map_err(|_| EosRpcError::InvalidField("ref_block_prefix"))?,
+		})
+	}
+}
+
+pub fn get_info(node_url: &str) -> Result<ChainInfo, EosRpcError> {
+	let json = post(node_url, GET_INFO_API, b"{}".to_vec())?;
+
+	ChainInfo::decode(&json)
+}
+
+pub fn get_block(node_url: &str, block_id: &str) -> Result<BlockRef, EosRpcError> {
+	let req_body = JsonValue::Object(vec![
+		("block_num_or_id".chars().collect::<Vec<_>>(), JsonValue::String(block_id.chars().collect())),
+	]).serialize();
+	let json = post(node_url, GET_BLOCK_API, req_body)?;
+
+	BlockRef::decode(&json)
+}
+
+/// Chain id and the head block for a new transaction, they're from the same node
+pub(crate) fn get_chain_head<T: crate::Trait>(nodes: &EosNodes) -> Result<(ChainInfo, BlockRef), Error<T>> {
+	nodes.request(|node_url| {
+		let info = get_info(node_url)?;
+		let block = get_block(node_url, &info.head_block_id)?;
+		Ok((info, block))
+	}).map_err(Into::into)
+}
+
+/// Balance of the account in the token contract, the amount is in the precision of token
+pub(crate) fn get_currency_balance<T: crate::Trait>(
+	nodes: &EosNodes,
+	code: &str,
+	account: &str,
+	symbol: &str
+) -> Result<i64, Error<T>> {
+	let req_body = JsonValue::Object(vec![
+		("code".chars().collect::<Vec<_>>(), JsonValue::String(code.chars().collect())),
+		("account".chars().collect::<Vec<_>>(), JsonValue::String(account.chars().collect())),
+		("symbol".chars().collect::<Vec<_>>(), JsonValue::String(symbol.chars().collect())),
+	]).serialize();
+
+	nodes.request(|node_url| {
+		// balances are like ["1.0000 EOS"], it's empty if the account doesn't have the token
+		match post(node_url, GET_CURRENCY_BALANCE_API, req_body.clone())? {
+			JsonValue::Array(ref balances) => match balances.first() {
+				Some(JsonValue::String(ref chars)) => {
+					let asset = Asset::from_str(&String::from_iter(chars.iter()))
+						.map_err(|_| EosRpcError::InvalidField("balance"))?;
+					Ok(asset.amount)
+				}
+				None => Ok(0),
+				_ => Err(EosRpcError::InvalidField("balance")),
+			},
+			_ => Err(EosRpcError::InvalidField("balance")),
+		}
+	}).map_err(Into::into)
+}
+
+/// Push the signed transaction, and return its transaction id
+pub(crate) fn push_transaction<T: crate::Trait>(nodes: &EosNodes, signed_trx: Vec<u8>) -> Result<String, Error<T>> {
+	nodes.request(|node_url| {
+		let json = post(node_url, PUSH_TRANSACTION_API, signed_trx.clone())?;
+		string_field(&json, "transaction_id")
+	}).map_err(Into::into)
+}
+
+pub(crate) fn serialize_push_transaction_params<T: crate::Trait, AccountId>(multi_sig_tx: &MultiSigTx<AccountId>) -> Result<Vec<u8>, Error<T>> {
+	let serialized_signatures = {
+		let mut serialized_signatures = Vec::with_capacity(multi_sig_tx.multi_sig.signatures.len());
+		for tx_sig in multi_sig_tx.multi_sig.signatures.iter() {
+			let sig = Signature::read(&tx_sig.signature, &mut 0).map_err(|_| Error::<T>::EosChainError)?;
+			let val = JsonValue::String(sig.to_string().chars().collect());
+			serialized_signatures.push(val);
+		}
+		serialized_signatures
+	};
+
+	let signed_trx = JsonValue::Object(vec![
+		(
+			"signatures".chars().collect::<Vec<_>>(),
+			JsonValue::Array(serialized_signatures),
+		),
+		(
+			"compression".chars().collect::<Vec<_>>(),
+			JsonValue::String("none".chars().collect()),
+		),
+		(
+			"packed_context_free_data".chars().collect::<Vec<_>>(),
+			JsonValue::String(Vec::new()),
+		),
+		(
+			"packed_trx".chars().collect::<Vec<_>>(),
+			JsonValue::String(
+				hex::encode(&multi_sig_tx.raw_tx).chars().collect()
+			),
+		),
+	]).serialize();
+
+	Ok(signed_trx)
+}
+
+/// Transaction id in the response of `push_transaction`
+pub(crate) fn get_transaction_id(trx_response: &str) -> Result<String, EosRpcError> {
+	let json = parse_json(trx_response).map_err(|_| EosRpcError::InvalidJson)?;
+	if let Some(e) = node_error(&json) {
+		return Err(e);
+	}
+
+	string_field(&json, "transaction_id")
+}
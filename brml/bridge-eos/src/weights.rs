@@ -0,0 +1,203 @@
+// Copyright 2019-2020 Liebi Technologies.
+// This file is part of Bifrost.
+
+// Bifrost is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Bifrost is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Bifrost.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Weights for brml_bridge_eos
+//!
+//! Laid out as the output of the benchmark CLI, one function for each benchmark in
+//! `benchmarking.rs`. The numbers are estimated from the cost of EOS signature recovery and hashing,
+//! they haven't been measured yet. Replace this file with the generated one:
+//!
+//! ```sh
+//! bifrost-node benchmark --chain=dev --execution=wasm --wasm-execution=compiled \
+//!     --pallet=brml_bridge_eos --extrinsic=* --steps=50 --repeat=20 --raw
+//! ```
+
+#![allow(unused_parens)]
+
+use frame_support::{traits::Get, weights::Weight};
+use crate::Trait;
+
+/// How many producers are active on EOS
+pub const ACTIVE_PRODUCERS: u32 = 21;
+
+pub fn bridge_enable<T: Trait>() -> Weight {
+	(10_000_000 as Weight)
+		.saturating_add(T::DbWeight::get().writes(1 as Weight))
+}
+pub fn save_producer_schedule<T: Trait>(p: u32, ) -> Weight {
+	(10_000_000 as Weight)
+		.saturating_add((2_000_000 as Weight).saturating_mul(p as Weight))
+		.saturating_add(T::DbWeight::get().reads(6 as Weight))
+		.saturating_add(T::DbWeight::get().writes(5 as Weight))
+}
+pub fn init_schedule<T: Trait>(p: u32, ) -> Weight {
+	(10_000_000 as Weight)
+		.saturating_add((2_000_000 as Weight).saturating_mul(p as Weight))
+		.saturating_add(T::DbWeight::get().reads(3 as Weight))
+		.saturating_add(T::DbWeight::get().writes(3 as Weight))
+}
+pub fn grant_crosschain_privilege<T: Trait>() -> Weight {
+	(10_000_000 as Weight)
+		.saturating_add(T::DbWeight::get().reads(1 as Weight))
+		.saturating_add(T::DbWeight::get().writes(2 as Weight))
+}
+pub fn remove_crosschain_privilege<T: Trait>() -> Weight {
+	(10_000_000 as Weight)
+		.saturating_add(T::DbWeight::get().reads(1 as Weight))
+		.saturating_add(T::DbWeight::get().writes(2 as Weight))
+}
+pub fn set_contract_accounts<T: Trait>() -> Weight {
+	(10_000_000 as Weight)
+		.saturating_add(T::DbWeight::get().writes(1 as Weight))
+}
+pub fn set_bridge_paused<T: Trait>() -> Weight {
+	(10_000_000 as Weight)
+		.saturating_add(T::DbWeight::get().writes(2 as Weight))
+}
+pub fn set_withdraw_limit<T: Trait>() -> Weight {
+	(10_000_000 as Weight)
+		.saturating_add(T::DbWeight::get().writes(1 as Weight))
+}
+pub fn veto_delayed_withdraw<T: Trait>() -> Weight {
+	(30_000_000 as Weight)
+		.saturating_add(T::DbWeight::get().reads(6 as Weight))
+		.saturating_add(T::DbWeight::get().writes(7 as Weight))
+}
+pub fn execute_delayed_withdraws<T: Trait>(d: u32, e: u32, ) -> Weight {
+	(5_000_000 as Weight)
+		.saturating_add((100_000 as Weight).saturating_mul(d as Weight))
+		.saturating_add((40_000_000 as Weight).saturating_mul(e as Weight))
+		.saturating_add(T::DbWeight::get().reads(3 as Weight))
+		.saturating_add(T::DbWeight::get().reads((4 as Weight).saturating_mul(e as Weight)))
+		.saturating_add(T::DbWeight::get().writes(2 as Weight))
+		.saturating_add(T::DbWeight::get().writes((9 as Weight).saturating_mul(e as Weight)))
+}
+pub fn reset_bridge_reserve<T: Trait>() -> Weight {
+	(10_000_000 as Weight)
+		.saturating_add(T::DbWeight::get().reads(1 as Weight))
+		.saturating_add(T::DbWeight::get().writes(1 as Weight))
+}
+pub fn report_reserve_deficit<T: Trait>() -> Weight {
+	(10_000_000 as Weight)
+		.saturating_add(T::DbWeight::get().reads(4 as Weight))
+		.saturating_add(T::DbWeight::get().writes(1 as Weight))
+}
+pub fn set_notary_keys<T: Trait>(n: u32, p: u32, ) -> Weight {
+	(10_000_000 as Weight)
+		.saturating_add((10_000 as Weight).saturating_mul(n as Weight))
+		.saturating_add((1_000_000 as Weight).saturating_mul(p as Weight))
+		.saturating_add(T::DbWeight::get().reads(2 as Weight))
+		.saturating_add(T::DbWeight::get().reads((1 as Weight).saturating_mul(p as Weight)))
+		.saturating_add(T::DbWeight::get().writes(1 as Weight))
+		.saturating_add(T::DbWeight::get().writes((1 as Weight).saturating_mul(p as Weight)))
+}
+pub fn submit_signatures<T: Trait>(s: u32, ) -> Weight {
+	(10_000_000 as Weight)
+		.saturating_add((15_000_000 as Weight).saturating_mul(s as Weight))
+		.saturating_add(T::DbWeight::get().reads(2 as Weight))
+		.saturating_add(T::DbWeight::get().reads((2 as Weight).saturating_mul(s as Weight)))
+		.saturating_add(T::DbWeight::get().writes((2 as Weight).saturating_mul(s as Weight)))
+}
+pub fn set_notary_eos_key<T: Trait>(n: u32, ) -> Weight {
+	(10_000_000 as Weight)
+		.saturating_add((10_000 as Weight).saturating_mul(n as Weight))
+		.saturating_add(T::DbWeight::get().reads(1 as Weight))
+		.saturating_add(T::DbWeight::get().reads((1 as Weight).saturating_mul(n as Weight)))
+		.saturating_add(T::DbWeight::get().writes(1 as Weight))
+}
+pub fn set_bridge_token<T: Trait>() -> Weight {
+	(10_000_000 as Weight)
+		.saturating_add(T::DbWeight::get().reads(3 as Weight))
+		.saturating_add(T::DbWeight::get().writes(3 as Weight))
+}
+pub fn remove_bridge_token<T: Trait>() -> Weight {
+	(10_000_000 as Weight)
+		.saturating_add(T::DbWeight::get().reads(1 as Weight))
+		.saturating_add(T::DbWeight::get().writes(2 as Weight))
+}
+pub fn set_schedule_config<T: Trait>() -> Weight {
+	(10_000_000 as Weight)
+		.saturating_add(T::DbWeight::get().writes(3 as Weight))
+}
+pub fn set_relay_fee<T: Trait>() -> Weight {
+	(10_000_000 as Weight)
+		.saturating_add(T::DbWeight::get().writes(1 as Weight))
+}
+pub fn set_relay_reward<T: Trait>() -> Weight {
+	(10_000_000 as Weight)
+		.saturating_add(T::DbWeight::get().writes(1 as Weight))
+}
+pub fn claim_suspended_deposit<T: Trait>() -> Weight {
+	(30_000_000 as Weight)
+		.saturating_add(T::DbWeight::get().reads(4 as Weight))
+		.saturating_add(T::DbWeight::get().writes(4 as Weight))
+}
+pub fn set_bridge_token_enabled<T: Trait>() -> Weight {
+	(10_000_000 as Weight)
+		.saturating_add(T::DbWeight::get().reads(1 as Weight))
+		.saturating_add(T::DbWeight::get().writes(1 as Weight))
+}
+pub fn change_schedule<T: Trait>(h: u32, i: u32, p: u32, ) -> Weight {
+	(50_000_000 as Weight)
+		.saturating_add((328_000_000 as Weight).saturating_mul(h as Weight))
+		.saturating_add((4_000_000 as Weight).saturating_mul(i as Weight))
+		.saturating_add((4_000_000 as Weight).saturating_mul(p as Weight))
+		.saturating_add(T::DbWeight::get().reads(13 as Weight))
+		.saturating_add(T::DbWeight::get().writes(8 as Weight))
+}
+pub fn prove_action<T: Trait>(h: u32, i: u32, a: u32, p: u32, ) -> Weight {
+	(110_000_000 as Weight)
+		.saturating_add((328_000_000 as Weight).saturating_mul(h as Weight))
+		.saturating_add((4_000_000 as Weight).saturating_mul(i as Weight))
+		.saturating_add((3_000_000 as Weight).saturating_mul(a as Weight))
+		.saturating_add((2_000_000 as Weight).saturating_mul(p as Weight))
+		.saturating_add(T::DbWeight::get().reads(18 as Weight))
+		.saturating_add(T::DbWeight::get().writes(9 as Weight))
+}
+pub fn init_relay<T: Trait>() -> Weight {
+	(14_000_000 as Weight)
+		.saturating_add(T::DbWeight::get().writes(5 as Weight))
+}
+pub fn relay_block_headers<T: Trait>(h: u32, ) -> Weight {
+	(50_000_000 as Weight)
+		.saturating_add((328_000_000 as Weight).saturating_mul(h as Weight))
+		.saturating_add(T::DbWeight::get().reads(10 as Weight))
+		.saturating_add(T::DbWeight::get().reads((1 as Weight).saturating_mul(h as Weight)))
+		.saturating_add(T::DbWeight::get().writes(7 as Weight))
+		.saturating_add(T::DbWeight::get().writes((1 as Weight).saturating_mul(h as Weight)))
+}
+pub fn prove_action_by_relay<T: Trait>(a: u32, ) -> Weight {
+	(110_000_000 as Weight)
+		.saturating_add((3_000_000 as Weight).saturating_mul(a as Weight))
+		.saturating_add(T::DbWeight::get().reads(16 as Weight))
+		.saturating_add(T::DbWeight::get().writes(9 as Weight))
+}
+pub fn bridge_tx_report<T: Trait>(t: u32, ) -> Weight {
+	(10_000_000 as Weight)
+		.saturating_add(T::DbWeight::get().reads((4 as Weight).saturating_mul(t as Weight)))
+		.saturating_add(T::DbWeight::get().writes((5 as Weight).saturating_mul(t as Weight)))
+}
+pub fn prove_tx_out_expired<T: Trait>() -> Weight {
+	(10_000_000 as Weight)
+		.saturating_add(T::DbWeight::get().reads(5 as Weight))
+		.saturating_add(T::DbWeight::get().writes(2 as Weight))
+}
+pub fn asset_redeem<T: Trait>(m: u32, ) -> Weight {
+	(90_000_000 as Weight)
+		.saturating_add((10_000 as Weight).saturating_mul(m as Weight))
+		.saturating_add(T::DbWeight::get().reads(13 as Weight))
+		.saturating_add(T::DbWeight::get().writes(11 as Weight))
+}
@@ -14,29 +14,42 @@
 // You should have received a copy of the GNU General Public License
 // along with Bifrost.  If not, see <http://www.gnu.org/licenses/>.
 
+//! Bridge hub shared by all chain bridges.
+//!
+//! Blockchains and their assets are registered here, and every chain bridge, like bridge-eos,
+//! is plugged in by implementing `ChainBridge`. Redeeming an asset goes through the hub to the
+//! bridge connecting the asset's blockchain, and assets bridged from another blockchain are
+//! reported back to the hub.
+//!
+//! Accounts bind their addresses on other blockchains, with a proof of ownership where the
+//! chain bridge can verify one. Redeeming without a target address sends the asset to the
+//! first address bound on the asset's blockchain.
+//!
+//! Staking decisions made on Bifrost are sent as staking actions through the bridge connecting
+//! the asset's blockchain, so the assets locked by the bridge are staked to validators there.
+
 // Ensure we're `no_std` when compiling for Wasm.
 #![cfg_attr(not(feature = "std"), no_std)]
 
-use codec::{Decode, Encode};
-use frame_support::{decl_event, decl_module, decl_storage, ensure, Parameter};
-use sp_runtime::traits::{Member, AtLeast32Bit, SaturatedConversion};
+use codec::Encode;
+use frame_support::{decl_event, decl_error, decl_module, decl_storage, ensure, Parameter};
+use sp_runtime::{
+	DispatchResult,
+	traits::{Member, AtLeast32Bit, SaturatedConversion},
+};
 use sp_std::prelude::*;
-use system::ensure_root;
+use system::{ensure_root, ensure_signed};
 
 use node_primitives::{
-	AssetCreate, AssetIssue, AssetRedeem, BlockchainType,
-	BridgeAssetBalance, BridgeAssetFrom, BridgeAssetSymbol, BridgeAssetTo
+	AssetRedeem, AssetTrait, BlockchainType, BridgeAssetBalance, BridgeAssetFrom,
+	BridgeAssetSymbol, BridgeStake, ChainBridge, TokenType,
 };
 
 mod mock;
 mod tests;
 
-#[derive(Encode, Decode, Default, Clone, Eq, PartialEq, Debug)]
-pub struct Bank {
-	account: Vec<u8>,
-	authorities: Vec<Vec<u8>>,
-	threshold: u32,
-}
+/// Maximum number of addresses an account binds on each blockchain
+pub const MAX_BOUND_ACCOUNTS: usize = 8;
 
 /// The module configuration trait.
 pub trait Trait: system::Trait {
@@ -49,97 +62,370 @@ pub trait Trait: system::Trait {
 	/// The arithmetic type of asset identifier.
 	type AssetId: Member + Parameter + AtLeast32Bit + Default + Copy;
 
-	/// Assets create handler
-	type AssetCreate: AssetCreate<Self::AssetId, Self::Balance>;
-
-	/// Assets issue handler
-	type AssetIssue: AssetIssue<Self::AssetId, Self::AccountId, Self::Balance>;
-
 	/// The units in which we record asset precision.
 	type Precision: Member + Parameter + AtLeast32Bit + Default + Copy;
 
-	/// Bridge asset to another blockchain.
-	type BridgeAssetTo: BridgeAssetTo<Self::Precision, Self::Balance>;
+	/// The units in which we record costs.
+	type Cost: Member + Parameter + AtLeast32Bit + Default + Copy;
+
+	/// The units in which we record incomes.
+	type Income: Member + Parameter + AtLeast32Bit + Default + Copy;
+
+	/// Assets on Bifrost
+	type AssetTrait: AssetTrait<Self::AssetId, Self::AccountId, Self::Balance, Self::Cost, Self::Income>;
+
+	/// Bridges of other blockchains, like `(BridgeEos,)`
+	type ChainBridges: ChainBridge<Self::AccountId, Self::Precision, Self::Balance>;
+
+	/// Handler of assets bridged from other blockchains
+	type BridgeAssetFrom: BridgeAssetFrom<Self::AccountId, Self::Precision, Self::Balance>;
 }
 
 decl_event!(
-	pub enum Event<T> where <T as Trait>::AssetId {
-		/// Transaction from another blockchain was mapped.
-		BridgeTxMapped,
-		/// Transaction from another blockchain was received.
-		BridgeTxReceived,
-		/// Transaction received from another blockchain was confirmed.
-		BridgeTxReceiveConfirmed,
-		/// Transaction to another blockchain was sent.
-		BridgeTxSent,
-		/// Bridge asset was created.
-		BridgeAssetCreated(AssetId),
+	pub enum Event<T> where
+		<T as system::Trait>::AccountId,
+		<T as Trait>::AssetId,
+		<T as Trait>::Balance,
+	{
+		/// Blockchain was registered.
+		ChainRegistered(BlockchainType),
+		/// Blockchain was enabled or disabled.
+		ChainEnabled(BlockchainType, bool),
+		/// Asset was registered as the asset symbol on the blockchain.
+		BridgeAssetRegistered(AssetId, BlockchainType, Vec<u8>),
+		/// Asset was deregistered.
+		BridgeAssetDeregistered(AssetId),
+		/// Asset was bridged from another blockchain to the account.
+		BridgeTxReceived(AccountId, AssetId, TokenType, Balance),
+		/// Asset of the account was sent to the account on another blockchain.
+		BridgeTxSent(AccountId, AssetId, TokenType, Balance, BlockchainType, Vec<u8>),
+		/// Account bound the address on another blockchain.
+		AccountBound(AccountId, BlockchainType, Vec<u8>),
+		/// Address on another blockchain was unbound from the account.
+		AccountUnbound(AccountId, BlockchainType, Vec<u8>),
+		/// Asset was staked to the validator address on another blockchain for the validator.
+		BridgeStakeSent(AccountId, AssetId, Balance, BlockchainType, Vec<u8>),
+		/// Asset was un-staked from the validator address on another blockchain for the validator.
+		BridgeUnstakeSent(AccountId, AssetId, Balance, BlockchainType, Vec<u8>),
 	}
 );
 
+decl_error! {
+	pub enum Error for Module<T: Trait> {
+		/// The blockchain isn't registered
+		ChainNotRegistered,
+		/// The blockchain is registered already
+		ChainAlreadyRegistered,
+		/// No chain bridge connects the blockchain
+		NoChainBridge,
+		/// The blockchain is disabled
+		ChainDisabled,
+		/// The asset isn't registered
+		AssetNotRegistered,
+		/// The asset or the asset symbol is registered already
+		AssetAlreadyRegistered,
+		/// The asset doesn't exist on Bifrost
+		AssetNotExist,
+		/// Asset symbol is empty or longer than 32 bytes
+		InvalidSymbol,
+		/// Asset precision cannot exceed 16
+		InvalidPrecision,
+		/// No account on the blockchain to send asset to
+		NoForeignAccount,
+		/// The account isn't valid on the blockchain
+		InvalidForeignAccount,
+		/// The proof doesn't show ownership of the foreign account
+		InvalidAccountProof,
+		/// The foreign account is bound already
+		AccountAlreadyBound,
+		/// The foreign account isn't bound to the account
+		AccountNotBound,
+		/// The account binds too many addresses on the blockchain
+		TooManyBoundAccounts,
+	}
+}
+
 decl_storage! {
 	trait Store for Module<T: Trait> as Bridge {
-		// Associate account id in Bifrost to account in other blockchain
-		BridgeAccountIdToAccount get(fn bridge_account): map hasher(blake2_128_concat) T::AccountId => Vec<u8>;
+		/// Blockchains connected by the bridges, and whether they are enabled
+		RegisteredChains get(fn registered_chain): map hasher(blake2_128_concat) BlockchainType => Option<bool>;
+
+		/// Associate asset id in Bifrost to asset symbol in other blockchain
+		BridgeAssetIdToAsset get(fn bridge_asset): map hasher(blake2_128_concat) T::AssetId => Option<BridgeAssetSymbol<T::Precision>>;
 
-		// Associate asset id in Bifrost to asset symbol in other blockchain
-		BridgeAssetIdToAsset get(fn bridge_asset): map hasher(blake2_128_concat) T::AssetId => BridgeAssetSymbol<T::Precision>;
+		/// Associate asset symbol in other blockchain to asset id in Bifrost
+		BridgeAssetToAssetId get(fn bridge_asset_id): map hasher(blake2_128_concat) BridgeAssetSymbol<T::Precision> => Option<T::AssetId>;
 
-		// Associate account in other blockchain to account id in Bifrost
-		BridgeAccountToAccountId get(fn bridge_account_id): map hasher(blake2_128_concat) Vec<u8> => T::AccountId ;
+		/// Associate account id in Bifrost to accounts in other blockchain
+		BridgeAccountIdToAccount get(fn bridge_accounts): double_map hasher(blake2_128_concat) T::AccountId, hasher(blake2_128_concat) BlockchainType => Vec<Vec<u8>>;
 
-		// Associate asset symbol in other blockchain to asset id in Bifrost
-		BridgeAssetToAssetId get(fn bridge_asset_id): map hasher(blake2_128_concat) BridgeAssetSymbol<T::Precision> => T::AssetId;
+		/// Associate account in other blockchain to account id in Bifrost which proved owning it.
+		/// Accounts bound without proof are only kept by the account binding them.
+		BridgeAccountToAccountId get(fn bridge_account_id): double_map hasher(blake2_128_concat) BlockchainType, hasher(blake2_128_concat) Vec<u8> => Option<T::AccountId>;
+	}
+	add_extra_genesis {
+		/// Register assets of other blockchains, (asset, blockchain, symbol, precision)
+		config(bridge_assets): Vec<(T::AssetId, BlockchainType, Vec<u8>, T::Precision)>;
+		build(|config: &GenesisConfig<T>| {
+			for (asset_id, blockchain, symbol, precision) in config.bridge_assets.iter() {
+				RegisteredChains::insert(blockchain, true);
+				let asset_symbol = BridgeAssetSymbol::new(*blockchain, symbol.clone(), *precision);
+				<BridgeAssetIdToAsset<T>>::insert(asset_id, asset_symbol.clone());
+				<BridgeAssetToAssetId<T>>::insert(asset_symbol, asset_id);
+			}
+		});
 	}
 }
 
 decl_module! {
 	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		type Error = Error<T>;
 
 		fn deposit_event() = default;
 
-		pub fn bridge_asset_create(
+		/// Register a blockchain, which must be connected by one of the chain bridges
+		#[weight = T::DbWeight::get().reads_writes(1, 1)]
+		fn register_chain(origin, blockchain: BlockchainType) {
+			ensure_root(origin)?;
+
+			ensure!(!RegisteredChains::contains_key(blockchain), Error::<T>::ChainAlreadyRegistered);
+			ensure!(T::ChainBridges::connects(blockchain), Error::<T>::NoChainBridge);
+
+			RegisteredChains::insert(blockchain, true);
+
+			Self::deposit_event(RawEvent::ChainRegistered(blockchain));
+		}
+
+		/// Enable or disable bridging assets to a blockchain
+		#[weight = T::DbWeight::get().reads_writes(1, 1)]
+		fn set_chain_enabled(origin, blockchain: BlockchainType, enabled: bool) {
+			ensure_root(origin)?;
+
+			ensure!(RegisteredChains::contains_key(blockchain), Error::<T>::ChainNotRegistered);
+			RegisteredChains::insert(blockchain, enabled);
+
+			Self::deposit_event(RawEvent::ChainEnabled(blockchain, enabled));
+		}
+
+		/// Register an asset in Bifrost as the asset symbol on a registered blockchain
+		#[weight = T::DbWeight::get().reads_writes(4, 2)]
+		fn register_asset(
 			origin,
+			asset_id: T::AssetId,
 			blockchain: BlockchainType,
 			symbol: Vec<u8>,
 			precision: T::Precision,
 		) {
 			ensure_root(origin)?;
 
-			ensure!(symbol.len() > 0, "token symbol must great then 0");
-			ensure!(symbol.len() <= 32, "token symbol cannot exceed 32 bytes");
-			ensure!(precision.saturated_into::<u16>() <= 16, "token precision cannot exceed 16");
+			ensure!(RegisteredChains::contains_key(blockchain), Error::<T>::ChainNotRegistered);
+			ensure!(!symbol.is_empty() && symbol.len() <= 32, Error::<T>::InvalidSymbol);
+			ensure!(precision.saturated_into::<u16>() <= 16, Error::<T>::InvalidPrecision);
+			ensure!(T::AssetTrait::token_exists(asset_id), Error::<T>::AssetNotExist);
+
+			let asset_symbol = BridgeAssetSymbol::new(blockchain, symbol.clone(), precision);
+			ensure!(!<BridgeAssetIdToAsset<T>>::contains_key(asset_id), Error::<T>::AssetAlreadyRegistered);
+			ensure!(!<BridgeAssetToAssetId<T>>::contains_key(&asset_symbol), Error::<T>::AssetAlreadyRegistered);
 
-			let (asset_id, _) = T::AssetCreate::asset_create(symbol.clone(), precision.saturated_into::<u16>());
-			let asset_symbol = BridgeAssetSymbol::new(blockchain, symbol, precision);
 			<BridgeAssetIdToAsset<T>>::insert(asset_id, asset_symbol.clone());
 			<BridgeAssetToAssetId<T>>::insert(asset_symbol, asset_id);
 
-			Self::deposit_event(RawEvent::BridgeAssetCreated(asset_id));
+			Self::deposit_event(RawEvent::BridgeAssetRegistered(asset_id, blockchain, symbol));
+		}
+
+		/// Deregister an asset, it cannot be bridged anymore
+		#[weight = T::DbWeight::get().reads_writes(1, 2)]
+		fn deregister_asset(origin, asset_id: T::AssetId) {
+			ensure_root(origin)?;
+
+			let asset_symbol = <BridgeAssetIdToAsset<T>>::take(asset_id).ok_or(Error::<T>::AssetNotRegistered)?;
+			<BridgeAssetToAssetId<T>>::remove(asset_symbol);
+
+			Self::deposit_event(RawEvent::BridgeAssetDeregistered(asset_id));
+		}
+
+		/// Bind an address on a registered blockchain to the sender. The proof is the signature of
+		/// `binding_message` by the address, which may be omitted if the chain bridge cannot verify it.
+		/// Only a proven address is owned by the sender, an unproven one can be bound by anyone.
+		#[weight = T::DbWeight::get().reads_writes(3, 2)]
+		fn bind_account(origin, blockchain: BlockchainType, account: Vec<u8>, proof: Option<Vec<u8>>) {
+			let who = ensure_signed(origin)?;
+
+			ensure!(RegisteredChains::contains_key(blockchain), Error::<T>::ChainNotRegistered);
+			ensure!(T::ChainBridges::is_valid_account(blockchain, &account), Error::<T>::InvalidForeignAccount);
+
+			let mut accounts = <BridgeAccountIdToAccount<T>>::get(&who, blockchain);
+			ensure!(!accounts.contains(&account), Error::<T>::AccountAlreadyBound);
+			ensure!(accounts.len() < MAX_BOUND_ACCOUNTS, Error::<T>::TooManyBoundAccounts);
+
+			let proven = proof.is_some();
+			ensure!(
+				!proven || !<BridgeAccountToAccountId<T>>::contains_key(blockchain, &account),
+				Error::<T>::AccountAlreadyBound
+			);
+
+			let message = Self::binding_message(&who, blockchain);
+			ensure!(
+				T::ChainBridges::verify_account_proof(blockchain, &account, &message, proof.as_deref()),
+				Error::<T>::InvalidAccountProof
+			);
+
+			accounts.push(account.clone());
+			<BridgeAccountIdToAccount<T>>::insert(&who, blockchain, accounts);
+			if proven {
+				<BridgeAccountToAccountId<T>>::insert(blockchain, &account, &who);
+			}
+
+			Self::deposit_event(RawEvent::AccountBound(who, blockchain, account));
+		}
+
+		/// Unbind an address on a blockchain from the sender
+		#[weight = T::DbWeight::get().reads_writes(2, 2)]
+		fn unbind_account(origin, blockchain: BlockchainType, account: Vec<u8>) {
+			let who = ensure_signed(origin)?;
+
+			ensure!(
+				<BridgeAccountIdToAccount<T>>::get(&who, blockchain).contains(&account),
+				Error::<T>::AccountNotBound
+			);
+
+			Self::remove_binding(who, blockchain, account);
+		}
+
+		/// Unbind a proven address on a blockchain from the account which proved it, like one whose
+		/// key is compromised
+		#[weight = T::DbWeight::get().reads_writes(2, 2)]
+		fn force_unbind_account(origin, blockchain: BlockchainType, account: Vec<u8>) {
+			ensure_root(origin)?;
+
+			let owner = <BridgeAccountToAccountId<T>>::get(blockchain, &account).ok_or(Error::<T>::AccountNotBound)?;
+
+			Self::remove_binding(owner, blockchain, account);
 		}
 	}
 }
 
 impl<T: Trait> AssetRedeem<T::AssetId, T::AccountId, T::Balance> for Module<T> {
-	fn asset_redeem(asset_id: T::AssetId, target: T::AccountId, amount: T::Balance, to_name: Option<Vec<u8>>) {
-		let account = <BridgeAccountIdToAccount<T>>::get(target);
-		let symbol = <BridgeAssetIdToAsset<T>>::get(asset_id);
-		let bridge_asset = BridgeAssetBalance::<T::Precision, T::Balance> {
+	fn asset_redeem(
+		asset_id: T::AssetId,
+		token_type: TokenType,
+		target: T::AccountId,
+		amount: T::Balance,
+		to_name: Option<Vec<u8>>,
+	) -> DispatchResult {
+		let symbol = <BridgeAssetIdToAsset<T>>::get(asset_id).ok_or(Error::<T>::AssetNotRegistered)?;
+		let blockchain = symbol.blockchain;
+		ensure!(Self::chain_enabled(blockchain)?, Error::<T>::ChainDisabled);
+
+		// send to the first address bound on the blockchain by default
+		let to = match to_name {
+			Some(to) => to,
+			None => <BridgeAccountIdToAccount<T>>::get(&target, blockchain)
+				.into_iter()
+				.next()
+				.ok_or(Error::<T>::NoForeignAccount)?,
+		};
+		ensure!(T::ChainBridges::is_valid_account(blockchain, &to), Error::<T>::InvalidForeignAccount);
+
+		let bridge_asset = BridgeAssetBalance {
 			symbol,
 			amount,
+			memo: Vec::new(),
+			from: target.clone(),
+			token_type,
 		};
-		T::BridgeAssetTo::bridge_asset_to(account, bridge_asset);
+		T::ChainBridges::send_asset(to.clone(), bridge_asset)?;
+
+		Self::deposit_event(RawEvent::BridgeTxSent(target, asset_id, token_type, amount, blockchain, to));
+
+		Ok(())
+	}
+}
+
+impl<T: Trait> BridgeStake<T::AssetId, T::AccountId, T::Balance> for Module<T> {
+	fn bridge_stake(
+		asset_id: T::AssetId,
+		validator: T::AccountId,
+		validator_address: Vec<u8>,
+		amount: T::Balance,
+	) -> DispatchResult {
+		let bridge_asset = Self::staking_asset(asset_id, &validator, &validator_address, amount)?;
+		let blockchain = bridge_asset.symbol.blockchain;
+		T::ChainBridges::stake(validator_address.clone(), bridge_asset)?;
+
+		Self::deposit_event(RawEvent::BridgeStakeSent(validator, asset_id, amount, blockchain, validator_address));
+
+		Ok(())
+	}
+
+	fn bridge_unstake(
+		asset_id: T::AssetId,
+		validator: T::AccountId,
+		validator_address: Vec<u8>,
+		amount: T::Balance,
+	) -> DispatchResult {
+		let bridge_asset = Self::staking_asset(asset_id, &validator, &validator_address, amount)?;
+		let blockchain = bridge_asset.symbol.blockchain;
+		T::ChainBridges::unstake(validator_address.clone(), bridge_asset)?;
+
+		Self::deposit_event(RawEvent::BridgeUnstakeSent(validator, asset_id, amount, blockchain, validator_address));
+
+		Ok(())
 	}
 }
 
 impl<T: Trait> BridgeAssetFrom<T::AccountId, T::Precision, T::Balance> for Module<T> {
-	fn bridge_asset_from(target: T::AccountId, bridge_asset: BridgeAssetBalance<T::Precision, T::Balance>) {
-		let asset_id = <BridgeAssetToAssetId<T>>::get(bridge_asset.symbol);
-		T::AssetIssue::asset_issue(asset_id, TokenType::VToken, target.clone(), bridge_asset.amount);
+	fn bridge_asset_from(target: T::AccountId, bridge_asset: BridgeAssetBalance<T::AccountId, T::Precision, T::Balance>) {
+		// the chain bridge has issued the asset to target already
+		if let Some(asset_id) = <BridgeAssetToAssetId<T>>::get(&bridge_asset.symbol) {
+			let (token_type, amount) = (bridge_asset.token_type, bridge_asset.amount);
+			Self::deposit_event(RawEvent::BridgeTxReceived(target.clone(), asset_id, token_type, amount));
+		}
+
+		T::BridgeAssetFrom::bridge_asset_from(target, bridge_asset);
 	}
 }
 
-// The main implementation block for the module.
 impl<T: Trait> Module<T> {
+	/// Message signed by the foreign account to prove it's bound by the account
+	pub fn binding_message(who: &T::AccountId, blockchain: BlockchainType) -> Vec<u8> {
+		(b"bifrost/bind-account", who, blockchain).encode()
+	}
+
+	/// Whether the registered blockchain is enabled
+	fn chain_enabled(blockchain: BlockchainType) -> Result<bool, Error<T>> {
+		RegisteredChains::get(blockchain).ok_or(Error::<T>::ChainNotRegistered)
+	}
+
+	/// Asset staked to the validator address on the enabled blockchain of the asset
+	fn staking_asset(
+		asset_id: T::AssetId,
+		validator: &T::AccountId,
+		validator_address: &[u8],
+		amount: T::Balance,
+	) -> Result<BridgeAssetBalance<T::AccountId, T::Precision, T::Balance>, Error<T>> {
+		let symbol = <BridgeAssetIdToAsset<T>>::get(asset_id).ok_or(Error::<T>::AssetNotRegistered)?;
+		ensure!(Self::chain_enabled(symbol.blockchain)?, Error::<T>::ChainDisabled);
+		ensure!(
+			T::ChainBridges::is_valid_account(symbol.blockchain, validator_address),
+			Error::<T>::InvalidForeignAccount
+		);
 
+		Ok(BridgeAssetBalance {
+			symbol,
+			amount,
+			memo: Vec::new(),
+			from: validator.clone(),
+			token_type: TokenType::Token,
+		})
+	}
+
+	fn remove_binding(who: T::AccountId, blockchain: BlockchainType, account: Vec<u8>) {
+		if <BridgeAccountToAccountId<T>>::get(blockchain, &account).as_ref() == Some(&who) {
+			<BridgeAccountToAccountId<T>>::remove(blockchain, &account);
+		}
+		<BridgeAccountIdToAccount<T>>::mutate(&who, blockchain, |accounts| accounts.retain(|bound| *bound != account));
+
+		Self::deposit_event(RawEvent::AccountUnbound(who, blockchain, account));
+	}
 }
@@ -18,9 +18,10 @@
 
 #![cfg(test)]
 
+use core::cell::RefCell;
 use frame_support::{impl_outer_origin, impl_outer_dispatch, impl_outer_event, parameter_types};
 use sp_core::H256;
-use sp_runtime::{Perbill, traits::{BlakeTwo256, IdentityLookup}, testing::{Header, TestXt}};
+use sp_runtime::{DispatchError, Perbill, traits::{BlakeTwo256, IdentityLookup}, testing::Header};
 use super::*;
 
 impl_outer_origin! {
@@ -33,10 +34,6 @@ impl_outer_dispatch! {
 	}
 }
 
-/// An extrinsic type used for tests.
-pub type Extrinsic = TestXt<Call, ()>;
-type SubmitTransaction = system::offchain::TransactionSubmitter<(), Call, Extrinsic>;
-
 #[derive(Clone, Eq, PartialEq)]
 pub struct Test;
 
@@ -64,20 +61,89 @@ impl system::Trait for Test {
 	type MaximumBlockLength = MaximumBlockLength;
 	type AvailableBlockRatio = AvailableBlockRatio;
 	type Version = ();
+	type AccountData = ();
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type DbWeight = ();
+	type BlockExecutionWeight = ();
+	type ExtrinsicBaseWeight = ();
 }
 
-parameter_types! {
-	pub const SettlementPeriod: u64 = 24 * 60 * 10;
+thread_local! {
+	/// Assets sent by the mock EOS bridge, (target, symbol, amount)
+	pub static SENT_ASSETS: RefCell<Vec<(Vec<u8>, Vec<u8>, u64)>> = RefCell::new(Vec::new());
+	/// Staking actions of the mock EOS bridge, (validator, symbol, amount, is staking)
+	pub static STAKING_ACTIONS: RefCell<Vec<(Vec<u8>, Vec<u8>, u64, bool)>> = RefCell::new(Vec::new());
+}
+
+/// Mock bridge connecting EOS, it takes the asset from the account like bridge-eos
+pub struct MockEosBridge;
+
+impl ChainBridge<u64, u32, u64> for MockEosBridge {
+	fn connects(blockchain: BlockchainType) -> bool {
+		blockchain == BlockchainType::EOS
+	}
+
+	fn is_valid_account(_: BlockchainType, account: &[u8]) -> bool {
+		!account.is_empty() && account.len() <= 12
+	}
+
+	// the mock signature of a message is the message itself, and binding without proof is allowed like EOS
+	fn verify_account_proof(_: BlockchainType, _: &[u8], message: &[u8], proof: Option<&[u8]>) -> bool {
+		proof.map_or(true, |proof| proof == message)
+	}
+
+	fn send_asset(target: Vec<u8>, bridge_asset: BridgeAssetBalance<u64, u32, u64>) -> DispatchResult {
+		let asset_id = Assets::asset_id_exists(&bridge_asset.from, &bridge_asset.symbol.symbol, bridge_asset.symbol.precision as u16)
+			.ok_or(DispatchError::Other("asset doesn't exist"))?;
+		let balance = Assets::get_account_asset(&asset_id, bridge_asset.token_type, &bridge_asset.from).balance;
+		if balance < bridge_asset.amount {
+			return Err(DispatchError::Other("insufficient balance"));
+		}
+
+		Assets::asset_destroy(asset_id, bridge_asset.token_type, bridge_asset.from, bridge_asset.amount);
+		SENT_ASSETS.with(|sent| sent.borrow_mut().push((target, bridge_asset.symbol.symbol, bridge_asset.amount)));
+		Ok(())
+	}
+
+	fn stake(validator: Vec<u8>, bridge_asset: BridgeAssetBalance<u64, u32, u64>) -> DispatchResult {
+		STAKING_ACTIONS.with(|actions| {
+			actions.borrow_mut().push((validator, bridge_asset.symbol.symbol, bridge_asset.amount, true))
+		});
+		Ok(())
+	}
+
+	fn unstake(validator: Vec<u8>, bridge_asset: BridgeAssetBalance<u64, u32, u64>) -> DispatchResult {
+		STAKING_ACTIONS.with(|actions| {
+			actions.borrow_mut().push((validator, bridge_asset.symbol.symbol, bridge_asset.amount, false))
+		});
+		Ok(())
+	}
 }
 
 impl Trait for Test {
 	type Event = TestEvent;
 	type Balance = u64;
 	type AssetId = u32;
-	type AssetCreate = ();
-	type AssetIssue = ();
 	type Precision = u32;
-	type BridgeAssetTo = ();
+	type Cost = u64;
+	type Income = u64;
+	type AssetTrait = Assets;
+	type ChainBridges = (MockEosBridge,);
+	type BridgeAssetFrom = ();
+}
+
+impl assets::Trait for Test {
+	type Event = TestEvent;
+	type Balance = u64;
+	type AssetId = u32;
+	type Price = u64;
+	type Cost = u64;
+	type Income = u64;
+	type Convert = u64;
+	type AssetRedeem = Bridge;
+	type FetchConvertPrice = ();
+	type ClearingHandler = ();
 }
 
 mod bridge {
@@ -86,14 +152,25 @@ mod bridge {
 
 impl_outer_event! {
 	pub enum TestEvent for Test {
+		system<T>,
 		bridge<T>,
+		assets<T>,
 	}
 }
 
 pub type Bridge = Module<Test>;
+pub type BridgeError = Error<Test>;
+pub type Assets = assets::Module<Test>;
 pub type System = system::Module<Test>;
 
-pub fn new_test_ext() -> sp_io::TestExternalities {
-	let t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
-	t.into()
+pub(crate) fn sent_assets() -> Vec<(Vec<u8>, Vec<u8>, u64)> {
+	SENT_ASSETS.with(|sent| sent.borrow().clone())
+}
+
+pub(crate) fn staking_actions() -> Vec<(Vec<u8>, Vec<u8>, u64, bool)> {
+	STAKING_ACTIONS.with(|actions| actions.borrow().clone())
+}
+
+pub(crate) fn new_test_ext() -> sp_io::TestExternalities {
+	system::GenesisConfig::default().build_storage::<Test>().unwrap().into()
 }
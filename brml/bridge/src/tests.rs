@@ -16,4 +16,340 @@
 
 //! Tests for the module.
 
-#![cfg(test)]
\ No newline at end of file
+#![cfg(test)]
+
+use crate::*;
+use crate::mock::*;
+use frame_support::{assert_ok, assert_noop};
+use node_primitives::AssetSymbol;
+use sp_runtime::DispatchError;
+
+const EOS_ASSET_ID: u32 = 2;
+
+fn has_event(event: RawEvent<u64, u32, u64>) -> bool {
+	System::events().iter().any(|record| record.event == TestEvent::bridge(event.clone()))
+}
+
+/// Create EOS asset, issue 100 EOS to account 1, and register it on EOS
+fn setup_eos_asset() {
+	System::set_block_number(1);
+	assert_ok!(Assets::create(Origin::ROOT, b"EOS".to_vec(), 4));
+	assert_ok!(Assets::issue(Origin::ROOT, AssetSymbol::EOS, TokenType::Token, 1, 100));
+	assert_ok!(Bridge::register_chain(Origin::ROOT, BlockchainType::EOS));
+	assert_ok!(Bridge::register_asset(Origin::ROOT, EOS_ASSET_ID, BlockchainType::EOS, b"EOS".to_vec(), 4));
+}
+
+#[test]
+fn register_chain_should_work() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+
+		assert_noop!(
+			Bridge::register_chain(Origin::signed(1), BlockchainType::EOS),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(Bridge::register_chain(Origin::ROOT, BlockchainType::BIFROST), BridgeError::NoChainBridge);
+
+		assert_ok!(Bridge::register_chain(Origin::ROOT, BlockchainType::EOS));
+		assert_eq!(Bridge::registered_chain(BlockchainType::EOS), Some(true));
+		assert!(has_event(RawEvent::ChainRegistered(BlockchainType::EOS)));
+		assert_noop!(Bridge::register_chain(Origin::ROOT, BlockchainType::EOS), BridgeError::ChainAlreadyRegistered);
+
+		assert_ok!(Bridge::set_chain_enabled(Origin::ROOT, BlockchainType::EOS, false));
+		assert_eq!(Bridge::registered_chain(BlockchainType::EOS), Some(false));
+		assert_noop!(
+			Bridge::set_chain_enabled(Origin::ROOT, BlockchainType::BIFROST, true),
+			BridgeError::ChainNotRegistered
+		);
+	});
+}
+
+#[test]
+fn register_asset_should_work() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(Assets::create(Origin::ROOT, b"EOS".to_vec(), 4));
+
+		assert_noop!(
+			Bridge::register_asset(Origin::ROOT, EOS_ASSET_ID, BlockchainType::EOS, b"EOS".to_vec(), 4),
+			BridgeError::ChainNotRegistered
+		);
+		assert_ok!(Bridge::register_chain(Origin::ROOT, BlockchainType::EOS));
+
+		assert_noop!(
+			Bridge::register_asset(Origin::ROOT, EOS_ASSET_ID, BlockchainType::EOS, vec![], 4),
+			BridgeError::InvalidSymbol
+		);
+		assert_noop!(
+			Bridge::register_asset(Origin::ROOT, EOS_ASSET_ID, BlockchainType::EOS, vec![b'A'; 33], 4),
+			BridgeError::InvalidSymbol
+		);
+		assert_noop!(
+			Bridge::register_asset(Origin::ROOT, EOS_ASSET_ID, BlockchainType::EOS, b"EOS".to_vec(), 17),
+			BridgeError::InvalidPrecision
+		);
+		assert_noop!(
+			Bridge::register_asset(Origin::ROOT, 100, BlockchainType::EOS, b"EOS".to_vec(), 4),
+			BridgeError::AssetNotExist
+		);
+
+		assert_ok!(Bridge::register_asset(Origin::ROOT, EOS_ASSET_ID, BlockchainType::EOS, b"EOS".to_vec(), 4));
+		let symbol = BridgeAssetSymbol::new(BlockchainType::EOS, b"EOS".to_vec(), 4);
+		assert_eq!(Bridge::bridge_asset(EOS_ASSET_ID), Some(symbol.clone()));
+		assert_eq!(Bridge::bridge_asset_id(&symbol), Some(EOS_ASSET_ID));
+		assert!(has_event(RawEvent::BridgeAssetRegistered(EOS_ASSET_ID, BlockchainType::EOS, b"EOS".to_vec())));
+
+		// neither the asset nor the symbol can be registered twice
+		assert_noop!(
+			Bridge::register_asset(Origin::ROOT, EOS_ASSET_ID, BlockchainType::EOS, b"IQ".to_vec(), 3),
+			BridgeError::AssetAlreadyRegistered
+		);
+		assert_ok!(Assets::create(Origin::ROOT, b"IQ".to_vec(), 3));
+		let iq_id = Assets::next_asset_id() - 1;
+		assert_noop!(
+			Bridge::register_asset(Origin::ROOT, iq_id, BlockchainType::EOS, b"EOS".to_vec(), 4),
+			BridgeError::AssetAlreadyRegistered
+		);
+
+		assert_ok!(Bridge::deregister_asset(Origin::ROOT, EOS_ASSET_ID));
+		assert_eq!(Bridge::bridge_asset(EOS_ASSET_ID), None);
+		assert_eq!(Bridge::bridge_asset_id(&symbol), None);
+		assert_noop!(Bridge::deregister_asset(Origin::ROOT, EOS_ASSET_ID), BridgeError::AssetNotRegistered);
+	});
+}
+
+#[test]
+fn redeem_should_send_asset_through_chain_bridge() {
+	new_test_ext().execute_with(|| {
+		setup_eos_asset();
+
+		assert_ok!(Assets::redeem(Origin::signed(1), AssetSymbol::EOS, TokenType::Token, 30, Some(b"alice".to_vec())));
+
+		assert_eq!(Assets::asset_balances(EOS_ASSET_ID, TokenType::Token, 1), 70);
+		assert_eq!(sent_assets(), vec![(b"alice".to_vec(), b"EOS".to_vec(), 30)]);
+		assert!(has_event(RawEvent::BridgeTxSent(
+			1, EOS_ASSET_ID, TokenType::Token, 30, BlockchainType::EOS, b"alice".to_vec()
+		)));
+	});
+}
+
+#[test]
+fn redeem_should_be_rejected_by_bridge_hub() {
+	new_test_ext().execute_with(|| {
+		setup_eos_asset();
+
+		assert_noop!(
+			Assets::redeem(Origin::signed(1), AssetSymbol::EOS, TokenType::Token, 30, None),
+			BridgeError::NoForeignAccount
+		);
+		assert_noop!(
+			Assets::redeem(Origin::signed(1), AssetSymbol::EOS, TokenType::Token, 30, Some(b"alice.bifrost.eos".to_vec())),
+			BridgeError::InvalidForeignAccount
+		);
+
+		assert_ok!(Bridge::set_chain_enabled(Origin::ROOT, BlockchainType::EOS, false));
+		assert_noop!(
+			Assets::redeem(Origin::signed(1), AssetSymbol::EOS, TokenType::Token, 30, Some(b"alice".to_vec())),
+			BridgeError::ChainDisabled
+		);
+
+		assert_ok!(Bridge::deregister_asset(Origin::ROOT, EOS_ASSET_ID));
+		assert_noop!(
+			Assets::redeem(Origin::signed(1), AssetSymbol::EOS, TokenType::Token, 30, Some(b"alice".to_vec())),
+			BridgeError::AssetNotRegistered
+		);
+
+		assert_eq!(Assets::asset_balances(EOS_ASSET_ID, TokenType::Token, 1), 100);
+		assert!(sent_assets().is_empty());
+	});
+}
+
+#[test]
+fn bridge_asset_from_should_be_recorded() {
+	new_test_ext().execute_with(|| {
+		setup_eos_asset();
+
+		let bridge_asset = BridgeAssetBalance {
+			symbol: BridgeAssetSymbol::new(BlockchainType::EOS, b"EOS".to_vec(), 4),
+			amount: 20,
+			memo: vec![],
+			from: 2,
+			token_type: TokenType::Token,
+		};
+		<Bridge as BridgeAssetFrom<_, _, _>>::bridge_asset_from(2, bridge_asset);
+
+		assert!(has_event(RawEvent::BridgeTxReceived(2, EOS_ASSET_ID, TokenType::Token, 20)));
+	});
+}
+
+#[test]
+fn genesis_should_register_bridge_assets() {
+	let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	GenesisConfig::<Test> {
+		bridge_assets: vec![(EOS_ASSET_ID, BlockchainType::EOS, b"EOS".to_vec(), 4)],
+	}.assimilate_storage(&mut t).unwrap();
+
+	sp_io::TestExternalities::from(t).execute_with(|| {
+		let symbol = BridgeAssetSymbol::new(BlockchainType::EOS, b"EOS".to_vec(), 4);
+		assert_eq!(Bridge::registered_chain(BlockchainType::EOS), Some(true));
+		assert_eq!(Bridge::bridge_asset(EOS_ASSET_ID), Some(symbol.clone()));
+		assert_eq!(Bridge::bridge_asset_id(&symbol), Some(EOS_ASSET_ID));
+	});
+}
+
+#[test]
+fn bind_account_should_work() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+
+		assert_noop!(
+			Bridge::bind_account(Origin::signed(1), BlockchainType::EOS, b"alice".to_vec(), None),
+			BridgeError::ChainNotRegistered
+		);
+		assert_ok!(Bridge::register_chain(Origin::ROOT, BlockchainType::EOS));
+
+		assert_noop!(
+			Bridge::bind_account(Origin::signed(1), BlockchainType::EOS, b"alice.bifrost.eos".to_vec(), None),
+			BridgeError::InvalidForeignAccount
+		);
+		assert_noop!(
+			Bridge::bind_account(Origin::signed(1), BlockchainType::EOS, b"alice".to_vec(), Some(b"forged".to_vec())),
+			BridgeError::InvalidAccountProof
+		);
+
+		// a proof signed for account 2 cannot bind to account 1
+		let proof = Bridge::binding_message(&2, BlockchainType::EOS);
+		assert_noop!(
+			Bridge::bind_account(Origin::signed(1), BlockchainType::EOS, b"alice".to_vec(), Some(proof)),
+			BridgeError::InvalidAccountProof
+		);
+
+		let proof = Bridge::binding_message(&1, BlockchainType::EOS);
+		assert_ok!(Bridge::bind_account(Origin::signed(1), BlockchainType::EOS, b"alice".to_vec(), Some(proof)));
+		assert_ok!(Bridge::bind_account(Origin::signed(1), BlockchainType::EOS, b"bob".to_vec(), None));
+		assert_eq!(Bridge::bridge_accounts(1, BlockchainType::EOS), vec![b"alice".to_vec(), b"bob".to_vec()]);
+		assert_eq!(Bridge::bridge_account_id(BlockchainType::EOS, b"alice".to_vec()), Some(1));
+		assert!(has_event(RawEvent::AccountBound(1, BlockchainType::EOS, b"alice".to_vec())));
+		assert_noop!(
+			Bridge::bind_account(Origin::signed(1), BlockchainType::EOS, b"bob".to_vec(), None),
+			BridgeError::AccountAlreadyBound
+		);
+
+		// unproven address isn't owned by the account, so it cannot be squatted
+		assert_eq!(Bridge::bridge_account_id(BlockchainType::EOS, b"bob".to_vec()), None);
+		assert_ok!(Bridge::bind_account(Origin::signed(2), BlockchainType::EOS, b"bob".to_vec(), None));
+		let proof = Bridge::binding_message(&3, BlockchainType::EOS);
+		assert_ok!(Bridge::bind_account(Origin::signed(3), BlockchainType::EOS, b"bob".to_vec(), Some(proof)));
+		assert_eq!(Bridge::bridge_account_id(BlockchainType::EOS, b"bob".to_vec()), Some(3));
+
+		// proven address is owned by one account
+		let proof = Bridge::binding_message(&2, BlockchainType::EOS);
+		assert_noop!(
+			Bridge::bind_account(Origin::signed(2), BlockchainType::EOS, b"alice".to_vec(), Some(proof)),
+			BridgeError::AccountAlreadyBound
+		);
+		assert_ok!(Bridge::bind_account(Origin::signed(2), BlockchainType::EOS, b"alice".to_vec(), None));
+		assert_eq!(Bridge::bridge_account_id(BlockchainType::EOS, b"alice".to_vec()), Some(1));
+	});
+}
+
+#[test]
+fn bound_accounts_should_be_limited() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Bridge::register_chain(Origin::ROOT, BlockchainType::EOS));
+
+		for i in 0..MAX_BOUND_ACCOUNTS {
+			let account = format!("account{}", i + 1).into_bytes();
+			assert_ok!(Bridge::bind_account(Origin::signed(1), BlockchainType::EOS, account, None));
+		}
+		assert_noop!(
+			Bridge::bind_account(Origin::signed(1), BlockchainType::EOS, b"alice".to_vec(), None),
+			BridgeError::TooManyBoundAccounts
+		);
+	});
+}
+
+#[test]
+fn unbind_account_should_work() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(Bridge::register_chain(Origin::ROOT, BlockchainType::EOS));
+		assert_ok!(Bridge::bind_account(Origin::signed(1), BlockchainType::EOS, b"alice".to_vec(), None));
+		let proof = Bridge::binding_message(&1, BlockchainType::EOS);
+		assert_ok!(Bridge::bind_account(Origin::signed(1), BlockchainType::EOS, b"bob".to_vec(), Some(proof)));
+
+		assert_noop!(
+			Bridge::unbind_account(Origin::signed(2), BlockchainType::EOS, b"alice".to_vec()),
+			BridgeError::AccountNotBound
+		);
+		assert_ok!(Bridge::unbind_account(Origin::signed(1), BlockchainType::EOS, b"alice".to_vec()));
+		assert_eq!(Bridge::bridge_accounts(1, BlockchainType::EOS), vec![b"bob".to_vec()]);
+		assert_eq!(Bridge::bridge_account_id(BlockchainType::EOS, b"alice".to_vec()), None);
+		assert!(has_event(RawEvent::AccountUnbound(1, BlockchainType::EOS, b"alice".to_vec())));
+
+		// root removes a proven binding, then the address can be proven by another account
+		assert_noop!(
+			Bridge::force_unbind_account(Origin::signed(2), BlockchainType::EOS, b"bob".to_vec()),
+			DispatchError::BadOrigin
+		);
+		assert_ok!(Bridge::force_unbind_account(Origin::ROOT, BlockchainType::EOS, b"bob".to_vec()));
+		assert!(Bridge::bridge_accounts(1, BlockchainType::EOS).is_empty());
+		let proof = Bridge::binding_message(&2, BlockchainType::EOS);
+		assert_ok!(Bridge::bind_account(Origin::signed(2), BlockchainType::EOS, b"bob".to_vec(), Some(proof)));
+		assert_eq!(Bridge::bridge_account_id(BlockchainType::EOS, b"bob".to_vec()), Some(2));
+		assert_noop!(
+			Bridge::force_unbind_account(Origin::ROOT, BlockchainType::EOS, b"alice".to_vec()),
+			BridgeError::AccountNotBound
+		);
+	});
+}
+
+#[test]
+fn redeem_should_default_to_bound_account() {
+	new_test_ext().execute_with(|| {
+		setup_eos_asset();
+		assert_ok!(Bridge::bind_account(Origin::signed(1), BlockchainType::EOS, b"alice".to_vec(), None));
+		assert_ok!(Bridge::bind_account(Origin::signed(1), BlockchainType::EOS, b"bob".to_vec(), None));
+
+		assert_ok!(Assets::redeem(Origin::signed(1), AssetSymbol::EOS, TokenType::Token, 30, None));
+		assert_ok!(Assets::redeem(Origin::signed(1), AssetSymbol::EOS, TokenType::Token, 20, Some(b"bob".to_vec())));
+
+		assert_eq!(Assets::asset_balances(EOS_ASSET_ID, TokenType::Token, 1), 50);
+		assert_eq!(sent_assets(), vec![
+			(b"alice".to_vec(), b"EOS".to_vec(), 30),
+			(b"bob".to_vec(), b"EOS".to_vec(), 20),
+		]);
+	});
+}
+
+#[test]
+fn staking_should_be_sent_to_chain_bridge() {
+	new_test_ext().execute_with(|| {
+		setup_eos_asset();
+
+		assert_ok!(Bridge::bridge_stake(EOS_ASSET_ID, 5, b"producer1".to_vec(), 40));
+		assert_ok!(Bridge::bridge_unstake(EOS_ASSET_ID, 5, b"producer1".to_vec(), 15));
+		assert_eq!(staking_actions(), vec![
+			(b"producer1".to_vec(), b"EOS".to_vec(), 40, true),
+			(b"producer1".to_vec(), b"EOS".to_vec(), 15, false),
+		]);
+		assert!(has_event(RawEvent::BridgeStakeSent(5, EOS_ASSET_ID, 40, BlockchainType::EOS, b"producer1".to_vec())));
+		assert!(has_event(RawEvent::BridgeUnstakeSent(5, EOS_ASSET_ID, 15, BlockchainType::EOS, b"producer1".to_vec())));
+	});
+}
+
+#[test]
+fn staking_should_be_rejected() {
+	new_test_ext().execute_with(|| {
+		setup_eos_asset();
+
+		// asset isn't registered on any blockchain
+		assert_noop!(Bridge::bridge_stake(0, 5, b"producer1".to_vec(), 40), BridgeError::AssetNotRegistered);
+		// invalid EOS account
+		assert_noop!(Bridge::bridge_stake(EOS_ASSET_ID, 5, b"producer1.too.long".to_vec(), 40), BridgeError::InvalidForeignAccount);
+
+		assert_ok!(Bridge::set_chain_enabled(Origin::ROOT, BlockchainType::EOS, false));
+		assert_noop!(Bridge::bridge_unstake(EOS_ASSET_ID, 5, b"producer1".to_vec(), 40), BridgeError::ChainDisabled);
+		assert!(staking_actions().is_empty());
+	});
+}
@@ -1,111 +1,262 @@
 //! A pallet to interact with Chainlink nodes
+//!
+//! Requests are tracked on-chain: each one gets an incrementing identifier, is assigned to a
+//! registered operator, and escrows the requester's fee until it is either fulfilled by that
+//! operator, cancelled by the requester or killed after `ValidityPeriod` blocks.
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
-use frame_support::{decl_event, decl_module, decl_storage};
-use codec::Encode;
+mod mock;
+mod tests;
+
+use frame_support::{
+	decl_event, decl_module, decl_storage, decl_error, ensure, Parameter,
+	dispatch::DispatchResult,
+	traits::{Currency, Get, ReservableCurrency, BalanceStatus},
+	weights::{GetDispatchInfo, Weight},
+};
+use codec::{Encode, Decode};
+use sp_runtime::traits::{Dispatchable, Saturating, Zero};
 use sp_std::prelude::Vec;
+use system::{ensure_root, ensure_signed};
+
+pub type BalanceOf<T> = <<T as Trait>::Currency as Currency<<T as system::Trait>::AccountId>>::Balance;
 
 pub trait Trait: system::Trait {
 	type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+
+	/// Currency used to pay operators for fulfilled requests.
+	type Currency: ReservableCurrency<Self::AccountId>;
+
+	/// The call dispatched with the operator's answer once a request is fulfilled.
+	type Callback: Parameter + Dispatchable<Origin = <Self as system::Trait>::Origin> + CallbackWithParameter + GetDispatchInfo;
+
+	/// The most weight a callback can take, it's charged on every answer.
+	type MaxCallbackWeight: Get<Weight>;
+
+	/// How many blocks a request stays valid before it is killed and its fee refunded.
+	type ValidityPeriod: Get<Self::BlockNumber>;
+}
+
+/// A callback that can be filled with the result of an oracle request.
+pub trait CallbackWithParameter {
+	/// Returns a copy of this callback carrying `result`, or `None` if `result` cannot be decoded.
+	fn with_result(&self, result: Vec<u8>) -> Option<Self> where Self: Sized;
 }
 
 pub type SpecIndex = u32;
 pub type RequestIdentifier = u64;
 pub type DataVersion = u64;
 
-pub fn create_request_event_from_parameters<T: system::Trait, U: Encode>(spec_index: SpecIndex, request_id: RequestIdentifier, requester: T::AccountId, data_version: DataVersion, parameters: U, callback: Vec<u8>) -> Event<T> {
-	create_request_event::<T>(spec_index, request_id, requester, data_version, parameters.encode(), callback)
+/// A pending oracle request
+#[derive(Encode, Decode, Clone, Eq, PartialEq, Debug)]
+pub struct Request<AccountId, Callback, BlockNumber, Balance> {
+	/// Who sent the request and paid the fee
+	pub requester: AccountId,
+	/// The only operator allowed to answer this request
+	pub operator: AccountId,
+	/// Call dispatched with the answer
+	pub callback: Callback,
+	/// The block after which this request is killed
+	pub expires_at: BlockNumber,
+	/// Fee reserved from requester, paid to operator on fulfillment
+	pub fee: Balance,
 }
 
-pub fn create_request_event<T: system::Trait>(spec_index: SpecIndex, request_id: RequestIdentifier, requester: T::AccountId, data_version: DataVersion, data: Vec<u8>, callback: Vec<u8>) -> Event<T> {
-	RawEvent::OracleRequest(spec_index, request_id, requester, data_version, data, callback)
+decl_error! {
+	pub enum Error for Module<T: Trait> {
+		/// The operator is already registered
+		OperatorAlreadyRegistered,
+		/// The operator is not registered
+		UnknownOperator,
+		/// The request doesn't exist or has already been answered
+		UnknownRequest,
+		/// Only the operator assigned to this request can answer it
+		WrongOperator,
+		/// Only the requester can cancel this request
+		WrongRequester,
+		/// Requester hasn't enough free balance to pay the fee
+		InsufficientFee,
+		/// The result cannot be decoded by the callback
+		UnknownCallback,
+		/// The callback takes more weight than `MaxCallbackWeight`
+		CallbackTooHeavy,
+	}
 }
 
 decl_storage! {
-    trait Store for Module<T: Trait> as ChainlinkStorage {
-    }
+	trait Store for Module<T: Trait> as ChainlinkStorage {
+		/// Operators allowed to answer requests
+		pub Operators get(fn operator): map hasher(blake2_128_concat) T::AccountId => bool;
+		/// The next request identifier up for grabs
+		pub NextRequestIdentifier get(fn next_request_id): RequestIdentifier;
+		/// Requests waiting for an answer
+		pub Requests get(fn request): map hasher(twox_64_concat) RequestIdentifier
+			=> Option<Request<T::AccountId, T::Callback, T::BlockNumber, BalanceOf<T>>>;
+		/// Requests that expire at a given block
+		pub RequestExpirations get(fn request_expirations): map hasher(twox_64_concat) T::BlockNumber => Vec<RequestIdentifier>;
+	}
 }
 
 decl_module! {
 	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		type Error = Error<T>;
+
+		/// How many blocks a request stays valid.
+		const ValidityPeriod: T::BlockNumber = T::ValidityPeriod::get();
+
+		/// The most weight a callback can take.
+		const MaxCallbackWeight: Weight = T::MaxCallbackWeight::get();
+
+		fn deposit_event() = default;
+
+		/// Register `operator` to answer requests. Answers are dispatched as root, so only root
+		/// decides who can answer.
+		#[weight = T::DbWeight::get().reads_writes(1, 1)]
+		pub fn register_operator(origin, operator: T::AccountId) {
+			ensure_root(origin)?;
+
+			ensure!(!<Operators<T>>::get(&operator), Error::<T>::OperatorAlreadyRegistered);
+			<Operators<T>>::insert(&operator, true);
+
+			Self::deposit_event(RawEvent::OperatorRegistered(operator));
+		}
+
+		/// Unregister `operator`. Requests already assigned to it can still be answered.
+		#[weight = T::DbWeight::get().reads_writes(1, 1)]
+		pub fn unregister_operator(origin, operator: T::AccountId) {
+			ensure_root(origin)?;
+
+			ensure!(<Operators<T>>::take(&operator), Error::<T>::UnknownOperator);
+
+			Self::deposit_event(RawEvent::OperatorUnregistered(operator));
+		}
+
+		/// Answer request `request_id`. Only the assigned operator can do it, it receives the
+		/// escrowed fee and the request's callback is dispatched with `result` as root.
+		#[weight = T::DbWeight::get().reads_writes(2, 3).saturating_add(T::MaxCallbackWeight::get())]
+		pub fn callback(origin, request_id: RequestIdentifier, result: Vec<u8>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			let request = <Requests<T>>::get(request_id).ok_or(Error::<T>::UnknownRequest)?;
+			ensure!(request.operator == who, Error::<T>::WrongOperator);
+
+			let callback = request.callback.with_result(result.clone()).ok_or(Error::<T>::UnknownCallback)?;
+
+			// calls aren't transactional, so the request is kept and the fee isn't paid if the callback fails
+			callback.dispatch(system::RawOrigin::Root.into()).map_err(|e| e.error)?;
+
+			Self::remove_request(request_id, request.expires_at);
+			// the fee has been reserved at request time, so it cannot fail apart from rounding
+			let _ = T::Currency::repatriate_reserved(&request.requester, &who, request.fee, BalanceStatus::Free);
+
+			Self::deposit_event(RawEvent::OracleAnswer(who, request_id, request.requester, result, request.fee));
+
+			Ok(())
+		}
+
+		/// Cancel a request that hasn't been answered yet and get its fee back.
+		#[weight = T::DbWeight::get().reads_writes(2, 3)]
+		pub fn cancel_request(origin, request_id: RequestIdentifier) {
+			let who = ensure_signed(origin)?;
+
+			let request = <Requests<T>>::get(request_id).ok_or(Error::<T>::UnknownRequest)?;
+			ensure!(request.requester == who, Error::<T>::WrongRequester);
+
+			Self::remove_request(request_id, request.expires_at);
+			T::Currency::unreserve(&who, request.fee);
+
+			Self::deposit_event(RawEvent::KillRequest(request_id));
+		}
+
+		fn on_finalize(now: T::BlockNumber) {
+			// kill all requests which are still pending after their validity period
+			for request_id in <RequestExpirations<T>>::take(now) {
+				if let Some(request) = <Requests<T>>::take(request_id) {
+					T::Currency::unreserve(&request.requester, request.fee);
+					Self::deposit_event(RawEvent::KillRequest(request_id));
+				}
+			}
+		}
 	}
 }
 
 decl_event!(
-	pub enum Event<T> where AccountId = <T as system::Trait>::AccountId {
-		OracleRequest(SpecIndex, RequestIdentifier, AccountId, DataVersion, Vec<u8>, Vec<u8>),
+	pub enum Event<T> where AccountId = <T as system::Trait>::AccountId, Balance = BalanceOf<T> {
+		/// A request has been sent to an operator.
+		/// (operator, spec index, request id, requester, data version, data, callback name, fee)
+		OracleRequest(AccountId, SpecIndex, RequestIdentifier, AccountId, DataVersion, Vec<u8>, Vec<u8>, Balance),
+		/// A request has been answered. (operator, request id, requester, result, fee)
+		OracleAnswer(AccountId, RequestIdentifier, AccountId, Vec<u8>, Balance),
+		/// A new operator has been registered.
+		OperatorRegistered(AccountId),
+		/// An operator has been unregistered.
+		OperatorUnregistered(AccountId),
+		/// A request has been cancelled or has expired, its fee was refunded.
+		KillRequest(RequestIdentifier),
 	}
 );
 
-// #[cfg(test)]
-// mod tests {
-// 	use super::*;
-//
-// 	use sp_core::H256;
-// 	use frame_support::{impl_outer_origin, assert_ok, parameter_types, weights::Weight};
-// 	use sp_runtime::{
-// 		traits::{BlakeTwo256, IdentityLookup}, testing::Header, Perbill,
-// 	};
-//
-// 	impl_outer_origin! {
-// 		pub enum Origin for Test {}
-// 	}
-//
-// 	#[derive(Clone, Eq, PartialEq)]
-// 	pub struct Test;
-// 	parameter_types! {
-// 		pub const BlockHashCount: u64 = 250;
-// 		pub const MaximumBlockWeight: Weight = 1024;
-// 		pub const MaximumBlockLength: u32 = 2 * 1024;
-// 		pub const AvailableBlockRatio: Perbill = Perbill::from_percent(75);
-// 	}
-// 	impl system::Trait for Test {
-// 		type Origin = Origin;
-// 		type Call = ();
-// 		type Index = u64;
-// 		type BlockNumber = u64;
-// 		type Hash = H256;
-// 		type Hashing = BlakeTwo256;
-// 		type AccountId = u64;
-// 		type Lookup = IdentityLookup<Self::AccountId>;
-// 		type Header = Header;
-// 		type Event = ();
-// 		type BlockHashCount = BlockHashCount;
-// 		type MaximumBlockWeight = MaximumBlockWeight;
-// 		type MaximumBlockLength = MaximumBlockLength;
-// 		type AvailableBlockRatio = AvailableBlockRatio;
-// 		type Version = ();
-// 		type ModuleToIndex = ();
-// 	}
-// 	impl Trait for Test {
-// 		type Event = ();
-// 	}
-// 	type Chainlink = Module<Test>;
-//
-// 	fn new_test_ext() -> sp_io::TestExternalities {
-// 		system::GenesisConfig::default().build_storage::<Test>().unwrap().into()
-// 	}
-//
-// 	#[derive(Debug, PartialEq, Encode, Decode)]
-// 	struct Params {
-// 		a: u32
-// 	}
-//
-// 	#[test]
-// 	fn it_works_for_default_value() {
-// 		new_test_ext().execute_with(|| {
-// 			assert_ok!(Chainlink::send_request(Origin::signed(1), 42));
-// 			assert_eq!(Chainlink::something(), Some(42));
-//
-// 			let b = Params { a: 5 };
-// 			let mut data: &[u8] = &Params::encode(&b);
-// 			//let mut data: &[u8] = &<(u32, &str)>::encode(&(1, "sdqf"));
-// 			//assert_eq!(data, vec![]);
-// 			assert_eq!(b, Params::decode(&mut data).unwrap());
-// 			//let mut da: &[u8] = b"\x0f";
-// 			//let res:&[u8]  = &<(u32, &str)>::decode(&mut data).unwrap();
-// 			//assert_eq!(<()>::decode(&mut data).unwrap(), &(1, "sdqf"));
-// 		});
-// 	}
-// }
\ No newline at end of file
+impl<T: Trait> Module<T> {
+	/// Send a request with SCALE-encoded `parameters` to `operator`.
+	pub fn initiate_request_with_parameters<U: Encode>(
+		requester: T::AccountId,
+		operator: T::AccountId,
+		spec_index: SpecIndex,
+		data_version: DataVersion,
+		parameters: U,
+		fee: BalanceOf<T>,
+		callback_name: Vec<u8>,
+		callback: T::Callback,
+	) -> Result<RequestIdentifier, Error<T>> {
+		Self::initiate_request(requester, operator, spec_index, data_version, parameters.encode(), fee, callback_name, callback)
+	}
+
+	/// Send a request to `operator`, reserving `fee` from `requester` until the request is
+	/// answered, cancelled or expired. The callback can't take more than `MaxCallbackWeight`.
+	pub fn initiate_request(
+		requester: T::AccountId,
+		operator: T::AccountId,
+		spec_index: SpecIndex,
+		data_version: DataVersion,
+		data: Vec<u8>,
+		fee: BalanceOf<T>,
+		callback_name: Vec<u8>,
+		callback: T::Callback,
+	) -> Result<RequestIdentifier, Error<T>> {
+		ensure!(<Operators<T>>::get(&operator), Error::<T>::UnknownOperator);
+		ensure!(
+			callback.get_dispatch_info().weight <= T::MaxCallbackWeight::get(),
+			Error::<T>::CallbackTooHeavy
+		);
+		if !fee.is_zero() {
+			T::Currency::reserve(&requester, fee).map_err(|_| Error::<T>::InsufficientFee)?;
+		}
+
+		let request_id = NextRequestIdentifier::mutate(|id| {
+			let current = *id;
+			*id = id.wrapping_add(1);
+			current
+		});
+
+		let now = <system::Module<T>>::block_number();
+		let expires_at = now.saturating_add(T::ValidityPeriod::get());
+		<Requests<T>>::insert(request_id, Request {
+			requester: requester.clone(),
+			operator: operator.clone(),
+			callback,
+			expires_at,
+			fee,
+		});
+		<RequestExpirations<T>>::append(expires_at, request_id);
+
+		Self::deposit_event(RawEvent::OracleRequest(operator, spec_index, request_id, requester, data_version, data, callback_name, fee));
+
+		Ok(request_id)
+	}
+
+	fn remove_request(request_id: RequestIdentifier, expires_at: T::BlockNumber) {
+		<Requests<T>>::remove(request_id);
+		<RequestExpirations<T>>::mutate(expires_at, |ids| ids.retain(|id| *id != request_id));
+	}
+}
@@ -0,0 +1,153 @@
+//! Test utilities
+
+#![cfg(test)]
+
+use frame_support::{impl_outer_origin, impl_outer_event, parameter_types, traits::OnFinalize};
+use sp_core::H256;
+use sp_runtime::{Perbill, traits::{BlakeTwo256, IdentityLookup}, testing::Header};
+use super::*;
+
+impl_outer_origin! {
+	pub enum Origin for Test {}
+}
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct Test;
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+	pub const MaximumBlockWeight: u32 = 1024;
+	pub const MaximumBlockLength: u32 = 2 * 1024;
+	pub const AvailableBlockRatio: Perbill = Perbill::one();
+	pub const ExistentialDeposit: u64 = 1;
+	pub const ValidityPeriod: u64 = 10;
+	pub const MaxCallbackWeight: Weight = 100;
+}
+
+impl system::Trait for Test {
+	type Origin = Origin;
+	type Call = ();
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = TestEvent;
+	type ModuleToIndex = ();
+	type BlockHashCount = BlockHashCount;
+	type MaximumBlockWeight = MaximumBlockWeight;
+	type MaximumBlockLength = MaximumBlockLength;
+	type AvailableBlockRatio = AvailableBlockRatio;
+	type Version = ();
+	type AccountData = pallet_balances::AccountData<u64>;
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type DbWeight = ();
+	type BlockExecutionWeight = ();
+	type ExtrinsicBaseWeight = ();
+}
+
+impl pallet_balances::Trait for Test {
+	type Balance = u64;
+	type DustRemoval = ();
+	type Event = TestEvent;
+	type ExistentialDeposit = ExistentialDeposit;
+	type AccountStore = System;
+}
+
+/// Module receiving answers of requests
+pub mod consumer {
+	use frame_support::{decl_module, decl_storage, dispatch::DispatchResult, ensure, storage::StorageValue};
+	use system::ensure_root;
+
+	pub trait Trait: system::Trait {}
+
+	decl_storage! {
+		trait Store for Module<T: Trait> as Consumer {
+			pub Answer get(fn answer): Option<u32>;
+		}
+	}
+
+	decl_module! {
+		pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+			/// Zero answer is rejected
+			#[weight = 0]
+			pub fn receive(origin, answer: u32) -> DispatchResult {
+				ensure_root(origin)?;
+				ensure!(answer != 0, "zero answer");
+				Answer::put(answer);
+
+				Ok(())
+			}
+
+			/// Heavier than any callback can be
+			#[weight = 1000]
+			pub fn receive_heavy(origin, answer: u32) -> DispatchResult {
+				ensure_root(origin)?;
+				Answer::put(answer);
+
+				Ok(())
+			}
+		}
+	}
+}
+
+impl consumer::Trait for Test {}
+
+impl CallbackWithParameter for consumer::Call<Test> {
+	fn with_result(&self, result: Vec<u8>) -> Option<Self> {
+		match self {
+			consumer::Call::receive(_) => u32::decode(&mut &result[..]).ok().map(consumer::Call::receive),
+			consumer::Call::receive_heavy(_) => u32::decode(&mut &result[..]).ok().map(consumer::Call::receive_heavy),
+			_ => None,
+		}
+	}
+}
+
+impl Trait for Test {
+	type Event = TestEvent;
+	type Currency = Balances;
+	type Callback = consumer::Call<Test>;
+	type ValidityPeriod = ValidityPeriod;
+	type MaxCallbackWeight = MaxCallbackWeight;
+}
+
+mod chainlink {
+	pub use crate::Event;
+}
+
+impl_outer_event! {
+	pub enum TestEvent for Test {
+		system<T>,
+		pallet_balances<T>,
+		chainlink<T>,
+	}
+}
+
+pub type Chainlink = Module<Test>;
+pub type ChainlinkError = Error<Test>;
+pub type Consumer = consumer::Module<Test>;
+pub type Balances = pallet_balances::Module<Test>;
+pub type System = system::Module<Test>;
+
+// simulate block production
+pub(crate) fn run_to_block(n: u64) {
+	while System::block_number() < n {
+		Chainlink::on_finalize(System::block_number());
+		System::on_finalize(System::block_number());
+		System::set_block_number(System::block_number() + 1);
+	}
+}
+
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_balances::GenesisConfig::<Test> {
+		balances: vec![(1, 100), (2, 100)],
+	}.assimilate_storage(&mut t).unwrap();
+
+	let mut ext: sp_io::TestExternalities = t.into();
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
@@ -0,0 +1,125 @@
+//! Tests for the module.
+
+#![cfg(test)]
+
+use crate::*;
+use crate::mock::*;
+use frame_support::{assert_ok, assert_noop};
+use sp_runtime::DispatchError;
+
+fn request(fee: u64) -> RequestIdentifier {
+	Chainlink::initiate_request(1, 2, 0, 1, vec![], fee, b"receive".to_vec(), consumer::Call::receive(0))
+		.expect("operator is registered")
+}
+
+#[test]
+fn operators_should_be_registered() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(Chainlink::register_operator(Origin::signed(2), 2), DispatchError::BadOrigin);
+		assert_ok!(Chainlink::register_operator(Origin::ROOT, 2));
+		assert!(Chainlink::operator(2));
+		assert_noop!(Chainlink::register_operator(Origin::ROOT, 2), ChainlinkError::OperatorAlreadyRegistered);
+
+		assert_noop!(Chainlink::unregister_operator(Origin::signed(2), 2), DispatchError::BadOrigin);
+		assert_ok!(Chainlink::unregister_operator(Origin::ROOT, 2));
+		assert!(!Chainlink::operator(2));
+		assert_noop!(Chainlink::unregister_operator(Origin::ROOT, 2), ChainlinkError::UnknownOperator);
+	});
+}
+
+#[test]
+fn request_should_reserve_fee() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(
+			Chainlink::initiate_request(1, 2, 0, 1, vec![], 10, vec![], consumer::Call::receive(0)).map_err(DispatchError::from),
+			Err(ChainlinkError::UnknownOperator.into())
+		);
+
+		assert_ok!(Chainlink::register_operator(Origin::ROOT, 2));
+		assert_eq!(
+			Chainlink::initiate_request(1, 2, 0, 1, vec![], 1000, vec![], consumer::Call::receive(0)).map_err(DispatchError::from),
+			Err(ChainlinkError::InsufficientFee.into())
+		);
+
+		assert_eq!(
+			Chainlink::initiate_request(1, 2, 0, 1, vec![], 10, vec![], consumer::Call::receive_heavy(0)).map_err(DispatchError::from),
+			Err(ChainlinkError::CallbackTooHeavy.into())
+		);
+
+		assert_eq!(request(10), 0);
+		assert_eq!(request(10), 1);
+		assert_eq!(Balances::reserved_balance(1), 20);
+		assert_eq!(Chainlink::request_expirations(1 + ValidityPeriod::get()), vec![0, 1]);
+		assert_eq!(Chainlink::request(0).map(|request| request.operator), Some(2));
+	});
+}
+
+#[test]
+fn callback_should_answer_request_and_pay_fee() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Chainlink::register_operator(Origin::ROOT, 2));
+		let id = request(10);
+
+		assert_noop!(Chainlink::callback(Origin::signed(1), id, 7u32.encode()), ChainlinkError::WrongOperator);
+		assert_noop!(Chainlink::callback(Origin::signed(2), id + 1, 7u32.encode()), ChainlinkError::UnknownRequest);
+		assert_noop!(Chainlink::callback(Origin::signed(2), id, vec![1]), ChainlinkError::UnknownCallback);
+
+		assert_ok!(Chainlink::callback(Origin::signed(2), id, 7u32.encode()));
+		assert_eq!(Consumer::answer(), Some(7));
+		assert!(Chainlink::request(id).is_none());
+		assert!(Chainlink::request_expirations(1 + ValidityPeriod::get()).is_empty());
+		assert_eq!(Balances::free_balance(1), 90);
+		assert_eq!(Balances::reserved_balance(1), 0);
+		assert_eq!(Balances::free_balance(2), 110);
+	});
+}
+
+#[test]
+fn failed_callback_should_keep_request() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Chainlink::register_operator(Origin::ROOT, 2));
+		let id = request(10);
+
+		// consumer rejects the answer, so nothing is paid
+		assert!(Chainlink::callback(Origin::signed(2), id, 0u32.encode()).is_err());
+		assert!(Chainlink::request(id).is_some());
+		assert_eq!(Balances::reserved_balance(1), 10);
+		assert_eq!(Balances::free_balance(2), 100);
+
+		assert_ok!(Chainlink::callback(Origin::signed(2), id, 3u32.encode()));
+		assert_eq!(Consumer::answer(), Some(3));
+	});
+}
+
+#[test]
+fn cancelled_request_should_refund_fee() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Chainlink::register_operator(Origin::ROOT, 2));
+		let id = request(10);
+
+		assert_noop!(Chainlink::cancel_request(Origin::signed(2), id), ChainlinkError::WrongRequester);
+		assert_ok!(Chainlink::cancel_request(Origin::signed(1), id));
+		assert!(Chainlink::request(id).is_none());
+		assert_eq!(Balances::free_balance(1), 100);
+		assert_eq!(Balances::reserved_balance(1), 0);
+
+		assert_noop!(Chainlink::callback(Origin::signed(2), id, 7u32.encode()), ChainlinkError::UnknownRequest);
+	});
+}
+
+#[test]
+fn expired_request_should_be_killed() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Chainlink::register_operator(Origin::ROOT, 2));
+		let id = request(10);
+
+		run_to_block(ValidityPeriod::get());
+		assert!(Chainlink::request(id).is_some());
+
+		run_to_block(ValidityPeriod::get() + 2);
+		assert!(Chainlink::request(id).is_none());
+		assert_eq!(Balances::reserved_balance(1), 0);
+		assert_eq!(Balances::free_balance(1), 100);
+		assert_noop!(Chainlink::callback(Origin::signed(2), id, 7u32.encode()), ChainlinkError::UnknownRequest);
+	});
+}
@@ -27,7 +27,7 @@ use frame_support::traits::Get;
 use frame_support::weights::{FunctionOf, DispatchClass, Weight, Pays};
 use frame_support::{Parameter, decl_event, decl_error, decl_module, decl_storage, debug, ensure, StorageValue, IterableStorageMap};
 use frame_system::{self as system, ensure_root, ensure_signed};
-use node_primitives::{AssetTrait, AssetSymbol, ConvertPool, FetchConvertPrice, AssetReward, TokenType};
+use node_primitives::{AssetTrait, AssetSymbol, ConvertPool, FetchConvertPrice, AssetReward, StakingPool, TokenType};
 use sp_runtime::traits::{AtLeast32Bit, Member, Saturating, Zero};
 
 pub trait Trait: frame_system::Trait {
@@ -385,3 +385,10 @@ impl<T: Trait> AssetReward<T::AssetId, T::Balance> for Module<T> {
 		}
 	}
 }
+
+impl<T: Trait> StakingPool<T::AssetId, T::Balance> for Module<T> {
+	fn staking_pool(token_id: T::AssetId) -> T::Balance {
+		// tokens converted to vtokens are staked
+		<Pool<T>>::get(token_id).token_pool
+	}
+}
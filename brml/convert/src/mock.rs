@@ -59,6 +59,7 @@ impl assets::Trait for Test {
 	type Convert = u64;
 	type AssetRedeem = ();
 	type FetchConvertPrice = ();
+	type ClearingHandler = ();
 }
 
 impl system::Trait for Test {
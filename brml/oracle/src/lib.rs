@@ -1,14 +1,66 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
-use chainlink::{Event, create_request_event_from_parameters};
+#[macro_use]
+extern crate alloc;
+
+use alloc::string::{String, ToString};
+use chainlink::{BalanceOf, CallbackWithParameter};
+use codec::Decode;
 use frame_support::traits::{Get};
-use frame_support::{Parameter, decl_module, decl_storage, dispatch::DispatchResult};
+use frame_support::{
+	Parameter, decl_event, decl_error, decl_module, decl_storage, debug, ensure, IterableStorageMap,
+	dispatch::DispatchResult,
+};
+use lite_json::{parse_json, JsonValue};
 use node_primitives::TokenPriceHandler;
-use system::ensure_signed;
+use sp_core::crypto::KeyTypeId;
+use sp_runtime::offchain::{http, Duration};
+use system::{
+	ensure_root, ensure_signed,
+	offchain::{AppCrypto, CreateSignedTransaction, SendSignedTransaction, Signer},
+};
 use sp_std::prelude::*;
 use sp_runtime::traits::{Member, AtLeast32Bit, Zero, SaturatedConversion};
 
-pub trait Trait: system::Trait {
+mod mock;
+mod tests;
+
+/// Key type of accounts which sign the prices fetched by offchain worker.
+pub const KEY_TYPE: KeyTypeId = KeyTypeId(*b"orcl");
+
+/// Prices are recorded with 8 decimals, same as the requests sent to chainlink nodes.
+const PRICE_MULTIPLIER: u128 = 100_000_000;
+
+/// How long offchain worker waits for a price source to respond.
+const FETCH_TIMEOUT_MILLIS: u64 = 3_000;
+
+pub mod crypto {
+	use super::KEY_TYPE;
+	use sp_core::sr25519::Signature as Sr25519Signature;
+	use sp_runtime::{
+		app_crypto::{app_crypto, sr25519},
+		traits::Verify, MultiSignature, MultiSigner,
+	};
+	app_crypto!(sr25519, KEY_TYPE);
+
+	/// Identity used by offchain worker to sign price reports in runtime.
+	pub struct OracleAuthId;
+
+	impl system::offchain::AppCrypto<MultiSigner, MultiSignature> for OracleAuthId {
+		type RuntimeAppPublic = Public;
+		type GenericSignature = sp_core::sr25519::Signature;
+		type GenericPublic = sp_core::sr25519::Public;
+	}
+
+	impl system::offchain::AppCrypto<<Sr25519Signature as Verify>::Signer, Sr25519Signature> for OracleAuthId {
+		type RuntimeAppPublic = Public;
+		type GenericSignature = sp_core::sr25519::Signature;
+		type GenericPublic = sp_core::sr25519::Public;
+	}
+}
+
+pub trait Trait: chainlink::Trait + CreateSignedTransaction<Call<Self>> {
+	/// The overarching event type.
 	type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
 
 	/// The units in which we record prices.
@@ -16,61 +68,319 @@ pub trait Trait: system::Trait {
 
 	/// Handler for fetch token price
 	type TokenPriceHandler: TokenPriceHandler<Self::Price>;
+
+	/// The callback type of chainlink pallet, which is this pallet's call in runtime
+	type ChainlinkCallback: From<Call<Self>> + Into<<Self as chainlink::Trait>::Callback>;
+
+	/// The identifier type for offchain worker to sign price reports.
+	type AuthorityId: AppCrypto<Self::Public, Self::Signature>;
+
+	/// How often (in blocks) offchain worker fetches prices.
+	type FetchPeriod: Get<Self::BlockNumber>;
+}
+
+decl_event! {
+	pub enum Event<T> where <T as system::Trait>::AccountId {
+		/// A price source has been set. (symbol, url, json path)
+		PriceSourceSet(Vec<u8>, Vec<u8>, Vec<u8>),
+		/// A price source has been removed.
+		PriceSourceRemoved(Vec<u8>),
+		/// A reporter has been allowed to submit prices.
+		PriceReporterAdded(AccountId),
+		/// A reporter has been removed.
+		PriceReporterRemoved(AccountId),
+		/// A price has been submitted by a reporter. (reporter, symbol, price)
+		PriceReported(AccountId, Vec<u8>, u128),
+	}
+}
+
+decl_error! {
+	pub enum Error for Module<T: Trait> {
+		/// The account is not allowed to submit prices
+		NotPriceReporter,
+		/// There's no price source for this symbol
+		UnknownPriceSource,
+		/// Symbol, url or json path is empty
+		InvalidPriceSource,
+	}
 }
 
 decl_storage! {
 	trait Store for Module<T: Trait> as Oracle {
 		pub Result: u128;
+		/// Prices fetched by offchain worker, symbol => (url, json path to price, e.g. `tick.close`)
+		pub PriceSources get(fn price_sources): map hasher(blake2_128_concat) Vec<u8> => (Vec<u8>, Vec<u8>);
+		/// Accounts allowed to submit prices fetched by offchain worker
+		pub PriceReporters get(fn price_reporters): Vec<T::AccountId>;
 	}
 }
 
 decl_module! {
 	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		type Error = Error<T>;
+
+		/// How often (in blocks) offchain worker fetches prices.
+		const FetchPeriod: T::BlockNumber = T::FetchPeriod::get();
+
 		fn deposit_event() = default;
 
-		#[weight = T::DbWeight::get().reads_writes(1, 1)]
-		pub fn send_request(origin) -> DispatchResult {
-			// TODO Investigate if Enum can be safely used to refer to a callback
-			// let name: &str = stringify!(Call::<T>::callback); 'Example :: callback'
-			// For now , simply rely on a string to identify the callback
+		#[weight = T::DbWeight::get().reads_writes(3, 3)]
+		pub fn send_request(origin, operator: T::AccountId, fee: BalanceOf<T>) -> DispatchResult {
 			let who : <T as system::Trait>::AccountId = ensure_signed(origin)?;
-			Self::deposit_event(create_request_event_from_parameters::<T, (&[u8], &[u8], &[u8], &[u8], &[u8], &[u8])>(1, 0, who, 0, (b"get", b"https://min-api.cryptocompare.com/data/pricemultifull?fsyms=ETH&tsyms=USD", b"path", b"RAW.ETH.USD.PRICE", b"times", b"100000000"), "Oracle.callback".into()));
-			Ok(())
+			Self::request_price(who, operator, fee, b"https://min-api.cryptocompare.com/data/pricemultifull?fsyms=ETH&tsyms=USD", b"RAW.ETH.USD.PRICE", b"Oracle.callback", Call::callback(0))
 		}
 
+		/// Answer of `send_request`, only dispatched by chainlink pallet once the operator answered.
 		#[weight = T::DbWeight::get().reads_writes(1, 1)]
 		pub fn callback(origin, result: u128) -> DispatchResult {
-			let _who : <T as system::Trait>::AccountId = ensure_signed(origin)?;
+			ensure_root(origin)?;
 			<Result>::put(result);
 			Ok(())
 		}
 
-		#[weight = T::DbWeight::get().reads_writes(1, 1)]
-		pub fn send_request_price_eos(origin) -> DispatchResult {
+		#[weight = T::DbWeight::get().reads_writes(3, 3)]
+		pub fn send_request_price_eos(origin, operator: T::AccountId, fee: BalanceOf<T>) -> DispatchResult {
 			let who : <T as system::Trait>::AccountId = ensure_signed(origin)?;
-			Self::deposit_event(create_request_event_from_parameters::<T, (&[u8], &[u8], &[u8], &[u8], &[u8], &[u8])>(1, 0, who.clone(), 0, (b"get", b"https://api.huobi.pro/market/detail/merged?symbol=eosusdt", b"path", b"tick.close", b"times", b"100000000"), "Oracle.callback_price_eos".into()));
-			Ok(())
+			Self::request_price(who, operator, fee, b"https://api.huobi.pro/market/detail/merged?symbol=eosusdt", b"tick.close", b"Oracle.callback_price_eos", Call::callback_price_eos(0))
 		}
 
-		#[weight = T::DbWeight::get().reads_writes(1, 1)]
-		pub fn send_request_price_iost(origin) -> DispatchResult {
+		#[weight = T::DbWeight::get().reads_writes(3, 3)]
+		pub fn send_request_price_iost(origin, operator: T::AccountId, fee: BalanceOf<T>) -> DispatchResult {
 			let who : <T as system::Trait>::AccountId = ensure_signed(origin)?;
-			Self::deposit_event(create_request_event_from_parameters::<T, (&[u8], &[u8], &[u8], &[u8], &[u8], &[u8])>(1, 0, who.clone(), 0, (b"get", b"https://api.huobi.pro/market/detail/merged?symbol=iostusdt", b"path", b"tick.close", b"times", b"100000000"), "Oracle.callback_price_iost".into()));
-			Ok(())
+			Self::request_price(who, operator, fee, b"https://api.huobi.pro/market/detail/merged?symbol=iostusdt", b"tick.close", b"Oracle.callback_price_iost", Call::callback_price_iost(0))
 		}
 
 		#[weight = T::DbWeight::get().reads_writes(1, 1)]
 		pub fn callback_price_eos(origin, result: u128) -> DispatchResult {
-			let _who : <T as system::Trait>::AccountId = ensure_signed(origin)?;
+			ensure_root(origin)?;
 			T::TokenPriceHandler::set_token_price(b"EOS".to_vec(), result.saturated_into());
 			Ok(())
 		}
 
 		#[weight = T::DbWeight::get().reads_writes(1, 1)]
 		pub fn callback_price_iost(origin, result: u128) -> DispatchResult {
-			let _who : <T as system::Trait>::AccountId = ensure_signed(origin)?;
+			ensure_root(origin)?;
 			T::TokenPriceHandler::set_token_price(b"IOST".to_vec(), result.saturated_into());
 			Ok(())
 		}
+
+		/// Let offchain worker fetch price of `symbol` from `url`, reading the value at `path`.
+		#[weight = T::DbWeight::get().writes(1)]
+		pub fn set_price_source(origin, symbol: Vec<u8>, url: Vec<u8>, path: Vec<u8>) {
+			ensure_root(origin)?;
+
+			ensure!(!symbol.is_empty() && !url.is_empty() && !path.is_empty(), Error::<T>::InvalidPriceSource);
+			<PriceSources>::insert(&symbol, (&url, &path));
+
+			Self::deposit_event(RawEvent::PriceSourceSet(symbol, url, path));
+		}
+
+		#[weight = T::DbWeight::get().writes(1)]
+		pub fn remove_price_source(origin, symbol: Vec<u8>) {
+			ensure_root(origin)?;
+
+			ensure!(<PriceSources>::contains_key(&symbol), Error::<T>::UnknownPriceSource);
+			<PriceSources>::remove(&symbol);
+
+			Self::deposit_event(RawEvent::PriceSourceRemoved(symbol));
+		}
+
+		#[weight = T::DbWeight::get().reads_writes(1, 1)]
+		pub fn add_price_reporter(origin, reporter: T::AccountId) {
+			ensure_root(origin)?;
+
+			<PriceReporters<T>>::mutate(|reporters| {
+				if !reporters.contains(&reporter) {
+					reporters.push(reporter.clone());
+				}
+			});
+
+			Self::deposit_event(RawEvent::PriceReporterAdded(reporter));
+		}
+
+		#[weight = T::DbWeight::get().reads_writes(1, 1)]
+		pub fn remove_price_reporter(origin, reporter: T::AccountId) {
+			ensure_root(origin)?;
+
+			<PriceReporters<T>>::mutate(|reporters| reporters.retain(|r| *r != reporter));
+
+			Self::deposit_event(RawEvent::PriceReporterRemoved(reporter));
+		}
+
+		/// Submit prices fetched by offchain worker, (symbol, price with 8 decimals).
+		#[weight = T::DbWeight::get().reads_writes(1 + prices.len() as u64, prices.len() as u64)]
+		pub fn submit_prices(origin, prices: Vec<(Vec<u8>, u128)>) {
+			let who = ensure_signed(origin)?;
+
+			ensure!(<PriceReporters<T>>::get().contains(&who), Error::<T>::NotPriceReporter);
+
+			// nothing is stored if any of the prices is invalid
+			ensure!(
+				prices.iter().all(|(symbol, _)| <PriceSources>::contains_key(symbol)),
+				Error::<T>::UnknownPriceSource
+			);
+
+			for (symbol, price) in prices {
+				T::TokenPriceHandler::set_token_price(symbol.clone(), price.saturated_into());
+				Self::deposit_event(RawEvent::PriceReported(who.clone(), symbol, price));
+			}
+		}
+
+		// Runs after every block.
+		fn offchain_worker(now_block: T::BlockNumber) {
+			debug::RuntimeLogger::init();
+
+			if !(now_block % T::FetchPeriod::get()).is_zero() {
+				return;
+			}
+
+			match Self::fetch_and_submit_prices() {
+				Ok(_) => debug::info!(target: "oracle", "submitted prices at {:?}.", now_block),
+				Err(e) => debug::warn!(target: "oracle", "failed to submit prices due to: {}", e),
+			}
+		}
+	}
+}
+
+impl<T: Trait> Module<T> {
+	fn request_price(
+		who: T::AccountId,
+		operator: T::AccountId,
+		fee: BalanceOf<T>,
+		url: &[u8],
+		path: &[u8],
+		callback_name: &[u8],
+		callback: Call<T>,
+	) -> DispatchResult {
+		// TODO Investigate if Enum can be safely used to refer to a callback
+		// let name: &str = stringify!(Call::<T>::callback); 'Example :: callback'
+		// For now , simply rely on a string to identify the callback
+		let parameters: (&[u8], &[u8], &[u8], &[u8], &[u8], &[u8]) = (b"get", url, b"path", path, b"times", b"100000000");
+		<chainlink::Module<T>>::initiate_request_with_parameters(who, operator, 1, 0, parameters, fee, callback_name.to_vec(), T::ChainlinkCallback::from(callback).into())?;
+		Ok(())
+	}
+
+	fn fetch_and_submit_prices() -> Result<(), &'static str> {
+		let signer = Signer::<T, T::AuthorityId>::all_accounts();
+		if !signer.can_sign() {
+			return Err("No local accounts available to sign price reports.");
+		}
+
+		let prices = <PriceSources as IterableStorageMap<_, _>>::iter()
+			.filter_map(|(symbol, (url, path))| {
+				match Self::fetch_price(&url, &path) {
+					Ok(price) => Some((symbol, price)),
+					Err(e) => {
+						debug::warn!(target: "oracle", "failed to fetch price of {:?} due to: {:?}", symbol, e);
+						None
+					}
+				}
+			})
+			.collect::<Vec<_>>();
+		if prices.is_empty() {
+			return Err("No price is fetched.");
+		}
+
+		let results = signer.send_signed_transaction(|_account| Call::submit_prices(prices.clone()));
+		for (account, result) in &results {
+			if result.is_err() {
+				debug::warn!(target: "oracle", "[{:?}] failed to submit prices", account.id);
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Fetch `url` and read the price at `path` of the response.
+	pub(crate) fn fetch_price(url: &[u8], path: &[u8]) -> Result<u128, http::Error> {
+		let url = core::str::from_utf8(url).map_err(|_| http::Error::Unknown)?;
+		let deadline = sp_io::offchain::timestamp().add(Duration::from_millis(FETCH_TIMEOUT_MILLIS));
+
+		let pending = http::Request::get(url)
+			.deadline(deadline)
+			.send()
+			.map_err(|_| http::Error::IoError)?;
+		let response = pending.try_wait(deadline)
+			.map_err(|_| http::Error::DeadlineReached)??;
+		if response.code != 200 {
+			debug::warn!(target: "oracle", "unexpected status code: {}", response.code);
+			return Err(http::Error::Unknown);
+		}
+
+		let body = response.body().collect::<Vec<u8>>();
+		let body_str = core::str::from_utf8(&body).map_err(|_| http::Error::Unknown)?;
+
+		Self::parse_price(body_str, path).ok_or(http::Error::Unknown)
+	}
+
+	/// Read the price at dot separated `path`, like `tick.close`, of a json document.
+	/// The price could be either a number or a string and is scaled to 8 decimals.
+	pub(crate) fn parse_price(body: &str, path: &[u8]) -> Option<u128> {
+		let path = core::str::from_utf8(path).ok()?;
+		let json = parse_json(body).ok()?;
+
+		let mut value = &json;
+		let mut last_key = "";
+		for key in path.split('.') {
+			last_key = key;
+			value = match value {
+				JsonValue::Object(ref items) => {
+					let key = key.chars().collect::<Vec<char>>();
+					&items.iter().find(|item| item.0 == key)?.1
+				}
+				_ => return None,
+			};
+		}
+
+		let price = match value {
+			JsonValue::Number(number) => {
+				// the sign of a number like -0.5 is lost in its zero integer part
+				if number.integer < 0 || number.exponent != 0 || Self::has_negative_number(body, last_key) {
+					return None;
+				}
+				let fraction = format!("{:0>width$}", number.fraction, width = number.fraction_length as usize);
+				Self::scale_decimal(&number.integer.to_string(), &fraction)?
+			}
+			JsonValue::String(chars) => {
+				let decimal = chars.iter().collect::<String>();
+				let mut parts = decimal.splitn(2, '.');
+				let integer = parts.next()?;
+				let fraction = parts.next().unwrap_or("");
+				Self::scale_decimal(integer, fraction)?
+			}
+			_ => return None,
+		};
+
+		Some(price)
+	}
+
+	/// Whether any number of `key` in the json document is negative.
+	fn has_negative_number(body: &str, key: &str) -> bool {
+		let quoted = format!("\"{}\"", key);
+		body.match_indices(&quoted).any(|(start, _)| {
+			let rest = body[start + quoted.len()..].trim_start();
+			rest.starts_with(':') && rest[1..].trim_start().starts_with('-')
+		})
+	}
+
+	fn scale_decimal(integer: &str, fraction: &str) -> Option<u128> {
+		let integer = integer.parse::<u128>().ok()?;
+		// keep 8 decimals at most, extra decimals are truncated
+		let fraction = format!("{:0<8.8}", fraction);
+		let fraction = fraction.parse::<u128>().ok()?;
+
+		integer.checked_mul(PRICE_MULTIPLIER)?.checked_add(fraction)
+	}
+}
+
+impl<T: Trait> CallbackWithParameter for Call<T> {
+	fn with_result(&self, result: Vec<u8>) -> Option<Self> {
+		let result = u128::decode(&mut &result[..]).ok()?;
+		match self {
+			Call::callback(_) => Some(Call::callback(result)),
+			Call::callback_price_eos(_) => Some(Call::callback_price_eos(result)),
+			Call::callback_price_iost(_) => Some(Call::callback_price_iost(result)),
+			_ => None,
+		}
 	}
 }
@@ -0,0 +1,141 @@
+// Copyright 2019-2020 Liebi Technologies.
+// This file is part of Bifrost.
+
+// Bifrost is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Bifrost is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Bifrost.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Tests for the module.
+
+#![cfg(test)]
+
+use crate::*;
+use crate::mock::*;
+use codec::Decode;
+use frame_support::{assert_ok, assert_noop};
+use sp_core::{
+	offchain::{OffchainExt, TransactionPoolExt, testing},
+	testing::KeyStore,
+	traits::KeystoreExt,
+};
+use sp_runtime::{RuntimeAppPublic, traits::OffchainWorker};
+
+const PHRASE: &str = "news slush supreme milk chapter athlete soap sausage put clutch what kitten";
+const HUOBI_URL: &str = "https://api.huobi.pro/market/detail/merged?symbol=eosusdt";
+const HUOBI_RESPONSE: &[u8] = br#"{"status":"ok","tick":{"close":2.5052,"high":2.6}}"#;
+
+fn expect_price_request(state: &mut testing::OffchainState) {
+	state.expect_request(0, testing::PendingRequest {
+		method: "GET".into(),
+		uri: HUOBI_URL.into(),
+		response: Some(HUOBI_RESPONSE.to_vec()),
+		sent: true,
+		..Default::default()
+	});
+}
+
+#[test]
+fn parse_price_should_work() {
+	assert_eq!(Oracle::parse_price(r#"{"tick":{"close":2.5052}}"#, b"tick.close"), Some(250_520_000));
+	assert_eq!(Oracle::parse_price(r#"{"RAW":{"ETH":{"USD":{"PRICE":"231.07"}}}}"#, b"RAW.ETH.USD.PRICE"), Some(23_107_000_000));
+	assert_eq!(Oracle::parse_price(r#"{"price":7}"#, b"price"), Some(700_000_000));
+	// extra decimals are truncated
+	assert_eq!(Oracle::parse_price(r#"{"price":0.123456789}"#, b"price"), Some(12_345_678));
+	assert_eq!(Oracle::parse_price(r#"{"tick":{"close":2.5}}"#, b"tick.open"), None);
+	assert_eq!(Oracle::parse_price(r#"{"tick":{"close":-2.5}}"#, b"tick.close"), None);
+	assert_eq!(Oracle::parse_price(r#"{"tick":{"close":-0.5}}"#, b"tick.close"), None);
+	assert_eq!(Oracle::parse_price(r#"{"tick":{"close" : -0.5}}"#, b"tick.close"), None);
+	assert_eq!(Oracle::parse_price(r#"{"tick":{"close":"-0.5"}}"#, b"tick.close"), None);
+	assert_eq!(Oracle::parse_price(r#"{"tick":{"close":0.5,"change":-0.1}}"#, b"tick.close"), Some(50_000_000));
+	assert_eq!(Oracle::parse_price(r#"{"tick":{"close":"abc"}}"#, b"tick.close"), None);
+	assert_eq!(Oracle::parse_price("not a json", b"tick.close"), None);
+}
+
+#[test]
+fn fetch_price_from_mock_server_should_work() {
+	let (offchain, state) = testing::TestOffchainExt::new();
+	let mut t = sp_io::TestExternalities::default();
+	t.register_extension(OffchainExt::new(offchain));
+
+	expect_price_request(&mut state.write());
+
+	t.execute_with(|| {
+		let price = Oracle::fetch_price(HUOBI_URL.as_bytes(), b"tick.close");
+		assert_eq!(price.ok(), Some(250_520_000));
+	});
+}
+
+#[test]
+fn offchain_worker_should_submit_signed_prices() {
+	let (offchain, state) = testing::TestOffchainExt::new();
+	let (pool, pool_state) = testing::TestTransactionPoolExt::new();
+	let keystore = KeyStore::new();
+	keystore.write().sr25519_generate_new(
+		crypto::Public::ID,
+		Some(&format!("{}/hunter1", PHRASE))
+	).unwrap();
+
+	let mut t = new_test_ext();
+	t.register_extension(OffchainExt::new(offchain));
+	t.register_extension(TransactionPoolExt::new(pool));
+	t.register_extension(KeystoreExt(keystore));
+
+	expect_price_request(&mut state.write());
+
+	t.execute_with(|| {
+		assert_ok!(Oracle::set_price_source(Origin::ROOT, b"EOS".to_vec(), HUOBI_URL.as_bytes().to_vec(), b"tick.close".to_vec()));
+
+		// not the time to fetch prices
+		Oracle::offchain_worker(1);
+		assert!(pool_state.read().transactions.is_empty());
+
+		Oracle::offchain_worker(FetchPeriod::get());
+
+		let tx = pool_state.write().transactions.pop().unwrap();
+		assert!(pool_state.read().transactions.is_empty());
+		let tx = Extrinsic::decode(&mut &*tx).unwrap();
+		assert_eq!(tx.signature.unwrap().0, 0);
+		assert_eq!(tx.call, Call::Oracle(crate::Call::submit_prices(vec![(b"EOS".to_vec(), 250_520_000)])));
+	});
+}
+
+#[test]
+fn submit_prices_should_only_accept_reporters() {
+	new_test_ext().execute_with(|| {
+		let reporter = sp_core::sr25519::Public::from_raw([1u8; 32]);
+		let stranger = sp_core::sr25519::Public::from_raw([2u8; 32]);
+
+		assert_ok!(Oracle::set_price_source(Origin::ROOT, b"EOS".to_vec(), HUOBI_URL.as_bytes().to_vec(), b"tick.close".to_vec()));
+		assert_ok!(Oracle::add_price_reporter(Origin::ROOT, reporter));
+
+		assert_noop!(
+			Oracle::submit_prices(Origin::signed(stranger), vec![(b"EOS".to_vec(), 1)]),
+			OracleError::NotPriceReporter
+		);
+		assert_noop!(
+			Oracle::submit_prices(Origin::signed(reporter), vec![(b"BTC".to_vec(), 1)]),
+			OracleError::UnknownPriceSource
+		);
+		// a valid price before an invalid one isn't stored either
+		assert_noop!(
+			Oracle::submit_prices(Origin::signed(reporter), vec![(b"EOS".to_vec(), 1), (b"BTC".to_vec(), 1)]),
+			OracleError::UnknownPriceSource
+		);
+		assert_ok!(Oracle::submit_prices(Origin::signed(reporter), vec![(b"EOS".to_vec(), 1)]));
+
+		assert_ok!(Oracle::remove_price_reporter(Origin::ROOT, reporter));
+		assert_noop!(
+			Oracle::submit_prices(Origin::signed(reporter), vec![(b"EOS".to_vec(), 1)]),
+			OracleError::NotPriceReporter
+		);
+	});
+}
@@ -17,13 +17,16 @@
 // Ensure we're `no_std` when compiling for Wasm.
 #![cfg_attr(not(feature = "std"), no_std)]
 
-use sp_std::prelude::*;
 use codec::{Encode, Decode};
-use node_primitives::{ClearingHandler, AssetIssue};
 use frame_support::{
-	Parameter, decl_module, decl_event, decl_storage, traits::Get
+	Parameter, decl_module, decl_event, decl_error, decl_storage, ensure, traits::Get, weights::Weight,
+	IterableStorageDoubleMap,
 };
-use sp_runtime::traits::{Member, AtLeast32Bit, One, Zero, SaturatedConversion, Saturating};
+use node_primitives::{AssetReward, AssetSymbol, AssetTrait, ClearingHandler, TokenType};
+use sp_runtime::{ModuleId, helpers_128bit::multiply_by_rational};
+use sp_runtime::traits::{AccountIdConversion, Member, AtLeast32Bit, One, Zero, SaturatedConversion, Saturating};
+use sp_std::prelude::*;
+use system::ensure_signed;
 
 mod mock;
 mod tests;
@@ -31,47 +34,57 @@ mod tests;
 #[derive(Encode, Decode, Default, Eq, PartialEq, Debug, Clone, Copy)]
 pub struct BalanceDuration<BlockNumber, Balance, Duration> {
 	/// the block number recorded last time
-	last_block: BlockNumber,
+	pub last_block: BlockNumber,
 	/// the balance recorded last time
-	last_balance: Balance,
-	/// Duration of balance, value = balance * (last_block - start_block)
-	value: Duration,
+	pub last_balance: Balance,
+	/// Duration of balance, the sum of balance * blocks it was held in the settlement
+	pub value: Duration,
 }
 
 impl<BlockNumber, Balance, Duration> BalanceDuration<BlockNumber, Balance, Duration> where
 	BlockNumber: Copy + AtLeast32Bit,
-	Balance: Copy + AtLeast32Bit + From<BlockNumber>,
+	Balance: Copy + AtLeast32Bit,
 	Duration: Copy + AtLeast32Bit + From<Balance>,
 {
-	fn new<SettlementId, SettlementPeriod>(
-		stl_id: SettlementId,
+	/// Start recording in the settlement started at `start_block`, `prev_amount` was held since then.
+	fn new(
+		start_block: BlockNumber,
 		last_block: BlockNumber,
 		prev_amount: Balance,
 		curr_amount: Balance,
-	) -> Self
-		where
-			SettlementId: Copy + AtLeast32Bit,
-			SettlementPeriod: Get<BlockNumber>,
-	{
-		let stl_index = stl_id.saturated_into::<u64>();
-		let stl_period = SettlementPeriod::get().saturated_into::<u64>();
-		let start_block: BlockNumber = (stl_index * stl_period).saturated_into::<BlockNumber>();
-		let blocks: BlockNumber = last_block - start_block;
-		let value: Duration = (prev_amount * blocks.into()).into();
-
-		Self {
-			last_block,
-			last_balance: curr_amount,
-			value,
-		}
+	) -> Self {
+		let mut balance_duration = Self {
+			last_block: start_block,
+			last_balance: prev_amount,
+			value: Zero::zero(),
+		};
+		balance_duration.update(last_block, curr_amount);
+		balance_duration
 	}
 
 	fn update(&mut self, last_block: BlockNumber, curr_amount: Balance) {
-		let blocks = last_block - self.last_block;
-		self.value += (self.last_balance * blocks.into()).into();
+		let blocks: Duration = last_block.saturating_sub(self.last_block).saturated_into::<u128>().saturated_into();
+		self.value = self.value.saturating_add(Duration::from(self.last_balance).saturating_mul(blocks));
 		self.last_block = last_block;
 		self.last_balance = curr_amount;
 	}
+
+	/// Close the record at `end_block`, the last balance is held until then.
+	fn close(&mut self, end_block: BlockNumber) {
+		let last_balance = self.last_balance;
+		self.update(end_block, last_balance);
+	}
+}
+
+/// Rewards shared by vToken holders of an asset in a settlement
+#[derive(Encode, Decode, Default, Eq, PartialEq, Debug, Clone, Copy)]
+pub struct RewardPool<Balance, Duration> {
+	/// Total rewards deposited in the settlement
+	pub reward: Balance,
+	/// Total duration of vToken supply, known after the settlement is closed
+	pub duration: Duration,
+	/// Rewards already credited to holders
+	pub distributed: Balance,
 }
 
 /// The module configuration trait.
@@ -80,7 +93,7 @@ pub trait Trait: system::Trait + brml_assets::Trait {
 	type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
 
 	/// Settlement id
-	type SettlementId: Member + Parameter + AtLeast32Bit + Default + Copy + Into<Self::BlockNumber>;
+	type SettlementId: Member + Parameter + AtLeast32Bit + Default + Copy;
 
 	/// How often (in blocks) new settlement are started.
 	type SettlementPeriod: Get<Self::BlockNumber>;
@@ -88,51 +101,112 @@ pub trait Trait: system::Trait + brml_assets::Trait {
 	/// The value that represent the duration of balance.
 	type Duration: Member + Parameter + AtLeast32Bit + Default + Copy + From<Self::Balance>;
 
-	// Assets issue handler
-	type AssetIssue: AssetIssue<Self::AssetId, Self::AccountId, Self::Balance>;
+	/// Maximum number of account records cleared in a block when closing a settlement.
+	type MaxClearingsPerBlock: Get<u32>;
+
+	/// Account which holds deposited rewards until they're claimed.
+	type RewardPotId: Get<ModuleId>;
 }
 
 decl_event!(
-	pub enum Event<T> where <T as Trait>::SettlementId,
+	pub enum Event<T> where
+		<T as system::Trait>::AccountId,
+		<T as brml_assets::Trait>::AssetId,
+		<T as brml_assets::Trait>::Balance,
+		<T as Trait>::SettlementId,
 	{
 		/// New Settlement Started.
 		NewSettlement(SettlementId),
+		/// All records of the settlement are cleared and rewards are credited.
+		SettlementClosed(SettlementId),
+		/// Reward deposited to the pool of the asset in the settlement.
+		RewardDeposited(SettlementId, AssetId, Balance),
+		/// Account claimed the rewards of the asset.
+		RewardClaimed(AccountId, AssetId, Balance),
 	}
 );
 
+decl_error! {
+	pub enum Error for Module<T: Trait> {
+		/// Asset id doesn't exist
+		TokenNotExist,
+		/// There is no reward to claim
+		NoRewardToClaim,
+		/// The reward pot doesn't hold enough of the asset
+		RewardPotExhausted,
+	}
+}
+
 decl_storage! {
 	trait Store for Module<T: Trait> as Settlement {
-		/// Records for asset clearing corresponding to an account id
-		ClearingAssets get(fn clearing_assets): linked_map hasher(blake2_128_concat) (T::AssetId, T::AccountId, T::SettlementId)
-			=> BalanceDuration<T::BlockNumber, T::Balance, T::Duration>;
-		/// Records for token clearing corresponding to an asset id
-		ClearingTokens get(fn clearing_tokens): linked_map hasher(blake2_128_concat) (T::AssetId, T::SettlementId)
-			=> BalanceDuration<T::BlockNumber, T::Balance, T::Duration>;
-		/// The next settlement identifier up for grabs.
-		NextSettlementId get(fn next_settlement_id): T::SettlementId;
-//		/// Records for settlements
-//		Settlements get(settlements): map (T::AssetId, T::SettlementId)
-////			=> Settlement<T::Balance, T::BlockNumber>;
-//			=> SettlementStatus<T::Balance, T::BlockNumber>;
+		/// Records for vToken balance duration of accounts in the settlement
+		pub ClearingAssets get(fn clearing_assets): double_map hasher(twox_64_concat) T::SettlementId,
+			hasher(blake2_128_concat) (T::AssetId, T::AccountId) => Option<BalanceDuration<T::BlockNumber, T::Balance, T::Duration>>;
+		/// Records for vToken total supply duration in the settlement
+		pub ClearingTokens get(fn clearing_tokens): double_map hasher(twox_64_concat) T::SettlementId,
+			hasher(blake2_128_concat) T::AssetId => Option<BalanceDuration<T::BlockNumber, T::Balance, T::Duration>>;
+		/// The ongoing settlement.
+		pub CurrentSettlementId get(fn current_settlement_id): T::SettlementId;
+		/// The block number which the settlement started at.
+		pub SettlementStarts get(fn settlement_start): map hasher(twox_64_concat) T::SettlementId => T::BlockNumber;
+		/// The settlement whose account records are being cleared.
+		pub ClosingSettlementId get(fn closing_settlement_id): Option<T::SettlementId>;
+		/// Rewards of the asset in the settlement.
+		pub RewardPools get(fn reward_pools): double_map hasher(twox_64_concat) T::SettlementId,
+			hasher(blake2_128_concat) T::AssetId => RewardPool<T::Balance, T::Duration>;
+		/// Rewards credited to the account and not claimed yet.
+		pub PendingRewards get(fn pending_rewards): double_map hasher(blake2_128_concat) T::AssetId,
+			hasher(blake2_128_concat) T::AccountId => T::Balance;
 	}
 }
 
 decl_module! {
 	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		type Error = Error<T>;
+
 		/// How often (in blocks) new settlement are started.
 		const SettlementPeriod: T::BlockNumber = T::SettlementPeriod::get();
 
+		/// Maximum number of account records cleared in a block when closing a settlement.
+		const MaxClearingsPerBlock: u32 = T::MaxClearingsPerBlock::get();
+
 		fn deposit_event() = default;
 
-		fn on_initialize(now_block: T::BlockNumber) {
-			// check if need to begin a new settlement
-			if (now_block % T::SettlementPeriod::get()).is_zero() {
-				Self::new_settlement();
-			}
+		/// Claim the rewards of the asset credited to the origin, they're paid from the reward pot.
+		#[weight = T::DbWeight::get().reads_writes(5, 5)]
+		pub fn claim_reward(origin, id: AssetSymbol) {
+			let origin = ensure_signed(origin)?;
+			let asset_id = T::AssetId::from(id as u32);
+
+			let reward = <PendingRewards<T>>::get(asset_id, &origin);
+			ensure!(!reward.is_zero(), Error::<T>::NoRewardToClaim);
+
+			let pot = Self::reward_pot();
+			let pot_balance = <brml_assets::Module<T>>::get_account_asset(&asset_id, TokenType::Token, &pot).balance;
+			ensure!(pot_balance >= reward, Error::<T>::RewardPotExhausted);
+
+			<PendingRewards<T>>::remove(asset_id, &origin);
+
+			<brml_assets::Module<T>>::asset_transfer(asset_id, TokenType::Token, pot, origin.clone(), reward);
+
+			Self::deposit_event(RawEvent::RewardClaimed(origin, asset_id, reward));
 		}
 
-		fn on_finalize(now_block: T::BlockNumber) {
-			Self::settlement(now_block);
+		fn on_initialize(now_block: T::BlockNumber) -> Weight {
+			let mut weight = T::DbWeight::get().reads(2);
+
+			// a new settlement begins after the previous one is cleared
+			let curr_stl_id = Self::current_settlement_id();
+			let next_start = Self::settlement_start(curr_stl_id).saturating_add(T::SettlementPeriod::get());
+			if now_block >= next_start && Self::closing_settlement_id().is_none() {
+				weight = weight.saturating_add(Self::new_settlement(now_block));
+			}
+
+			if let Some(stl_id) = Self::closing_settlement_id() {
+				weight = weight.saturating_add(Self::clear_assets(stl_id, T::MaxClearingsPerBlock::get()));
+			}
+
+			weight
 		}
 	}
 }
@@ -140,96 +214,178 @@ decl_module! {
 impl<T: Trait> ClearingHandler<T::AssetId, T::AccountId, T::BlockNumber, T::Balance> for Module<T> {
 	fn asset_clearing(
 		asset_id: T::AssetId,
+		token_type: TokenType,
 		target: T::AccountId,
 		last_block: T::BlockNumber,
 		prev_amount: T::Balance,
 		curr_amount: T::Balance,
 	) {
-		let curr_stl_id: T::SettlementId = Self::current_settlement_id();
-
-		let index = (asset_id, target.clone(), curr_stl_id);
-		if <ClearingAssets<T>>::contains_key(&index) {
-			<ClearingAssets<T>>::mutate(&index, |clearing_asset| {
-				clearing_asset.update(last_block, curr_amount);
-			});
-		} else {
-			let balance_duration = BalanceDuration::new::<_, T::SettlementPeriod>(
-				curr_stl_id,
-				last_block,
-				prev_amount,
-				curr_amount,
-			);
-			<ClearingAssets<T>>::insert(&index, balance_duration);
+		// only vToken holders share the rewards
+		if token_type != TokenType::VToken {
+			return;
 		}
+
+		let curr_stl_id = Self::current_settlement_id();
+		let start_block = Self::settlement_start(curr_stl_id);
+		<ClearingAssets<T>>::mutate(curr_stl_id, (asset_id, target), |clearing_asset| {
+			match clearing_asset {
+				Some(balance_duration) => balance_duration.update(last_block, curr_amount),
+				None => {
+					*clearing_asset = Some(BalanceDuration::new(start_block, last_block, prev_amount, curr_amount));
+				}
+			}
+		});
 	}
 
 	fn token_clearing(
 		asset_id: T::AssetId,
+		token_type: TokenType,
 		last_block: T::BlockNumber,
 		prev_amount: T::Balance,
 		curr_amount: T::Balance,
 	) {
-		let curr_stl_id: T::SettlementId = Self::current_settlement_id();
+		if token_type != TokenType::VToken {
+			return;
+		}
 
-		let index = (asset_id, curr_stl_id);
-		if <ClearingTokens<T>>::contains_key(&index) {
-			<ClearingTokens<T>>::mutate(&index, |clearing_token| {
-				clearing_token.update(last_block, curr_amount);
-			});
-		} else {
-			let balance_duration = BalanceDuration::new::<_, T::SettlementPeriod>(
-				curr_stl_id,
-				last_block,
-				prev_amount,
-				curr_amount,
-			);
-			<ClearingTokens<T>>::insert(&index, balance_duration);
+		let curr_stl_id = Self::current_settlement_id();
+		let start_block = Self::settlement_start(curr_stl_id);
+		<ClearingTokens<T>>::mutate(curr_stl_id, asset_id, |clearing_token| {
+			match clearing_token {
+				Some(balance_duration) => balance_duration.update(last_block, curr_amount),
+				None => {
+					*clearing_token = Some(BalanceDuration::new(start_block, last_block, prev_amount, curr_amount));
+				}
+			}
+		});
+	}
+}
+
+impl<T: Trait> AssetReward<T::AssetId, T::Balance> for Module<T> {
+	/// The reward is taken out of circulation by the caller, like staking fees deducted from
+	/// validator deposits, and it's held by the reward pot until vToken holders claim it.
+	fn set_asset_reward(asset_id: T::AssetId, reward: T::Balance) -> Result<(), ()> {
+		if !<brml_assets::Tokens<T>>::contains_key(asset_id) {
+			return Err(());
 		}
+
+		<brml_assets::Module<T>>::asset_issue(asset_id, TokenType::Token, Self::reward_pot(), reward);
+
+		let curr_stl_id = Self::current_settlement_id();
+		<RewardPools<T>>::mutate(curr_stl_id, asset_id, |pool| {
+			pool.reward = pool.reward.saturating_add(reward);
+		});
+
+		Self::deposit_event(RawEvent::RewardDeposited(curr_stl_id, asset_id, reward));
+
+		Ok(())
 	}
 }
 
 // The main implementation block for the module.
 impl<T: Trait> Module<T> {
-	fn current_settlement_id() -> T::SettlementId {
-		Self::next_settlement_id().saturating_sub(1.into())
+	/// Account which holds rewards until they're claimed
+	pub fn reward_pot() -> T::AccountId {
+		T::RewardPotId::get().into_account()
 	}
 
-	fn new_settlement() {
-		let new_stl_id: T::SettlementId = <NextSettlementId<T>>::mutate(|id| {
-			*id += One::one();
-			*id
-		});
+	/// Close the ongoing settlement at `now_block` and start a new one. Token supplies are cleared
+	/// at once, account records are cleared later by `clear_assets`.
+	fn new_settlement(now_block: T::BlockNumber) -> Weight {
+		let prev_stl_id = Self::current_settlement_id();
+		let new_stl_id = prev_stl_id.saturating_add(One::one());
+		<CurrentSettlementId<T>>::put(new_stl_id);
+		<SettlementStarts<T>>::insert(new_stl_id, now_block);
+		<ClosingSettlementId<T>>::put(prev_stl_id);
+
+		let mut tokens: u64 = 0;
+		for (asset_id, mut clearing_token) in <ClearingTokens<T>>::iter_prefix(prev_stl_id) {
+			clearing_token.close(now_block);
+			<ClearingTokens<T>>::insert(prev_stl_id, asset_id, clearing_token);
+			<RewardPools<T>>::mutate(prev_stl_id, asset_id, |pool| pool.duration = clearing_token.value);
+
+			let total_supply = clearing_token.last_balance;
+			if !total_supply.is_zero() {
+				let balance_duration = BalanceDuration::new(now_block, now_block, total_supply, total_supply);
+				<ClearingTokens<T>>::insert(new_stl_id, asset_id, balance_duration);
+			}
+			tokens += 1;
+		}
 
 		Self::deposit_event(RawEvent::NewSettlement(new_stl_id));
+
+		T::DbWeight::get().reads_writes(1 + 2 * tokens, 4 + 3 * tokens)
 	}
 
-	fn settlement(_now_block: T::BlockNumber) {
-		let curr_stl_id: T::SettlementId = Self::current_settlement_id();
-		let stl_blocks = T::SettlementPeriod::get();
-
-		// Update token's balance duration
-		for ((asset_id, stl_id), _clearing_token) in <ClearingTokens<T>>::enumerate()
-			.filter(|((_, stl_id), _)| *stl_id < curr_stl_id)
-		{
-			let last_block = stl_blocks * (stl_id + One::one()).into() - One::one();
-			let token = <brml_assets::Tokens<T>>::get(asset_id);
-			Self::token_clearing(asset_id, last_block, token.total_supply, token.total_supply);
+	/// Clear at most `limit` account records of the closed settlement, credit rewards by their
+	/// duration and carry their balances over to the ongoing settlement.
+	fn clear_assets(stl_id: T::SettlementId, limit: u32) -> Weight {
+		let end_block = Self::settlement_start(stl_id.saturating_add(One::one()));
+		let curr_stl_id = Self::current_settlement_id();
+
+		let records: Vec<_> = <ClearingAssets<T>>::iter_prefix(stl_id).take(limit as usize).collect();
+		let cleared = records.len() as u64;
+		for ((asset_id, target), mut clearing_asset) in records {
+			<ClearingAssets<T>>::remove(stl_id, (asset_id, target.clone()));
+			clearing_asset.close(end_block);
+
+			let reward = <RewardPools<T>>::mutate(stl_id, asset_id, |pool| {
+				let reward = Self::reward_share(pool, clearing_asset.value);
+				pool.distributed = pool.distributed.saturating_add(reward);
+				reward
+			});
+			if !reward.is_zero() {
+				<PendingRewards<T>>::mutate(asset_id, &target, |pending| *pending = pending.saturating_add(reward));
+			}
+
+			// the account may have changed its balance in the ongoing settlement already
+			let balance = clearing_asset.last_balance;
+			let index = (asset_id, target);
+			if !balance.is_zero() && !<ClearingAssets<T>>::contains_key(curr_stl_id, &index) {
+				let balance_duration = BalanceDuration::new(end_block, end_block, balance, balance);
+				<ClearingAssets<T>>::insert(curr_stl_id, index, balance_duration);
+			}
 		}
 
-		for (index, _clearing_asset) in <ClearingAssets<T>>::enumerate()
-			.filter(|((_, _, stl_id), _)| *stl_id < curr_stl_id)
-		{
-			let (asset_id, target, stl_id) = index.clone();
+		let mut weight = T::DbWeight::get().reads_writes(2 + 3 * cleared, 4 * cleared);
+		if <ClearingAssets<T>>::iter_prefix(stl_id).next().is_none() {
+			weight = weight.saturating_add(Self::close_settlement(stl_id, curr_stl_id));
+		}
 
-			// Calculate account balance duration
-			let last_block = stl_blocks * (stl_id + One::one()).into() - One::one();
-			let amount = <brml_assets::Balances<T>>::get((asset_id, target.clone()));
-			Self::asset_clearing(asset_id, target.clone(), last_block, amount, amount);
+		weight
+	}
 
-			// Transfer to Balance, and remove clearing_asset record
-			let clearing_asset = <ClearingAssets<T>>::take(&index);
-			let amount = clearing_asset.last_balance;
-			T::AssetIssue::asset_issue(asset_id, target.clone(), amount);
+	/// Finish closing the settlement, rewards not distributed are moved to the ongoing settlement.
+	fn close_settlement(stl_id: T::SettlementId, curr_stl_id: T::SettlementId) -> Weight {
+		let mut pools: u64 = 0;
+		for (asset_id, pool) in <RewardPools<T>>::iter_prefix(stl_id) {
+			let remains = pool.reward.saturating_sub(pool.distributed);
+			if !remains.is_zero() {
+				<RewardPools<T>>::mutate(curr_stl_id, asset_id, |curr_pool| {
+					curr_pool.reward = curr_pool.reward.saturating_add(remains);
+				});
+			}
+			pools += 1;
 		}
+		<ClosingSettlementId<T>>::kill();
+
+		Self::deposit_event(RawEvent::SettlementClosed(stl_id));
+
+		T::DbWeight::get().reads_writes(1 + pools, 1 + pools)
+	}
+
+	/// Share of rewards in the pool for the balance duration.
+	fn reward_share(pool: &RewardPool<T::Balance, T::Duration>, duration: T::Duration) -> T::Balance {
+		if pool.duration.is_zero() {
+			return Zero::zero();
+		}
+
+		let share = multiply_by_rational(
+			pool.reward.saturated_into::<u128>(),
+			duration.saturated_into::<u128>(),
+			pool.duration.saturated_into::<u128>(),
+		).unwrap_or_default();
+
+		share.saturated_into::<T::Balance>().min(pool.reward.saturating_sub(pool.distributed))
 	}
 }
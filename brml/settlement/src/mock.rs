@@ -18,9 +18,9 @@
 
 #![cfg(test)]
 
-use frame_support::{impl_outer_origin, parameter_types};
+use frame_support::{impl_outer_origin, impl_outer_event, parameter_types, traits::{OnInitialize, OnFinalize}};
 use sp_core::H256;
-use sp_runtime::{Perbill, traits::{BlakeTwo256, IdentityLookup}, testing::Header};
+use sp_runtime::{ModuleId, Perbill, traits::{BlakeTwo256, IdentityLookup}, testing::Header};
 use super::*;
 
 impl_outer_origin! {
@@ -47,39 +47,81 @@ impl system::Trait for Test {
 	type AccountId = u64;
 	type Lookup = IdentityLookup<Self::AccountId>;
 	type Header = Header;
-	type Event = ();
+	type Event = TestEvent;
 	type ModuleToIndex = ();
 	type BlockHashCount = BlockHashCount;
 	type MaximumBlockWeight = MaximumBlockWeight;
 	type MaximumBlockLength = MaximumBlockLength;
 	type AvailableBlockRatio = AvailableBlockRatio;
 	type Version = ();
+	type AccountData = ();
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type DbWeight = ();
+	type BlockExecutionWeight = ();
+	type ExtrinsicBaseWeight = ();
 }
 
 impl brml_assets::Trait for Test {
-	type Event = ();
+	type Event = TestEvent;
 	type Balance = u64;
 	type AssetId = u32;
-	type ClearingHandler = Settlement;
+	type Price = u64;
+	type Cost = u64;
+	type Income = u64;
+	type Convert = u64;
 	type AssetRedeem = ();
+	type FetchConvertPrice = ();
+	type ClearingHandler = Settlement;
 }
 
 parameter_types! {
-	pub const SettlementPeriod: u64 = 24 * 60 * 10;
+	pub const SettlementPeriod: u64 = 100;
+	pub const MaxClearingsPerBlock: u32 = 2;
+	pub const RewardPotId: ModuleId = ModuleId(*b"bf/stlrw");
 }
 
 impl Trait for Test {
-	type Event = ();
+	type Event = TestEvent;
 	type SettlementId = u32;
 	type SettlementPeriod = SettlementPeriod;
 	type Duration = u128;
-	type AssetIssue = Assets;
+	type MaxClearingsPerBlock = MaxClearingsPerBlock;
+	type RewardPotId = RewardPotId;
+}
+
+mod settlement {
+	pub use crate::Event;
+}
+
+impl_outer_event! {
+	pub enum TestEvent for Test {
+		system<T>,
+		brml_assets<T>,
+		settlement<T>,
+	}
 }
 
 pub type Settlement = Module<Test>;
+pub type SettlementError = Error<Test>;
 pub type Assets = brml_assets::Module<Test>;
 pub type System = system::Module<Test>;
 
+// simulate block production
+pub(crate) fn run_to_block(n: u64) {
+	while System::block_number() < n {
+		System::on_finalize(System::block_number());
+		System::set_block_number(System::block_number() + 1);
+		System::on_initialize(System::block_number());
+		Settlement::on_initialize(System::block_number());
+	}
+}
+
 pub fn new_test_ext() -> runtime_io::TestExternalities {
-	system::GenesisConfig::default().build_storage::<Test>().unwrap().into()
+	let mut ext: runtime_io::TestExternalities = system::GenesisConfig::default().build_storage::<Test>().unwrap().into();
+	ext.execute_with(|| {
+		System::set_block_number(1);
+		assert!(Assets::create(Origin::ROOT, b"DOT".to_vec(), 12).is_ok());
+	});
+	ext
 }
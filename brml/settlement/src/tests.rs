@@ -18,201 +18,231 @@
 
 #![cfg(test)]
 
-use crate::mock::{Assets, Settlement, Origin, System, new_test_ext};
-use frame_support::assert_ok;
-use sp_runtime::traits::{OnInitialize, OnFinalize};
+use crate::mock::*;
+use frame_support::{assert_noop, assert_ok};
+use node_primitives::AssetSymbol;
 use super::*;
 
-const SETTLEMENT_PERIOD: u64 = 24 * 60 * 10;
+fn balance_duration(last_block: u64, last_balance: u64, value: u128) -> Option<BalanceDuration<u64, u64, u128>> {
+	Some(BalanceDuration { last_block, last_balance, value })
+}
+
+#[test]
+fn issuing_vtoken_clearing_should_work() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Assets::issue(Origin::ROOT, AssetSymbol::DOT, TokenType::VToken, 1, 10000));
+		assert_eq!(Settlement::clearing_assets(0, (0, 1)), balance_duration(1, 10000, 0));
+		assert_eq!(Settlement::clearing_tokens(0, 0), balance_duration(1, 10000, 0));
+
+		System::set_block_number(50);
+		assert_ok!(Assets::issue(Origin::ROOT, AssetSymbol::DOT, TokenType::VToken, 2, 20000));
+		assert_eq!(Settlement::clearing_assets(0, (0, 2)), balance_duration(50, 20000, 0));
+		assert_eq!(Settlement::clearing_tokens(0, 0), balance_duration(50, 30000, 10000 * (50 - 1)));
+
+		System::set_block_number(80);
+		assert_ok!(Assets::issue(Origin::ROOT, AssetSymbol::DOT, TokenType::VToken, 2, 30000));
+		assert_eq!(Settlement::clearing_assets(0, (0, 2)), balance_duration(80, 50000, 20000 * (80 - 50)));
+		assert_eq!(
+			Settlement::clearing_tokens(0, 0),
+			balance_duration(80, 60000, 10000 * (50 - 1) + 30000 * (80 - 50)),
+		);
+	});
+}
 
 #[test]
-fn issuing_asset_clearing_should_work() {
+fn token_should_not_be_cleared() {
 	new_test_ext().execute_with(|| {
-		assert_ok!(Assets::create(Origin::ROOT, vec![0x12, 0x34], 8));
-
-		assert_ok!(Assets::issue(Origin::ROOT, 0, 1, 10000));
-		assert_eq!(Settlement::clearing_assets((0, 1, 0)), BalanceDuration {
-			last_block: 1,
-			last_balance: 10000,
-			value: 0,
-		});
-		assert_eq!(Settlement::clearing_tokens((0, 0)), BalanceDuration {
-			last_block: 1,
-			last_balance: 10000,
-			value: 0,
-		});
-
-		System::set_block_number(100);
-		assert_ok!(Assets::issue(Origin::ROOT, 0, 2, 20000));
-		assert_eq!(Settlement::clearing_assets((0, 2, 0)), BalanceDuration {
-			last_block: 100,
-			last_balance: 20000,
-			value: 0,
-		});
-		assert_eq!(Settlement::clearing_tokens((0, 0)), BalanceDuration {
-			last_block: 100,
-			last_balance: 30000,
-			value: 10000 * (100 - 1),
-		});
-
-		System::set_block_number(200);
-		assert_ok!(Assets::issue(Origin::ROOT, 0, 2, 30000));
-		assert_eq!(Settlement::clearing_assets((0, 2, 0)), BalanceDuration {
-			last_block: 200,
-			last_balance: 50000,
-			value: 20000 * (200 - 100),
-		});
-		assert_eq!(Settlement::clearing_tokens((0, 0)), BalanceDuration {
-			last_block: 200,
-			last_balance: 60000,
-			value: 10000 * (100 - 1) + 30000 * (200 - 100),
-		});
+		assert_ok!(Assets::issue(Origin::ROOT, AssetSymbol::DOT, TokenType::Token, 1, 10000));
+		assert_eq!(Settlement::clearing_assets(0, (0, 1)), None);
+		assert_eq!(Settlement::clearing_tokens(0, 0), None);
 	});
 }
 
 #[test]
-fn transfer_asset_clearing_should_work() {
+fn transfer_vtoken_clearing_should_work() {
 	new_test_ext().execute_with(|| {
-		assert_ok!(Assets::create(Origin::ROOT, vec![0x12, 0x34], 8));
-
-		assert_ok!(Assets::issue(Origin::ROOT, 0, 1, 10000));
-		assert_eq!(Settlement::clearing_assets((0, 1, 0)), BalanceDuration {
-			last_block: 1,
-			last_balance: 10000,
-			value: 0,
-		});
-
-		System::set_block_number(100);
-		assert_ok!(Assets::transfer(Origin::signed(1), 0, 2, 1000));
-		assert_eq!(Settlement::clearing_assets((0, 1, 0)), BalanceDuration {
-			last_block: 100,
-			last_balance: 9000,
-			value: 10000 * (100 - 1),
-		});
-		assert_eq!(Settlement::clearing_assets((0, 2, 0)), BalanceDuration {
-			last_block: 100,
-			last_balance: 1000,
-			value: 0,
-		});
+		assert_ok!(Assets::issue(Origin::ROOT, AssetSymbol::DOT, TokenType::VToken, 1, 10000));
+
+		System::set_block_number(50);
+		assert_ok!(Assets::transfer(Origin::signed(1), AssetSymbol::DOT, TokenType::VToken, 2, 1000));
+		assert_eq!(Settlement::clearing_assets(0, (0, 1)), balance_duration(50, 9000, 10000 * (50 - 1)));
+		assert_eq!(Settlement::clearing_assets(0, (0, 2)), balance_duration(50, 1000, 0));
+		// transfer doesn't change total supply
+		assert_eq!(Settlement::clearing_tokens(0, 0), balance_duration(1, 10000, 0));
 	});
 }
 
 #[test]
-fn destroy_asset_clearing_should_work() {
+fn destroy_vtoken_clearing_should_work() {
 	new_test_ext().execute_with(|| {
-		assert_ok!(Assets::create(Origin::ROOT, vec![0x12, 0x34], 8));
-
-		assert_ok!(Assets::issue(Origin::ROOT, 0, 1, 10000));
-		assert_eq!(Settlement::clearing_assets((0, 1, 0)), BalanceDuration {
-			last_block: 1,
-			last_balance: 10000,
-			value: 0,
-		});
-
-		System::set_block_number(100);
-		assert_ok!(Assets::destroy(Origin::signed(1), 0, 1000));
-		assert_eq!(Settlement::clearing_assets((0, 1, 0)), BalanceDuration {
-			last_block: 100,
-			last_balance: 9000,
-			value: 10000 * (100 - 1),
-		});
-
-		System::set_block_number(200);
-		assert_ok!(Assets::destroy(Origin::signed(1), 0, 500));
-		assert_eq!(Settlement::clearing_assets((0, 1, 0)), BalanceDuration {
-			last_block: 200,
-			last_balance: 8500,
-			value: 10000 * (100 - 1) + 9000 * (200 - 100),
-		});
+		assert_ok!(Assets::issue(Origin::ROOT, AssetSymbol::DOT, TokenType::VToken, 1, 10000));
+
+		System::set_block_number(50);
+		assert_ok!(Assets::destroy(Origin::signed(1), AssetSymbol::DOT, TokenType::VToken, 1000));
+		assert_eq!(Settlement::clearing_assets(0, (0, 1)), balance_duration(50, 9000, 10000 * (50 - 1)));
+		assert_eq!(Settlement::clearing_tokens(0, 0), balance_duration(50, 9000, 10000 * (50 - 1)));
+
+		System::set_block_number(80);
+		assert_ok!(Assets::destroy(Origin::signed(1), AssetSymbol::DOT, TokenType::VToken, 500));
+		assert_eq!(
+			Settlement::clearing_assets(0, (0, 1)),
+			balance_duration(80, 8500, 10000 * (50 - 1) + 9000 * (80 - 50)),
+		);
 	});
 }
 
 #[test]
 fn new_settlement_should_work() {
 	new_test_ext().execute_with(|| {
-		Settlement::on_initialize(0);
-		assert_eq!(Settlement::next_settlement_id(), 1);
+		assert_eq!(Settlement::current_settlement_id(), 0);
+
+		run_to_block(99);
+		assert_eq!(Settlement::current_settlement_id(), 0);
 
-		Settlement::on_initialize(SETTLEMENT_PERIOD);
-		assert_eq!(Settlement::next_settlement_id(), 2);
+		run_to_block(100);
+		assert_eq!(Settlement::current_settlement_id(), 1);
+		assert_eq!(Settlement::settlement_start(1), 100);
+		assert!(System::events().iter().any(|r| r.event == TestEvent::settlement(RawEvent::NewSettlement(1))));
 
-		Settlement::on_initialize(SETTLEMENT_PERIOD * 2);
-		assert_eq!(Settlement::next_settlement_id(), 3);
+		run_to_block(200);
+		assert_eq!(Settlement::current_settlement_id(), 2);
+		assert_eq!(Settlement::settlement_start(2), 200);
+	});
+}
+
+#[test]
+fn new_settlement_should_close_and_carry_over_balances() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Assets::issue(Origin::ROOT, AssetSymbol::DOT, TokenType::VToken, 1, 10000));
+
+		run_to_block(50);
+		assert_ok!(Assets::transfer(Origin::signed(1), AssetSymbol::DOT, TokenType::VToken, 2, 4000));
+
+		run_to_block(100);
+		// token supply of the closed settlement is brought to its end, account records are cleared
+		assert_eq!(Settlement::clearing_tokens(0, 0), balance_duration(100, 10000, 10000 * 99));
+		assert_eq!(Settlement::reward_pools(0, 0).duration, 10000 * 99);
+		assert_eq!(Settlement::clearing_assets(0, (0, 1)), None);
+		assert_eq!(Settlement::clearing_assets(0, (0, 2)), None);
+		assert_eq!(Settlement::closing_settlement_id(), None);
+		assert!(System::events().iter().any(|r| r.event == TestEvent::settlement(RawEvent::SettlementClosed(0))));
+
+		// balances are carried over to the new settlement
+		assert_eq!(Settlement::clearing_assets(1, (0, 1)), balance_duration(100, 6000, 0));
+		assert_eq!(Settlement::clearing_assets(1, (0, 2)), balance_duration(100, 4000, 0));
+		assert_eq!(Settlement::clearing_tokens(1, 0), balance_duration(100, 10000, 0));
+
+		// empty balance is not carried over
+		run_to_block(150);
+		assert_ok!(Assets::transfer(Origin::signed(2), AssetSymbol::DOT, TokenType::VToken, 3, 4000));
+		assert_eq!(Settlement::clearing_assets(1, (0, 2)), balance_duration(150, 0, 4000 * 50));
+		assert_eq!(Settlement::clearing_assets(1, (0, 3)), balance_duration(150, 4000, 0));
+		run_to_block(200);
+		assert_eq!(Settlement::clearing_assets(2, (0, 1)), balance_duration(200, 6000, 0));
+		assert_eq!(Settlement::clearing_assets(2, (0, 2)), None);
+		assert_eq!(Settlement::clearing_assets(2, (0, 3)), balance_duration(200, 4000, 0));
 	});
 }
 
 #[test]
-fn destroy_clearing_record_should_work() {
+fn depositing_reward_should_work() {
 	new_test_ext().execute_with(|| {
-		Settlement::on_initialize(0);
-
-		assert_ok!(Assets::create(Origin::ROOT, vec![0x12, 0x34], 8));
-
-		assert_ok!(Assets::issue(Origin::ROOT, 0, 1, 10000));
-		assert_eq!(Settlement::clearing_assets((0, 1, 0)), BalanceDuration {
-			last_block: 1,
-			last_balance: 10000,
-			value: 0,
-		});
-
-		System::set_block_number(100);
-		assert_ok!(Assets::destroy(Origin::signed(1), 0, 1000));
-		assert_eq!(Settlement::clearing_assets((0, 1, Settlement::current_settlement_id())),
-			BalanceDuration {
-				last_block: 100,
-				last_balance: 9000,
-				value: 10000 * (100 - 1),
-			}
+		assert_ok!(Settlement::set_asset_reward(0, 1000));
+		assert_ok!(Settlement::set_asset_reward(0, 500));
+		assert_eq!(Settlement::reward_pools(0, 0).reward, 1500);
+		assert_eq!(Assets::asset_balances(0, TokenType::Token, Settlement::reward_pot()), 1500);
+		assert!(System::events().iter().any(|r| r.event == TestEvent::settlement(RawEvent::RewardDeposited(0, 0, 500))));
+
+		// asset doesn't exist
+		assert_eq!(Settlement::set_asset_reward(5, 1000), Err(()));
+
+		run_to_block(100);
+		assert_ok!(Settlement::set_asset_reward(0, 300));
+		assert_eq!(Settlement::reward_pools(1, 0).reward, 1500 + 300);
+	});
+}
+
+#[test]
+fn reward_should_be_shared_by_balance_duration() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Assets::issue(Origin::ROOT, AssetSymbol::DOT, TokenType::VToken, 1, 10000));
+		assert_ok!(Settlement::set_asset_reward(0, 1000));
+
+		run_to_block(50);
+		assert_ok!(Assets::transfer(Origin::signed(1), AssetSymbol::DOT, TokenType::VToken, 2, 4000));
+
+		run_to_block(100);
+		// 1000 * 790000 / 990000 and 1000 * 200000 / 990000
+		assert_eq!(Settlement::pending_rewards(0, 1), 797);
+		assert_eq!(Settlement::pending_rewards(0, 2), 202);
+		assert_eq!(Settlement::reward_pools(0, 0).distributed, 999);
+		// rounding remains go to the ongoing settlement
+		assert_eq!(Settlement::reward_pools(1, 0).reward, 1);
+
+		// rewards are paid from the reward pot
+		assert_ok!(Settlement::claim_reward(Origin::signed(1), AssetSymbol::DOT));
+		assert_eq!(Assets::asset_balances(0, TokenType::Token, 1), 797);
+		assert_eq!(Assets::asset_balances(0, TokenType::Token, Settlement::reward_pot()), 1000 - 797);
+		assert_eq!(Settlement::pending_rewards(0, 1), 0);
+		assert!(System::events().iter().any(|r| r.event == TestEvent::settlement(RawEvent::RewardClaimed(1, 0, 797))));
+
+		assert_noop!(
+			Settlement::claim_reward(Origin::signed(1), AssetSymbol::DOT),
+			SettlementError::NoRewardToClaim,
+		);
+		assert_noop!(
+			Settlement::claim_reward(Origin::signed(3), AssetSymbol::DOT),
+			SettlementError::NoRewardToClaim,
 		);
 
-		System::set_block_number(SETTLEMENT_PERIOD + 100);
-		Settlement::on_initialize(SETTLEMENT_PERIOD);
-		let curr_stl_id = Settlement::current_settlement_id();
-		assert_eq!(curr_stl_id, 1);
-		assert_eq!(Settlement::next_settlement_id(), 2);
-		//		System::set_block_number(200);
-		assert_ok!(Assets::destroy(Origin::signed(1), 0, 500));
-		assert_eq!(Settlement::clearing_assets((0, 1, curr_stl_id)), BalanceDuration {
-			last_block: SETTLEMENT_PERIOD + 100,
-			last_balance: 8500,
-			value: 9000 * 100,
-		});
+		// rewards are never paid more than the pot holds
+		<PendingRewards<Test>>::insert(0, 3, 1000);
+		assert_noop!(
+			Settlement::claim_reward(Origin::signed(3), AssetSymbol::DOT),
+			SettlementError::RewardPotExhausted,
+		);
+	});
+}
+
+#[test]
+fn reward_without_holders_should_be_moved_to_next_settlement() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Settlement::set_asset_reward(0, 500));
+
+		run_to_block(100);
+		assert_eq!(Settlement::closing_settlement_id(), None);
+		assert_eq!(Settlement::reward_pools(0, 0).distributed, 0);
+		assert_eq!(Settlement::reward_pools(1, 0).reward, 500);
 	});
 }
 
 #[test]
-fn enumerate_should_work() {
+fn clearing_should_be_bounded_per_block() {
 	new_test_ext().execute_with(|| {
-		Settlement::on_initialize(0);
-
-		assert_ok!(Assets::create(Origin::ROOT, vec![0x12, 0x34], 8));
-
-		assert_ok!(Assets::issue(Origin::ROOT, 0, 1, 10000));
-		assert_eq!(Settlement::clearing_assets((0, 1, 0)), BalanceDuration {
-			last_block: 1,
-			last_balance: 10000,
-			value: 0,
-		});
-
-		assert_ok!(Assets::issue(Origin::ROOT, 0, 2, 5000));
-		assert_eq!(Settlement::clearing_assets((0, 2, 0)), BalanceDuration {
-			last_block: 1,
-			last_balance: 5000,
-			value: 0,
-		});
-		Settlement::on_finalize(0);
-
-		const SETTLEMENT_PERIOD: u64 = 24 * 60 * 10;
-		System::set_block_number(SETTLEMENT_PERIOD);
-		Settlement::on_initialize(SETTLEMENT_PERIOD);
-
-		assert_ok!(Assets::issue(Origin::ROOT, 0, 2, 5000));
-		assert_eq!(Settlement::clearing_assets((0, 2, 1)), BalanceDuration {
-			last_block: 14400,
-			last_balance: 10000,
-			value: 0,
-		});
-
-//		Settlement::on_finalize(SETTLEMENT_PERIOD);
+		for who in 1..=3 {
+			assert_ok!(Assets::issue(Origin::ROOT, AssetSymbol::DOT, TokenType::VToken, who, 1000));
+		}
+		assert_ok!(Settlement::set_asset_reward(0, 3000));
+
+		run_to_block(100);
+		assert_eq!(Settlement::closing_settlement_id(), Some(0));
+		assert_eq!(<ClearingAssets<Test>>::iter_prefix(0).count(), 1);
+
+		// balances change in the new settlement before the account is cleared
+		for who in 1..=3 {
+			assert_ok!(Assets::issue(Origin::ROOT, AssetSymbol::DOT, TokenType::VToken, who, 1000));
+		}
+
+		run_to_block(101);
+		assert_eq!(Settlement::closing_settlement_id(), None);
+		assert_eq!(<ClearingAssets<Test>>::iter_prefix(0).count(), 0);
+		for who in 1..=3 {
+			assert_eq!(Settlement::pending_rewards(0, who), 1000);
+			assert_eq!(Settlement::clearing_assets(1, (0, who)), balance_duration(100, 2000, 0));
+		}
+
+		assert_eq!(Settlement::current_settlement_id(), 1);
+		run_to_block(200);
+		assert_eq!(Settlement::current_settlement_id(), 2);
 	});
 }
@@ -104,6 +104,7 @@ impl assets::Trait for Test {
 	type Convert = u64;
 	type AssetRedeem = ();
 	type FetchConvertPrice = ();
+	type ClearingHandler = ();
 }
 
 pub type Swap = crate::Module<Test>;
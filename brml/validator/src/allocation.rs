@@ -0,0 +1,95 @@
+// Copyright 2019-2020 Liebi Technologies.
+// This file is part of Bifrost.
+
+// Bifrost is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Bifrost is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Bifrost.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Allocation of a staking pool across validators.
+//!
+//! Each validator is weighted by its deposit times its performance score, and the pool is
+//! allocated in proportion to the weights. No validator gets more than it needs, the part over
+//! its need goes to the others by the same weights, so the whole pool is allocated unless all
+//! validators have got what they need.
+
+use sp_runtime::{Permill, helpers_128bit::multiply_by_rational};
+use sp_std::prelude::*;
+
+/// Validator competing for the pool
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct Candidate {
+	/// The most it can be staked
+	pub need: u128,
+	/// Reward deposited by the validator
+	pub deposit: u128,
+	/// Performance of the validator
+	pub score: Permill,
+}
+
+impl Candidate {
+	/// Validator without need, deposit or score gets nothing
+	pub fn weight(&self) -> u128 {
+		if self.need == 0 {
+			return 0;
+		}
+
+		self.score * self.deposit
+	}
+}
+
+/// Amount allocated to each candidate, in the order of candidates.
+///
+/// Rounding dust goes to the heaviest candidates one unit each, ties are broken by the order.
+pub fn allocate(pool: u128, candidates: &[Candidate]) -> Vec<u128> {
+	let mut allocations = Vec::new();
+	allocations.resize(candidates.len(), 0u128);
+	let mut active: Vec<usize> = (0..candidates.len()).filter(|&i| candidates[i].weight() > 0).collect();
+	let mut remaining = pool;
+
+	while remaining > 0 && !active.is_empty() {
+		let total_weight = active.iter().fold(0u128, |total, &i| total.saturating_add(candidates[i].weight()));
+		let share = |i: usize| multiply_by_rational(remaining, candidates[i].weight(), total_weight).unwrap_or(0);
+
+		// candidates whose share covers the need get the need, and the rest is shared again
+		let capped: Vec<usize> = active.iter().copied().filter(|&i| share(i) >= candidates[i].need).collect();
+		if capped.is_empty() {
+			let mut allocated = 0u128;
+			for &i in active.iter() {
+				let amount = share(i).min(remaining - allocated);
+				allocations[i] = amount;
+				allocated += amount;
+			}
+			remaining -= allocated;
+			break;
+		}
+
+		for &i in capped.iter() {
+			let amount = candidates[i].need.min(remaining);
+			allocations[i] = amount;
+			remaining -= amount;
+		}
+		active.retain(|i| !capped.contains(i));
+	}
+
+	active.sort_by(|&a, &b| candidates[b].weight().cmp(&candidates[a].weight()).then(a.cmp(&b)));
+	for &i in active.iter() {
+		if remaining == 0 {
+			break;
+		}
+		if allocations[i] < candidates[i].need {
+			allocations[i] += 1;
+			remaining -= 1;
+		}
+	}
+
+	allocations
+}
@@ -14,20 +14,32 @@
 // You should have received a copy of the GNU General Public License
 // along with Bifrost.  If not, see <http://www.gnu.org/licenses/>.
 
+//! Validators on other blockchains register here, and deposit the reward for staking. The reward
+//! is deducted from deposits as they're staked, and shared by vToken holders of the asset.
+//!
+//! Assets locked in the staking pool are allocated across registered validators each era, in
+//! proportion to their deposit and performance score, and up to what they need. Differences from
+//! the current staking are sent as staking actions through the bridge.
+
 #![cfg_attr(not(feature = "std"), no_std)]
 
+pub mod allocation;
 mod mock;
 mod tests;
 
 use codec::{Encode, Decode};
 use core::convert::{From, Into};
 use frame_support::traits::Get;
-use frame_support::storage::{StorageMap, IterableStorageDoubleMap};
+use frame_support::storage::{StorageMap, StorageValue, IterableStorageMap, IterableStorageDoubleMap};
 use frame_support::{decl_event, decl_error, decl_module, decl_storage, ensure, Parameter};
+use frame_support::dispatch::DispatchResult;
+use frame_support::weights::Weight;
 use frame_system::{self as system, ensure_root, ensure_signed};
-use node_primitives::{AssetSymbol, AssetTrait, TokenType};
-use sp_runtime::RuntimeDebug;
+use node_primitives::{AssetReward, AssetSymbol, AssetTrait, BridgeStake, StakingPool, TokenType};
+use sp_runtime::{Permill, RuntimeDebug, SaturatedConversion};
 use sp_runtime::traits::{Member, Saturating, AtLeast32Bit, Zero};
+use allocation::Candidate;
+use sp_std::prelude::*;
 
 #[derive(Encode, Decode, Clone, Default, PartialEq, Eq, RuntimeDebug)]
 pub struct AssetConfig<Balance> {
@@ -78,6 +90,16 @@ pub trait Trait: frame_system::Trait {
 	/// The units in which we record incomes.
 	type Income: Member + Parameter + AtLeast32Bit + Default + Copy;
 	type AssetTrait: AssetTrait<Self::AssetId, Self::AccountId, Self::Balance, Self::Cost, Self::Income>;
+	/// Stake assets to validators on the blockchains they are bridged from.
+	type BridgeStake: BridgeStake<Self::AssetId, Self::AccountId, Self::Balance>;
+	/// Assets which are allocated across validators.
+	type StakingPool: StakingPool<Self::AssetId, Self::Balance>;
+	/// Reward deducted from deposits of validators goes to vToken holders.
+	type AssetReward: AssetReward<Self::AssetId, Self::Balance>;
+	/// Staking is rebalanced at the start of each era.
+	type EraLength: Get<Self::BlockNumber>;
+	/// The most validators registered for an asset, it bounds the work of rebalancing the asset.
+	type MaxValidators: Get<u32>;
 }
 
 decl_event! {
@@ -100,6 +122,12 @@ decl_event! {
 		ValidatorStaked(AssetSymbol, AccountId, Balance),
 		/// The amount of asset un-staked from the account.
 		ValidatorUnStaked(AssetSymbol, AccountId, Balance),
+		/// Performance score of the validator is set.
+		PerformanceScoreSet(AssetSymbol, AccountId, Permill),
+		/// Staking of the asset is rebalanced in the era, with the total amount staked.
+		Rebalanced(AssetSymbol, u32, Balance),
+		/// The validator couldn't be rebalanced to the amount, by bridge or by assets not released.
+		RebalanceFailed(AssetSymbol, AccountId, Balance),
 	}
 }
 
@@ -117,6 +145,8 @@ decl_error! {
 		StakingAmountExceeded,
 		/// The staking amount is insufficient for un-staking.
 		StakingAmountInsufficient,
+		/// The asset has got as many validators as it can be rebalanced.
+		TooManyValidators,
 	}
 }
 
@@ -131,6 +161,15 @@ decl_storage! {
 			=> Validator<T::Balance, T::BlockNumber>;
 		/// The locked amount of asset of account for staking.
 		LockedBalances get(fn locked_balances): map hasher(blake2_128_concat) T::AccountId => T::Balance;
+		/// Performance score of the validator, which weights its allocation.
+		PerformanceScores get(fn performance_scores): double_map hasher(blake2_128_concat) AssetSymbol, hasher(blake2_128_concat) T::AccountId
+			=> Permill = Permill::one();
+		/// The current era, staking is rebalanced when a new era starts.
+		CurrentEra get(fn current_era): u32;
+		/// How many validators are registered for the asset.
+		ValidatorCount get(fn validator_count): map hasher(blake2_128_concat) AssetSymbol => u32;
+		/// Assets to be rebalanced in the current era, one in each block.
+		PendingRebalances get(fn pending_rebalances): Vec<AssetSymbol>;
 	}
 }
 
@@ -138,9 +177,11 @@ decl_module! {
 	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
 		type Error = Error<T>;
 
+		const MaxValidators: u32 = T::MaxValidators::get();
+
 		fn deposit_event() = default;
 
-		#[weight = 0]
+		#[weight = T::DbWeight::get().writes(1)]
 		fn set_asset(
 			origin,
 			asset_symbol: AssetSymbol,
@@ -163,27 +204,8 @@ decl_module! {
 			amount: T::Balance,
 		) {
 			let _ = ensure_root(origin)?;
-			ensure!(
-				Validators::<T>::contains_key(&asset_symbol, &target),
-				Error::<T>::ValidatorNotRegistered
-			);
-			let validator = Validators::<T>::get(&asset_symbol, &target);
-			ensure!(
-				validator.need - validator.staking >= amount,
-				Error::<T>::StakingAmountExceeded,
-			);
 
-			Validators::<T>::mutate(&asset_symbol, &target, |validator| {
-				validator.staking = validator.staking.saturating_add(amount);
-			});
-
-			AssetLockedBalances::<T>::mutate(&asset_symbol, |balance| {
-				*balance = balance.saturating_add(amount);
-			});
-
-			// TODO stake asset by bridge module
-
-			Self::deposit_event(RawEvent::ValidatorStaked(asset_symbol, target, amount));
+			Self::do_staking(asset_symbol, target, amount)?;
 		}
 
 		#[weight = T::DbWeight::get().writes(1)]
@@ -194,30 +216,29 @@ decl_module! {
 			amount: T::Balance,
 		) {
 			let _ = ensure_root(origin)?;
+
+			Self::do_unstaking(asset_symbol, target, amount)?;
+		}
+
+		#[weight = T::DbWeight::get().reads_writes(1, 1)]
+		fn set_performance_score(
+			origin,
+			asset_symbol: AssetSymbol,
+			target: T::AccountId,
+			score: Permill,
+		) {
+			let _ = ensure_root(origin)?;
 			ensure!(
 				Validators::<T>::contains_key(&asset_symbol, &target),
 				Error::<T>::ValidatorNotRegistered
 			);
-			let validator = Validators::<T>::get(&asset_symbol, &target);
-			ensure!(
-				validator.staking >= amount,
-				Error::<T>::StakingAmountInsufficient,
-			);
 
-			Validators::<T>::mutate(&asset_symbol, &target, |validator| {
-				validator.staking = validator.staking.saturating_sub(amount);
-			});
-
-			AssetLockedBalances::<T>::mutate(&asset_symbol, |balance| {
-				*balance = balance.saturating_sub(amount);
-			});
+			PerformanceScores::<T>::insert(&asset_symbol, &target, score);
 
-			// TODO un-stake asset by bridge module
-
-			Self::deposit_event(RawEvent::ValidatorUnStaked(asset_symbol, target, amount));
+			Self::deposit_event(RawEvent::PerformanceScoreSet(asset_symbol, target, score));
 		}
 
-		#[weight = T::DbWeight::get().writes(1)]
+		#[weight = T::DbWeight::get().reads_writes(2, 2)]
 		fn register(
 			origin,
 			asset_symbol: AssetSymbol,
@@ -230,9 +251,14 @@ decl_module! {
 				!Validators::<T>::contains_key(&asset_symbol, &origin),
 				Error::<T>::ValidatorRegistered
 			);
+			ensure!(
+				ValidatorCount::get(&asset_symbol) < T::MaxValidators::get(),
+				Error::<T>::TooManyValidators
+			);
 
 			let validator  = Validator::new(need, validator_address);
 			Validators::<T>::insert(&asset_symbol, &origin, &validator);
+			ValidatorCount::mutate(&asset_symbol, |count| *count += 1);
 
 			Self::deposit_event(RawEvent::ValidatorRegistered(asset_symbol, origin, validator));
 		}
@@ -247,7 +273,7 @@ decl_module! {
 			);
 
 			Validators::<T>::mutate(&asset_symbol, &origin, |validator| {
-				validator.need = validator.need.saturating_add(amount);
+				validator.need = amount;
 			});
 
 			Self::deposit_event(RawEvent::ValidatorNeedAmountSet(asset_symbol, origin, amount));
@@ -291,6 +317,32 @@ decl_module! {
 			Self::deposit_event(RawEvent::ValidatorWithdrawn(asset_symbol, origin, amount));
 		}
 
+		fn on_initialize(now_block: T::BlockNumber) -> Weight {
+			let mut weight = T::DbWeight::get().reads(1);
+
+			let era_length = T::EraLength::get();
+			if !era_length.is_zero() && (now_block % era_length).is_zero() {
+				CurrentEra::mutate(|era| *era += 1);
+				// assets not rebalanced in the last era are rebalanced in this era
+				let assets: Vec<AssetSymbol> = AssetConfigs::<T>::iter().map(|(asset_symbol, _)| asset_symbol).collect();
+				weight = weight.saturating_add(T::DbWeight::get().reads_writes(1 + assets.len() as Weight, 2));
+				PendingRebalances::put(assets);
+			}
+
+			// one asset is rebalanced in each block, so a block stakes at most `MaxValidators` through bridge
+			let next = PendingRebalances::mutate(|assets| if assets.is_empty() { None } else { Some(assets.remove(0)) });
+			if let Some(asset_symbol) = next {
+				let validators = Self::rebalance(asset_symbol, CurrentEra::get()) as Weight;
+
+				// each validator is read with its score, and may be staked through bridge
+				weight = weight.saturating_add(
+					T::DbWeight::get().reads_writes(4 + validators * 4, 1 + validators * 2)
+				);
+			}
+
+			weight
+		}
+
 		fn on_finalize(now_block: T::BlockNumber) {
 			Self::validator_deduct(now_block);
 		}
@@ -298,6 +350,110 @@ decl_module! {
 }
 
 impl<T: Trait> Module<T> {
+	/// Staking of each validator of the asset now and after rebalancing, (validator, staking, allocation)
+	pub fn allocation_plan(asset_symbol: AssetSymbol) -> Vec<(T::AccountId, T::Balance, T::Balance)> {
+		let pool = T::StakingPool::staking_pool(asset_symbol.into());
+		let validators: Vec<(T::AccountId, Validator<T::Balance, T::BlockNumber>)> =
+			Validators::<T>::iter_prefix(&asset_symbol).collect();
+		let candidates: Vec<Candidate> = validators.iter()
+			.map(|(account_id, validator)| Candidate {
+				need: validator.need.saturated_into(),
+				deposit: validator.deposit.saturated_into(),
+				score: PerformanceScores::<T>::get(&asset_symbol, account_id),
+			})
+			.collect();
+		let allocations = allocation::allocate(pool.saturated_into(), &candidates);
+
+		validators.into_iter()
+			.zip(allocations)
+			.map(|((account_id, validator), allocation)| (account_id, validator.staking, allocation.saturated_into()))
+			.collect()
+	}
+
+	/// Stake or un-stake the difference between staking and allocation of each validator, and
+	/// return how many validators are rebalanced
+	fn rebalance(asset_symbol: AssetSymbol, era: u32) -> usize {
+		let plan = Self::allocation_plan(asset_symbol);
+
+		// un-staking goes first, so assets are released before they're staked to others
+		let mut unreleased = T::Balance::zero();
+		for (account_id, staking, allocation) in plan.iter().filter(|(_, staking, allocation)| staking > allocation) {
+			if Self::do_unstaking(asset_symbol, account_id.clone(), *staking - *allocation).is_err() {
+				unreleased = unreleased.saturating_add(*staking - *allocation);
+				Self::deposit_event(RawEvent::RebalanceFailed(asset_symbol, account_id.clone(), *allocation));
+			}
+		}
+
+		// assets still staked to validators which failed un-staking can't be staked to others
+		for (account_id, staking, allocation) in plan.iter().filter(|(_, staking, allocation)| staking < allocation) {
+			let cut = unreleased.min(*allocation - *staking);
+			unreleased -= cut;
+			let amount = *allocation - *staking - cut;
+			let staked = !amount.is_zero() && Self::do_staking(asset_symbol, account_id.clone(), amount).is_ok();
+			if !staked || !cut.is_zero() {
+				Self::deposit_event(RawEvent::RebalanceFailed(asset_symbol, account_id.clone(), *allocation));
+			}
+		}
+
+		Self::deposit_event(RawEvent::Rebalanced(asset_symbol, era, AssetLockedBalances::<T>::get(&asset_symbol)));
+
+		plan.len()
+	}
+
+	fn do_staking(asset_symbol: AssetSymbol, target: T::AccountId, amount: T::Balance) -> DispatchResult {
+		ensure!(
+			Validators::<T>::contains_key(&asset_symbol, &target),
+			Error::<T>::ValidatorNotRegistered
+		);
+		let validator = Validators::<T>::get(&asset_symbol, &target);
+		ensure!(
+			validator.need.saturating_sub(validator.staking) >= amount,
+			Error::<T>::StakingAmountExceeded,
+		);
+
+		// stake asset on its blockchain by bridge module
+		T::BridgeStake::bridge_stake(asset_symbol.into(), target.clone(), validator.validator_address, amount)?;
+
+		Validators::<T>::mutate(&asset_symbol, &target, |validator| {
+			validator.staking = validator.staking.saturating_add(amount);
+		});
+
+		AssetLockedBalances::<T>::mutate(&asset_symbol, |balance| {
+			*balance = balance.saturating_add(amount);
+		});
+
+		Self::deposit_event(RawEvent::ValidatorStaked(asset_symbol, target, amount));
+
+		Ok(())
+	}
+
+	fn do_unstaking(asset_symbol: AssetSymbol, target: T::AccountId, amount: T::Balance) -> DispatchResult {
+		ensure!(
+			Validators::<T>::contains_key(&asset_symbol, &target),
+			Error::<T>::ValidatorNotRegistered
+		);
+		let validator = Validators::<T>::get(&asset_symbol, &target);
+		ensure!(
+			validator.staking >= amount,
+			Error::<T>::StakingAmountInsufficient,
+		);
+
+		// un-stake asset on its blockchain by bridge module
+		T::BridgeStake::bridge_unstake(asset_symbol.into(), target.clone(), validator.validator_address, amount)?;
+
+		Validators::<T>::mutate(&asset_symbol, &target, |validator| {
+			validator.staking = validator.staking.saturating_sub(amount);
+		});
+
+		AssetLockedBalances::<T>::mutate(&asset_symbol, |balance| {
+			*balance = balance.saturating_sub(amount);
+		});
+
+		Self::deposit_event(RawEvent::ValidatorUnStaked(asset_symbol, target, amount));
+
+		Ok(())
+	}
+
 	fn asset_lock(
 		account_id: T::AccountId,
 		asset_symbol: AssetSymbol,
@@ -363,22 +519,25 @@ impl<T: Trait> Module<T> {
 			let min_reward_per_block = asset_config.min_reward_per_block;
 
 			let min_fee = min_reward_per_block.saturating_mul(redeem_duration.into());
-			let mut fee = Zero::zero();
-			if min_fee >= val.deposit {
-				fee = val.deposit;
-				val.deposit = Zero::zero();
+			let fee = if min_fee >= val.deposit {
+				val.deposit
 			} else {
 				let blocks = now_block - val.last_block;
-				fee = val.staking.saturating_mul(val.reward_per_block.saturating_mul(blocks.into()));
+				val.staking.saturating_mul(val.reward_per_block.saturating_mul(blocks.into())).min(val.deposit)
+			};
+
+			// the deposit was destroyed when it's locked, so the fee funds the reward
+			if !fee.is_zero() && T::AssetReward::set_asset_reward(asset_symbol.into(), fee).is_ok() {
 				val.deposit = val.deposit.saturating_sub(fee);
+				LockedBalances::<T>::mutate(&account_id, |locked_balance| {
+					*locked_balance = locked_balance.saturating_sub(fee)
+				});
 			}
 
 			val.last_block = now_block;
 
 			// update validator
 			Validators::<T>::insert(&asset_symbol, &account_id, val);
-
-			// TODO call redeem from bridge-eos
 		}
 	}
 }
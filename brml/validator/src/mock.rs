@@ -16,11 +16,12 @@
 
 #![cfg(test)]
 
+use std::cell::RefCell;
 use frame_support::{
 	impl_outer_origin, impl_outer_dispatch, impl_outer_event, parameter_types, traits::{OnInitialize, OnFinalize}
 };
 use sp_core::H256;
-use sp_runtime::{Perbill, testing::Header, traits::{BlakeTwo256, IdentityLookup}};
+use sp_runtime::{DispatchResult, DispatchError, Perbill, testing::Header, traits::{BlakeTwo256, IdentityLookup}};
 use super::*;
 
 impl_outer_dispatch! {
@@ -39,6 +40,7 @@ impl_outer_origin! {
 impl_outer_event! {
 	pub enum TestEvent for Test {
 		system<T>,
+		assets<T>,
 		brml_validator<T>,
 	}
 }
@@ -53,6 +55,8 @@ parameter_types! {
 	pub const MaximumBlockLength: u32 = 4 * 1024 * 1024;
 	pub const AvailableBlockRatio: Perbill = Perbill::from_percent(75);
 	pub const UncleGenerations: u32 = 5;
+	pub const EraLength: u64 = 10;
+	pub const MaxValidators: u32 = 3;
 }
 
 impl frame_system::Trait for Test {
@@ -80,12 +84,110 @@ impl frame_system::Trait for Test {
 	type ExtrinsicBaseWeight = ();
 }
 
+impl assets::Trait for Test {
+	type Event = TestEvent;
+	type Balance = u64;
+	type AssetId = u32;
+	type Price = u64;
+	type Cost = u64;
+	type Income = u64;
+	type Convert = u64;
+	type AssetRedeem = ();
+	type FetchConvertPrice = ();
+	type ClearingHandler = ();
+}
+
+/// Staking action sent to bridge, (asset, validator, validator address, amount, is staking)
+pub(crate) type StakingAction = (u32, u64, Vec<u8>, u64, bool);
+
+thread_local! {
+	static STAKING_ACTIONS: RefCell<Vec<StakingAction>> = RefCell::new(Vec::new());
+	static STAKING_POOL: RefCell<u64> = RefCell::new(0);
+	static UNSTAKING_REJECTED: RefCell<bool> = RefCell::new(false);
+	static ASSET_REWARDS: RefCell<Vec<(u32, u64)>> = RefCell::new(Vec::new());
+}
+
+/// Validator address which no blockchain accepts
+pub(crate) const INVALID_ADDRESS: &[u8] = b"invalid";
+
+pub struct MockBridgeStake;
+
+impl MockBridgeStake {
+	fn send(asset_id: u32, validator: u64, validator_address: Vec<u8>, amount: u64, is_stake: bool) -> DispatchResult {
+		if validator_address == INVALID_ADDRESS {
+			return Err(DispatchError::Other("invalid validator address"));
+		}
+		if !is_stake && UNSTAKING_REJECTED.with(|rejected| *rejected.borrow()) {
+			return Err(DispatchError::Other("un-staking rejected"));
+		}
+		STAKING_ACTIONS.with(|actions| actions.borrow_mut().push((asset_id, validator, validator_address, amount, is_stake)));
+
+		Ok(())
+	}
+}
+
+impl BridgeStake<u32, u64, u64> for MockBridgeStake {
+	fn bridge_stake(asset_id: u32, validator: u64, validator_address: Vec<u8>, amount: u64) -> DispatchResult {
+		Self::send(asset_id, validator, validator_address, amount, true)
+	}
+
+	fn bridge_unstake(asset_id: u32, validator: u64, validator_address: Vec<u8>, amount: u64) -> DispatchResult {
+		Self::send(asset_id, validator, validator_address, amount, false)
+	}
+}
+
+pub(crate) fn staking_actions() -> Vec<StakingAction> {
+	STAKING_ACTIONS.with(|actions| actions.borrow().clone())
+}
+
+/// Make bridge reject all un-staking
+pub(crate) fn set_unstaking_rejected(rejected: bool) {
+	UNSTAKING_REJECTED.with(|r| *r.borrow_mut() = rejected);
+}
+
+pub struct MockStakingPool;
+
+impl StakingPool<u32, u64> for MockStakingPool {
+	fn staking_pool(_: u32) -> u64 {
+		STAKING_POOL.with(|pool| *pool.borrow())
+	}
+}
+
+pub(crate) fn set_staking_pool(amount: u64) {
+	STAKING_POOL.with(|pool| *pool.borrow_mut() = amount);
+}
+
+pub struct MockAssetReward;
+
+impl AssetReward<u32, u64> for MockAssetReward {
+	fn set_asset_reward(asset_id: u32, reward: u64) -> Result<(), ()> {
+		ASSET_REWARDS.with(|rewards| rewards.borrow_mut().push((asset_id, reward)));
+		Ok(())
+	}
+}
+
+/// Rewards deposited for vToken holders, (asset, reward)
+pub(crate) fn asset_rewards() -> Vec<(u32, u64)> {
+	ASSET_REWARDS.with(|rewards| rewards.borrow().clone())
+}
+
 impl crate::Trait for Test {
 	type Event = TestEvent;
 	type Balance = u64;
+	type AssetId = u32;
+	type Cost = u64;
+	type Income = u64;
+	type AssetTrait = Assets;
+	type BridgeStake = MockBridgeStake;
+	type StakingPool = MockStakingPool;
+	type AssetReward = MockAssetReward;
+	type EraLength = EraLength;
+	type MaxValidators = MaxValidators;
 }
 
 pub type Validator = crate::Module<Test>;
+pub type ValidatorError = crate::Error<Test>;
+pub type Assets = assets::Module<Test>;
 pub type System = frame_system::Module<Test>;
 
 // simulate block production
@@ -101,5 +203,13 @@ pub(crate) fn run_to_block(n: u64) {
 
 // mockup runtime
 pub(crate) fn new_test_ext() -> sp_io::TestExternalities {
-	system::GenesisConfig::default().build_storage::<Test>().unwrap().into()
+	let mut ext: sp_io::TestExternalities = system::GenesisConfig::default().build_storage::<Test>().unwrap().into();
+	ext.execute_with(|| {
+		System::set_block_number(1);
+		// asset ids follow AssetSymbol
+		for symbol in [b"DOT".to_vec(), b"KSM".to_vec(), b"EOS".to_vec()].iter() {
+			assert!(Assets::create(Origin::ROOT, symbol.clone(), 12).is_ok());
+		}
+	});
+	ext
 }
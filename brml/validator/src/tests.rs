@@ -20,5 +20,301 @@
 
 use crate::*;
 use crate::mock::*;
-use frame_support::assert_ok;
+// the module, rather than the validator struct
+use crate::mock::Validator;
+use frame_support::{assert_ok, assert_noop};
+use allocation::{allocate, Candidate};
+use rand::{Rng, SeedableRng, rngs::StdRng};
 
+#[test]
+fn staking_should_be_sent_to_bridge() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Validator::register(Origin::signed(1), AssetSymbol::EOS, 100, b"producer1".to_vec()));
+
+		assert_ok!(Validator::staking(Origin::ROOT, AssetSymbol::EOS, 1, 60));
+		assert_eq!(Validator::validators(AssetSymbol::EOS, 1).staking, 60);
+		assert_eq!(Validator::asset_locked_balances(AssetSymbol::EOS), 60);
+
+		assert_ok!(Validator::unstaking(Origin::ROOT, AssetSymbol::EOS, 1, 20));
+		assert_eq!(Validator::validators(AssetSymbol::EOS, 1).staking, 40);
+		assert_eq!(Validator::asset_locked_balances(AssetSymbol::EOS), 40);
+
+		assert_eq!(staking_actions(), vec![
+			(2, 1, b"producer1".to_vec(), 60, true),
+			(2, 1, b"producer1".to_vec(), 20, false),
+		]);
+	});
+}
+
+#[test]
+fn staking_should_be_rejected() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			Validator::staking(Origin::ROOT, AssetSymbol::EOS, 1, 60),
+			ValidatorError::ValidatorNotRegistered
+		);
+
+		assert_ok!(Validator::register(Origin::signed(1), AssetSymbol::EOS, 100, b"producer1".to_vec()));
+		assert!(Validator::staking(Origin::signed(1), AssetSymbol::EOS, 1, 60).is_err());
+		assert_noop!(
+			Validator::staking(Origin::ROOT, AssetSymbol::EOS, 1, 101),
+			ValidatorError::StakingAmountExceeded
+		);
+		assert_noop!(
+			Validator::unstaking(Origin::ROOT, AssetSymbol::EOS, 1, 1),
+			ValidatorError::StakingAmountInsufficient
+		);
+
+		// nothing is staked if bridge rejects it
+		assert_ok!(Validator::register(Origin::signed(2), AssetSymbol::EOS, 100, INVALID_ADDRESS.to_vec()));
+		assert!(Validator::staking(Origin::ROOT, AssetSymbol::EOS, 2, 60).is_err());
+		assert_eq!(Validator::validators(AssetSymbol::EOS, 2).staking, 0);
+		assert_eq!(Validator::asset_locked_balances(AssetSymbol::EOS), 0);
+		assert!(staking_actions().is_empty());
+	});
+}
+
+#[test]
+fn need_amount_should_be_replaced() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			Validator::set_need_amount(Origin::signed(1), AssetSymbol::EOS, 60),
+			ValidatorError::ValidatorNotRegistered
+		);
+
+		assert_ok!(Validator::register(Origin::signed(1), AssetSymbol::EOS, 100, b"producer1".to_vec()));
+		assert_ok!(Validator::set_need_amount(Origin::signed(1), AssetSymbol::EOS, 60));
+		assert_eq!(Validator::validators(AssetSymbol::EOS, 1).need, 60);
+		assert_ok!(Validator::set_need_amount(Origin::signed(1), AssetSymbol::EOS, 80));
+		assert_eq!(Validator::validators(AssetSymbol::EOS, 1).need, 80);
+	});
+}
+
+fn candidate(need: u128, deposit: u128, score: u32) -> Candidate {
+	Candidate { need, deposit, score: Permill::from_percent(score) }
+}
+
+#[test]
+fn allocation_should_be_weighted_and_capped_by_need() {
+	let candidates = vec![
+		candidate(1000, 100, 100),
+		candidate(200, 300, 100),
+		candidate(1000, 100, 50),
+		candidate(0, 500, 100),
+		candidate(1000, 0, 100),
+	];
+	// the second one is capped by its need, and the rest is shared by 100:50
+	assert_eq!(allocate(1000, &candidates), vec![534, 200, 266, 0, 0]);
+	// validators only get what they need
+	assert_eq!(allocate(10_000, &candidates), vec![1000, 200, 1000, 0, 0]);
+	assert_eq!(allocate(0, &candidates), vec![0; 5]);
+	assert!(allocate(1000, &[]).is_empty());
+}
+
+#[test]
+fn allocation_properties_should_hold() {
+	let mut rng = StdRng::seed_from_u64(42);
+	for _ in 0..2000 {
+		let pool: u128 = rng.gen_range(0, 1_000_000);
+		let candidates: Vec<Candidate> = (0..rng.gen_range(0, 20))
+			.map(|_| Candidate {
+				need: if rng.gen_bool(0.1) { 0 } else { rng.gen_range(0, 200_000) },
+				deposit: if rng.gen_bool(0.1) { 0 } else { rng.gen_range(0, 100_000) },
+				score: Permill::from_parts(if rng.gen_bool(0.1) { 0 } else { rng.gen_range(0, 1_000_001) }),
+			})
+			.collect();
+		let allocations = allocate(pool, &candidates);
+		assert_eq!(allocations.len(), candidates.len());
+
+		let mut needed = 0u128;
+		for (candidate, allocation) in candidates.iter().zip(allocations.iter()) {
+			// nobody gets more than it needs, or anything without weight
+			assert!(*allocation <= candidate.need);
+			if candidate.weight() == 0 {
+				assert_eq!(*allocation, 0);
+			} else {
+				needed += candidate.need;
+			}
+		}
+		// the pool is allocated as much as validators need
+		assert_eq!(allocations.iter().sum::<u128>(), pool.min(needed));
+
+		// heavier validator gets no less than a lighter one, except rounding, unless it's capped
+		for (i, a) in candidates.iter().enumerate() {
+			for (j, b) in candidates.iter().enumerate() {
+				if a.weight() > 0 && a.weight() >= b.weight() && allocations[i] < a.need {
+					assert!(allocations[i] + 1 >= allocations[j], "{:?} {:?} {:?}", pool, candidates, allocations);
+				}
+			}
+		}
+	}
+}
+
+fn register_with_deposit(who: u64, need: u64, deposit: u64, validator_address: &[u8]) {
+	assert_ok!(Assets::issue(Origin::ROOT, AssetSymbol::EOS, TokenType::Token, who, deposit));
+	assert_ok!(Validator::register(Origin::signed(who), AssetSymbol::EOS, need, validator_address.to_vec()));
+	assert_ok!(Validator::deposit(Origin::signed(who), AssetSymbol::EOS, deposit));
+}
+
+#[test]
+fn staking_should_be_rebalanced_each_era() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Validator::set_asset(Origin::ROOT, AssetSymbol::EOS, 0, 0));
+		register_with_deposit(1, 100, 100, b"producer1");
+		register_with_deposit(2, 1000, 300, b"producer2");
+		set_staking_pool(600);
+
+		let mut plan = Validator::allocation_plan(AssetSymbol::EOS);
+		plan.sort();
+		assert_eq!(plan, vec![(1, 0, 100), (2, 0, 500)]);
+
+		run_to_block(10);
+		assert_eq!(Validator::current_era(), 1);
+		assert_eq!(Validator::validators(AssetSymbol::EOS, 1).staking, 100);
+		assert_eq!(Validator::validators(AssetSymbol::EOS, 2).staking, 500);
+		assert_eq!(Validator::asset_locked_balances(AssetSymbol::EOS), 600);
+
+		// nothing changes within the era
+		assert_noop!(
+			Validator::set_performance_score(Origin::signed(1), AssetSymbol::EOS, 2, Permill::zero()),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_noop!(
+			Validator::set_performance_score(Origin::ROOT, AssetSymbol::EOS, 3, Permill::zero()),
+			ValidatorError::ValidatorNotRegistered
+		);
+		assert_ok!(Validator::set_performance_score(Origin::ROOT, AssetSymbol::EOS, 2, Permill::zero()));
+		run_to_block(19);
+		assert_eq!(Validator::validators(AssetSymbol::EOS, 2).staking, 500);
+
+		// validator without score is un-staked in the next era
+		run_to_block(20);
+		assert_eq!(Validator::current_era(), 2);
+		assert_eq!(Validator::validators(AssetSymbol::EOS, 1).staking, 100);
+		assert_eq!(Validator::validators(AssetSymbol::EOS, 2).staking, 0);
+		assert_eq!(Validator::asset_locked_balances(AssetSymbol::EOS), 100);
+
+		let mut actions = staking_actions();
+		actions.sort();
+		assert_eq!(actions, vec![
+			(2, 1, b"producer1".to_vec(), 100, true),
+			(2, 2, b"producer2".to_vec(), 500, false),
+			(2, 2, b"producer2".to_vec(), 500, true),
+		]);
+	});
+}
+
+#[test]
+fn rebalance_should_skip_validator_rejected_by_bridge() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Validator::set_asset(Origin::ROOT, AssetSymbol::EOS, 0, 0));
+		register_with_deposit(1, 100, 100, b"producer1");
+		register_with_deposit(3, 100, 100, INVALID_ADDRESS);
+		set_staking_pool(1000);
+
+		run_to_block(10);
+		assert_eq!(Validator::validators(AssetSymbol::EOS, 1).staking, 100);
+		assert_eq!(Validator::validators(AssetSymbol::EOS, 3).staking, 0);
+		assert_eq!(Validator::asset_locked_balances(AssetSymbol::EOS), 100);
+
+		let events: Vec<TestEvent> = System::events().into_iter().map(|record| record.event).collect();
+		assert!(events.contains(&TestEvent::brml_validator(RawEvent::RebalanceFailed(AssetSymbol::EOS, 3, 100))));
+		assert!(events.contains(&TestEvent::brml_validator(RawEvent::Rebalanced(AssetSymbol::EOS, 1, 100))));
+	});
+}
+
+#[test]
+fn staking_should_be_cut_by_failed_unstaking() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Validator::set_asset(Origin::ROOT, AssetSymbol::EOS, 0, 0));
+		register_with_deposit(1, 100, 100, b"producer1");
+		register_with_deposit(2, 1000, 300, b"producer2");
+		set_staking_pool(600);
+
+		run_to_block(10);
+		assert_eq!(Validator::validators(AssetSymbol::EOS, 2).staking, 500);
+
+		// validator 3 would take over validator 2, if validator 2 could be un-staked
+		register_with_deposit(3, 1000, 100, b"producer3");
+		assert_ok!(Validator::set_performance_score(Origin::ROOT, AssetSymbol::EOS, 2, Permill::zero()));
+		set_unstaking_rejected(true);
+
+		run_to_block(20);
+		assert_eq!(Validator::validators(AssetSymbol::EOS, 1).staking, 100);
+		assert_eq!(Validator::validators(AssetSymbol::EOS, 2).staking, 500);
+		assert_eq!(Validator::validators(AssetSymbol::EOS, 3).staking, 0);
+		assert_eq!(Validator::asset_locked_balances(AssetSymbol::EOS), 600);
+
+		let events: Vec<TestEvent> = System::events().into_iter().map(|record| record.event).collect();
+		assert!(events.contains(&TestEvent::brml_validator(RawEvent::RebalanceFailed(AssetSymbol::EOS, 2, 0))));
+		assert!(events.contains(&TestEvent::brml_validator(RawEvent::RebalanceFailed(AssetSymbol::EOS, 3, 500))));
+
+		// validator 3 is staked once validator 2 is released
+		set_unstaking_rejected(false);
+		run_to_block(30);
+		assert_eq!(Validator::validators(AssetSymbol::EOS, 2).staking, 0);
+		assert_eq!(Validator::validators(AssetSymbol::EOS, 3).staking, 500);
+		assert_eq!(Validator::asset_locked_balances(AssetSymbol::EOS), 600);
+	});
+}
+
+#[test]
+fn assets_should_be_rebalanced_one_per_block() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Validator::set_asset(Origin::ROOT, AssetSymbol::EOS, 0, 0));
+		assert_ok!(Validator::set_asset(Origin::ROOT, AssetSymbol::DOT, 0, 0));
+		register_with_deposit(1, 100, 100, b"producer1");
+		assert_ok!(Assets::issue(Origin::ROOT, AssetSymbol::DOT, TokenType::Token, 1, 100));
+		assert_ok!(Validator::register(Origin::signed(1), AssetSymbol::DOT, 100, b"validator1".to_vec()));
+		assert_ok!(Validator::deposit(Origin::signed(1), AssetSymbol::DOT, 100));
+		set_staking_pool(100);
+
+		run_to_block(10);
+		assert_eq!(Validator::pending_rebalances().len(), 1);
+		let staked = |asset_symbol: AssetSymbol| Validator::asset_locked_balances(asset_symbol);
+		assert_eq!(staked(AssetSymbol::EOS) + staked(AssetSymbol::DOT), 100);
+
+		run_to_block(11);
+		assert!(Validator::pending_rebalances().is_empty());
+		assert_eq!(staked(AssetSymbol::EOS), 100);
+		assert_eq!(staked(AssetSymbol::DOT), 100);
+		assert_eq!(Validator::current_era(), 1);
+	});
+}
+
+#[test]
+fn validators_of_asset_should_be_capped() {
+	new_test_ext().execute_with(|| {
+		for who in 1..=MaxValidators::get() as u64 {
+			assert_ok!(Validator::register(Origin::signed(who), AssetSymbol::EOS, 100, b"producer".to_vec()));
+		}
+		assert_eq!(Validator::validator_count(AssetSymbol::EOS), MaxValidators::get());
+		assert_noop!(
+			Validator::register(Origin::signed(10), AssetSymbol::EOS, 100, b"producer".to_vec()),
+			ValidatorError::TooManyValidators
+		);
+
+		// the cap is for each asset
+		assert_ok!(Validator::register(Origin::signed(10), AssetSymbol::DOT, 100, b"validator".to_vec()));
+	});
+}
+
+#[test]
+fn deducted_fee_should_be_rewarded_to_vtoken_holders() {
+	new_test_ext().execute_with(|| {
+		// the deposit is less than the minimum fee of the redeem duration
+		assert_ok!(Validator::set_asset(Origin::ROOT, AssetSymbol::EOS, 10, 10));
+		register_with_deposit(1, 100, 50, b"producer1");
+
+		run_to_block(2);
+		assert_eq!(asset_rewards(), vec![(AssetSymbol::EOS as u32, 50)]);
+		assert_eq!(Validator::validators(AssetSymbol::EOS, 1).deposit, 0);
+		assert_eq!(Validator::locked_balances(1), 0);
+
+		// the fee can't be withdrawn after it's rewarded
+		assert_noop!(
+			Validator::withdraw(Origin::signed(1), AssetSymbol::EOS, 50),
+			ValidatorError::LockedBalanceNotEnough
+		);
+	});
+}
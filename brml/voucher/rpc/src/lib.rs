@@ -0,0 +1,74 @@
+// Copyright 2019-2020 Liebi Technologies.
+// This file is part of Bifrost.
+
+// Bifrost is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Bifrost is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Bifrost.  If not, see <http://www.gnu.org/licenses/>.
+
+use codec::Codec;
+use jsonrpc_derive::rpc;
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result as JsonRpcResult};
+use std::sync::Arc;
+use std::marker::PhantomData;
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+pub use self::gen_client::Client as VoucherClient;
+pub use voucher_rpc_runtime_api::{self as runtime_api, VoucherApi as VoucherRuntimeApi};
+
+#[derive(Clone, Debug)]
+pub struct Voucher<C, Block> {
+	client: Arc<C>,
+	_marker: PhantomData<Block>
+}
+
+impl<C, Block> Voucher<C, Block> {
+	pub fn new(client: Arc<C>) -> Self {
+		Self {
+			client,
+			_marker: PhantomData
+		}
+	}
+}
+
+#[rpc]
+pub trait VoucherApi<BlockHash, AccountId, Balance> {
+	/// rpc method for paging through all vouchers
+	#[rpc(name = "voucher_getVouchers")]
+	fn get_vouchers(&self, start: u32, count: u32, at: Option<BlockHash>) -> JsonRpcResult<Vec<(AccountId, Balance)>>;
+}
+
+impl<C, Block, AccountId, Balance> VoucherApi<<Block as BlockT>::Hash, AccountId, Balance>
+for Voucher<C, Block>
+	where
+		Block: BlockT,
+		C: Send + Sync + 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block>,
+		C::Api: VoucherRuntimeApi<Block, AccountId, Balance>,
+		AccountId: Codec,
+		Balance: Codec,
+{
+	fn get_vouchers(
+		&self,
+		start: u32,
+		count: u32,
+		at: Option<<Block as BlockT>::Hash>
+	) -> JsonRpcResult<Vec<(AccountId, Balance)>> {
+		let voucher_rpc_api = self.client.runtime_api();
+		let at = BlockId::<Block>::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+		voucher_rpc_api.vouchers(&at, start, count).map_err(|e| RpcError {
+			code: ErrorCode::InternalError,
+			message: "Failed to get vouchers.".to_owned(),
+			data: Some(format!("{:?}", e).into()),
+		})
+	}
+}
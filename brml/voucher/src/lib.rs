@@ -18,12 +18,23 @@
 
 extern crate alloc;
 
-use alloc::collections::btree_map::BTreeMap;
+use alloc::{collections::btree_map::BTreeMap, vec::Vec};
 use core::convert::TryInto;
-use frame_support::traits::{Get};
-use frame_support::{decl_module, decl_event, decl_storage, decl_error, debug, ensure, Parameter, IterableStorageMap};
-use sp_runtime::traits::{AtLeast32Bit, Member, MaybeSerializeDeserialize, StaticLookup, Zero};
-use frame_system::{self as system, ensure_root};
+use frame_support::traits::{Currency, ExistenceRequirement, Get, Imbalance, VestingSchedule};
+use frame_support::{decl_module, decl_event, decl_storage, decl_error, ensure, Parameter, IterableStorageMap};
+use sp_runtime::traits::{AtLeast32Bit, Member, MaybeSerializeDeserialize, SaturatedConversion, Saturating, StaticLookup, Zero};
+use frame_system::{self as system, ensure_root, ensure_signed};
+
+mod mock;
+mod tests;
+
+/// How many vouchers can be queried at most in one page
+pub const MAX_VOUCHERS_PER_PAGE: u32 = 1000;
+
+/// How many vouchers can be reset at most in one call
+pub const MAX_VOUCHERS_PER_RESET: u32 = 1000;
+
+type BncOf<T> = <<T as Trait>::Currency as Currency<<T as system::Trait>::AccountId>>::Balance;
 
 pub trait Trait: system::Trait {
 	/// The overarching event type.
@@ -38,13 +49,37 @@ pub trait Trait: system::Trait {
 		+ Copy
 		+ Zero
 		+ Into<u128>;
+	/// BNC which vouchers are redeemed to
+	type Currency: Currency<Self::AccountId>;
+	/// Lock redeemed BNC with a vesting schedule
+	type VestingSchedule: VestingSchedule<Self::AccountId, Moment = Self::BlockNumber, Currency = Self::Currency>;
 }
 
 decl_event! {
-	pub enum Event<T> where <T as system::Trait>::AccountId, <T as Trait>::Balance {
+	pub enum Event<T> where
+		<T as system::Trait>::AccountId,
+		<T as system::Trait>::BlockNumber,
+		<T as Trait>::Balance,
+	{
 		/// A event indicate user receives transaction.
 		IssuedVoucher(AccountId, Balance),
 		DestroyedVoucher(AccountId, Balance),
+		/// Voucher has been redeemed to BNC
+		RedeemedVoucher(AccountId, Balance),
+		/// Redeemed BNC is locked, (who, locked, unlocked per block, starting block)
+		RedeemedVoucherVested(AccountId, Balance, Balance, BlockNumber),
+		/// Vesting for redemption is changed, (delay, duration)
+		RedeemVestingSet(Option<(BlockNumber, BlockNumber)>),
+		/// Account that redeemed BNC is transferred from, none means BNC is minted
+		RedeemSourceSet(Option<AccountId>),
+		/// Voucher is transferred, (from, to, amount)
+		TransferredVoucher(AccountId, AccountId, Balance),
+		/// Voucher transfer is enabled or disabled
+		VoucherTransferSet(bool),
+		/// Voucher cap of an account is changed
+		VoucherCapSet(AccountId, Option<Balance>),
+		/// All vouchers have been reset
+		VouchersReset,
 	}
 }
 
@@ -52,6 +87,22 @@ decl_error! {
 	pub enum Error for Module<T: Trait> {
 		/// Transferring too big balance
 		TransferringTooBigBalance,
+		/// Cannot redeem zero voucher
+		ZeroRedeemAmount,
+		/// The account has a vesting schedule already
+		ExistingVestingSchedule,
+		/// The vesting duration cannot be zero
+		InvalidVestingDuration,
+		/// Redeem source account hasn't enough BNC
+		InsufficientRedeemSource,
+		/// Voucher transfer is not enabled
+		VoucherTransferDisabled,
+		/// Cannot transfer zero voucher
+		ZeroTransferAmount,
+		/// Voucher balance would exceed the account cap
+		ExceedVoucherCap,
+		/// Redeemed BNC cannot be credited, since it's below the existential deposit of a new account
+		RedeemBelowExistentialDeposit,
 	}
 }
 
@@ -63,6 +114,18 @@ decl_storage! {
 		TotalSuppliedBNC get(fn toal_bnc): T::Balance = (80_000_000u128 * 10u128.pow(12)).try_into().map_err(|_| "failed to u128 conversion").unwrap();
 		/// Current remaining BNC adds all others vouchers, equaling to TotalSuppliedBNC
 		RemainingBNC get(fn remaining_bnc): T::Balance = (80_000_000u128 * 10u128.pow(12)).try_into().map_err(|_| "failed to u128 conversion").unwrap();
+		/// How much voucher has been redeemed to BNC by each account
+		RedeemedVoucher get(fn redeemed_voucher): map hasher(blake2_128_concat) T::AccountId => T::Balance;
+		/// How much voucher has been redeemed to BNC by all accounts
+		TotalRedeemedVoucher get(fn total_redeemed_voucher): T::Balance;
+		/// Account that redeemed BNC is transferred from, BNC is minted if it's none
+		RedeemSource get(fn redeem_source): Option<T::AccountId>;
+		/// Redeemed BNC is locked if set, (delay in blocks before unlocking, unlocking duration in blocks)
+		RedeemVesting get(fn redeem_vesting): Option<(T::BlockNumber, T::BlockNumber)>;
+		/// Whether holders can transfer vouchers to each other
+		VoucherTransferEnabled get(fn voucher_transfer_enabled): bool;
+		/// The max voucher an account can hold, no limit if it's none
+		VoucherCaps get(fn voucher_cap): map hasher(blake2_128_concat) T::AccountId => Option<T::Balance>;
 	}
 	add_extra_genesis {
 		build(|config: &GenesisConfig<T>| {
@@ -84,7 +147,7 @@ decl_module! {
 
 		fn deposit_event() = default;
 
-		#[weight = T::DbWeight::get().reads_writes(1, 1)]
+		#[weight = T::DbWeight::get().reads_writes(3, 2)]
 		pub fn issue_voucher(
 			origin,
 			dest: <T::Lookup as StaticLookup>::Source,
@@ -98,39 +161,122 @@ decl_module! {
 
 			// ensure this address added into bifrost node
 			let dest = T::Lookup::lookup(dest)?;
+			Self::ensure_within_cap(&dest, amount)?;
 
-			// do not send balances for a account multiple times, just for one time
-			if <BalancesVoucher<T>>::contains_key(&dest) {
-				// increase balances for this account
-				<BalancesVoucher<T>>::mutate(&dest, |balance| {
-					*balance += amount;
+			Self::do_issue(dest, amount);
+		}
+
+		/// Issue vouchers to a batch of accounts, nothing is issued if any of them fails.
+		#[weight = T::DbWeight::get().reads_writes(1 + 2 * vouchers.len() as u64, 1 + vouchers.len() as u64)]
+		pub fn issue_vouchers(
+			origin,
+			vouchers: Vec<(<T::Lookup as StaticLookup>::Source, T::Balance)>,
+		) {
+			ensure_root(origin)?;
+
+			let mut vouchers_to_issue: BTreeMap<T::AccountId, T::Balance> = BTreeMap::new();
+			for (dest, amount) in vouchers {
+				let dest = T::Lookup::lookup(dest)?;
+				let issuing = vouchers_to_issue.entry(dest).or_insert_with(Zero::zero);
+				*issuing = issuing.saturating_add(amount);
+			}
+
+			let mut total: T::Balance = Zero::zero();
+			for (dest, amount) in vouchers_to_issue.iter() {
+				Self::ensure_within_cap(dest, *amount)?;
+				total = total.saturating_add(*amount);
+			}
+			ensure!(<RemainingBNC<T>>::get() >= total, Error::<T>::TransferringTooBigBalance);
+
+			for (dest, amount) in vouchers_to_issue {
+				Self::do_issue(dest, amount);
+			}
+		}
+
+		/// Destroy at most `limit` vouchers, capped by `MAX_VOUCHERS_PER_RESET`, and give them back
+		/// to remaining BNC, call it repeatedly until `VouchersReset` is emitted.
+		#[weight = T::DbWeight::get().reads_writes(
+			2 + (*limit).min(MAX_VOUCHERS_PER_RESET) as u64,
+			1 + (*limit).min(MAX_VOUCHERS_PER_RESET) as u64,
+		)]
+		fn reset_vouchers(origin, limit: u32) {
+			ensure_root(origin)?;
+
+			let limit = limit.min(MAX_VOUCHERS_PER_RESET) as usize;
+			let vouchers: Vec<(T::AccountId, T::Balance)> = <BalancesVoucher<T>>::iter()
+				.take(limit.saturating_add(1))
+				.collect();
+			let finished = vouchers.len() <= limit;
+
+			for (who, balance) in vouchers.into_iter().take(limit) {
+				<BalancesVoucher<T>>::remove(&who);
+				<RemainingBNC<T>>::mutate(|remaining| {
+					*remaining = remaining.saturating_add(balance);
 				});
-			} else {
-				<BalancesVoucher<T>>::insert(&dest, amount);
+
+				Self::deposit_event(RawEvent::DestroyedVoucher(who, balance));
 			}
 
-			// reduce from total BNC
-			<RemainingBNC<T>>::mutate(|balance| {
+			if finished {
+				Self::deposit_event(RawEvent::VouchersReset);
+			}
+		}
+
+		/// Transfer vouchers to another holder if voucher transfer is enabled.
+		#[weight = T::DbWeight::get().reads_writes(4, 2)]
+		pub fn transfer(
+			origin,
+			dest: <T::Lookup as StaticLookup>::Source,
+			#[compact]
+			amount: T::Balance,
+		) {
+			let from = ensure_signed(origin)?;
+			let dest = T::Lookup::lookup(dest)?;
+
+			ensure!(Self::voucher_transfer_enabled(), Error::<T>::VoucherTransferDisabled);
+			ensure!(!amount.is_zero(), Error::<T>::ZeroTransferAmount);
+			ensure!(<BalancesVoucher<T>>::get(&from) >= amount, Error::<T>::TransferringTooBigBalance);
+
+			if from != dest {
+				Self::ensure_within_cap(&dest, amount)?;
+
+				<BalancesVoucher<T>>::mutate(&from, |balance| {
 					*balance -= amount;
-			});
+				});
+				<BalancesVoucher<T>>::mutate(&dest, |balance| {
+					*balance += amount;
+				});
+			}
 
-			Self::deposit_event(RawEvent::IssuedVoucher(dest, amount));
+			Self::deposit_event(RawEvent::TransferredVoucher(from, dest, amount));
 		}
 
+		/// Enable or disable voucher transfer between holders.
 		#[weight = T::DbWeight::get().writes(1)]
-		fn intialize_all_voucher(origin) {
+		pub fn set_voucher_transfer(origin, enabled: bool) {
 			ensure_root(origin)?;
 
-			let total = TotalSuppliedBNC::<T>::get();
-			<RemainingBNC<T>>::mutate(|balance| {
-				*balance = total;
-			});
+			VoucherTransferEnabled::put(enabled);
 
-			for (who, _) in <BalancesVoucher<T>>::iter() {
-				<BalancesVoucher<T>>::mutate(&who, |balance| {
-					*balance = 0.into();
-				});
+			Self::deposit_event(RawEvent::VoucherTransferSet(enabled));
+		}
+
+		/// Set how much voucher an account can hold at most, none means no limit.
+		#[weight = T::DbWeight::get().writes(1)]
+		pub fn set_voucher_cap(
+			origin,
+			who: <T::Lookup as StaticLookup>::Source,
+			cap: Option<T::Balance>,
+		) {
+			ensure_root(origin)?;
+
+			let who = T::Lookup::lookup(who)?;
+			match cap {
+				Some(cap) => <VoucherCaps<T>>::insert(&who, cap),
+				None => <VoucherCaps<T>>::remove(&who),
 			}
+
+			Self::deposit_event(RawEvent::VoucherCapSet(who, cap));
 		}
 
 		#[weight = T::DbWeight::get().writes(1)]
@@ -171,29 +317,111 @@ decl_module! {
 			Self::deposit_event(RawEvent::DestroyedVoucher(dest, amount));
 		}
 
-		#[weight = T::DbWeight::get().reads_writes(1, 1)]
-		pub fn export_all_vouchers(origin) {
+		/// Set the account which redeemed BNC is transferred from, mint BNC if it's none.
+		#[weight = T::DbWeight::get().writes(1)]
+		pub fn set_redeem_source(origin, source: Option<T::AccountId>) {
 			ensure_root(origin)?;
 
-			let mut vouchers = BTreeMap::new();
-			for (who, balance) in <BalancesVoucher<T>>::iter() {
-				vouchers.insert(who, balance);
+			<RedeemSource<T>>::set(source.clone());
+
+			Self::deposit_event(RawEvent::RedeemSourceSet(source));
+		}
+
+		/// Lock redeemed BNC, they start being unlocked `delay` blocks after redemption,
+		/// and are fully unlocked in `duration` blocks. None means no lock.
+		#[weight = T::DbWeight::get().writes(1)]
+		pub fn set_redeem_vesting(origin, vesting: Option<(T::BlockNumber, T::BlockNumber)>) {
+			ensure_root(origin)?;
+
+			if let Some((_, duration)) = vesting {
+				ensure!(!duration.is_zero(), Error::<T>::InvalidVestingDuration);
 			}
-			#[cfg(feature = "std")]
-			{
-				use std::io::prelude::*;
-				if let Ok(ref current_path) = std::env::current_dir() {
-					let vouchers_path = std::path::Path::join(current_path, "all_vouchers.json");
-					match (std::fs::File::create(vouchers_path), serde_json::to_vec(&vouchers)) {
-						(Ok(ref mut file), Ok(ref bytes)) => {
-							if !file.write_all(&bytes[..]).is_ok() {
-								debug::warn!("failed to export all vouchers");
-							}
-						}
-						_ => debug::warn!("failed to export all vouchers"),
-					}
+			<RedeemVesting<T>>::set(vesting);
+
+			Self::deposit_event(RawEvent::RedeemVestingSet(vesting));
+		}
+
+		/// Redeem `amount` voucher to BNC.
+		#[weight = T::DbWeight::get().reads_writes(6, 6)]
+		pub fn redeem(origin, #[compact] amount: T::Balance) {
+			let who = ensure_signed(origin)?;
+
+			ensure!(!amount.is_zero(), Error::<T>::ZeroRedeemAmount);
+			ensure!(<BalancesVoucher<T>>::get(&who) >= amount, Error::<T>::TransferringTooBigBalance);
+
+			let bnc: BncOf<T> = amount.into().saturated_into();
+			let vesting = <RedeemVesting<T>>::get();
+			// an account can only have one vesting schedule
+			if vesting.is_some() {
+				ensure!(T::VestingSchedule::vesting_balance(&who).is_none(), Error::<T>::ExistingVestingSchedule);
+			}
+
+			// all checks are done, redeem
+			match <RedeemSource<T>>::get() {
+				Some(source) => {
+					T::Currency::transfer(&source, &who, bnc, ExistenceRequirement::KeepAlive)
+						.map_err(|_| Error::<T>::InsufficientRedeemSource)?;
+				}
+				None => {
+					// nothing is credited if a new account gets less than the existential deposit
+					let minted = T::Currency::deposit_creating(&who, bnc);
+					ensure!(minted.peek() == bnc, Error::<T>::RedeemBelowExistentialDeposit);
 				}
 			}
+
+			<BalancesVoucher<T>>::mutate(&who, |balance| {
+				*balance -= amount;
+			});
+			<RedeemedVoucher<T>>::mutate(&who, |redeemed| {
+				*redeemed = redeemed.saturating_add(amount);
+			});
+			<TotalRedeemedVoucher<T>>::mutate(|redeemed| {
+				*redeemed = redeemed.saturating_add(amount);
+			});
+
+			if let Some((delay, duration)) = vesting {
+				let starting_block = <system::Module<T>>::block_number().saturating_add(delay);
+				let duration: u128 = duration.saturated_into();
+				let per_block: T::Balance = (amount.into() / duration).max(1).into();
+				T::VestingSchedule::add_vesting_schedule(&who, bnc, per_block.into().saturated_into(), starting_block)?;
+
+				Self::deposit_event(RawEvent::RedeemedVoucherVested(who.clone(), amount, per_block, starting_block));
+			}
+
+			Self::deposit_event(RawEvent::RedeemedVoucher(who, amount));
+		}
+	}
+}
+
+impl<T: Trait> Module<T> {
+	/// Page through all vouchers, return at most `count` vouchers starting from the `start`th one.
+	pub fn vouchers(start: u32, count: u32) -> Vec<(T::AccountId, T::Balance)> {
+		let count = count.min(MAX_VOUCHERS_PER_PAGE);
+		<BalancesVoucher<T>>::iter()
+			.skip(start as usize)
+			.take(count as usize)
+			.collect()
+	}
+
+	fn ensure_within_cap(who: &T::AccountId, amount: T::Balance) -> Result<(), Error<T>> {
+		if let Some(cap) = Self::voucher_cap(who) {
+			let balance = <BalancesVoucher<T>>::get(who).saturating_add(amount);
+			ensure!(balance <= cap, Error::<T>::ExceedVoucherCap);
 		}
+
+		Ok(())
+	}
+
+	fn do_issue(dest: T::AccountId, amount: T::Balance) {
+		<BalancesVoucher<T>>::mutate(&dest, |balance| {
+			*balance += amount;
+		});
+
+		// reduce from total BNC
+		<RemainingBNC<T>>::mutate(|balance| {
+			*balance -= amount;
+		});
+
+		Self::deposit_event(RawEvent::IssuedVoucher(dest, amount));
 	}
 }
@@ -0,0 +1,223 @@
+// Copyright 2019-2020 Liebi Technologies.
+// This file is part of Bifrost.
+
+// Bifrost is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Bifrost is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Bifrost.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Tests for the module.
+
+#![cfg(test)]
+
+use crate::*;
+use crate::mock::*;
+use frame_support::{assert_ok, assert_noop};
+
+#[test]
+fn redeem_should_mint_bnc() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(Voucher::redeem(Origin::signed(2), 0), VoucherError::ZeroRedeemAmount);
+		assert_noop!(Voucher::redeem(Origin::signed(2), 501), VoucherError::TransferringTooBigBalance);
+
+		assert_ok!(Voucher::redeem(Origin::signed(2), 100));
+		assert_eq!(Balances::free_balance(2), 100);
+		assert_eq!(Voucher::voucher(2), 400);
+		assert_eq!(Voucher::redeemed_voucher(2), 100);
+		assert_eq!(Voucher::total_redeemed_voucher(), 100);
+		assert_eq!(last_event(), TestEvent::voucher(RawEvent::RedeemedVoucher(2, 100)));
+	});
+}
+
+#[test]
+fn redeem_below_existential_deposit_should_keep_voucher() {
+	new_test_ext().execute_with(|| {
+		// account 2 doesn't exist, and 5 BNC cannot create it
+		assert_noop!(Voucher::redeem(Origin::signed(2), 5), VoucherError::RedeemBelowExistentialDeposit);
+		assert_eq!(Voucher::voucher(2), 500);
+
+		// an existing account can get less than the existential deposit
+		assert_ok!(Voucher::redeem(Origin::signed(1), 5));
+		assert_eq!(Balances::free_balance(1), 105);
+		assert_eq!(Voucher::voucher(1), 995);
+	});
+}
+
+#[test]
+fn redeem_should_transfer_from_source() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Voucher::set_redeem_source(Origin::ROOT, Some(9)));
+		assert_eq!(Voucher::redeem_source(), Some(9));
+
+		assert_ok!(Voucher::redeem(Origin::signed(1), 200));
+		assert_eq!(Balances::free_balance(1), 300);
+		assert_eq!(Balances::free_balance(9), 800);
+		assert_eq!(Balances::total_issuance(), 1_100);
+
+		// source must be kept alive
+		assert_noop!(Voucher::redeem(Origin::signed(1), 800), VoucherError::InsufficientRedeemSource);
+	});
+}
+
+#[test]
+fn redeem_should_be_vested() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(Voucher::set_redeem_vesting(Origin::ROOT, Some((5, 0))), VoucherError::InvalidVestingDuration);
+		assert_ok!(Voucher::set_redeem_vesting(Origin::ROOT, Some((5, 10))));
+
+		assert_ok!(Voucher::redeem(Origin::signed(2), 100));
+		assert!(System::events().iter().any(|record| {
+			record.event == TestEvent::voucher(RawEvent::RedeemedVoucherVested(2, 100, 10, 6))
+		}));
+		assert_eq!(Vesting::vesting_balance(&2), Some(100));
+
+		// only one vesting schedule for an account
+		assert_noop!(Voucher::redeem(Origin::signed(2), 100), VoucherError::ExistingVestingSchedule);
+
+		System::set_block_number(11);
+		assert_eq!(Vesting::vesting_balance(&2), Some(50));
+
+		assert_ok!(Voucher::set_redeem_vesting(Origin::ROOT, None));
+		assert_ok!(Voucher::redeem(Origin::signed(1), 100));
+		assert_eq!(Vesting::vesting_balance(&1), None);
+	});
+}
+
+#[test]
+fn vouchers_should_be_paged() {
+	new_test_ext().execute_with(|| {
+		for who in 10..20 {
+			assert_ok!(Voucher::issue_voucher(Origin::ROOT, who, who as u128));
+		}
+
+		let mut all = Voucher::vouchers(0, 100);
+		assert_eq!(all.len(), 12);
+
+		// pages are in the same order as all vouchers
+		let mut paged = Vec::new();
+		for start in (0..12).step_by(5) {
+			let page = Voucher::vouchers(start, 5);
+			assert!(page.len() <= 5);
+			paged.extend(page);
+		}
+		assert_eq!(paged, all);
+		assert!(Voucher::vouchers(12, 5).is_empty());
+		assert!(Voucher::vouchers(0, 0).is_empty());
+
+		all.sort();
+		assert_eq!(&all[..2], &[(1, 1_000), (2, 500)]);
+		assert_eq!(all[2..].to_vec(), (10..20).map(|who| (who, who as u128)).collect::<Vec<_>>());
+	});
+}
+
+#[test]
+fn transfer_should_work_when_enabled() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(Voucher::transfer(Origin::signed(1), 3, 100), VoucherError::VoucherTransferDisabled);
+
+		assert_ok!(Voucher::set_voucher_transfer(Origin::ROOT, true));
+		assert!(Voucher::voucher_transfer_enabled());
+		assert_noop!(Voucher::transfer(Origin::signed(1), 3, 0), VoucherError::ZeroTransferAmount);
+		assert_noop!(Voucher::transfer(Origin::signed(1), 3, 1_001), VoucherError::TransferringTooBigBalance);
+
+		assert_ok!(Voucher::transfer(Origin::signed(1), 3, 100));
+		assert_eq!(Voucher::voucher(1), 900);
+		assert_eq!(Voucher::voucher(3), 100);
+		assert_eq!(last_event(), TestEvent::voucher(RawEvent::TransferredVoucher(1, 3, 100)));
+
+		// transferring to self changes nothing
+		assert_ok!(Voucher::transfer(Origin::signed(1), 1, 900));
+		assert_eq!(Voucher::voucher(1), 900);
+		assert_eq!(Voucher::remaining_bnc(), GENESIS_REMAINING_BNC);
+
+		assert_ok!(Voucher::set_voucher_transfer(Origin::ROOT, false));
+		assert_noop!(Voucher::transfer(Origin::signed(3), 1, 100), VoucherError::VoucherTransferDisabled);
+	});
+}
+
+#[test]
+fn voucher_cap_should_limit_balance() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Voucher::set_voucher_transfer(Origin::ROOT, true));
+		assert_ok!(Voucher::set_voucher_cap(Origin::ROOT, 3, Some(150)));
+		assert_eq!(Voucher::voucher_cap(3), Some(150));
+
+		assert_ok!(Voucher::transfer(Origin::signed(1), 3, 100));
+		assert_noop!(Voucher::transfer(Origin::signed(1), 3, 51), VoucherError::ExceedVoucherCap);
+		assert_noop!(Voucher::issue_voucher(Origin::ROOT, 3, 51), VoucherError::ExceedVoucherCap);
+		assert_ok!(Voucher::issue_voucher(Origin::ROOT, 3, 50));
+		assert_eq!(Voucher::voucher(3), 150);
+
+		assert_ok!(Voucher::set_voucher_cap(Origin::ROOT, 3, None));
+		assert_eq!(Voucher::voucher_cap(3), None);
+		assert_ok!(Voucher::transfer(Origin::signed(1), 3, 100));
+		assert_eq!(Voucher::voucher(3), 250);
+	});
+}
+
+#[test]
+fn issue_vouchers_should_be_all_or_nothing() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Voucher::issue_vouchers(Origin::ROOT, vec![(3, 10), (4, 5), (3, 20)]));
+		assert_eq!(Voucher::voucher(3), 30);
+		assert_eq!(Voucher::voucher(4), 5);
+		assert_eq!(Voucher::remaining_bnc(), GENESIS_REMAINING_BNC - 35);
+
+		// amounts to the same account are summed before checking the cap
+		assert_ok!(Voucher::set_voucher_cap(Origin::ROOT, 4, Some(10)));
+		assert_noop!(
+			Voucher::issue_vouchers(Origin::ROOT, vec![(3, 10), (4, 3), (4, 3)]),
+			VoucherError::ExceedVoucherCap
+		);
+		assert_noop!(
+			Voucher::issue_vouchers(Origin::ROOT, vec![(3, 10), (5, GENESIS_REMAINING_BNC)]),
+			VoucherError::TransferringTooBigBalance
+		);
+		assert_noop!(Voucher::issue_vouchers(Origin::signed(1), vec![(3, 10)]), sp_runtime::DispatchError::BadOrigin);
+	});
+}
+
+#[test]
+fn reset_vouchers_should_be_paged() {
+	new_test_ext().execute_with(|| {
+		for who in 10..15 {
+			assert_ok!(Voucher::issue_voucher(Origin::ROOT, who, 100));
+		}
+		let reset = TestEvent::voucher(RawEvent::VouchersReset);
+
+		assert_ok!(Voucher::reset_vouchers(Origin::ROOT, 3));
+		assert_eq!(Voucher::vouchers(0, 100).len(), 4);
+		assert!(!System::events().iter().any(|record| record.event == reset));
+
+		assert_ok!(Voucher::reset_vouchers(Origin::ROOT, 3));
+		assert_eq!(Voucher::vouchers(0, 100).len(), 1);
+		assert!(!System::events().iter().any(|record| record.event == reset));
+
+		assert_ok!(Voucher::reset_vouchers(Origin::ROOT, 3));
+		assert!(Voucher::vouchers(0, 100).is_empty());
+		assert_eq!(last_event(), reset);
+		assert_eq!(Voucher::remaining_bnc(), Voucher::toal_bnc());
+	});
+}
+
+#[test]
+fn reset_vouchers_should_cap_limit() {
+	new_test_ext().execute_with(|| {
+		for who in 10..15 {
+			assert_ok!(Voucher::issue_voucher(Origin::ROOT, who, 100));
+		}
+
+		assert_ok!(Voucher::reset_vouchers(Origin::ROOT, u32::max_value()));
+		assert!(Voucher::vouchers(0, 100).is_empty());
+		assert_eq!(last_event(), TestEvent::voucher(RawEvent::VouchersReset));
+		assert_eq!(Voucher::remaining_bnc(), Voucher::toal_bnc());
+	});
+}
@@ -1,10 +1,11 @@
 ../target/release/subkey insert //Alice//stash acco http://127.0.0.1:4321/
 ../target/release/subkey insert //Bob//stash acco http://127.0.0.1:1234/
 
-# EOS node address
+# EOS node address, several nodes can be separated by commas, they are tried in order
 ../target/release/subkey localstorage-set EOS_NODE_URL http://[eos_producer_ip]:8888/ http://127.0.0.1:1234
 ../target/release/subkey localstorage-set EOS_NODE_URL http://[eos_producer_ip]:8888/ http://127.0.0.1:4321
 
-# EOS accounts for Multisignature
-../target/release/subkey localstorage-set EOS_SECRET_KEY 5JNV39rZLZWr5p1hdLXVVNvJsXpgZnzvTrcZYJggTPuv1GzChB6 http://127.0.0.1:1234 # testa
-../target/release/subkey localstorage-set EOS_SECRET_KEY 5KDXMiphWpzETsNpp3eL3sjWAa4gMvMXCtMquT2PDpKtV1STbHp http://127.0.0.1:4321 # testb
+# EOS accounts for Multisignature, each notary signs with its own key, stored under `EOS_SECRET_KEY` followed by
+# the notary account id. Root sets the EOS public key of each notary by `bridgeEos.setNotaryEosKey`.
+../target/release/subkey localstorage-set 0x454f535f5345435245545f4b4559fe65717dad0447d715f660a0a58411de509b42e6efb8375f562f58a554d5860e 5JNV39rZLZWr5p1hdLXVVNvJsXpgZnzvTrcZYJggTPuv1GzChB6 http://127.0.0.1:1234 # testa for Bob//stash
+../target/release/subkey localstorage-set 0x454f535f5345435245545f4b4559be5ddb1579b72e84524fc29e78609e3caf42e85aa118ebfe0b0ad404b5bdd25f 5KDXMiphWpzETsNpp3eL3sjWAa4gMvMXCtMquT2PDpKtV1STbHp http://127.0.0.1:4321 # testb for Alice//stash